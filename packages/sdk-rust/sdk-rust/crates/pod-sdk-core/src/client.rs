@@ -3,9 +3,11 @@
 //! High-level client for interacting with the PoD Protocol on Solana.
 //! Follows Web3.js v2.0 patterns with modern RPC client architecture.
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 
 use anchor_client::{Client, Cluster, Program};
 use solana_sdk::{
@@ -14,16 +16,26 @@ use solana_sdk::{
     signature::Signature,
     signer::{keypair::Keypair, Signer},
 };
-use solana_rpc_client::rpc_client::RpcClient;
-use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcTransactionConfig};
+use solana_rpc_client::{
+    nonblocking::rpc_client::RpcClient as AsyncRpcClient,
+    rpc_client::{RpcClient, RpcClientConfig},
+};
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
 
 use crate::{
     config::PodComConfig,
-    error::{PodComError, Result},
+    error::{NetworkError, PodComError, Result},
     services::{
-        AgentService, MessageService, ChannelService, EscrowService,
-        AnalyticsService, DiscoveryService, IPFSService, ZKCompressionService,
-        base::{ServiceConfig, ServiceHealth, ServiceMetrics},
+        ServiceBuilder, ServiceRegistry,
+        base::{
+            program_accounts_config, shared_account_cache, simulation_config, DryRunResult, Outcome, ServiceConfig,
+            ServiceHealth, ServiceMetrics, TaskRegistry,
+        },
+    },
+    utils::{
+        account::MAX_MULTIPLE_ACCOUNTS,
+        snapshot::{SnapshotCache, SnapshotKind, SnapshotManifest, SnapshotWriter},
+        transport::{build_proxy, rpc_sender},
     },
 };
 
@@ -38,24 +50,31 @@ pub struct PodComClient {
     /// Modern RPC client following Web3.js v2.0 patterns
     rpc_client: Arc<RpcClient>,
     
+    /// Nonblocking RPC client, shared with every service
+    async_rpc_client: Arc<AsyncRpcClient>,
+    
     /// Anchor program instance
     program: Option<Program<Arc<Keypair>>>,
     
     /// Wallet keypair
     wallet: Option<Arc<Keypair>>,
     
-    /// Core services - Web3.js v2.0 aligned architecture
-    pub agents: AgentService,
-    pub messages: MessageService,
-    pub channels: ChannelService,
-    pub escrow: EscrowService,
-    pub analytics: AnalyticsService,
-    pub discovery: DiscoveryService,
-    pub ipfs: IPFSService,
-    pub zk_compression: ZKCompressionService,
+    /// Core services, reachable as fields of the client, e.g. `client.agents`
+    services: ServiceRegistry,
     
     /// Client metrics
     metrics: Arc<RwLock<ClientMetrics>>,
+    
+    /// Accounts loaded by [`import_snapshot`](Self::import_snapshot)
+    snapshot_cache: Arc<RwLock<SnapshotCache>>,
+    
+    /// Background tasks of the client and its services, all aborted on drop
+    tasks: TaskRegistry,
+    /// The client's owner ID in `tasks`, for tasks started by [`spawn_task`](Self::spawn_task)
+    task_owner: u64,
+    
+    /// Whether [`shutdown`](Self::shutdown) has completed
+    shut_down: AtomicBool,
 }
 
 impl std::fmt::Debug for PodComClient {
@@ -70,6 +89,20 @@ impl std::fmt::Debug for PodComClient {
     }
 }
 
+impl std::ops::Deref for PodComClient {
+    type Target = ServiceRegistry;
+    
+    fn deref(&self) -> &ServiceRegistry {
+        &self.services
+    }
+}
+
+impl std::ops::DerefMut for PodComClient {
+    fn deref_mut(&mut self) -> &mut ServiceRegistry {
+        &mut self.services
+    }
+}
+
 impl PodComClient {
     /// Create a new PoD Protocol client
     /// 
@@ -80,18 +113,67 @@ impl PodComClient {
         
         // Create RPC client using modern patterns (equivalent to createSolanaRpc)
         let rpc_client = Arc::new(Self::create_rpc_client(&config)?);
+        let async_rpc_client = Arc::new(Self::create_async_rpc_client(&config)?);
+        
+        let tasks = TaskRegistry::default();
+        let task_owner = tasks.new_owner();
+        let services = ServiceBuilder::new(
+            Self::service_config(&config, &rpc_client, &async_rpc_client, &tasks)?,
+        ).build()?;
+        
+        Ok(Self {
+            config,
+            rpc_client,
+            async_rpc_client,
+            program: None,
+            wallet: None,
+            
+            services,
+            
+            metrics: Arc::new(RwLock::new(ClientMetrics::default())),
+            snapshot_cache: Arc::new(RwLock::new(SnapshotCache::new())),
+            tasks,
+            task_owner,
+            shut_down: AtomicBool::new(false),
+        })
+    }
+    
+    /// Build the configuration shared by all services
+    fn service_config(
+        config: &PodComConfig,
+        rpc_client: &Arc<RpcClient>,
+        async_rpc_client: &Arc<AsyncRpcClient>,
+        tasks: &TaskRegistry,
+    ) -> Result<ServiceConfig> {
+        let ipfs_proxy = config.ipfs_config.proxy_url
+            .as_deref()
+            .map(|url| build_proxy(url, config.ipfs_config.no_proxy.as_deref()))
+            .transpose()?;
+        let rpc_proxy = config.network.proxy_url
+            .as_deref()
+            .map(|url| build_proxy(url, config.network.no_proxy.as_deref()))
+            .transpose()?;
         
-        // Create service configuration
-        let service_config = ServiceConfig {
+        Ok(ServiceConfig {
             rpc_client: rpc_client.clone(),
+            async_rpc_client: async_rpc_client.clone(),
+            allowed_rpc_methods: config.security_config.allowed_rpc_methods.clone(),
+            rpc_proxy,
             program_id: config.program_id,
+            program_id_override: None,
+            compute_budget: config.performance_config.compute_budget.clone(),
+            dry_run: config.dry_run,
             commitment: config.commitment,
             retry_config: config.retry_config.clone(),
             timeout: config.network.timeout,
             rate_limit_config: config.rate_limit_config.clone(),
             cache_config: config.cache_config.clone(),
+            account_cache: shared_account_cache(&config.cache_config),
+            tasks: tasks.clone(),
             cluster: config.rpc_url.clone(),
             rpc_timeout_secs: config.network.timeout.as_secs(),
+            ws_url: config.ws_url.clone(),
+            agent_config: Some(config.agent_config.clone()),
             message_config: None,
             channel_config: None,
             escrow_config: None,
@@ -99,46 +181,52 @@ impl PodComClient {
             discovery_config: None,
             compression_config: None,
             ipfs_endpoint: Some(config.ipfs_config.ipfs_endpoint.clone()),
+            ipfs_proxy,
+            ipfs_max_file_size: Some(config.ipfs_config.max_file_size),
+            ipfs_timeout: Some(config.ipfs_config.timeout),
             zk_compression_config: Some(config.zk_compression_config.clone()),
-        };
-        
-        Ok(Self {
-            config,
-            rpc_client,
-            program: None,
-            wallet: None,
-            
-            // Initialize all services
-            agents: AgentService::new(service_config.clone()),
-            messages: MessageService::new(service_config.clone()),
-            channels: ChannelService::new(service_config.clone()),
-            escrow: EscrowService::new(service_config.clone()),
-            analytics: AnalyticsService::new(service_config.clone()),
-            discovery: DiscoveryService::new(service_config.clone()),
-            ipfs: IPFSService::new(service_config.clone()),
-            zk_compression: ZKCompressionService::new(service_config),
-            
-            metrics: Arc::new(RwLock::new(ClientMetrics::default())),
         })
     }
     
+    /// Replace the client's services with those from a registry
+    fn install_services(&mut self, services: ServiceRegistry) {
+        self.services = services;
+    }
+    
     /// Create RPC client using Web3.js v2.0 aligned patterns
     /// 
-    /// This is the Rust equivalent of `createSolanaRpc()` from Web3.js v2.0
+    /// This is the Rust equivalent of `createSolanaRpc()` from Web3.js v2.0.
+    /// All requests go through [`AllowlistSender`](crate::utils::transport::AllowlistSender), so the configured RPC
+    /// method allowlist is enforced for every call the client makes.
     fn create_rpc_client(config: &PodComConfig) -> Result<RpcClient> {
-        let client = RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            config.commitment,
+        let client = RpcClient::new_sender(
+            rpc_sender(config)?,
+            RpcClientConfig::with_commitment(config.commitment),
         );
         
         Ok(client)
     }
     
+    /// Create the nonblocking RPC client services use inside async operations
+    /// 
+    /// It gets its own sender, with the same allowlist and proxy as
+    /// [`create_rpc_client`](Self::create_rpc_client).
+    fn create_async_rpc_client(config: &PodComConfig) -> Result<AsyncRpcClient> {
+        Ok(AsyncRpcClient::new_sender(
+            rpc_sender(config)?,
+            RpcClientConfig::with_commitment(config.commitment),
+        ))
+    }
+    
     /// Initialize the client with a wallet (equivalent to Web3.js v2.0 connection patterns)
     /// 
     /// This method sets up the connection similar to how Web3.js v2.0 handles
     /// wallet connections and program initialization.
     pub async fn initialize(&mut self, wallet: Option<Keypair>) -> Result<()> {
+        if self.config.network.warm_up {
+            self.warm_up_connection().await;
+        }
+        
         if let Some(wallet) = wallet {
             let wallet = Arc::new(wallet);
             
@@ -153,39 +241,38 @@ impl PodComClient {
             // Create program instance
             let program = client.program(self.config.program_id)?;
             
-            // Store the main program instance first
+            // Build and initialize all services from the same program instance
+            let services = ServiceBuilder::new(
+                Self::service_config(&self.config, &self.rpc_client, &self.async_rpc_client, &self.tasks)?,
+            )
+                .build_initialized(program.clone())
+                .await?;
+            self.install_services(services);
+            
             self.program = Some(program);
             self.wallet = Some(wallet);
-            
-            // Initialize all services with individual program instances since Program doesn't implement Clone
-            let agents_program = client.program(self.config.program_id)?;
-            self.agents.initialize(agents_program).await?;
-            
-            let messages_program = client.program(self.config.program_id)?;
-            self.messages.initialize(messages_program).await?;
-            
-            let channels_program = client.program(self.config.program_id)?;
-            self.channels.initialize(channels_program).await?;
-            
-            let escrow_program = client.program(self.config.program_id)?;
-            self.escrow.initialize(escrow_program).await?;
-            
-            let analytics_program = client.program(self.config.program_id)?;
-            self.analytics.initialize(analytics_program).await?;
-            
-            let discovery_program = client.program(self.config.program_id)?;
-            self.discovery.initialize(discovery_program).await?;
-            
-            let ipfs_program = client.program(self.config.program_id)?;
-            self.ipfs.initialize(ipfs_program).await?;
-            
-            let zk_compression_program = client.program(self.config.program_id)?;
-            self.zk_compression.initialize(zk_compression_program).await?;
         }
         
         Ok(())
     }
     
+    /// Prime the RPC connection with a cheap `getHealth` request
+    /// 
+    /// Failures and timeouts are logged and never fail initialization.
+    async fn warm_up_connection(&self) -> bool {
+        let rpc_client = self.rpc_client.clone();
+        warm_up(self.config.network.warm_up_timeout, move || {
+            rpc_client.get_health().map_err(|e| {
+                NetworkError::RpcFailed {
+                    method: "getHealth".to_string(),
+                    reason: e.to_string(),
+                }
+                .into()
+            })
+        })
+        .await
+    }
+    
     /// Get the wallet public key
     pub fn wallet_pubkey(&self) -> Result<Pubkey> {
         self.wallet
@@ -226,11 +313,103 @@ impl PodComClient {
         }
     }
     
+    /// Export all agent, channel, message and escrow accounts as a snapshot
+    /// 
+    /// Addresses are listed per account kind without their data, then fetched
+    /// and written in `getMultipleAccounts`-sized pages, so memory use stays
+    /// bounded regardless of how many accounts exist.
+    pub async fn export_snapshot<W: std::io::Write>(
+        &self,
+        writer: W,
+        compress: bool,
+    ) -> Result<SnapshotManifest> {
+        let mut snapshot = SnapshotWriter::new(writer, compress)?;
+        
+        for kind in SnapshotKind::ALL {
+            let config = match kind {
+                SnapshotKind::Agent => program_accounts_config::<pod_com::AgentAccount>(self.config.commitment),
+                SnapshotKind::Channel => program_accounts_config::<pod_com::ChannelAccount>(self.config.commitment),
+                SnapshotKind::Message => program_accounts_config::<pod_com::MessageAccount>(self.config.commitment),
+                SnapshotKind::Escrow => program_accounts_config::<pod_com::EscrowAccount>(self.config.commitment),
+            };
+            let addresses = self.list_program_addresses(config).await?;
+            tracing::debug!("Exporting {} {:?} accounts", addresses.len(), kind);
+            
+            for page in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+                let accounts = self.async_rpc_client.get_multiple_accounts(page).await.map_err(|e| -> PodComError {
+                    NetworkError::RpcFailed {
+                        method: "getMultipleAccounts".to_string(),
+                        reason: e.to_string(),
+                    }.into()
+                })?;
+                
+                // Accounts closed since listing are skipped
+                for (address, account) in page.iter().zip(accounts) {
+                    if let Some(account) = account {
+                        snapshot.write_account(kind, address, &account.data)?;
+                    }
+                }
+            }
+        }
+        
+        let manifest = snapshot.finish()?;
+        tracing::info!("Exported snapshot of {} accounts", manifest.total());
+        Ok(manifest)
+    }
+    
+    /// Load a snapshot into the client's local snapshot cache
+    /// 
+    /// The archive is verified against its manifest before the cache is
+    /// replaced; on failure the previous cache contents are kept.
+    pub async fn import_snapshot<R: std::io::Read>(&self, reader: R) -> Result<SnapshotManifest> {
+        let mut cache = self.snapshot_cache.write().await;
+        let manifest = cache.load(reader)?;
+        tracing::info!("Imported snapshot of {} accounts", manifest.total());
+        Ok(manifest)
+    }
+    
+    /// Accounts loaded from the most recent snapshot import
+    pub fn snapshot_cache(&self) -> Arc<RwLock<SnapshotCache>> {
+        self.snapshot_cache.clone()
+    }
+    
+    /// List program account addresses matching `config` without their data
+    async fn list_program_addresses(
+        &self,
+        mut config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<Pubkey>> {
+        config.account_config.data_slice = Some(solana_account_decoder::UiDataSliceConfig {
+            offset: 0,
+            length: 0,
+        });
+        
+        let accounts = self.async_rpc_client
+            .get_program_accounts_with_config(&self.config.program_id, config)
+            .await
+            .map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "getProgramAccounts".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?;
+        
+        Ok(accounts.into_iter().map(|(address, _)| address).collect())
+    }
+    
     /// Get client metrics
     pub async fn metrics(&self) -> ClientMetrics {
         self.metrics.read().await.clone()
     }
     
+    /// Render every service's metrics in the Prometheus text exposition format
+    #[cfg(feature = "prometheus")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+    pub fn metrics_text(&self) -> String {
+        crate::utils::prometheus::render_metrics(
+            self.services.services().into_iter().map(|(name, service)| (name, service.metrics())),
+        )
+    }
+    
     /// Update client metrics
     async fn update_metrics<F>(&self, updater: F)
     where
@@ -240,22 +419,38 @@ impl PodComClient {
         updater(&mut metrics);
     }
     
+    /// Spawn a background task, such as a subscription pump, owned by the client
+    /// 
+    /// The task is tracked in the same registry as the services' background
+    /// tasks, and is aborted when the client is shut down or dropped.
+    pub fn spawn_task<F>(&self, task: F) -> JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let handle = tokio::spawn(task);
+        self.tasks.track(self.task_owner, handle.abort_handle());
+        handle
+    }
+    
     /// Graceful shutdown
+    /// 
+    /// Aborts the client's own background tasks, then shuts services down in
+    /// reverse order, each stopping its own. A client dropped without a
+    /// successful shutdown logs a warning and aborts every task.
     pub async fn shutdown(&mut self) -> Result<()> {
+        self.tasks.take_owned(self.task_owner).iter().for_each(tokio::task::AbortHandle::abort);
+        
         // Shutdown all services in reverse order
-        self.zk_compression.shutdown().await?;
-        self.ipfs.shutdown().await?;
-        self.discovery.shutdown().await?;
-        self.analytics.shutdown().await?;
-        self.escrow.shutdown().await?;
-        self.channels.shutdown().await?;
-        self.messages.shutdown().await?;
-        self.agents.shutdown().await?;
+        for (_, service) in self.services.services_mut().into_iter().rev() {
+            service.shutdown().await?;
+        }
         
         // Clear program and wallet
         self.program = None;
         self.wallet = None;
         
+        self.shut_down.store(true, Ordering::Release);
         Ok(())
     }
     
@@ -279,10 +474,22 @@ impl PodComClient {
     }
     
     /// Send and confirm transaction with modern patterns
+    /// 
+    /// With [`PodComConfig::dry_run`] set the transaction is simulated instead.
     pub async fn send_and_confirm_transaction(
         &self,
         transaction: &solana_sdk::transaction::Transaction,
-    ) -> Result<Signature> {
+    ) -> Result<Outcome> {
+        if self.config.dry_run {
+            let result = self.rpc_client
+                .simulate_transaction_with_config(transaction, simulation_config(self.config.commitment))
+                .map_err(|e| PodComError::Network(NetworkError::RpcFailed {
+                    method: "simulateTransaction".to_string(),
+                    reason: e.to_string(),
+                }))?;
+            return Ok(Outcome::Simulated(DryRunResult::from_simulation(transaction, result.value)));
+        }
+        
         let signature = self.rpc_client
             .send_and_confirm_transaction(transaction)
             .map_err(|e| PodComError::Network(crate::error::NetworkError::RpcFailed {
@@ -295,7 +502,7 @@ impl PodComClient {
             m.transactions_sent += 1;
         }).await;
         
-        Ok(signature)
+        Ok(Outcome::Sent(signature))
     }
 }
 
@@ -354,10 +561,52 @@ pub trait BaseService: Send + Sync {
 
 
 
+/// Run a blocking warm-up probe, giving up after `timeout`
+/// 
+/// Returns whether the probe completed successfully in time.
+async fn warm_up<F>(timeout: Duration, probe: F) -> bool
+where
+    F: FnOnce() -> Result<()> + Send + 'static,
+{
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(probe)).await {
+        Ok(Ok(Ok(()))) => {
+            tracing::debug!("RPC connection warm-up completed");
+            true
+        }
+        Ok(Ok(Err(e))) => {
+            tracing::warn!("RPC connection warm-up failed: {}", e);
+            false
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("RPC connection warm-up task panicked: {}", e);
+            false
+        }
+        Err(_) => {
+            tracing::warn!("RPC connection warm-up timed out after {:?}", timeout);
+            false
+        }
+    }
+}
+
+impl Drop for PodComClient {
+    fn drop(&mut self) {
+        if self.shut_down.load(Ordering::Acquire) {
+            return;
+        }
+        
+        tracing::warn!(
+            pending_tasks = self.tasks.running(),
+            "PodComClient dropped without calling shutdown(); aborting background tasks"
+        );
+        self.tasks.abort_all();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::PodComConfig;
+    use crate::testing::TraceCapture;
 
     #[test]
     fn test_client_creation() {
@@ -380,4 +629,91 @@ mod tests {
         let cluster = client.determine_cluster().unwrap();
         assert!(matches!(cluster, Cluster::Devnet));
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_warm_up_probe_called() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let probe_calls = calls.clone();
+
+        let warmed = warm_up(Duration::from_secs(1), move || {
+            probe_calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        })
+        .await;
+
+        assert!(warmed);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_failure_is_not_fatal() {
+        let failed = warm_up(Duration::from_secs(1), || Err(PodComError::NotInitialized)).await;
+        assert!(!failed);
+
+        let timed_out = warm_up(Duration::from_millis(10), || {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(())
+        })
+        .await;
+        assert!(!timed_out);
+    }
+
+    #[test]
+    fn test_warm_up_disabled_by_default() {
+        assert!(!PodComConfig::localnet().network.warm_up);
+        assert!(!PodComConfig::devnet().network.warm_up);
+        assert!(!PodComConfig::mainnet().network.warm_up);
+    }
+
+    #[tokio::test]
+    async fn test_drop_without_shutdown_warns_and_aborts_tasks() {
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let capture = TraceCapture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+        
+        let client = PodComClient::new(PodComConfig::localnet()).unwrap();
+        let task = client.spawn_task(futures::future::pending::<()>());
+        drop(client);
+        
+        assert!(task.await.unwrap_err().is_cancelled());
+        let warnings = capture.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("without calling shutdown"));
+    }
+
+    #[tokio::test]
+    async fn test_drop_aborts_service_tasks() {
+        let client = PodComClient::new(PodComConfig::localnet()).unwrap();
+        let mut config = crate::config::test_config();
+        config.tasks = client.tasks.clone();
+        let service = crate::services::base::ServiceBase::new(config);
+        let (held, released) = tokio::sync::oneshot::channel::<()>();
+        
+        // Ignores cancellation and outlives the client, so only the client's abort stops it
+        service.spawn_task(move |_shutdown| async move {
+            let _held = held;
+            futures::future::pending::<()>().await;
+        });
+        drop(client);
+        
+        assert!(released.await.is_err());
+        drop(service);
+    }
+
+    #[tokio::test]
+    async fn test_drop_after_shutdown_is_silent() {
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let capture = TraceCapture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+        
+        let mut client = PodComClient::new(PodComConfig::localnet()).unwrap();
+        let task = client.spawn_task(futures::future::pending::<()>());
+        client.shutdown().await.unwrap();
+        drop(client);
+        
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(capture.warnings().is_empty());
+    }
+}
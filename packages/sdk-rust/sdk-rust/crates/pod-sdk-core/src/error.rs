@@ -4,7 +4,7 @@
 //! hierarchical error types, context, and recovery information.
 
 use thiserror::Error;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use std::time::Duration;
 
 // Import EscrowStatus from pod_sdk_types to avoid duplication
@@ -68,9 +68,12 @@ pub enum PodComError {
     #[error("Cryptographic error: {0}")]
     Crypto(#[from] pod_sdk_crypto::CryptoError),
     
-    /// Anchor client errors
+    /// Anchor client errors the program or a simulation rejected
+    /// 
+    /// Transport failures from the Anchor client become
+    /// [`NetworkError::RpcFailed`] instead, since only those can succeed on retry.
     #[error("Anchor client error: {0}")]
-    AnchorClient(#[from] anchor_client::ClientError),
+    AnchorClient(anchor_client::ClientError),
     
     /// Client not initialized
     #[error("Client not initialized - call initialize() first")]
@@ -124,6 +127,10 @@ pub enum PodComError {
     #[error("Missing proof hash")]
     MissingProofHash,
     
+    /// Account data could not be decoded safely
+    #[error("Malformed account data: {reason}")]
+    MalformedAccountData { reason: String },
+    
     /// Content too large
     #[error("Content too large: {size} bytes (max: {max_size})")]
     ContentTooLarge { size: usize, max_size: usize },
@@ -140,6 +147,26 @@ pub enum PodComError {
     #[error("Rate limited for operation {operation}, retry after {retry_after:?}")]
     RateLimited { operation: String, retry_after: Option<Duration> },
     
+    /// Transaction did not reach the requested commitment in time
+    /// 
+    /// The transaction may still land; check `signature` before resubmitting.
+    #[error("Transaction {signature} not confirmed within {timeout:?}")]
+    ConfirmationTimeout { signature: Signature, timeout: Duration },
+    
+    /// Transaction's blockhash expired before it reached the requested commitment
+    /// 
+    /// The transaction can no longer land, so it is safe to rebuild and resend.
+    #[error("Transaction {signature} expired after block height {last_valid_block_height}")]
+    TransactionExpired { signature: Signature, last_valid_block_height: u64 },
+    
+    /// Transaction landed but failed on chain
+    #[error("Transaction {signature} failed: {reason}")]
+    TransactionFailed { signature: Signature, reason: String },
+    
+    /// Simulating a transaction showed the program would reject it
+    #[error("Transaction simulation failed: {reason}")]
+    SimulationFailed { reason: String, logs: Vec<String> },
+    
     /// Agent has active channels
     #[error("Agent {agent_address} has {channel_count} active channels")]
     AgentHasActiveChannels { agent_address: Pubkey, channel_count: usize },
@@ -152,6 +179,10 @@ pub enum PodComError {
     #[error("Channel {channel_address} participant limit reached (max: {participant_limit})")]
     ChannelParticipantLimitReached { channel_address: Pubkey, participant_limit: usize },
     
+    /// Channel account fields contradict each other
+    #[error("Inconsistent state for channel {channel_address}: {state}")]
+    InconsistentChannelState { channel_address: Pubkey, state: pod_sdk_types::InconsistentChannelState },
+    
     /// Participant already exists
     #[error("Participant {participant} already exists in channel {channel_address}")]
     ParticipantAlreadyExists { channel_address: Pubkey, participant: Pubkey },
@@ -188,6 +219,10 @@ pub enum PodComError {
     #[error("Invalid compression proof: {proof_hash}")]
     InvalidCompressionProof { proof_hash: String },
     
+    /// A locally generated proof does not match the account that commits to it
+    #[error("Proof mismatch {proof_hash}: {reason}")]
+    ProofMismatch { proof_hash: String, reason: String },
+    
     /// Compression data integrity failed
     #[error("Compression data integrity failed: expected {expected_commitment:?}, computed {computed_commitment:?}")]
     CompressionDataIntegrityFailed { 
@@ -238,6 +273,14 @@ pub enum AgentError {
     /// Unauthorized operation
     #[error("Unauthorized operation for agent: {pubkey}")]
     Unauthorized { pubkey: Pubkey },
+    
+    /// Agent metadata document exceeds the download limit
+    #[error("Agent metadata at {uri} exceeds {limit} bytes")]
+    MetadataTooLarge { uri: String, limit: usize },
+    
+    /// Agent metadata document could not be parsed
+    #[error("Invalid agent metadata at {uri}: {reason}")]
+    InvalidMetadata { uri: String, reason: String },
 }
 
 /// Message service specific errors
@@ -278,6 +321,18 @@ pub enum MessageError {
     /// Insufficient funds for fees
     #[error("Insufficient funds: required {required}, available {available}")]
     InsufficientFunds { required: u64, available: u64 },
+    
+    /// Recipient cannot execute the command
+    #[error("Recipient {recipient} lacks required capabilities: required {required:#x}, has {actual:#x}")]
+    RecipientLacksCapability { recipient: Pubkey, required: u64, actual: u64 },
+    
+    /// No correlated response arrived in time
+    #[error("No response to command {command} within {timeout:?}")]
+    ResponseTimeout { command: Pubkey, timeout: Duration },
+    
+    /// Message was written with an incompatible, newer protocol version
+    #[error("Unsupported protocol version {version:#06x} (supported: {supported:#06x})")]
+    UnsupportedProtocolVersion { version: u16, supported: u16 },
 }
 
 /// Channel service specific errors
@@ -307,6 +362,10 @@ pub enum ChannelError {
     #[error("Invalid channel name: {name} - {reason}")]
     InvalidName { name: String, reason: String },
     
+    /// Private channel joined without a usable invitation
+    #[error("Channel {channel} is private and requires an invitation")]
+    PrivateRequiresInvitation { channel: Pubkey },
+    
     /// Invalid invitation
     #[error("Invalid invitation: {invitation}")]
     InvalidInvitation { invitation: Pubkey },
@@ -410,6 +469,10 @@ pub enum IpfsError {
     /// Pin failed
     #[error("Pin operation failed: {hash}")]
     PinFailed { hash: String },
+    
+    /// IPFS API returned an error status
+    #[error("IPFS {command} failed with status {status}: {message}")]
+    ApiError { command: String, status: u16, message: String },
 }
 
 /// ZK Compression service specific errors
@@ -470,6 +533,10 @@ pub enum NetworkError {
     /// TLS error
     #[error("TLS error: {reason}")]
     TlsError { reason: String },
+    
+    /// RPC method blocked by the configured allowlist
+    #[error("RPC method not allowed: {method}")]
+    RpcMethodNotAllowed { method: String },
 }
 
 /// Configuration errors
@@ -607,9 +674,12 @@ pub trait RetryableError {
 }
 
 impl RetryableError for PodComError {
+    /// Transient network failures and rate limits are retryable; configuration,
+    /// validation, and unimplemented-feature errors never succeed on retry.
     fn is_retryable(&self) -> bool {
         match self {
             PodComError::Network(err) => err.is_retryable(),
+            PodComError::RateLimited { .. } => true,
             PodComError::Agent(AgentError::RateLimitExceeded { .. }) => true,
             PodComError::Message(MessageError::RateLimit { .. }) => true,
             PodComError::Security(SecurityError::RateLimitExceeded { .. }) => true,
@@ -620,6 +690,7 @@ impl RetryableError for PodComError {
     fn retry_after(&self) -> Option<Duration> {
         match self {
             PodComError::Network(err) => err.retry_after(),
+            PodComError::RateLimited { retry_after, .. } => *retry_after,
             PodComError::Agent(AgentError::RateLimitExceeded { window, .. }) => Some(*window),
             PodComError::Message(MessageError::RateLimit { window, .. }) => Some(*window),
             _ => None,
@@ -635,6 +706,21 @@ impl RetryableError for PodComError {
                 suggestions: vec!["Call client.initialize() first".to_string()],
                 severity: ErrorSeverity::High,
             },
+            PodComError::ConfirmationTimeout { .. } => ErrorRecovery {
+                retryable: false,
+                retry_after: None,
+                suggestions: vec![
+                    "Check the signature status before resubmitting".to_string(),
+                    "Increase the confirmation timeout".to_string(),
+                ],
+                severity: ErrorSeverity::Medium,
+            },
+            PodComError::TransactionExpired { .. } => ErrorRecovery {
+                retryable: true,
+                retry_after: None,
+                suggestions: vec!["Rebuild the transaction with a fresh blockhash and resend it".to_string()],
+                severity: ErrorSeverity::Medium,
+            },
             PodComError::WalletNotInitialized => ErrorRecovery {
                 retryable: false,
                 retry_after: None,
@@ -717,11 +803,18 @@ macro_rules! internal_error {
 }
 
 impl From<anchor_client::ClientError> for PodComError {
+    /// Only transport failures map to the retryable [`NetworkError::RpcFailed`];
+    /// program errors and failed preflight simulations would fail again.
     fn from(error: anchor_client::ClientError) -> Self {
-        PodComError::Network(NetworkError::RpcFailed {
-            method: "anchor_client".to_string(),
-            reason: error.to_string(),
-        })
+        match &error {
+            anchor_client::ClientError::SolanaClientError(e) if e.get_transaction_error().is_none() => {
+                PodComError::Network(NetworkError::RpcFailed {
+                    method: "anchor_client".to_string(),
+                    reason: error.to_string(),
+                })
+            }
+            _ => PodComError::AnchorClient(error),
+        }
     }
 }
 
@@ -734,6 +827,42 @@ impl From<reqwest::Error> for PodComError {
     }
 }
 
+impl From<pod_sdk_types::ValidationError> for PodComError {
+    fn from(error: pod_sdk_types::ValidationError) -> Self {
+        use pod_sdk_types::ValidationError as RequestError;
+        
+        let error = match error {
+            RequestError::EmptyPayload => ValidationError::EmptyField {
+                field: "payload".to_string(),
+            },
+            RequestError::PayloadTooLarge { len, max } => ValidationError::TooLong {
+                field: "payload".to_string(),
+                actual: len,
+                max,
+            },
+            RequestError::ExpiryTooShort { expiry, min_secs } => ValidationError::OutOfRange {
+                field: "expiry".to_string(),
+                value: format!("{:?}", expiry),
+                min: format!("{}s", min_secs),
+                max: "unbounded".to_string(),
+            },
+            RequestError::InvalidLength { field, actual: 0, .. } => ValidationError::EmptyField {
+                field: field.to_string(),
+            },
+            RequestError::InvalidLength { field, max, actual } => ValidationError::TooLong {
+                field: field.to_string(),
+                actual,
+                max,
+            },
+            RequestError::UnsupportedContentType => ValidationError::InvalidFormat {
+                field: "content_type".to_string(),
+                reason: format!("must be one of {:?}", pod_sdk_types::SUPPORTED_CONTENT_TYPES),
+            },
+        };
+        PodComError::Validation(error)
+    }
+}
+
 impl From<serde_json::Error> for PodComError {
     fn from(error: serde_json::Error) -> Self {
         PodComError::Config(ConfigError::ParseError {
@@ -764,6 +893,57 @@ mod tests {
         assert_eq!(error.retry_after(), Some(Duration::from_secs(15)));
     }
 
+    #[test]
+    fn test_retryable_classification() {
+        let cases = [
+            (PodComError::Network(NetworkError::Timeout { timeout: Duration::from_secs(1) }), true),
+            (PodComError::Network(NetworkError::ConnectionFailed {
+                endpoint: "http://localhost:8899".to_string(),
+                reason: "reset".to_string(),
+            }), true),
+            (PodComError::Network(NetworkError::RateLimited { retry_after: Duration::from_secs(2) }), true),
+            (PodComError::RateLimited { operation: "send_message".to_string(), retry_after: None }, true),
+            (PodComError::InvalidConfiguration { message: "bad program id".to_string() }, false),
+            (PodComError::NotImplemented { feature: "dispute_escrow".to_string() }, false),
+            (PodComError::Validation(ValidationError::InvalidFormat {
+                field: "name".to_string(),
+                reason: "empty".to_string(),
+            }), false),
+            (PodComError::NotInitialized, false),
+        ];
+        
+        for (error, retryable) in cases {
+            assert_eq!(error.is_retryable(), retryable, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_anchor_client_errors_retry_only_transport_failures() {
+        use solana_rpc_client_api::client_error::{Error as RpcError, ErrorKind};
+        use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+        let transport = RpcError::from(ErrorKind::Custom("connection reset".to_string()));
+        assert!(PodComError::from(anchor_client::ClientError::SolanaClientError(transport)).is_retryable());
+
+        let rejected = RpcError::from(TransactionError::InstructionError(0, InstructionError::Custom(6000)));
+        let error = PodComError::from(anchor_client::ClientError::SolanaClientError(rejected));
+        assert!(matches!(error, PodComError::AnchorClient(_)));
+        assert!(!error.is_retryable());
+
+        let simulation = PodComError::SimulationFailed { reason: "custom program error: 0x1770".to_string(), logs: vec![] };
+        assert!(!simulation.is_retryable());
+    }
+
+    #[test]
+    fn test_rate_limited_retry_after() {
+        let error = PodComError::RateLimited {
+            operation: "send_message".to_string(),
+            retry_after: Some(Duration::from_millis(250)),
+        };
+        assert_eq!(error.retry_after(), Some(Duration::from_millis(250)));
+        assert!(error.recovery_info().retryable);
+    }
+
     #[test]
     fn test_error_recovery_info() {
         let error = PodComError::NotInitialized;
@@ -15,7 +15,7 @@
 //! ## Quick Start
 //!
 //! ```rust,no_run
-//! use pod_sdk_core::{PodComClient, PodComConfig};
+//! use pod_sdk_core::{AgentCapabilities, PodComClient, PodComConfig, RegisterAgentRequest};
 //! use solana_sdk::signer::keypair::Keypair;
 //!
 //! #[tokio::main]
@@ -29,12 +29,12 @@
 //!     client.initialize(Some(wallet)).await?;
 //!     
 //!     // Register an AI agent
-//!     let agent_tx = client.agents.register_agent(
-//!         1024, // capabilities bitmask
-//!         "https://api.myagent.com/metadata".to_string()
-//!     ).await?;
+//!     let agent = client.agents.register_agent(RegisterAgentRequest {
+//!         capabilities: AgentCapabilities::all().into(), // capabilities bitmask
+//!         metadata_uri: "https://api.myagent.com/metadata".to_string(),
+//!     }).await?;
 //!     
-//!     println!("Agent registered: {}", agent_tx);
+//!     println!("Agent registered: {:?}", agent);
 //!     Ok(())
 //! }
 //! ```
@@ -45,13 +45,15 @@
 
 // Public exports - Core client (Web3.js v2.0 aligned)
 pub use client::{PodComClient, ClientMetrics};
-pub use config::{PodComConfig, NetworkConfig, RetryConfig, RateLimitConfig, CacheConfig, SecurityConfig, PerformanceConfig};
+pub use config::{PodComConfig, NetworkConfig, RetryConfig, JitterMode, RateLimitConfig, CacheConfig, SecurityConfig, PerformanceConfig, ComputeBudgetConfig};
 pub use error::{PodComError, Result};
+pub use utils::pagination::{Page, PageRequest};
 
 // Public exports - Services (Web3.js v2.0 aligned)
 pub use services::{
     AgentService, MessageService, ChannelService, EscrowService,
-    AnalyticsService, DiscoveryService, IPFSService, ZKCompressionService
+    AnalyticsService, DiscoveryService, IPFSService, ZKCompressionService,
+    DryRunResult, DynService, DynServiceError, Outcome,
 };
 
 // Public exports - Types
@@ -139,16 +141,28 @@ pub mod ipfs;
 #[cfg_attr(docsrs, doc(cfg(feature = "profiling")))]
 pub mod profiling;
 
-#[cfg(target_arch = "wasm32")]
-#[cfg_attr(docsrs, doc(cfg(target_arch = "wasm32")))]
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_arch = "wasm32", feature = "wasm"))))]
 pub mod wasm;
 
+#[cfg(any(test, feature = "testing"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 /// The official PoD Protocol program ID on Solana
 pub const PROGRAM_ID: Pubkey = solana_sdk::pubkey!("PoD1111111111111111111111111111111111111111");
 
 /// SDK version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Newest message wire-format version this SDK writes (high byte major, low byte minor)
+///
+/// Messages are sealed with the oldest version that can represent them, so
+/// messages without a content type are still written as `0x0100`. Readers
+/// accept messages whose major version is not newer than their own, and read
+/// content stored without a version as version 0.
+pub const PROTOCOL_VERSION: u16 = 0x0200;
+
 /// SDK build information
 pub mod build_info {
     /// Git commit hash (if available)
@@ -9,22 +9,26 @@ use std::collections::HashMap;
 
 use anchor_client::Program;
 use async_trait::async_trait;
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
 use solana_sdk::{
+    message::Message,
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
     system_instruction,
 };
 
 // Import the actual program types
-use pod_com::{AgentAccount, ChannelAccount, MessageAccount};
+use pod_com::{AgentAccount, ChannelAccount, ChannelParticipant, MessageAccount};
 
 use pod_sdk_types::{
-    RegisterAgentRequest, MessageType, MessageStatus, ChannelVisibility,
+    pda, RegisterAgentRequest, MessageType, MessageStatus, ChannelVisibility, MAX_METADATA_URI_LENGTH,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    error::{AgentError, MessageError, NetworkError, PodComError, Result},
+    services::base::{AgentConfig, BaseService, Outcome, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    services::message::pack_instructions,
     types::{
         CreateAgentParams, UpdateAgentParams, FilterOptions,
         BatchOperationResult, RequestOptions,
@@ -32,6 +36,8 @@ use crate::{
     utils::{
         account::{derive_agent_pda, validate_agent_account},
         crypto::hash_message,
+        pagination::{Page, PageRequest},
+        transport::http_client,
     },
     client::BaseService,
 };
@@ -46,53 +52,114 @@ impl AgentService {
     /// Create a new agent service
     pub fn new(config: ServiceConfig) -> Self {
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("agent"),
         }
     }
 
+    /// Download and parse the metadata document an agent points to
+    /// 
+    /// `ipfs://` and `ar://` URIs are resolved through the gateways in
+    /// [`AgentConfig`]; `https://` URIs are fetched directly. Documents larger
+    /// than the configured limit are rejected without being read in full.
+    pub async fn fetch_metadata(&self, agent: &AgentAccount) -> Result<AgentMetadata> {
+        let operation_name = "fetch_metadata";
+        
+        self.base.execute_operation(operation_name, || async {
+            let config = self.base.config().agent_config.clone().unwrap_or_default();
+            let url = resolve_metadata_uri(&agent.metadata_uri, &config)?;
+            
+            // Metadata is external content, so it follows the IPFS proxy settings
+            let client = http_client(self.base.config().timeout, self.base.config().ipfs_proxy.clone())?;
+            let body = download_limited(&client, &url, &agent.metadata_uri, config.metadata_max_bytes).await?;
+            
+            serde_json::from_slice(&body).map_err(|e| -> PodComError {
+                AgentError::InvalidMetadata {
+                    uri: agent.metadata_uri.clone(),
+                    reason: e.to_string(),
+                }.into()
+            })
+        }).await
+    }
+
     /// Create a new agent account
+    /// 
+    /// Registers `owner` as an agent, using `params.description` as its
+    /// metadata URI, and returns the new account.
     pub async fn create_agent(
         &self,
         owner: &Keypair,
         params: CreateAgentParams,
-    ) -> Result<(Pubkey, AgentAccount)> {
-        let operation_name = "create_agent";
+    ) -> Result<Outcome<(Pubkey, AgentAccount)>> {
+        let request = RegisterAgentRequest {
+            capabilities: params.capabilities,
+            metadata_uri: params.description.clone(),
+        };
+        let agent_pda = match self.register("create_agent", Some(owner), request).await? {
+            Outcome::Sent(agent_pda) => agent_pda,
+            Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+        };
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            // Derive agent PDA
-            let (agent_pda, _bump) = derive_agent_pda(&owner.pubkey(), &params.name)?;
-            
-            // Build instruction
-            let ix = program
-                .request()
-                .accounts(pod_com::accounts::RegisterAgent {
-                    agent_account: agent_pda,
-                    signer: owner.pubkey(),
-                    system_program: solana_sdk::system_program::id(),
-                })
-                .args(pod_com::instruction::RegisterAgent {
-                    capabilities: params.capabilities,
-                    metadata_uri: params.description.clone(),
-                })
-                .signer(owner);
+        // Fetch created account
+        let agent_account = self.get_agent_account(&agent_pda).await?;
+        tracing::info!(agent_address = %agent_pda, name = %params.name, "Agent created successfully");
+        
+        Ok(Outcome::Sent((agent_pda, agent_account)))
+    }
 
-            // Send transaction
-            let signature = ix.send()?;
+    /// Register the payer as an agent
+    /// 
+    /// The request is validated before the instruction is built, so an empty
+    /// or oversized metadata URI fails locally instead of on-chain. Returns
+    /// the new agent account's address.
+    pub async fn register_agent(&self, request: RegisterAgentRequest) -> Result<Outcome<Pubkey>> {
+        self.register("register_agent", None, request).await
+    }
+
+    /// Register `owner`, or the payer if `None`, as an agent
+    async fn register(
+        &self,
+        operation_name: &str,
+        owner: Option<&Keypair>,
+        request: RegisterAgentRequest,
+    ) -> Result<Outcome<Pubkey>> {
+        request.validate()?;
+        
+        self.base.execute_transaction(operation_name, || async {
+            let owner_pubkey = match owner {
+                Some(owner) => owner.pubkey(),
+                None => self.base.program()?.payer(),
+            };
+            let (agent_pda, _) = pda::agent_pda(&self.base.config().effective_program_id(), &owner_pubkey);
             
-            // Fetch created account
-            let agent_account = self.get_agent_account(&agent_pda).await?;
+            let ix = self.base.request(|builder| {
+                let builder = builder
+                    .accounts(pod_com::accounts::RegisterAgent {
+                        agent_account: agent_pda,
+                        signer: owner_pubkey,
+                        system_program: solana_sdk::system_program::id(),
+                    })
+                    .args(pod_com::instruction::RegisterAgent {
+                        capabilities: request.capabilities,
+                        metadata_uri: request.metadata_uri.clone(),
+                    });
+                match owner {
+                    Some(owner) => builder.signer(owner),
+                    None => builder,
+                }
+            }).await?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             tracing::info!(
                 agent_address = %agent_pda,
                 signature = %signature,
-                owner = %owner.pubkey(),
-                name = %params.name,
-                "Agent created successfully"
+                owner = %owner_pubkey,
+                "Agent registered successfully"
             );
-
-            Ok((agent_pda, agent_account))
+            
+            Ok(Outcome::Sent(agent_pda))
         }).await
     }
 
@@ -102,12 +169,10 @@ impl AgentService {
         agent_address: &Pubkey,
         owner: &Keypair,
         params: UpdateAgentParams,
-    ) -> Result<AgentAccount> {
+    ) -> Result<Outcome<AgentAccount>> {
         let operation_name = "update_agent";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             // Verify ownership
             let agent_account = self.get_agent_account(agent_address).await?;
             if agent_account.owner != owner.pubkey() {
@@ -118,20 +183,23 @@ impl AgentService {
             }
             
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
                 })
                 .args(pod_com::instruction::UpdateAgent {
                     capabilities: params.capabilities,
-                    metadata_uri: params.description,
+                    metadata_uri: params.description.clone(),
                 })
-                .signer(owner);
+                .signer(owner)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(agent_address);
             
             // Fetch updated account
             let updated_account = self.get_agent_account(agent_address).await?;
@@ -143,7 +211,7 @@ impl AgentService {
                 "Agent updated successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -151,12 +219,19 @@ impl AgentService {
     pub async fn get_agent_account(&self, agent_address: &Pubkey) -> Result<AgentAccount> {
         let operation_name = "get_agent_account";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<AgentAccount>(*agent_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let mut account_data = self.base.fetch_account::<AgentAccount>(agent_address).await?;
             validate_agent_account(&account_data)?;
             
+            // Stopgap until the duplicated reputation fields are unified
+            if account_data.normalize() {
+                tracing::warn!(
+                    agent_address = %agent_address,
+                    reputation = account_data.reputation,
+                    "Agent account had divergent reputation fields; using on-chain values"
+                );
+            }
+            
             Ok(account_data)
         }).await
     }
@@ -169,27 +244,26 @@ impl AgentService {
     ) -> Result<(Pubkey, AgentAccount)> {
         let operation_name = "get_agent_by_name";
         
-        self.base.execute_operation(operation_name, async {
-            let (agent_pda, _bump) = derive_agent_pda(owner, name)?;
+        self.base.execute_operation(operation_name, || async {
+            #[allow(deprecated)]
+            let (agent_pda, _bump) = derive_agent_pda(owner, name, &self.base.config().effective_program_id())?;
             let agent_account = self.get_agent_account(&agent_pda).await?;
             
             Ok((agent_pda, agent_account))
         }).await
     }
 
-    /// List all agents owned by a specific owner
-    pub async fn list_agents_by_owner(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, AgentAccount)>> {
+    /// List agents owned by a specific owner, most recent first
+    pub async fn list_agents_by_owner(
+        &self,
+        owner: &Pubkey,
+        page: PageRequest,
+    ) -> Result<Page<(Pubkey, AgentAccount)>> {
         let operation_name = "list_agents_by_owner";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all agent accounts with the specified owner
-            let accounts = program
-                .accounts::<AgentAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
                 
             let mut owned_agents = Vec::new();
             
@@ -203,29 +277,50 @@ impl AgentService {
             // Sort by creation time (most recent first)
             owned_agents.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
             
-            Ok(owned_agents)
+            Page::from_sorted(owned_agents, &page)
+        }).await
+    }
+
+    /// Find agents holding every capability in `mask`, highest reputation first
+    ///
+    /// Agent accounts are scanned page by page, and the scan stops as soon
+    /// as `limit` matches are found. With a limit the result is therefore the
+    /// first matches in scan order, ranked by reputation, rather than the
+    /// highest-reputation matches of the whole protocol.
+    pub async fn find_agents_by_capability(&self, mask: u64, limit: Option<u32>) -> Result<Vec<AgentAccount>> {
+        let operation_name = "find_agents_by_capability";
+        
+        self.base.execute_operation(operation_name, || async {
+            let mut matches = CapabilityMatches::new(mask, limit);
+            
+            self.base.scan_accounts_while::<AgentAccount, _>(Vec::new(), AGENT_SCAN_PAGE_SIZE, |page| {
+                for (_, agent) in page {
+                    if matches.is_full() {
+                        return Ok(false);
+                    }
+                    matches.offer(agent);
+                }
+                Ok(!matches.is_full())
+            }).await?;
+            
+            Ok(matches.into_agents())
         }).await
     }
 
-    /// Get agent channels
+    /// Get the channels an agent actively participates in, newest first
+    ///
+    /// Channel accounts do not list their members. Each join creates a
+    /// participant account instead, so the agent's channels are found by
+    /// scanning participant accounts for `agent_address`.
     pub async fn get_agent_channels(&self, agent_address: &Pubkey) -> Result<Vec<(Pubkey, ChannelAccount)>> {
         let operation_name = "get_agent_channels";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            // Get all channel accounts where this agent is a participant
-            let accounts = program
-                .accounts::<ChannelAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
-                
+        self.base.execute_operation(operation_name, || async {
             let mut agent_channels = Vec::new();
-            
-            for (pubkey, account) in accounts {
-                if account.participants.contains(agent_address) {
-                    agent_channels.push((pubkey, account));
+            for membership in self.agent_memberships(agent_address).await? {
+                if membership.is_active {
+                    let channel = self.base.fetch_account::<ChannelAccount>(&membership.channel).await?;
+                    agent_channels.push((membership.channel, channel));
                 }
             }
             
@@ -236,17 +331,193 @@ impl AgentService {
         }).await
     }
 
-    /// Activate an agent (set status to active)
+    /// Participant accounts of `agent_address`, including channels it has left
+    async fn agent_memberships(&self, agent_address: &Pubkey) -> Result<Vec<ChannelParticipant>> {
+        let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            PARTICIPANT_AGENT_OFFSET,
+            agent_address.as_ref(),
+        ))];
+        
+        let mut memberships = Vec::new();
+        self.base.scan_accounts::<ChannelParticipant, _>(filters, MEMBERSHIP_SCAN_PAGE_SIZE, |page| {
+            memberships.extend(page.into_iter().map(|(_, membership)| membership));
+            Ok(())
+        }).await?;
+        
+        Ok(memberships)
+    }
+
+    /// Broadcast a message to every channel the agent has joined
+    ///
+    /// Channels where the agent may not broadcast are skipped and reported.
+    /// Posts are packed into as few transactions as fit. What the broadcast
+    /// costs the agent, the rent for every message account plus the
+    /// transaction fees when the agent is the fee payer, is checked against
+    /// its balance before anything is posted. Each transaction succeeds or
+    /// fails on its own, so a failed batch is reported without undoing the
+    /// posts before it.
+    pub async fn broadcast_to_all_channels(
+        &self,
+        agent: &Keypair,
+        body: &str,
+        message_type: MessageType,
+    ) -> Result<ChannelBroadcast> {
+        let operation_name = "broadcast_to_all_channels";
+        
+        self.base.execute_transaction(operation_name, || async {
+            let program = self.base.program()?;
+            let program_id = self.base.config().effective_program_id();
+            let rpc_client = &self.base.config().async_rpc_client;
+            let payer = program.payer();
+            let (agent_pda, _) = pda::agent_pda(&program_id, &agent.pubkey());
+            
+            // Resolve each channel's broadcast eligibility
+            let mut candidates = Vec::new();
+            for membership in self.agent_memberships(&agent_pda).await? {
+                let channel = self.base.fetch_account::<ChannelAccount>(&membership.channel).await?;
+                candidates.push(BroadcastCandidate {
+                    channel: membership.channel,
+                    channel_active: channel.is_active,
+                    participant_active: membership.is_active,
+                });
+            }
+            
+            let plan = plan_broadcast(candidates);
+            
+            let mut posts = Vec::with_capacity(plan.targets.len());
+            for channel_pda in &plan.targets {
+                let nonce: u64 = rand::random();
+                let (message_pda, _) = Pubkey::find_program_address(
+                    &[b"channel_message", channel_pda.as_ref(), agent.pubkey().as_ref(), &nonce.to_le_bytes()],
+                    &program_id,
+                );
+                let (participant_pda, _) = Pubkey::find_program_address(
+                    &[b"participant", channel_pda.as_ref(), agent_pda.as_ref()],
+                    &program_id,
+                );
+                
+                let post = program
+                    .request()
+                    .accounts(pod_com::accounts::BroadcastMessage {
+                        channel_account: *channel_pda,
+                        participant_account: participant_pda,
+                        agent_account: agent_pda,
+                        message_account: message_pda,
+                        user: agent.pubkey(),
+                        system_program: solana_sdk::system_program::id(),
+                    })
+                    .args(pod_com::instruction::BroadcastMessage {
+                        content: body.to_string(),
+                        message_type: to_program_message_type(message_type),
+                        reply_to: None,
+                        _nonce: nonce,
+                    })
+                    .instructions()?;
+                posts.extend(post);
+            }
+            let reserved = self.base.compute_budget_reserve();
+            let batches = pack_instructions(posts, &reserved, &payer);
+            
+            // Detect a balance shortfall before posting anywhere
+            let message_rent = rpc_client
+                .get_minimum_balance_for_rent_exemption(CHANNEL_MESSAGE_SPACE)
+                .await
+                .map_err(|e| -> PodComError {
+                    NetworkError::RpcFailed {
+                        method: "getMinimumBalanceForRentExemption".to_string(),
+                        reason: e.to_string(),
+                    }.into()
+                })?;
+            let mut transaction_fees = 0u64;
+            if payer == agent.pubkey() {
+                let blockhash = rpc_client.get_latest_blockhash().await.map_err(|e| -> PodComError {
+                    NetworkError::RpcFailed {
+                        method: "getLatestBlockhash".to_string(),
+                        reason: e.to_string(),
+                    }.into()
+                })?;
+                for batch in &batches {
+                    let message = Message::new_with_blockhash(&[reserved.as_slice(), batch].concat(), Some(&payer), &blockhash);
+                    let fee = rpc_client.get_fee_for_message(&message).await.map_err(|e| -> PodComError {
+                        NetworkError::RpcFailed {
+                            method: "getFeeForMessage".to_string(),
+                            reason: e.to_string(),
+                        }.into()
+                    })?;
+                    transaction_fees = transaction_fees.saturating_add(fee);
+                }
+            }
+            let total_cost = plan.cost(message_rent, transaction_fees);
+            let balance = rpc_client.get_balance(&agent.pubkey()).await.map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "getBalance".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?;
+            check_balance(total_cost, balance)?;
+            
+            let mut targets = plan.targets.iter();
+            let mut broadcast = ChannelBroadcast {
+                posted: Vec::with_capacity(plan.targets.len()),
+                failed: Vec::new(),
+                skipped: plan.skipped,
+                total_cost,
+            };
+            for batch in batches {
+                let channels: Vec<Pubkey> = targets.by_ref().take(batch.len()).copied().collect();
+                
+                let sent = match self.base
+                    .request(|builder| {
+                        batch.iter().cloned()
+                            .fold(builder, |builder, ix| builder.instruction(ix))
+                            .signer(agent)
+                    })
+                    .await
+                {
+                    Ok(request) => self.base.send(request).await,
+                    Err(e) => Err(e),
+                };
+                match sent {
+                    Ok(outcome) => {
+                        if matches!(outcome, Outcome::Sent(_)) {
+                            for channel_pda in &channels {
+                                self.base.invalidate(channel_pda);
+                            }
+                        }
+                        broadcast.posted.extend(channels.into_iter().map(|channel| (channel, outcome.clone())));
+                    }
+                    Err(e) => {
+                        broadcast.failed.extend(channels.into_iter().map(|channel| (channel, e.to_string())));
+                    }
+                }
+            }
+            
+            tracing::info!(
+                agent = %agent.pubkey(),
+                posted = broadcast.posted.len(),
+                failed = broadcast.failed.len(),
+                skipped = broadcast.skipped.len(),
+                total_cost = broadcast.total_cost,
+                "Broadcast to agent channels completed"
+            );
+            
+            Ok(broadcast)
+        }).await
+    }
+
+    /// Activate an agent
+    ///
+    /// pod-com's agent account has no active flag, so this sends an
+    /// `update_agent` that changes nothing but `last_updated`.
+    #[deprecated(since = "2.0.0", note = "pod-com has no agent active flag; this only refreshes `last_updated`")]
     pub async fn activate_agent(
         &self,
         agent_address: &Pubkey,
         owner: &Keypair,
-    ) -> Result<AgentAccount> {
+    ) -> Result<Outcome<AgentAccount>> {
         let operation_name = "activate_agent";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             // Verify ownership
             let agent_account = self.get_agent_account(agent_address).await?;
             if agent_account.owner != owner.pubkey() {
@@ -257,8 +528,7 @@ impl AgentService {
             }
             
             // Build instruction - using update_agent since pod-com doesn't have separate activate/deactivate
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
@@ -267,10 +537,14 @@ impl AgentService {
                     capabilities: None, // Keep current capabilities
                     metadata_uri: None, // Keep current metadata
                 })
-                .signer(owner);
+                .signer(owner)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(agent_address);
             
             // Fetch updated account
             let updated_account = self.get_agent_account(agent_address).await?;
@@ -282,21 +556,23 @@ impl AgentService {
                 "Agent activated successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
-    /// Deactivate an agent (set status to inactive)
+    /// Deactivate an agent
+    ///
+    /// pod-com's agent account has no active flag, so this sends an
+    /// `update_agent` that changes nothing but `last_updated`.
+    #[deprecated(since = "2.0.0", note = "pod-com has no agent active flag; this only refreshes `last_updated`")]
     pub async fn deactivate_agent(
         &self,
         agent_address: &Pubkey,
         owner: &Keypair,
-    ) -> Result<AgentAccount> {
+    ) -> Result<Outcome<AgentAccount>> {
         let operation_name = "deactivate_agent";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             // Verify ownership
             let agent_account = self.get_agent_account(agent_address).await?;
             if agent_account.owner != owner.pubkey() {
@@ -307,8 +583,7 @@ impl AgentService {
             }
             
             // Build instruction - using update_agent since pod-com doesn't have separate activate/deactivate
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
@@ -317,10 +592,14 @@ impl AgentService {
                     capabilities: None, // Keep current capabilities
                     metadata_uri: None, // Keep current metadata
                 })
-                .signer(owner);
+                .signer(owner)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(agent_address);
             
             // Fetch updated account
             let updated_account = self.get_agent_account(agent_address).await?;
@@ -332,7 +611,7 @@ impl AgentService {
                 "Agent deactivated successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -341,10 +620,10 @@ impl AgentService {
         &self,
         agent_address: &Pubkey,
         owner: &Keypair,
-    ) -> Result<()> {
+    ) -> Result<Outcome<()>> {
         let operation_name = "delete_agent";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             let program = self.base.program()?;
             
             // Verify ownership
@@ -372,7 +651,10 @@ impl AgentService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             tracing::info!(
                 agent_address = %agent_address,
@@ -381,7 +663,7 @@ impl AgentService {
                 "Agent deleted successfully"
             );
 
-            Ok(())
+            Ok(Outcome::Sent(()))
         }).await
     }
 
@@ -389,7 +671,7 @@ impl AgentService {
     pub async fn get_agent_stats(&self, agent_address: &Pubkey) -> Result<AgentStats> {
         let operation_name = "get_agent_stats";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let agent_account = self.get_agent_account(agent_address).await?;
             let channels = self.get_agent_channels(agent_address).await?;
             
@@ -418,17 +700,12 @@ impl AgentService {
         agent_address: &Pubkey,
         channels: &[(Pubkey, ChannelAccount)],
     ) -> Result<u64> {
-        let program = self.base.program()?;
         let mut total_messages = 0u64;
         
         // Count messages in each channel where this agent is a participant
         for (channel_pubkey, _) in channels {
             // Fetch message accounts for this channel and count messages from this agent
-            let message_accounts = program
-                .accounts::<MessageAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await
+            let message_accounts = self.base.fetch_program_accounts::<MessageAccount>().await
                 .unwrap_or_default();
                 
             for (_, message) in message_accounts {
@@ -488,6 +765,223 @@ impl AgentService {
     }
 }
 
+/// Outcome of broadcasting to all of an agent's channels
+#[derive(Debug, Clone)]
+pub struct ChannelBroadcast {
+    /// Channels posted to, with the outcome of the transaction carrying each post
+    /// 
+    /// In dry-run mode every batch is simulated, so each entry carries its
+    /// batch's [`Outcome::Simulated`].
+    pub posted: Vec<(Pubkey, Outcome)>,
+    /// Channels whose transaction failed, with the error
+    pub failed: Vec<(Pubkey, String)>,
+    /// Channels skipped, with the reason
+    pub skipped: Vec<(Pubkey, String)>,
+    /// Lamports the broadcast was estimated to cost the agent
+    pub total_cost: u64,
+}
+
+/// A channel the agent belongs to, with what is needed to decide whether to post
+#[derive(Debug, Clone)]
+struct BroadcastCandidate {
+    channel: Pubkey,
+    channel_active: bool,
+    participant_active: bool,
+}
+
+/// Channels to post to and channels to skip
+#[derive(Debug, Clone, Default)]
+struct BroadcastPlan {
+    targets: Vec<Pubkey>,
+    skipped: Vec<(Pubkey, String)>,
+}
+
+impl BroadcastPlan {
+    /// Lamports the agent pays: rent for each target's message account plus `transaction_fees`
+    /// 
+    /// Channel `fee_per_message` is only charged on join, so it is not part
+    /// of the cost.
+    fn cost(&self, message_rent: u64, transaction_fees: u64) -> u64 {
+        message_rent
+            .saturating_mul(self.targets.len() as u64)
+            .saturating_add(transaction_fees)
+    }
+}
+
+/// Fail if `balance` cannot cover `required` lamports
+fn check_balance(required: u64, balance: u64) -> Result<()> {
+    if balance < required {
+        return Err(MessageError::InsufficientFunds {
+            required,
+            available: balance,
+        }.into());
+    }
+    Ok(())
+}
+
+/// Split channels into broadcast targets and skipped channels
+fn plan_broadcast(candidates: Vec<BroadcastCandidate>) -> BroadcastPlan {
+    let mut plan = BroadcastPlan::default();
+    
+    for candidate in candidates {
+        if !candidate.channel_active {
+            plan.skipped.push((candidate.channel, "channel is inactive".to_string()));
+        } else if !candidate.participant_active {
+            plan.skipped.push((candidate.channel, "agent is not an active participant".to_string()));
+        } else {
+            plan.targets.push(candidate.channel);
+        }
+    }
+    
+    plan
+}
+
+/// Convert an SDK message type to the program's representation
+pub(crate) fn to_program_message_type(message_type: MessageType) -> pod_com::MessageType {
+    match message_type {
+        MessageType::Text => pod_com::MessageType::Text,
+        MessageType::Data => pod_com::MessageType::Data,
+        MessageType::Command => pod_com::MessageType::Command,
+        MessageType::Response => pod_com::MessageType::Response,
+        MessageType::Custom(code) => pod_com::MessageType::Custom(code),
+    }
+}
+
+/// Convert the program's message type to the SDK representation
+pub(crate) fn from_program_message_type(message_type: pod_com::MessageType) -> MessageType {
+    match message_type {
+        pod_com::MessageType::Text => MessageType::Text,
+        pod_com::MessageType::Data => MessageType::Data,
+        pod_com::MessageType::Command => MessageType::Command,
+        pod_com::MessageType::Response => MessageType::Response,
+        pod_com::MessageType::Custom(code) => MessageType::Custom(code),
+    }
+}
+
+/// Agent profile stored at an agent's `metadata_uri`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub avatar_uri: Option<String>,
+    /// Capability names as produced by `pod_sdk_types::capabilities::names`
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub endpoints: Vec<AgentEndpoint>,
+}
+
+/// A network endpoint an agent can be reached at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentEndpoint {
+    /// Protocol spoken at the endpoint, e.g. `"https"` or `"websocket"`
+    pub protocol: String,
+    pub url: String,
+}
+
+/// Agents whose capabilities include a mask, up to a limit, ranked by reputation
+struct CapabilityMatches {
+    mask: u64,
+    limit: usize,
+    /// Sorted by reputation, highest first
+    agents: Vec<AgentAccount>,
+}
+
+impl CapabilityMatches {
+    fn new(mask: u64, limit: Option<u32>) -> Self {
+        Self {
+            mask,
+            limit: limit.map_or(usize::MAX, |l| l as usize),
+            agents: Vec::new(),
+        }
+    }
+
+    /// Whether `limit` matches have been kept
+    fn is_full(&self) -> bool {
+        self.agents.len() >= self.limit
+    }
+
+    /// Keep `agent` if it has every capability in the mask and ranks within the limit
+    fn offer(&mut self, agent: AgentAccount) {
+        if agent.capabilities & self.mask != self.mask {
+            return;
+        }
+        
+        // Insert after agents with equal reputation so earlier matches win ties
+        let position = self.agents.partition_point(|kept| kept.reputation >= agent.reputation);
+        if position < self.limit {
+            self.agents.insert(position, agent);
+            self.agents.truncate(self.limit);
+        }
+    }
+
+    fn into_agents(self) -> Vec<AgentAccount> {
+        self.agents
+    }
+}
+
+/// Map a metadata URI to the HTTP(S) URL it is downloaded from
+fn resolve_metadata_uri(uri: &str, config: &AgentConfig) -> Result<String> {
+    let invalid = |reason: &str| -> PodComError {
+        AgentError::InvalidMetadataUri {
+            uri: uri.to_string(),
+            reason: reason.to_string(),
+        }.into()
+    };
+    
+    if uri.len() > MAX_METADATA_URI_LENGTH {
+        return Err(invalid(&format!("longer than {} bytes", MAX_METADATA_URI_LENGTH)));
+    }
+    
+    let (scheme, rest) = uri.split_once("://").ok_or_else(|| invalid("missing scheme"))?;
+    if rest.is_empty() {
+        return Err(invalid("missing resource"));
+    }
+    
+    match scheme.to_ascii_lowercase().as_str() {
+        "https" => Ok(uri.to_string()),
+        "ipfs" => {
+            // Accept the legacy `ipfs://ipfs/<cid>` form as well
+            let path = rest.strip_prefix("ipfs/").unwrap_or(rest);
+            Ok(format!("{}/ipfs/{}", config.ipfs_gateway.trim_end_matches('/'), path))
+        }
+        "ar" => Ok(format!("{}/{}", config.arweave_gateway.trim_end_matches('/'), rest)),
+        other => Err(invalid(&format!("unsupported scheme '{}' (expected ipfs, https or ar)", other))),
+    }
+}
+
+/// Download `url`, failing once the body exceeds `limit` bytes
+async fn download_limited(
+    client: &reqwest::Client,
+    url: &str,
+    uri: &str,
+    limit: usize,
+) -> Result<Vec<u8>> {
+    let too_large = || -> PodComError {
+        AgentError::MetadataTooLarge {
+            uri: uri.to_string(),
+            limit,
+        }.into()
+    };
+    
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    if response.content_length().map_or(false, |len| len > limit as u64) {
+        return Err(too_large());
+    }
+    
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    
+    Ok(body)
+}
+
 /// Agent statistics
 #[derive(Debug, Clone)]
 pub struct AgentStats {
@@ -575,9 +1069,12 @@ impl BaseService for AgentService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -588,12 +1085,27 @@ impl BaseService for AgentService {
     fn service_name(&self) -> &'static str {
         "agent"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
 }
 
+// Agent accounts fetched per RPC call when scanning
+const AGENT_SCAN_PAGE_SIZE: usize = 100;
+// Participant accounts fetched per RPC call when scanning memberships
+const MEMBERSHIP_SCAN_PAGE_SIZE: usize = 100;
+// Offset of `participant` (the agent PDA) in a pod-com ChannelParticipant
+const PARTICIPANT_AGENT_OFFSET: usize = 8 + 32;
+// Size of the message account pod-com creates for each broadcast post
+const CHANNEL_MESSAGE_SPACE: usize = 8 + 32 + 32 + 33 + 8 + 9 + (4 + 1000) + 1 + 1 + 6;
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::test_config;
+    use crate::testing::{MockRpc, SIMULATED_UNITS};
+    use solana_rpc_client_api::request::RpcRequest;
 
     #[tokio::test]
     async fn test_agent_service_creation() {
@@ -602,4 +1114,321 @@ mod tests {
         assert_eq!(service.service_name(), "agent");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_register_agent_validates_before_sending() {
+        use crate::error::ValidationError;
+        
+        let service = AgentService::new(test_config());
+        let request = |metadata_uri: String| RegisterAgentRequest { capabilities: 1, metadata_uri };
+        
+        let result = service.register_agent(request("a".repeat(MAX_METADATA_URI_LENGTH + 1))).await;
+        assert!(matches!(
+            result,
+            Err(PodComError::Validation(ValidationError::TooLong { actual, max: MAX_METADATA_URI_LENGTH, .. }))
+                if actual == MAX_METADATA_URI_LENGTH + 1
+        ));
+        assert!(matches!(
+            service.register_agent(request(String::new())).await,
+            Err(PodComError::Validation(ValidationError::EmptyField { .. }))
+        ));
+        
+        // A valid request gets past validation and fails only for lack of a program
+        assert!(matches!(
+            service.register_agent(request("a".repeat(MAX_METADATA_URI_LENGTH))).await,
+            Err(PodComError::NotInitialized)
+        ));
+    }
+
+    fn three_channels() -> (Vec<BroadcastCandidate>, [Pubkey; 3]) {
+        let channels = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let candidates = vec![
+            BroadcastCandidate { channel: channels[0], channel_active: true, participant_active: true },
+            BroadcastCandidate { channel: channels[1], channel_active: true, participant_active: false },
+            BroadcastCandidate { channel: channels[2], channel_active: true, participant_active: true },
+        ];
+        (candidates, channels)
+    }
+
+    #[test]
+    fn test_resolve_metadata_uri() {
+        let config = AgentConfig::default();
+        
+        assert_eq!(
+            resolve_metadata_uri("ipfs://bafyabc/agent.json", &config).unwrap(),
+            "https://ipfs.io/ipfs/bafyabc/agent.json"
+        );
+        assert_eq!(
+            resolve_metadata_uri("ipfs://ipfs/bafyabc", &config).unwrap(),
+            "https://ipfs.io/ipfs/bafyabc"
+        );
+        assert_eq!(resolve_metadata_uri("ar://tx123", &config).unwrap(), "https://arweave.net/tx123");
+        assert_eq!(
+            resolve_metadata_uri("https://example.com/a.json", &config).unwrap(),
+            "https://example.com/a.json"
+        );
+        
+        for bad in ["ftp://example.com/a.json", "http://example.com/a.json", "example.com", "ipfs://"] {
+            assert!(matches!(
+                resolve_metadata_uri(bad, &config),
+                Err(PodComError::Agent(AgentError::InvalidMetadataUri { .. }))
+            ), "{} should be rejected", bad);
+        }
+        
+        let long = format!("https://example.com/{}", "a".repeat(MAX_METADATA_URI_LENGTH));
+        assert!(resolve_metadata_uri(&long, &config).is_err());
+        
+        // Every scheme the registration builder accepts can be fetched
+        for scheme in pod_sdk_types::METADATA_URI_SCHEMES {
+            assert!(resolve_metadata_uri(&format!("{}agent.json", scheme), &config).is_ok(), "{}", scheme);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_metadata_through_gateway() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let gateway = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ipfs/bafyagent"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "helper",
+                "capabilities": ["AI_CHAT", "custom(2)"],
+                "endpoints": [{ "protocol": "https", "url": "https://agent.example" }],
+            })))
+            .mount(&gateway)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ipfs/bafyhuge"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b' '; 4096]))
+            .mount(&gateway)
+            .await;
+        
+        let config = AgentConfig {
+            ipfs_gateway: gateway.uri(),
+            metadata_max_bytes: 1024,
+            ..AgentConfig::default()
+        };
+        let client = reqwest::Client::new();
+        
+        let url = resolve_metadata_uri("ipfs://bafyagent", &config).unwrap();
+        let body = download_limited(&client, &url, "ipfs://bafyagent", config.metadata_max_bytes).await.unwrap();
+        let metadata: AgentMetadata = serde_json::from_slice(&body).unwrap();
+        assert_eq!(metadata.name, "helper");
+        assert_eq!(metadata.description, "");
+        assert_eq!(metadata.capabilities, vec!["AI_CHAT", "custom(2)"]);
+        assert_eq!(metadata.endpoints[0].url, "https://agent.example");
+        
+        let url = resolve_metadata_uri("ipfs://bafyhuge", &config).unwrap();
+        assert!(matches!(
+            download_limited(&client, &url, "ipfs://bafyhuge", config.metadata_max_bytes).await,
+            Err(PodComError::Agent(AgentError::MetadataTooLarge { limit: 1024, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_broadcast_skips_channel_without_permission() {
+        let (candidates, channels) = three_channels();
+        let plan = plan_broadcast(candidates);
+        
+        assert_eq!(plan.targets, vec![channels[0], channels[2]]);
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].0, channels[1]);
+    }
+
+    #[test]
+    fn test_broadcast_balance_shortfall_detected_up_front() {
+        let (candidates, _) = three_channels();
+        let plan = plan_broadcast(candidates);
+        
+        // Rent for the two posted messages, plus the transaction fees
+        let required = plan.cost(8_000_000, 10_000);
+        assert_eq!(required, 16_010_000);
+        assert!(check_balance(required, 16_010_000).is_ok());
+        assert!(matches!(
+            check_balance(required, 16_009_999),
+            Err(PodComError::Message(MessageError::InsufficientFunds { required: 16_010_000, available: 16_009_999 }))
+        ));
+    }
+
+    /// Dry-run service whose agent has joined three channels, having left the second
+    ///
+    /// The agent's wallet holds `balance` lamports and is not the fee payer.
+    async fn broadcasting_service(balance: u64) -> (AgentService, Keypair, [Pubkey; 3], MockRpc) {
+        use anchor_client::{Client, Cluster};
+        use crate::testing::{account_data, zeroed};
+        
+        let agent = Keypair::new();
+        let (agent_pda, _) = Pubkey::find_program_address(&[b"agent", agent.pubkey().as_ref()], &crate::PROGRAM_ID);
+        let channels = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        
+        let mut accounts = HashMap::new();
+        for (i, channel_pda) in channels.iter().enumerate() {
+            let mut channel: ChannelAccount = zeroed();
+            channel.is_active = true;
+            accounts.insert(channel_pda.to_string(), account_data(&channel));
+            
+            let (participant_pda, _) = Pubkey::find_program_address(
+                &[b"participant", channel_pda.as_ref(), agent_pda.as_ref()],
+                &crate::PROGRAM_ID,
+            );
+            let mut membership: ChannelParticipant = zeroed();
+            membership.channel = *channel_pda;
+            membership.participant = agent_pda;
+            membership.is_active = i != 1;
+            accounts.insert(participant_pda.to_string(), account_data(&membership));
+        }
+        // Another agent's membership, which the scan must not pick up
+        let mut other: ChannelParticipant = zeroed();
+        other.channel = channels[0];
+        other.participant = Pubkey::new_unique();
+        other.is_active = true;
+        accounts.insert(Pubkey::new_unique().to_string(), account_data(&other));
+        
+        let rpc = MockRpc::serving_with(accounts, move |request, _| match request {
+            RpcRequest::GetBalance => crate::testing::with_context(serde_json::json!(balance)),
+            other => panic!("unexpected RPC request {}", other),
+        });
+        let mut config = test_config();
+        config.dry_run = true;
+        config.async_rpc_client = rpc.client();
+        
+        let mut service = AgentService::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(Keypair::new())).program(crate::PROGRAM_ID).unwrap();
+        service.initialize(program).await.unwrap();
+        (service, agent, channels, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_posts_to_joined_channels() {
+        use solana_sdk::rent::Rent;
+        
+        let cost = 2 * Rent::default().minimum_balance(CHANNEL_MESSAGE_SPACE);
+        let (service, agent, channels, rpc) = broadcasting_service(cost).await;
+        
+        let broadcast = service.broadcast_to_all_channels(&agent, "hello", MessageType::Text).await.unwrap();
+        let mut posted: Vec<Pubkey> = broadcast.posted.iter().map(|(channel, _)| *channel).collect();
+        posted.sort();
+        let mut expected = vec![channels[0], channels[2]];
+        expected.sort();
+        assert_eq!(posted, expected);
+        assert!(broadcast.posted.iter().all(|(_, outcome)| outcome.simulated().is_some()));
+        assert_eq!(broadcast.skipped.len(), 1);
+        assert_eq!(broadcast.skipped[0].0, channels[1]);
+        assert!(broadcast.failed.is_empty());
+        assert_eq!(broadcast.total_cost, cost);
+        assert!(rpc.methods().contains(&"simulateTransaction".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_shortfall_posts_nowhere() {
+        use solana_sdk::rent::Rent;
+        
+        let cost = 2 * Rent::default().minimum_balance(CHANNEL_MESSAGE_SPACE);
+        let (service, agent, _, rpc) = broadcasting_service(cost - 1).await;
+        
+        let result = service.broadcast_to_all_channels(&agent, "hello", MessageType::Text).await;
+        assert!(matches!(
+            result,
+            Err(PodComError::Message(MessageError::InsufficientFunds { required, available }))
+                if required == cost && available == cost - 1
+        ));
+        assert!(!rpc.methods().contains(&"simulateTransaction".to_string()));
+    }
+
+    /// Serialized agent account fields, without the discriminator
+    fn agent_bytes(owner: &Pubkey, capabilities: u64, reputation: u64, metadata_uri: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&capabilities.to_le_bytes());
+        data.extend_from_slice(&reputation.to_le_bytes());
+        data.extend_from_slice(&0i64.to_le_bytes()); // last_updated
+        data.extend_from_slice(&(metadata_uri.len() as u32).to_le_bytes());
+        data.extend_from_slice(metadata_uri.as_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes()); // invites_sent
+        data.extend_from_slice(&0i64.to_le_bytes()); // last_invite_at
+        data.extend_from_slice(&[255, 0, 0, 0, 0, 0, 0, 0]); // bump + reserved
+        data
+    }
+
+    fn agent_with(capabilities: u64, reputation: u64) -> AgentAccount {
+        let data = agent_bytes(&Pubkey::new_unique(), capabilities, reputation, "");
+        borsh::BorshDeserialize::try_from_slice(&data).unwrap()
+    }
+
+    #[test]
+    fn test_capability_matches_filtered_and_ranked() {
+        use pod_sdk_types::capabilities::{AI_CHAT, CODE_GENERATION, DATA_ANALYSIS};
+        
+        let find = |mask: u64, limit: Option<u32>| {
+            let mut matches = CapabilityMatches::new(mask, limit);
+            for (capabilities, reputation) in [
+                (AI_CHAT, 500),
+                (AI_CHAT | CODE_GENERATION, 300),
+                (DATA_ANALYSIS, 900),
+                (AI_CHAT | CODE_GENERATION | DATA_ANALYSIS, 700),
+                (CODE_GENERATION, 800),
+                (AI_CHAT | CODE_GENERATION, 100),
+            ] {
+                if matches.is_full() {
+                    break;
+                }
+                matches.offer(agent_with(capabilities, reputation));
+            }
+            matches.into_agents().iter().map(|a| a.reputation).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(find(AI_CHAT, None), vec![700, 500, 300, 100]);
+        assert_eq!(find(AI_CHAT | CODE_GENERATION, None), vec![700, 300, 100]);
+        assert_eq!(find(AI_CHAT | CODE_GENERATION, Some(2)), vec![700, 300]);
+        // The scan stops at the first matches, ranking only those
+        assert_eq!(find(AI_CHAT, Some(1)), vec![500]);
+        assert_eq!(find(AI_CHAT, Some(3)), vec![700, 500, 300]);
+        assert!(find(AI_CHAT, Some(0)).is_empty());
+        assert_eq!(find(0, None).len(), 6);
+    }
+
+    /// Initialized service whose payer has registered an agent
+    async fn agent_service(dry_run: bool) -> (AgentService, Pubkey, MockRpc) {
+        use anchor_client::{Client, Cluster};
+        use anchor_lang::Discriminator;
+        
+        let payer = Keypair::new();
+        let (agent_pda, _) = Pubkey::find_program_address(&[b"agent", payer.pubkey().as_ref()], &crate::PROGRAM_ID);
+        let data = [
+            AgentAccount::DISCRIMINATOR.as_ref(),
+            &agent_bytes(&payer.pubkey(), 1, 100, "https://example.com/agent.json"),
+        ].concat();
+        let rpc = MockRpc::serving([(agent_pda.to_string(), data)].into_iter().collect());
+        
+        let mut config = test_config();
+        config.dry_run = dry_run;
+        config.async_rpc_client = rpc.client();
+        
+        let mut service = AgentService::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(payer)).program(crate::PROGRAM_ID).unwrap();
+        service.initialize(program).await.unwrap();
+        (service, agent_pda, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_register_agent_dry_run_simulates_without_sending() {
+        let (service, agent_pda, rpc) = agent_service(true).await;
+        let request = RegisterAgentRequest {
+            capabilities: 1,
+            metadata_uri: "https://example.com/agent.json".to_string(),
+        };
+        
+        match service.register_agent(request).await {
+            Ok(Outcome::Simulated(dry_run)) => {
+                assert!(dry_run.derived_addresses.contains(&agent_pda));
+                assert_eq!(dry_run.logs, vec!["Program log: Instruction: RegisterAgent"]);
+                assert_eq!(dry_run.units_consumed, Some(SIMULATED_UNITS));
+                assert_eq!(dry_run.error, None);
+            }
+            other => panic!("expected a dry run, got {:?}", other),
+        }
+        assert_eq!(rpc.methods(), vec!["simulateTransaction"]);
+    }
+}
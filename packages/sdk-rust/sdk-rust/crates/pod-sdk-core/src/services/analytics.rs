@@ -15,8 +15,8 @@ use solana_sdk::{
 use serde::{Deserialize, Serialize};
 
 use pod_sdk_types::{
-    AgentAccount, ChannelAccount, MessageAccount, EscrowAccount, AnalyticsAccount,
-    AnalyticsPeriod,
+    AgentAccount, ChannelAccount, MessageAccount, EscrowAccount, EscrowStatus, AnalyticsAccount,
+    AnalyticsPeriod, pda,
 };
 
 use crate::{
@@ -35,7 +35,7 @@ impl AnalyticsService {
     /// Create a new analytics service
     pub fn new(config: ServiceConfig) -> Self {
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("analytics"),
             metrics_cache: Arc::new(tokio::sync::RwLock::new(MetricsCache::new())),
         }
     }
@@ -44,8 +44,8 @@ impl AnalyticsService {
     pub async fn get_protocol_metrics(&self) -> Result<ProtocolMetrics> {
         let operation_name = "get_protocol_metrics";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_operation(operation_name, || async {
+            self.base.program()?;
             
             // Check cache first
             {
@@ -58,10 +58,10 @@ impl AnalyticsService {
             }
             
             // Collect fresh metrics
-            let agents = self.collect_agent_metrics(program).await?;
-            let channels = self.collect_channel_metrics(program).await?;
-            let messages = self.collect_message_metrics(program).await?;
-            let escrows = self.collect_escrow_metrics(program).await?;
+            let agents = self.collect_agent_metrics().await?;
+            let channels = self.collect_channel_metrics().await?;
+            let messages = self.collect_message_metrics().await?;
+            let escrows = self.collect_escrow_metrics().await?;
             
             let protocol_metrics = ProtocolMetrics {
                 total_agents: agents.total_count,
@@ -93,16 +93,16 @@ impl AnalyticsService {
     pub async fn get_agent_analytics(&self, agent_address: &Pubkey) -> Result<AgentAnalytics> {
         let operation_name = "get_agent_analytics";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_operation(operation_name, || async {
+            self.base.program()?;
             
-            let agent_account = program.account::<AgentAccount>(*agent_address)?;
+            let agent_account = self.base.fetch_account::<AgentAccount>(agent_address).await?;
             
             // Get agent's channels
-            let agent_channels = self.get_agent_channels(program, agent_address).await?;
+            let agent_channels = self.get_agent_channels(agent_address).await?;
             
             // Get agent's messages
-            let agent_messages = self.get_agent_messages(program, agent_address).await?;
+            let agent_messages = self.get_agent_messages(agent_address).await?;
             
             // Calculate analytics
             let total_channels = agent_channels.len() as u64;
@@ -144,11 +144,11 @@ impl AnalyticsService {
     pub async fn get_channel_analytics(&self, channel_address: &Pubkey) -> Result<ChannelAnalytics> {
         let operation_name = "get_channel_analytics";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_operation(operation_name, || async {
+            self.base.program()?;
             
-            let channel_account = program.account::<ChannelAccount>(*channel_address)?;
-            let channel_messages = self.get_channel_messages(program, channel_address).await?;
+            let channel_account = self.base.fetch_account::<ChannelAccount>(channel_address).await?;
+            let channel_messages = self.get_channel_messages(channel_address).await?;
             
             // Calculate message statistics
             let total_messages = channel_messages.len() as u64;
@@ -203,10 +203,10 @@ impl AnalyticsService {
     pub async fn get_usage_patterns(&self, time_range: TimeRange) -> Result<UsagePatterns> {
         let operation_name = "get_usage_patterns";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_operation(operation_name, || async {
+            self.base.program()?;
             
-            let all_messages = self.get_all_messages_in_range(program, time_range).await?;
+            let all_messages = self.get_all_messages_in_range(time_range).await?;
             
             // Analyze temporal patterns
             let hourly_distribution = self.analyze_hourly_distribution(&all_messages);
@@ -217,8 +217,8 @@ impl AnalyticsService {
             let size_distribution = self.analyze_message_sizes(&all_messages);
             
             // Network topology analysis
-            let network_density = self.calculate_network_density(program).await?;
-            let clustering_coefficient = self.calculate_clustering_coefficient(program).await?;
+            let network_density = self.calculate_network_density().await?;
+            let clustering_coefficient = self.calculate_clustering_coefficient().await?;
             
             let patterns = UsagePatterns {
                 time_range,
@@ -250,7 +250,7 @@ impl AnalyticsService {
     pub async fn generate_performance_report(&self, time_range: TimeRange) -> Result<PerformanceReport> {
         let operation_name = "generate_performance_report";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let protocol_metrics = self.get_protocol_metrics().await?;
             let usage_patterns = self.get_usage_patterns(time_range).await?;
             
@@ -278,13 +278,13 @@ impl AnalyticsService {
 
     // Helper methods for data collection and analysis
 
-    async fn collect_agent_metrics(&self, program: &Program<Arc<Keypair>>) -> Result<AgentMetricsSummary> {
-        let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+    async fn collect_agent_metrics(&self) -> Result<AgentMetricsSummary> {
+        let mut active_count = 0u64;
         
-        let total_count = accounts.len() as u64;
-        let active_count = accounts.iter()
-            .filter(|(_, agent)| agent.is_active)
-            .count() as u64;
+        let total_count = self.base.scan_accounts::<AgentAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            active_count += page.iter().filter(|(_, agent)| agent.is_active).count() as u64;
+            Ok(())
+        }).await?;
         
         Ok(AgentMetricsSummary {
             total_count,
@@ -292,13 +292,13 @@ impl AnalyticsService {
         })
     }
 
-    async fn collect_channel_metrics(&self, program: &Program<Arc<Keypair>>) -> Result<ChannelMetricsSummary> {
-        let accounts = program.accounts::<ChannelAccount>(vec![]).await?;
+    async fn collect_channel_metrics(&self) -> Result<ChannelMetricsSummary> {
+        let mut active_count = 0u64;
         
-        let total_count = accounts.len() as u64;
-        let active_count = accounts.iter()
-            .filter(|(_, channel)| channel.is_active)
-            .count() as u64;
+        let total_count = self.base.scan_accounts::<ChannelAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            active_count += page.iter().filter(|(_, channel)| channel.is_active).count() as u64;
+            Ok(())
+        }).await?;
         
         Ok(ChannelMetricsSummary {
             total_count,
@@ -306,18 +306,20 @@ impl AnalyticsService {
         })
     }
 
-    async fn collect_message_metrics(&self, program: &Program<Arc<Keypair>>) -> Result<MessageMetricsSummary> {
-        let accounts = program.accounts::<MessageAccount>(vec![]).await?;
-        
-        let total_count = accounts.len() as u64;
+    async fn collect_message_metrics(&self) -> Result<MessageMetricsSummary> {
         let now = chrono::Utc::now();
-        let last_24h_count = accounts.iter()
-            .filter(|(_, msg)| (now - msg.created_at).num_hours() <= 24)
-            .count() as u64;
+        let mut last_24h_count = 0u64;
+        let mut total_size = 0usize;
         
-        let total_size: usize = accounts.iter()
-            .map(|(_, msg)| msg.encrypted_content.len())
-            .sum();
+        let total_count = self.base.scan_accounts::<MessageAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            for (_, msg) in &page {
+                if (now - msg.created_at).num_hours() <= 24 {
+                    last_24h_count += 1;
+                }
+                total_size += msg.encrypted_content.len();
+            }
+            Ok(())
+        }).await?;
         
         let average_size = if total_count > 0 {
             total_size as f64 / total_count as f64
@@ -332,18 +334,17 @@ impl AnalyticsService {
         })
     }
 
-    async fn collect_escrow_metrics(&self, program: &Program<Arc<Keypair>>) -> Result<EscrowMetricsSummary> {
-        let accounts = program.accounts::<EscrowAccount>(vec![]).await?;
+    async fn collect_escrow_metrics(&self) -> Result<EscrowMetricsSummary> {
+        let mut active_count = 0u64;
+        let mut total_value_locked = 0u64;
         
-        let total_count = accounts.len() as u64;
-        let active_count = accounts.iter()
-            .filter(|(_, escrow)| matches!(escrow.status, EscrowStatus::Active))
-            .count() as u64;
-        
-        let total_value_locked = accounts.iter()
-            .filter(|(_, escrow)| matches!(escrow.status, EscrowStatus::Active))
-            .map(|(_, escrow)| escrow.amount)
-            .sum();
+        let total_count = self.base.scan_accounts::<EscrowAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            for (_, escrow) in page.iter().filter(|(_, escrow)| matches!(escrow.status, EscrowStatus::Active)) {
+                active_count += 1;
+                total_value_locked += escrow.amount;
+            }
+            Ok(())
+        }).await?;
         
         Ok(EscrowMetricsSummary {
             total_count,
@@ -353,46 +354,53 @@ impl AnalyticsService {
     }
 
     // Additional helper methods would go here...
-    async fn get_agent_channels(&self, program: &Program<Arc<Keypair>>, agent_address: &Pubkey) -> Result<Vec<ChannelAccount>> {
-        let accounts = program.accounts::<ChannelAccount>(vec![]).await?;
-        Ok(accounts.into_iter()
-            .map(|(_, account)| account)
-            .filter(|channel| channel.participants.contains(agent_address))
-            .collect())
+    async fn get_agent_channels(&self, agent_address: &Pubkey) -> Result<Vec<ChannelAccount>> {
+        let mut channels = Vec::new();
+        self.base.scan_accounts::<ChannelAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            channels.extend(page.into_iter()
+                .map(|(_, account)| account)
+                .filter(|channel| channel.participants.contains(agent_address)));
+            Ok(())
+        }).await?;
+        Ok(channels)
     }
 
-    async fn get_agent_messages(&self, program: &Program<Arc<Keypair>>, agent_address: &Pubkey) -> Result<Vec<MessageAccount>> {
-        let accounts = program.accounts::<MessageAccount>(vec![]).await?;
-        Ok(accounts.into_iter()
-            .map(|(_, account)| account)
-            .filter(|message| message.sender == *agent_address)
-            .collect())
+    async fn get_agent_messages(&self, agent_address: &Pubkey) -> Result<Vec<MessageAccount>> {
+        self.scan_messages(|message| message.sender == *agent_address).await
     }
 
-    async fn get_channel_messages(&self, program: &Program<Arc<Keypair>>, channel_address: &Pubkey) -> Result<Vec<MessageAccount>> {
-        let accounts = program.accounts::<MessageAccount>(vec![]).await?;
-        Ok(accounts.into_iter()
-            .map(|(_, account)| account)
-            .filter(|message| message.channel == *channel_address)
-            .collect())
+    async fn get_channel_messages(&self, channel_address: &Pubkey) -> Result<Vec<MessageAccount>> {
+        self.scan_messages(|message| message.channel == *channel_address).await
     }
 
-    async fn get_all_messages_in_range(&self, program: &Program<Arc<Keypair>>, time_range: TimeRange) -> Result<Vec<MessageAccount>> {
-        let accounts = program.accounts::<MessageAccount>(vec![]).await?;
-        Ok(accounts.into_iter()
-            .map(|(_, account)| account)
-            .filter(|message| time_range.contains(message.created_at))
-            .collect())
+    async fn get_all_messages_in_range(&self, time_range: TimeRange) -> Result<Vec<MessageAccount>> {
+        self.scan_messages(|message| time_range.contains(message.created_at)).await
     }
 
-    fn calculate_avg_response_time(&self, _messages: &[MessageAccount]) -> f64 {
-        // TODO: Implement response time calculation
-        100.0 // placeholder
+    /// Messages matching `keep`, scanned page by page so only matches are held
+    async fn scan_messages<F>(&self, keep: F) -> Result<Vec<MessageAccount>>
+    where
+        F: Fn(&MessageAccount) -> bool,
+    {
+        let mut messages = Vec::new();
+        self.base.scan_accounts::<MessageAccount, _>(Vec::new(), SCAN_PAGE_SIZE, |page| {
+            messages.extend(page.into_iter().map(|(_, account)| account).filter(|message| keep(message)));
+            Ok(())
+        }).await?;
+        Ok(messages)
     }
 
+    fn calculate_avg_response_time(&self, messages: &[MessageAccount]) -> f64 {
+        average_reply_latency_ms(&self.base.config().effective_program_id(), messages)
+    }
+
+    /// Reputation trend of an agent, in points per day
+    ///
+    /// pod-com sets an agent's reputation once, at registration, and keeps
+    /// no history of it, so there is nothing to fit and the trend is flat.
+    /// [`reputation_trend`] fits snapshots a caller has recorded itself.
     async fn calculate_reputation_trend(&self, _agent_address: &Pubkey) -> Result<f64> {
-        // TODO: Implement reputation trend calculation
-        Ok(0.1) // placeholder
+        Ok(0.0)
     }
 
     fn find_most_active_channel(&self, channels: &[ChannelAccount]) -> Option<Pubkey> {
@@ -440,12 +448,12 @@ impl AnalyticsService {
         HashMap::new()
     }
 
-    async fn calculate_network_density(&self, _program: &Program<Arc<Keypair>>) -> Result<f64> {
+    async fn calculate_network_density(&self) -> Result<f64> {
         // TODO: Implement network density calculation
         Ok(0.5)
     }
 
-    async fn calculate_clustering_coefficient(&self, _program: &Program<Arc<Keypair>>) -> Result<f64> {
+    async fn calculate_clustering_coefficient(&self) -> Result<f64> {
         // TODO: Implement clustering coefficient calculation
         Ok(0.3)
     }
@@ -657,7 +665,7 @@ impl BaseService for AnalyticsService {
     type Error = PodComError;
 
     async fn initialize(&mut self, program: Program<Arc<Keypair>>) -> Result<(), Self::Error> {
-        self.base.initialize(program).await?;
+        self.base.initialize().await?;
         Ok(())
     }
 
@@ -763,9 +771,12 @@ impl BaseService for AnalyticsService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -776,12 +787,89 @@ impl BaseService for AnalyticsService {
     fn service_name(&self) -> &'static str {
         "analytics"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
+}
+
+/// Accounts fetched per page when aggregating protocol-wide metrics
+const SCAN_PAGE_SIZE: usize = 100;
+/// Seconds per day, the unit of reputation trends
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Least-squares slope of `(unix_timestamp, value)` points, in value per day
+///
+/// Returns 0.0 with fewer than two points, or when every point shares the
+/// same timestamp and no slope can be fitted.
+pub fn reputation_trend(points: &[(i64, f64)]) -> f64 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    // Measure time in days from the first point to keep the sums well conditioned
+    let origin = points[0].0;
+    let days = |t: i64| (t - origin) as f64 / SECONDS_PER_DAY;
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|&(t, _)| days(t)).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), &(t, y)| {
+        let dx = days(t) - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+
+    if variance == 0.0 {
+        return 0.0;
+    }
+    covariance / variance
+}
+
+/// Mean delay in milliseconds between replies and the messages they answer
+///
+/// Replies whose parent is not among `messages` are ignored. Returns 0.0 when
+/// no reply can be paired with its parent.
+fn average_reply_latency_ms(program_id: &Pubkey, messages: &[MessageAccount]) -> f64 {
+    let sent_at: HashMap<Pubkey, i64> = messages
+        .iter()
+        .filter_map(|msg| Some((message_address(program_id, msg)?, msg.created_at)))
+        .collect();
+
+    let deltas: Vec<i64> = messages
+        .iter()
+        .filter_map(|msg| {
+            let parent_created_at = sent_at.get(msg.reply_to.as_ref()?)?;
+            Some(msg.created_at - parent_created_at)
+        })
+        .collect();
+
+    if deltas.is_empty() {
+        return 0.0;
+    }
+    deltas.iter().sum::<i64>() as f64 * 1000.0 / deltas.len() as f64
+}
+
+/// Recover a message account's PDA from the seeds the program derives it with
+fn message_address(program_id: &Pubkey, message: &MessageAccount) -> Option<Pubkey> {
+    Pubkey::create_program_address(
+        &[
+            pda::MESSAGE_SEED,
+            message.sender.as_ref(),
+            message.recipient.as_ref(),
+            &message.payload_hash,
+            &[pda::message_type_seed(&message.message_type)],
+            &[message.bump],
+        ],
+        program_id,
+    )
+    .ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::test_config;
+    use pod_sdk_types::{MessageStatus, MessageType};
 
     #[tokio::test]
     async fn test_analytics_service_creation() {
@@ -790,4 +878,64 @@ mod tests {
         assert_eq!(service.service_name(), "analytics");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    fn message(program_id: &Pubkey, seed: u8, created_at: i64, reply_to: Option<Pubkey>) -> (Pubkey, MessageAccount) {
+        let sender = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let payload_hash = [seed; 32];
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"message", sender.as_ref(), recipient.as_ref(), &payload_hash, &[0]],
+            program_id,
+        );
+        let account = MessageAccount {
+            sender,
+            recipient,
+            channel: None,
+            payload_hash,
+            message_type: MessageType::Text,
+            status: MessageStatus::Delivered,
+            created_at,
+            expires_at: created_at + 3600,
+            reply_to,
+            bump,
+        };
+        (address, account)
+    }
+
+    #[test]
+    fn test_average_reply_latency() {
+        let program_id = crate::PROGRAM_ID;
+        let (first, first_msg) = message(&program_id, 1, 1_000, None);
+        let (second, second_msg) = message(&program_id, 2, 1_010, None);
+        let (_, reply_a) = message(&program_id, 3, 1_002, Some(first));
+        let (_, reply_b) = message(&program_id, 4, 1_016, Some(second));
+        // Parent is not in the set, so this reply is ignored
+        let (_, orphan) = message(&program_id, 5, 9_999, Some(Pubkey::new_unique()));
+
+        let messages = vec![first_msg, second_msg, reply_a, reply_b, orphan];
+
+        // (2s + 6s) / 2 replies
+        assert_eq!(average_reply_latency_ms(&program_id, &messages), 4_000.0);
+        assert_eq!(average_reply_latency_ms(&program_id, &messages[..2]), 0.0);
+    }
+
+    #[test]
+    fn test_reputation_trend() {
+        let day = SECONDS_PER_DAY as i64;
+        let series = |f: fn(f64) -> f64| -> Vec<(i64, f64)> {
+            (0..5).map(|d| (1_700_000_000 + d * day, f(d as f64))).collect()
+        };
+
+        assert_eq!(reputation_trend(&series(|_| 12.0)), 0.0);
+        assert!((reputation_trend(&series(|d| 3.0 + 5.0 * d)) - 5.0).abs() < 1e-9);
+        assert!((reputation_trend(&series(|d| 10.0 - 2.0 * d)) + 2.0).abs() < 1e-9);
+
+        // Noisy rising series still trends upward
+        let noisy = vec![(0, 1.0), (day, 0.0), (2 * day, 4.0), (3 * day, 3.0)];
+        assert!(reputation_trend(&noisy) > 0.0);
+
+        assert_eq!(reputation_trend(&[]), 0.0);
+        assert_eq!(reputation_trend(&[(day, 7.0)]), 0.0);
+        assert_eq!(reputation_trend(&[(day, 1.0), (day, 9.0)]), 0.0);
+    }
+}
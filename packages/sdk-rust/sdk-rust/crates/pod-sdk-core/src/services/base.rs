@@ -3,34 +3,147 @@
 //! Provides the foundation for all PoD Protocol services with common functionality,
 //! lifecycle management, metrics collection, and error handling.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
-use anchor_client::Program;
+use anchor_client::{Program, RequestBuilder};
 use async_trait::async_trait;
+use hdrhistogram::Histogram;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    message::Message,
     pubkey::Pubkey,
-    signer::keypair::Keypair,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    transaction::Transaction,
 };
-use solana_rpc_client::rpc_client::RpcClient;
-use tokio::sync::RwLock;
+use solana_rpc_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
+use solana_rpc_client_api::{
+    config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    filter::RpcFilterType,
+    response::RpcSimulateTransactionResult,
+};
+use tokio::task::AbortHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 use crate::{
-    config::{RateLimitConfig, RetryConfig, CacheConfig, IPFSConfig, ZKCompressionConfig},
-    error::{PodComError, Result},
+    config::{
+        RateLimitConfig, RetryConfig, CacheConfig, ComputeBudgetConfig, IPFSConfig, ZKCompressionConfig,
+        MAX_COMPUTE_UNIT_LIMIT,
+    },
+    error::{AgentError, MessageError, NetworkError, PodComError, Result, RetryableError},
+    utils::{
+        account::MAX_MULTIPLE_ACCOUNTS, cache::AccountCache, decode::decode_account,
+        encoding::PayloadEncoding, network::RateLimiter, transport::check_rpc_method,
+    },
 };
 
+/// Raw account data cache shared between services
+pub type SharedAccountCache = Arc<Mutex<AccountCache<Vec<u8>>>>;
+
+/// Create the shared account cache for `config`, or `None` if caching is disabled
+pub fn shared_account_cache(config: &CacheConfig) -> Option<SharedAccountCache> {
+    AccountCache::from_config(config).map(|cache| Arc::new(Mutex::new(cache)))
+}
+
+/// Lock the shared account cache, emptying it if a panicking holder poisoned it
+/// 
+/// Entries written by a holder that panicked may be inconsistent, so they are
+/// dropped rather than served.
+fn lock_cache(cache: &SharedAccountCache) -> MutexGuard<'_, AccountCache<Vec<u8>>> {
+    cache.lock().unwrap_or_else(|poisoned| {
+        let mut guard = poisoned.into_inner();
+        guard.clear();
+        cache.clear_poison();
+        guard
+    })
+}
+
+/// Background tasks of a client and every service built from its config
+/// 
+/// Clones share one registry. Each task is tracked under the owner that
+/// spawned it, so a service can stop its own tasks on shutdown while the
+/// client can abort every task at once when it is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct TaskRegistry {
+    inner: Arc<Mutex<TaskRegistryInner>>,
+}
+
+#[derive(Debug, Default)]
+struct TaskRegistryInner {
+    next_owner: u64,
+    tasks: Vec<(u64, AbortHandle)>,
+}
+
+impl TaskRegistry {
+    fn lock(&self) -> MutexGuard<'_, TaskRegistryInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Identifier under which a new owner tracks its tasks
+    pub(crate) fn new_owner(&self) -> u64 {
+        let mut inner = self.lock();
+        inner.next_owner += 1;
+        inner.next_owner
+    }
+    
+    /// Track a task spawned by `owner`, forgetting tasks that have finished
+    pub(crate) fn track(&self, owner: u64, task: AbortHandle) {
+        let mut inner = self.lock();
+        inner.tasks.retain(|(_, task)| !task.is_finished());
+        inner.tasks.push((owner, task));
+    }
+    
+    /// Stop tracking `owner`'s tasks and return them
+    pub(crate) fn take_owned(&self, owner: u64) -> Vec<AbortHandle> {
+        let mut inner = self.lock();
+        let (owned, others) = std::mem::take(&mut inner.tasks)
+            .into_iter()
+            .partition(|(task_owner, _)| *task_owner == owner);
+        inner.tasks = others;
+        owned.into_iter().map(|(_, task)| task).collect()
+    }
+    
+    /// Tracked tasks that are still running
+    pub fn running(&self) -> usize {
+        self.lock().tasks.iter().filter(|(_, task)| !task.is_finished()).count()
+    }
+    
+    /// Abort every tracked task, whichever owner spawned it
+    pub fn abort_all(&self) {
+        for (_, task) in std::mem::take(&mut self.lock().tasks) {
+            task.abort();
+        }
+    }
+}
+
 /// Base configuration shared by all services
 #[derive(Clone)]
 pub struct ServiceConfig {
     /// RPC client for Solana interactions
     pub rpc_client: Arc<RpcClient>,
+    /// Nonblocking RPC client over the same transport, for use inside async operations
+    pub async_rpc_client: Arc<AsyncRpcClient>,
+    /// RPC methods the transport may call (`None` = unrestricted)
+    pub allowed_rpc_methods: Option<HashSet<String>>,
+    /// Proxy RPC traffic is routed through, if any
+    pub rpc_proxy: Option<reqwest::Proxy>,
     /// Program ID for the PoD Protocol
     pub program_id: Pubkey,
+    /// Program ID used by this service instead of `program_id`, e.g. a test deployment
+    pub program_id_override: Option<Pubkey>,
     /// Commitment level for transactions
     pub commitment: CommitmentConfig,
+    /// Compute budget instructions prepended to every transaction
+    pub compute_budget: ComputeBudgetConfig,
+    /// Simulate state-changing transactions instead of sending them
+    pub dry_run: bool,
     /// Retry configuration for failed operations
     pub retry_config: RetryConfig,
     /// Operation timeout duration
@@ -39,11 +152,21 @@ pub struct ServiceConfig {
     pub rate_limit_config: RateLimitConfig,
     /// Cache configuration
     pub cache_config: CacheConfig,
+    /// Account cache shared by every service built from this config (None = no caching)
+    /// 
+    /// One cache per client, so a transaction sent by one service invalidates
+    /// what the others read.
+    pub account_cache: Option<SharedAccountCache>,
+    /// Background tasks of every service built from this config
+    pub tasks: TaskRegistry,
     /// Cluster configuration
     pub cluster: String,
     /// RPC timeout in seconds
     pub rpc_timeout_secs: u64,
+    /// WebSocket endpoint for subscriptions
+    pub ws_url: Option<String>,
     /// Service-specific configurations
+    pub agent_config: Option<AgentConfig>,
     pub message_config: Option<MessageConfig>,
     pub channel_config: Option<ChannelConfig>,
     pub escrow_config: Option<EscrowConfig>,
@@ -51,21 +174,59 @@ pub struct ServiceConfig {
     pub discovery_config: Option<DiscoveryConfig>,
     pub compression_config: Option<CompressionConfig>,
     pub ipfs_endpoint: Option<String>,
+    /// Proxy for IPFS traffic, built from the validated IPFS configuration
+    pub ipfs_proxy: Option<reqwest::Proxy>,
+    /// Largest IPFS payload the services will download
+    pub ipfs_max_file_size: Option<usize>,
+    /// Timeout for each IPFS request
+    pub ipfs_timeout: Option<Duration>,
     pub zk_compression_config: Option<ZKCompressionConfig>,
 }
 
+impl ServiceConfig {
+    /// Program ID the service talks to: the override if set, else `program_id`
+    pub fn effective_program_id(&self) -> Pubkey {
+        self.program_id_override.unwrap_or(self.program_id)
+    }
+    
+    /// Check that a WebSocket subscription to `method` may be opened
+    /// 
+    /// Subscriptions bypass [`AllowlistSender`](crate::utils::transport::AllowlistSender),
+    /// so the allowlist is checked here instead. They are refused while an
+    /// RPC proxy is configured, since the WebSocket client cannot use it.
+    pub(crate) fn check_subscription(&self, method: &str) -> Result<()> {
+        check_rpc_method(self.allowed_rpc_methods.as_ref(), method)?;
+        if self.rpc_proxy.is_some() {
+            return Err(PodComError::InvalidConfiguration {
+                field: "network.proxy_url".to_string(),
+                reason: "WebSocket subscriptions cannot be routed through the RPC proxy".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Debug for ServiceConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ServiceConfig")
             .field("program_id", &self.program_id)
+            .field("program_id_override", &self.program_id_override)
             .field("commitment", &self.commitment)
+            .field("compute_budget", &self.compute_budget)
+            .field("dry_run", &self.dry_run)
             .field("retry_config", &self.retry_config)
             .field("timeout", &self.timeout)
             .field("rate_limit_config", &self.rate_limit_config)
             .field("cache_config", &self.cache_config)
+            .field("account_cache", &self.account_cache)
+            .field("tasks", &self.tasks)
             .field("rpc_client", &"<RpcClient>")
+            .field("allowed_rpc_methods", &self.allowed_rpc_methods)
+            .field("rpc_proxy", &self.rpc_proxy.is_some())
             .field("cluster", &self.cluster)
+            .field("ws_url", &self.ws_url)
             .field("rpc_timeout_secs", &self.rpc_timeout_secs)
+            .field("agent_config", &self.agent_config)
             .field("message_config", &self.message_config)
             .field("channel_config", &self.channel_config)
             .field("escrow_config", &self.escrow_config)
@@ -73,11 +234,36 @@ impl std::fmt::Debug for ServiceConfig {
             .field("discovery_config", &self.discovery_config)
             .field("compression_config", &self.compression_config)
             .field("ipfs_endpoint", &self.ipfs_endpoint)
+            .field("ipfs_proxy", &self.ipfs_proxy.is_some())
+            .field("ipfs_max_file_size", &self.ipfs_max_file_size)
+            .field("ipfs_timeout", &self.ipfs_timeout)
             .field("zk_compression_config", &self.zk_compression_config)
             .finish()
     }
 }
 
+/// Agent service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Largest agent metadata document that will be downloaded, in bytes
+    pub metadata_max_bytes: usize,
+    /// HTTP gateway used to resolve `ipfs://` metadata URIs
+    pub ipfs_gateway: String,
+    /// HTTP gateway used to resolve `ar://` metadata URIs
+    pub arweave_gateway: String,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            metadata_max_bytes: 64 * 1024,
+            ipfs_gateway: "https://ipfs.io".to_string(),
+            arweave_gateway: "https://arweave.net".to_string(),
+        }
+    }
+}
+
 /// Message service configuration
 #[derive(Debug, Clone)]
 pub struct MessageConfig {
@@ -85,6 +271,18 @@ pub struct MessageConfig {
     pub encryption_enabled: bool,
     pub retention_period_hours: u32,
     pub compression_threshold: u32,
+    /// Encoding applied to inline payloads before encryption
+    pub payload_encoding: PayloadEncoding,
+    /// Maintain a local full-text index of decrypted messages (opt-in)
+    pub local_search_enabled: bool,
+    /// Maximum number of postings held by the local search index
+    pub local_search_capacity: usize,
+    /// Expiry applied when a send request doesn't specify one (`None` = never expire)
+    pub default_message_expiry: Option<Duration>,
+    /// Recently seen message addresses remembered per subscription for deduplication
+    pub subscription_dedup_window: usize,
+    /// How long a sent payload is remembered so a retried send isn't repeated (`None` = off)
+    pub dedup_window: Option<Duration>,
 }
 
 /// Channel service configuration  
@@ -136,6 +334,70 @@ pub struct CompressionConfig {
     pub level: u8,
 }
 
+/// What a state-changing method did with its transaction
+/// 
+/// When [`ServiceConfig::dry_run`] is set, transactions are simulated
+/// instead of sent and methods return [`Outcome::Simulated`] without doing
+/// anything that depends on the transaction having landed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<T = Signature> {
+    /// The transaction was sent; carries the method's result
+    Sent(T),
+    /// The transaction was simulated, not sent
+    Simulated(DryRunResult),
+}
+
+impl<T> Outcome<T> {
+    /// The method's result if the transaction was sent
+    pub fn sent(self) -> Option<T> {
+        match self {
+            Outcome::Sent(value) => Some(value),
+            Outcome::Simulated(_) => None,
+        }
+    }
+    
+    /// The simulation if the transaction was only simulated
+    pub fn simulated(&self) -> Option<&DryRunResult> {
+        match self {
+            Outcome::Sent(_) => None,
+            Outcome::Simulated(dry_run) => Some(dry_run),
+        }
+    }
+    
+    /// Map the result of a sent transaction, leaving a simulation as is
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Outcome<U> {
+        match self {
+            Outcome::Sent(value) => Outcome::Sent(f(value)),
+            Outcome::Simulated(dry_run) => Outcome::Simulated(dry_run),
+        }
+    }
+}
+
+/// Outcome of simulating a transaction in dry-run mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunResult {
+    /// Program logs from the simulation
+    pub logs: Vec<String>,
+    /// Compute units the simulation consumed, if the node reported them
+    pub units_consumed: Option<u64>,
+    /// Program derived addresses the instructions reference, in first-use order
+    pub derived_addresses: Vec<Pubkey>,
+    /// Why the simulated transaction failed, if it did
+    pub error: Option<String>,
+}
+
+impl DryRunResult {
+    /// Summarize the simulation of `tx`
+    pub(crate) fn from_simulation(tx: &Transaction, result: RpcSimulateTransactionResult) -> Self {
+        Self {
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            derived_addresses: derived_addresses(&tx.message),
+            error: result.err.map(|err| err.to_string()),
+        }
+    }
+}
+
 /// Health status of a service
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ServiceHealth {
@@ -166,6 +428,12 @@ pub struct ServiceMetrics {
     pub avg_duration_ms: f64,
     /// Operations per second (calculated)
     pub ops_per_second: f64,
+    /// Accounts skipped because their data could not be decoded
+    #[serde(default)]
+    pub skipped_accounts: u64,
+    /// Latency summary of each operation
+    #[serde(skip)]
+    pub latencies: LatencySummaries,
 }
 
 impl ServiceMetrics {
@@ -187,6 +455,18 @@ impl ServiceMetrics {
         }
     }
     
+    /// Latency at percentile `p` (0 to 100) of `operation`
+    /// 
+    /// Zero if the operation has not been recorded.
+    pub fn percentile(&self, operation: &str, p: f64) -> Duration {
+        self.latencies.percentile(operation, p)
+    }
+    
+    /// Number of latencies recorded for `operation`
+    pub fn count(&self, operation: &str) -> u64 {
+        self.latencies.count(operation)
+    }
+    
     /// Update metrics with a new operation
     pub fn record_operation(&mut self, duration: Duration, success: bool) {
         self.operations_count += 1;
@@ -203,6 +483,201 @@ impl ServiceMetrics {
     }
 }
 
+/// Per-operation latency histograms
+///
+/// Latencies are kept in microseconds, to two significant digits, between
+/// 1µs and [`MAX_RECORDED_LATENCY`]; longer latencies count as the maximum.
+/// Only the first [`MAX_TRACKED_OPERATIONS`] operation names get a histogram,
+/// so memory stays bounded however many names callers use.
+#[derive(Debug, Clone, Default)]
+pub struct OperationLatencies {
+    histograms: HashMap<String, Histogram<u64>>,
+}
+
+impl OperationLatencies {
+    /// Record one call to `operation` taking `duration`
+    pub fn record(&mut self, operation: &str, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        if let Some(histogram) = self.histograms.get_mut(operation) {
+            histogram.saturating_record(micros);
+            return;
+        }
+        if self.histograms.len() >= MAX_TRACKED_OPERATIONS {
+            return;
+        }
+        
+        let mut histogram = Histogram::new_with_bounds(1, MAX_RECORDED_LATENCY.as_micros() as u64, 2)
+            .expect("latency histogram bounds are valid");
+        histogram.saturating_record(micros);
+        self.histograms.insert(operation.to_string(), histogram);
+    }
+    
+    /// Latency at percentile `p` (0 to 100) of `operation`, or zero if untracked
+    pub fn percentile(&self, operation: &str, p: f64) -> Duration {
+        self.histograms
+            .get(operation)
+            .map_or(Duration::ZERO, |histogram| Duration::from_micros(histogram.value_at_percentile(p)))
+    }
+    
+    /// Number of latencies recorded for `operation`
+    pub fn count(&self, operation: &str) -> u64 {
+        self.histograms.get(operation).map_or(0, |histogram| histogram.len())
+    }
+    
+    /// Approximate total latency recorded for `operation`
+    pub fn total(&self, operation: &str) -> Duration {
+        self.histograms.get(operation).map_or(Duration::ZERO, |histogram| {
+            Duration::from_secs_f64(histogram.mean() * histogram.len() as f64 / 1_000_000.0)
+        })
+    }
+    
+    /// Names of every tracked operation, in no particular order
+    pub fn operations(&self) -> impl Iterator<Item = &str> {
+        self.histograms.keys().map(String::as_str)
+    }
+    
+    /// Count, total and [`SUMMARY_PERCENTILES`] of every tracked operation
+    pub fn summarize(&self) -> LatencySummaries {
+        let operations = self.histograms
+            .keys()
+            .map(|operation| {
+                let summary = LatencySummary {
+                    count: self.count(operation),
+                    total: self.total(operation),
+                    percentiles: SUMMARY_PERCENTILES.map(|p| self.percentile(operation, p)),
+                };
+                (operation.clone(), summary)
+            })
+            .collect();
+        LatencySummaries { operations }
+    }
+}
+
+/// Latencies of one operation at the time of a [`MetricsRecorder::snapshot`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencySummary {
+    /// Number of latencies recorded
+    pub count: u64,
+    /// Approximate total latency recorded
+    pub total: Duration,
+    /// Latency at each of [`SUMMARY_PERCENTILES`]
+    pub percentiles: [Duration; SUMMARY_PERCENTILES.len()],
+}
+
+/// Per-operation latency summaries, as read from [`OperationLatencies`]
+#[derive(Debug, Clone, Default)]
+pub struct LatencySummaries {
+    operations: HashMap<String, LatencySummary>,
+}
+
+impl LatencySummaries {
+    /// Latency at percentile `p` (0 to 100) of `operation`, or zero if untracked
+    /// 
+    /// Percentiles not in [`SUMMARY_PERCENTILES`] round up to the next one
+    /// that is, or down to the highest.
+    pub fn percentile(&self, operation: &str, p: f64) -> Duration {
+        let Some(summary) = self.operations.get(operation) else {
+            return Duration::ZERO;
+        };
+        // Tolerate rounding in callers' arithmetic, e.g. `0.9 * 100.0`
+        let index = SUMMARY_PERCENTILES
+            .iter()
+            .position(|summarized| *summarized + 1e-9 >= p)
+            .unwrap_or(SUMMARY_PERCENTILES.len() - 1);
+        summary.percentiles[index]
+    }
+    
+    /// Number of latencies recorded for `operation`
+    pub fn count(&self, operation: &str) -> u64 {
+        self.operations.get(operation).map_or(0, |summary| summary.count)
+    }
+    
+    /// Approximate total latency recorded for `operation`
+    pub fn total(&self, operation: &str) -> Duration {
+        self.operations.get(operation).map_or(Duration::ZERO, |summary| summary.total)
+    }
+    
+    /// Summary of `operation`, if it is tracked
+    pub fn get(&self, operation: &str) -> Option<&LatencySummary> {
+        self.operations.get(operation)
+    }
+    
+    /// Names of every tracked operation, in no particular order
+    pub fn operations(&self) -> impl Iterator<Item = &str> {
+        self.operations.keys().map(String::as_str)
+    }
+}
+
+/// Lock-free operation counters backing [`ServiceMetrics`]
+///
+/// Updated by every [`ServiceBase::execute_operation`] call and readable
+/// from synchronous code, including from inside an async runtime. Only the
+/// per-operation latency histograms sit behind a lock.
+#[derive(Debug, Default)]
+pub struct MetricsRecorder {
+    operations_count: AtomicU64,
+    successful_operations: AtomicU64,
+    failed_operations: AtomicU64,
+    total_duration_ms: AtomicU64,
+    /// Milliseconds since the Unix epoch of the last operation; 0 if none
+    last_operation_ms: AtomicU64,
+    skipped_accounts: AtomicU64,
+    latencies: Mutex<OperationLatencies>,
+}
+
+impl MetricsRecorder {
+    /// Record the latency of one call to `operation`
+    pub fn record_latency(&self, operation: &str, duration: Duration) {
+        self.latencies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(operation, duration);
+    }
+    
+    /// Record a completed operation
+    pub fn record(&self, duration: Duration, success: bool) {
+        if success {
+            self.successful_operations.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed_operations.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_duration_ms.fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
+        self.last_operation_ms.store(chrono::Utc::now().timestamp_millis().max(1) as u64, Ordering::Relaxed);
+        // Counted last so readers never see more operations than outcomes
+        self.operations_count.fetch_add(1, Ordering::Release);
+    }
+    
+    /// Record an account skipped because it could not be decoded
+    pub fn record_skipped_account(&self) {
+        self.skipped_accounts.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Current metrics
+    pub fn snapshot(&self) -> ServiceMetrics {
+        let operations_count = self.operations_count.load(Ordering::Acquire);
+        let total_duration_ms = self.total_duration_ms.load(Ordering::Relaxed);
+        let last_operation_ms = self.last_operation_ms.load(Ordering::Relaxed);
+        
+        ServiceMetrics {
+            operations_count,
+            successful_operations: self.successful_operations.load(Ordering::Relaxed),
+            failed_operations: self.failed_operations.load(Ordering::Relaxed),
+            total_duration_ms,
+            last_operation_at: (last_operation_ms > 0)
+                .then(|| chrono::DateTime::from_timestamp_millis(last_operation_ms as i64))
+                .flatten(),
+            avg_duration_ms: if operations_count == 0 {
+                0.0
+            } else {
+                total_duration_ms as f64 / operations_count as f64
+            },
+            ops_per_second: 0.0,
+            skipped_accounts: self.skipped_accounts.load(Ordering::Relaxed),
+            latencies: self.latencies.lock().unwrap_or_else(|e| e.into_inner()).summarize(),
+        }
+    }
+}
+
 /// Base trait that all PoD Protocol services must implement
 #[async_trait]
 pub trait BaseService: Send + Sync {
@@ -220,6 +695,14 @@ pub trait BaseService: Send + Sync {
     /// Get service health status
     fn health_check(&self) -> ServiceHealth;
     
+    /// Get service health by probing the RPC endpoint
+    /// 
+    /// Falls back to [`health_check`](Self::health_check) for services
+    /// without an endpoint to probe.
+    async fn probe_health(&self) -> ServiceHealth {
+        self.health_check()
+    }
+    
     /// Get service metrics
     fn metrics(&self) -> ServiceMetrics;
     
@@ -228,30 +711,126 @@ pub trait BaseService: Send + Sync {
     
     /// Get service name for logging and metrics
     fn service_name(&self) -> &'static str;
+    
+    /// Number of operations currently in flight
+    fn pending_operations(&self) -> u64 {
+        0
+    }
+}
+
+/// Type-erased error returned through [`DynService`]
+pub type DynServiceError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Object-safe view of a service for dynamic dispatch.
+///
+/// [`BaseService`] carries an associated error type, so it cannot be used as a
+/// trait object. `DynService` erases the error and is implemented for every
+/// `BaseService`, letting callers hold `Vec<Box<dyn DynService>>` and iterate
+/// services uniformly. Its methods carry a `dyn_` prefix so calls on concrete
+/// services still resolve to `BaseService` without qualification.
+#[async_trait]
+pub trait DynService: Send + Sync {
+    /// Get service name for logging and metrics
+    fn dyn_service_name(&self) -> &'static str;
+    
+    /// Validate service configuration
+    fn dyn_validate_config(&self) -> Result<(), DynServiceError>;
+    
+    /// Get service health status
+    fn dyn_health_check(&self) -> ServiceHealth;
+    
+    /// Get service health by probing the RPC endpoint
+    async fn dyn_probe_health(&self) -> ServiceHealth;
+    
+    /// Get service metrics
+    fn dyn_metrics(&self) -> ServiceMetrics;
+    
+    /// Number of operations currently in flight
+    fn dyn_pending_operations(&self) -> u64;
+    
+    /// Graceful shutdown
+    async fn dyn_shutdown(&mut self) -> Result<(), DynServiceError>;
+}
+
+#[async_trait]
+impl<T> DynService for T
+where
+    T: BaseService,
+{
+    fn dyn_service_name(&self) -> &'static str {
+        self.service_name()
+    }
+    
+    fn dyn_validate_config(&self) -> Result<(), DynServiceError> {
+        self.validate_config().map_err(|e| Box::new(e) as DynServiceError)
+    }
+    
+    fn dyn_health_check(&self) -> ServiceHealth {
+        self.health_check()
+    }
+    
+    async fn dyn_probe_health(&self) -> ServiceHealth {
+        self.probe_health().await
+    }
+    
+    fn dyn_metrics(&self) -> ServiceMetrics {
+        self.metrics()
+    }
+    
+    fn dyn_pending_operations(&self) -> u64 {
+        self.pending_operations()
+    }
+    
+    async fn dyn_shutdown(&mut self) -> Result<(), DynServiceError> {
+        self.shutdown().await.map_err(|e| Box::new(e) as DynServiceError)
+    }
 }
 
 /// Common functionality shared by all services
 pub struct ServiceBase {
+    /// Name of the owning service, recorded on operation spans
+    service: &'static str,
     /// Service configuration
     config: ServiceConfig,
     /// Anchor program instance (None until initialized)
     program: Option<Program<Arc<Keypair>>>,
     /// Service metrics
-    metrics: Arc<RwLock<ServiceMetrics>>,
+    metrics: Arc<MetricsRecorder>,
     /// Initialization timestamp
     initialized_at: Option<Instant>,
     /// Rate limiter
-    rate_limiter: Arc<RwLock<RateLimiter>>,
+    rate_limiter: Arc<RateLimiter>,
+    /// Operations started but not yet completed
+    pending_operations: Arc<AtomicU64>,
+    /// Raw account data from recent fetches, shared with the other services (None when caching is disabled)
+    cache: Option<SharedAccountCache>,
+    /// Result of the last health probe and when it was taken
+    /// 
+    /// Held across a probe so concurrent callers wait for it instead of
+    /// probing again.
+    health_probe: Arc<tokio::sync::Mutex<Option<(Instant, ServiceHealth)>>>,
+    /// Cancelled on shutdown to stop subscriptions and background tasks
+    shutdown_token: CancellationToken,
+    /// Registry shared with the client, holding the tasks started by [`spawn_task`](Self::spawn_task)
+    tasks: TaskRegistry,
+    /// This service's owner ID in `tasks`
+    task_owner: u64,
 }
 
 impl std::fmt::Debug for ServiceBase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ServiceBase")
+            .field("service", &self.service)
             .field("config", &self.config)
             .field("metrics", &"<ServiceMetrics>")
             .field("initialized_at", &self.initialized_at)
             .field("rate_limiter", &"<RateLimiter>")
+            .field("pending_operations", &self.pending_operations())
             .field("program", &self.program.is_some())
+            .field("cache", &self.cache)
+            .field("health_probe", &self.health_probe)
+            .field("shutting_down", &self.shutdown_token.is_cancelled())
+            .field("tasks", &self.tasks)
             .finish()
     }
 }
@@ -259,19 +838,46 @@ impl std::fmt::Debug for ServiceBase {
 impl ServiceBase {
     /// Create a new service base
     pub fn new(config: ServiceConfig) -> Self {
-        let rate_limiter = Arc::new(RwLock::new(RateLimiter::new(config.rate_limit_config.clone())));
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_config.clone()));
+        let cache = config.account_cache.clone();
+        let tasks = config.tasks.clone();
+        let task_owner = tasks.new_owner();
         
         Self {
+            service: "service",
             config,
             program: None,
-            metrics: Arc::new(RwLock::new(ServiceMetrics::default())),
+            metrics: Arc::new(MetricsRecorder::default()),
             initialized_at: None,
             rate_limiter,
+            pending_operations: Arc::new(AtomicU64::new(0)),
+            cache,
+            health_probe: Arc::new(tokio::sync::Mutex::new(None)),
+            shutdown_token: CancellationToken::new(),
+            tasks,
+            task_owner,
         }
     }
     
+    /// Name the owning service on operation spans
+    pub fn with_service_name(mut self, service: &'static str) -> Self {
+        self.service = service;
+        self
+    }
+    
     /// Initialize with a program instance
+    /// 
+    /// When the config sets `program_id_override`, `program` must have been
+    /// created for that program ID.
     pub async fn initialize(&mut self, program: Program<Arc<Keypair>>) -> Result<()> {
+        let program_id = self.config.effective_program_id();
+        if program.id() != program_id {
+            return Err(PodComError::InvalidConfiguration {
+                field: "program_id_override".to_string(),
+                reason: format!("Program instance targets {}, expected {}", program.id(), program_id),
+            });
+        }
+        
         self.program = Some(program);
         self.initialized_at = Some(Instant::now());
         Ok(())
@@ -282,90 +888,638 @@ impl ServiceBase {
         self.program.as_ref().ok_or(PodComError::NotInitialized)
     }
     
-    /// Get service configuration
-    pub fn config(&self) -> &ServiceConfig {
-        &self.config
+    /// Start a transaction with the configured compute budget instructions first
+    /// 
+    /// `build` adds the service's instructions and signers. In
+    /// simulate-then-pad mode it runs twice: once to simulate the transaction
+    /// and measure its compute units, and again behind a limit padded from
+    /// that measurement.
+    pub async fn request<'a, F>(&'a self, build: F) -> Result<Request<'a>>
+    where
+        F: Fn(Request<'a>) -> Request<'a>,
+    {
+        let program = self.program()?;
+        let budget = &self.config.compute_budget;
+        
+        let unit_limit = if budget.simulate_then_pad {
+            let instructions = build(program.request()).instructions()?;
+            Some(self.simulate_unit_limit(&program.payer(), &instructions).await?)
+        } else {
+            budget.unit_limit
+        };
+        
+        let request = compute_budget_instructions(unit_limit, budget.unit_price_micro_lamports)
+            .into_iter()
+            .fold(program.request(), |request, ix| request.instruction(ix));
+        Ok(build(request))
     }
     
-    /// Get service metrics
-    pub async fn metrics(&self) -> ServiceMetrics {
-        self.metrics.read().await.clone()
+    /// Send a transaction built with [`request`](Self::request)
+    /// 
+    /// The transaction is signed over a fresh blockhash and confirmed with
+    /// [`send_and_confirm`](Self::send_and_confirm) at the configured
+    /// commitment, within the configured timeout. In dry-run mode the
+    /// transaction is simulated instead and the simulation is returned, so
+    /// the calling operation can stop before anything that depends on the
+    /// transaction having landed.
+    pub async fn send(&self, request: Request<'_>) -> Result<Outcome> {
+        if self.config.dry_run {
+            let instructions = request.instructions()?;
+            let tx = Transaction::new_with_payer(&instructions, Some(&self.program()?.payer()));
+            return Ok(Outcome::Simulated(self.simulate_send(&tx).await?));
+        }
+        
+        let (blockhash, last_valid_block_height) = self.config.async_rpc_client
+            .get_latest_blockhash_with_commitment(self.config.commitment)
+            .await
+            .map_err(rpc_failed("getLatestBlockhash"))?;
+        let tx = request.signed_transaction_with_blockhash(blockhash)?;
+        self.send_and_confirm(&tx, last_valid_block_height, None, self.config.timeout).await
     }
     
-    /// Record an operation in metrics
-    pub async fn record_operation(&self, duration: Duration, success: bool) {
-        let mut metrics = self.metrics.write().await;
-        metrics.record_operation(duration, success);
+    /// Simulate `tx` in place of sending it
+    async fn simulate_send(&self, tx: &Transaction) -> Result<DryRunResult> {
+        let dry_run = DryRunResult::from_simulation(tx, self.simulate(tx).await?);
+        
+        tracing::info!(
+            service = self.service,
+            units_consumed = ?dry_run.units_consumed,
+            failed = dry_run.error.is_some(),
+            "Dry run simulated transaction without sending it"
+        );
+        Ok(dry_run)
     }
     
-    /// Check rate limits before performing an operation
-    pub async fn check_rate_limit(&self, operation: &str) -> Result<()> {
-        let mut rate_limiter = self.rate_limiter.write().await;
-        if !rate_limiter.allow_operation(operation) {
-            return Err(PodComError::RateLimited {
-                operation: operation.to_string(),
-                retry_after: rate_limiter.retry_after(operation),
+    /// Compute budget instructions as sized in every transaction, for packing
+    /// 
+    /// In simulate-then-pad mode the limit is not known until the transaction
+    /// is simulated, so a placeholder of the same size stands in for it.
+    pub fn compute_budget_reserve(&self) -> Vec<Instruction> {
+        let budget = &self.config.compute_budget;
+        let unit_limit = if budget.simulate_then_pad {
+            Some(MAX_COMPUTE_UNIT_LIMIT)
+        } else {
+            budget.unit_limit
+        };
+        compute_budget_instructions(unit_limit, budget.unit_price_micro_lamports)
+    }
+    
+    /// Simulate `instructions` and pad the compute units they consume
+    async fn simulate_unit_limit(&self, payer: &Pubkey, instructions: &[Instruction]) -> Result<u32> {
+        // Simulate under the maximum limit so the default doesn't cap the measurement
+        let mut simulated = compute_budget_instructions(
+            Some(MAX_COMPUTE_UNIT_LIMIT),
+            self.config.compute_budget.unit_price_micro_lamports,
+        );
+        simulated.extend_from_slice(instructions);
+        let result = self.simulate(&Transaction::new_with_payer(&simulated, Some(payer))).await?;
+        
+        if let Some(err) = result.err {
+            return Err(PodComError::SimulationFailed {
+                reason: err.to_string(),
+                logs: result.logs.unwrap_or_default(),
             });
         }
-        Ok(())
+        let consumed = result.units_consumed.ok_or_else(|| -> PodComError {
+            NetworkError::InvalidResponse {
+                reason: "Simulation did not report units consumed".to_string(),
+            }.into()
+        })?;
+        
+        Ok(padded_unit_limit(consumed))
+    }
+    
+    /// Simulate `tx` without checking its signatures
+    async fn simulate(&self, tx: &Transaction) -> Result<RpcSimulateTransactionResult> {
+        self.config.async_rpc_client
+            .simulate_transaction_with_config(tx, simulation_config(self.config.commitment))
+            .await
+            .map(|response| response.value)
+            .map_err(rpc_failed("simulateTransaction"))
     }
     
-    /// Execute an operation with metrics, rate limiting, and error handling
-    pub async fn execute_operation<F, T, E>(
+    /// Submit a signed transaction and wait until it reaches `commitment`
+    /// 
+    /// Uses the configured commitment when `commitment` is `None`. In
+    /// dry-run mode the transaction is simulated instead. Signature
+    /// status is polled every [`CONFIRMATION_POLL_INTERVAL`] until the
+    /// transaction is confirmed, fails on chain, outlives
+    /// `last_valid_block_height` (the height returned with its blockhash),
+    /// or `timeout` passes. On timeout the error carries the signature so
+    /// the caller can check on the transaction later instead of resubmitting
+    /// it blindly; on expiry it can no longer land and is safe to rebuild.
+    pub async fn send_and_confirm(
         &self,
-        operation_name: &str,
-        operation: F,
-    ) -> Result<T>
+        tx: &Transaction,
+        last_valid_block_height: u64,
+        commitment: Option<CommitmentConfig>,
+        timeout: Duration,
+    ) -> Result<Outcome> {
+        if self.config.dry_run {
+            return Ok(Outcome::Simulated(self.simulate_send(tx).await?));
+        }
+        
+        let commitment = commitment.unwrap_or(self.config.commitment);
+        let rpc_client = &self.config.async_rpc_client;
+        let config = RpcSendTransactionConfig {
+            preflight_commitment: Some(commitment.commitment),
+            ..Default::default()
+        };
+        let signature = rpc_client
+            .send_transaction_with_config(tx, config)
+            .await
+            .map_err(rpc_failed("sendTransaction"))?;
+        let deadline = Instant::now() + timeout;
+        
+        loop {
+            // Read the height before the status: if it was already past the
+            // last valid height and the status is still unknown, it never lands
+            let block_height = rpc_client
+                .get_block_height_with_commitment(commitment)
+                .await
+                .map_err(rpc_failed("getBlockHeight"))?;
+            let status = rpc_client
+                .get_signature_statuses(&[signature])
+                .await
+                .map_err(rpc_failed("getSignatureStatuses"))?
+                .value
+                .into_iter()
+                .next()
+                .flatten();
+            
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    return Err(PodComError::TransactionFailed { signature, reason: err.to_string() });
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(Outcome::Sent(signature));
+                }
+            } else if block_height > last_valid_block_height {
+                return Err(PodComError::TransactionExpired { signature, last_valid_block_height });
+            }
+            
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PodComError::ConfirmationTimeout { signature, timeout });
+            }
+            tokio::time::sleep(CONFIRMATION_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+    
+    /// Fetch and decode a program account with bounded deserialization
+    /// 
+    /// Served from the account cache when a fresh entry exists.
+    pub async fn fetch_account<T>(&self, address: &Pubkey) -> Result<T>
     where
-        F: std::future::Future<Output = std::result::Result<T, E>>,
-        E: Into<PodComError>,
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
     {
-        // Check rate limits
-        self.check_rate_limit(operation_name).await?;
+        self.fetch_account_with(address, false).await
+    }
+    
+    /// Fetch and decode a program account, skipping the cache if `bypass_cache`
+    /// 
+    /// A bypassing fetch still refreshes the cached entry.
+    pub async fn fetch_account_with<T>(&self, address: &Pubkey, bypass_cache: bool) -> Result<T>
+    where
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
+    {
+        self.program()?;
         
-        let start_time = Instant::now();
+        if !bypass_cache {
+            if let Some(data) = self.cache.as_ref().and_then(|cache| lock_cache(cache).get(address)) {
+                return decode_account(&data);
+            }
+        }
         
-        // Execute operation
-        let result = operation.await;
+        let data = self.config.async_rpc_client.get_account_data(address).await.map_err(|e| -> PodComError {
+            NetworkError::RpcFailed {
+                method: "getAccountInfo".to_string(),
+                reason: e.to_string(),
+            }.into()
+        })?;
+        let account = decode_account(&data)?;
         
-        let duration = start_time.elapsed();
-        let success = result.is_ok();
+        if let Some(cache) = &self.cache {
+            lock_cache(cache).insert(*address, data);
+        }
+        Ok(account)
+    }
+    
+    /// Drop any cached state for `address`, e.g. after a transaction changed it
+    pub fn invalidate(&self, address: &Pubkey) {
+        if let Some(cache) = &self.cache {
+            lock_cache(cache).invalidate(address);
+        }
+    }
+    
+    /// Fetch and decode every program account of type `T` with bounded deserialization
+    ///
+    /// Accounts whose data cannot be decoded are skipped with a warning and
+    /// counted in [`ServiceMetrics::skipped_accounts`].
+    pub async fn fetch_program_accounts<T>(&self) -> Result<Vec<(Pubkey, T)>>
+    where
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
+    {
+        self.program()?;
         
-        // Record metrics
-        self.record_operation(duration, success).await;
+        let config = program_accounts_config::<T>(self.config.commitment);
+        let accounts = self.config.async_rpc_client
+            .get_program_accounts_with_config(&self.config.effective_program_id(), config)
+            .await
+            .map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "getProgramAccounts".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?;
         
-        // Convert error and return
-        result.map_err(|e| e.into())
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(address, account)| self.decode_or_skip(&address, &account.data))
+            .collect())
     }
     
-    /// Get health status
-    pub fn health_check(&self) -> ServiceHealth {
-        if self.program.is_none() {
-            return ServiceHealth::NotInitialized;
-        }
+    /// Scan program accounts of type `T` page by page
+    /// 
+    /// Matching addresses are listed through `getProgramAccounts` with an
+    /// empty `dataSlice`, narrowed by `filters` in addition to the account
+    /// discriminator. Account data is then fetched `page_size` accounts at a
+    /// time and handed to `on_page`, so callers can aggregate incrementally
+    /// without holding every account in memory. Accounts that cannot be
+    /// decoded or were closed since listing are skipped. Returns the number
+    /// of accounts passed to `on_page`.
+    pub async fn scan_accounts<T, F>(
+        &self,
+        filters: Vec<RpcFilterType>,
+        page_size: usize,
+        mut on_page: F,
+    ) -> Result<u64>
+    where
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
+        F: FnMut(Vec<(Pubkey, T)>) -> Result<()>,
+    {
+        self.scan_accounts_while(filters, page_size, |page| on_page(page).map(|()| true)).await
+    }
+    
+    /// Scan like [`scan_accounts`](Self::scan_accounts) until `on_page`
+    /// returns `false`
+    /// 
+    /// No page after the one `on_page` declines to continue from is fetched.
+    pub async fn scan_accounts_while<T, F>(
+        &self,
+        filters: Vec<RpcFilterType>,
+        page_size: usize,
+        mut on_page: F,
+    ) -> Result<u64>
+    where
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
+        F: FnMut(Vec<(Pubkey, T)>) -> Result<bool>,
+    {
+        let mut config = program_accounts_config::<T>(self.config.commitment);
+        config.filters.get_or_insert_with(Vec::new).extend(filters);
+        config.account_config.data_slice = Some(solana_account_decoder::UiDataSliceConfig {
+            offset: 0,
+            length: 0,
+        });
         
-        // Check if service has been running for a reasonable time
-        if let Some(init_time) = self.initialized_at {
-            if init_time.elapsed() < Duration::from_secs(5) {
-                return ServiceHealth::Healthy; // Recently initialized
+        let addresses: Vec<Pubkey> = self.config.async_rpc_client
+            .get_program_accounts_with_config(&self.config.effective_program_id(), config)
+            .await
+            .map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "getProgramAccounts".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect();
+        
+        let mut scanned = 0u64;
+        for page in addresses.chunks(page_size.clamp(1, MAX_MULTIPLE_ACCOUNTS)) {
+            let accounts = self.config.async_rpc_client.get_multiple_accounts(page).await.map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "getMultipleAccounts".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?;
+            
+            let decoded: Vec<(Pubkey, T)> = page
+                .iter()
+                .zip(accounts)
+                .filter_map(|(address, account)| self.decode_or_skip(address, &account?.data))
+                .collect();
+            
+            scanned += decoded.len() as u64;
+            if !on_page(decoded)? {
+                break;
             }
         }
         
-        // Perform sophisticated health checks based on metrics
-        let metrics = futures::executor::block_on(self.metrics());
-        
-        // If no operations have been performed yet, consider healthy
-        if metrics.operations_count == 0 {
-            return ServiceHealth::Healthy;
+        Ok(scanned)
+    }
+    
+    /// Decode a listed account, or log and count it as skipped
+    fn decode_or_skip<T>(&self, address: &Pubkey, data: &[u8]) -> Option<(Pubkey, T)>
+    where
+        T: anchor_lang::Discriminator + borsh::BorshDeserialize,
+    {
+        match decode_account(data) {
+            Ok(decoded) => Some((*address, decoded)),
+            Err(e) => {
+                tracing::warn!(service = self.service, "Skipping undecodable account {}: {}", address, e);
+                self.metrics.record_skipped_account();
+                None
+            }
         }
-        
-        // Check error rate
-        let error_rate = metrics.error_rate();
-        let recent_activity = metrics.last_operation_at
-            .map(|last| {
-                let now = chrono::Utc::now();
-                let minutes_since_last = now.signed_duration_since(last).num_minutes();
-                minutes_since_last < 5 // Active within last 5 minutes
+    }
+    
+    /// Get service configuration
+    pub fn config(&self) -> &ServiceConfig {
+        &self.config
+    }
+    
+    /// Get service metrics
+    pub async fn metrics(&self) -> ServiceMetrics {
+        self.metrics_snapshot()
+    }
+    
+    /// Get service metrics without awaiting, safe to call from any context
+    pub fn metrics_snapshot(&self) -> ServiceMetrics {
+        self.metrics.snapshot()
+    }
+    
+    /// Get the number of operations currently in flight
+    pub fn pending_operations(&self) -> u64 {
+        self.pending_operations.load(Ordering::Acquire)
+    }
+    
+    /// Record an operation in metrics
+    pub async fn record_operation(&self, duration: Duration, success: bool) {
+        self.metrics.record(duration, success);
+    }
+    
+    /// Check rate limits before performing an operation
+    /// 
+    /// Waits for the service's [`RateLimiter`] to allow the request, failing
+    /// with [`PodComError::RateLimited`] if that would take longer than the
+    /// RPC timeout.
+    pub async fn check_rate_limit(&self, operation: &str) -> Result<()> {
+        let max_wait = Duration::from_secs(self.config.rpc_timeout_secs);
+        self.rate_limiter.acquire(operation, max_wait).await
+    }
+    
+    /// Execute an operation with metrics, rate limiting, error handling, and retries
+    /// 
+    /// `operation` is called once per attempt. Transient failures are retried
+    /// with the service's configured [`RetryConfig`]; see
+    /// [`execute_operation_with`](Self::execute_operation_with). Only use
+    /// this for reads; operations that send transactions go through
+    /// [`execute_transaction`](Self::execute_transaction).
+    pub async fn execute_operation<F, Fut, T, E>(
+        &self,
+        operation_name: &str,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        let retry_config = self.config.retry_config.clone();
+        self.execute_operation_with(operation_name, retry_config, operation).await
+    }
+
+    /// Execute a state-changing operation with metrics and rate limiting, but no retries
+    ///
+    /// Sending a transaction is not idempotent: one whose send or
+    /// confirmation timed out may still land, so attempting `operation`
+    /// again could submit it twice. Reads inside `operation` that go through
+    /// [`execute_operation`](Self::execute_operation) are still retried.
+    pub async fn execute_transaction<F, Fut, T, E>(
+        &self,
+        operation_name: &str,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        self.execute_once(operation_name, operation).await
+    }
+
+    /// Execute an operation with metrics and rate limiting in a single attempt
+    ///
+    /// For operations that must not be repeated as a whole, such as sends or
+    /// waits that already poll for their result.
+    pub async fn execute_once<F, Fut, T, E>(
+        &self,
+        operation_name: &str,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        let retry_config = RetryConfig {
+            max_attempts: 1,
+            max_retries: 0,
+            ..self.config.retry_config.clone()
+        };
+        self.execute_operation_with(operation_name, retry_config, operation).await
+    }
+
+    /// Execute an operation, retrying transient failures with `retry_config`
+    /// 
+    /// Makes at most `max_attempts` attempts, backing off exponentially between
+    /// them. Only errors that are [retryable](RetryableError::is_retryable),
+    /// such as RPC timeouts and dropped connections, are retried; any other
+    /// error is returned immediately.
+    /// 
+    /// The operation runs inside an [operation span](Self::operation_span),
+    /// which closes with a success or error event carrying the elapsed time.
+    pub async fn execute_operation_with<F, Fut, T, E>(
+        &self,
+        operation_name: &str,
+        retry_config: RetryConfig,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        async {
+            let start_time = Instant::now();
+            let result = self.retry_operation(operation_name, retry_config, operation).await;
+            let elapsed_ms = start_time.elapsed().as_millis() as u64;
+            
+            match &result {
+                Ok(_) => tracing::info!(elapsed_ms, "Operation succeeded"),
+                Err(e) => tracing::error!(elapsed_ms, error = %e, "Operation failed"),
+            }
+            result
+        }
+        .instrument(self.operation_span(operation_name))
+        .await
+    }
+    
+    /// Span for one call to `operation_name`
+    /// 
+    /// Records the `service`, `operation`, paying `wallet`, `program_id`, and a
+    /// random `request_id`. The values are only computed when the span is
+    /// enabled, so operations cost nothing extra with tracing off.
+    pub fn operation_span(&self, operation_name: &str) -> tracing::Span {
+        let span = tracing::info_span!(
+            "service_operation",
+            service = self.service,
+            operation = operation_name,
+            wallet = tracing::field::Empty,
+            program_id = tracing::field::Empty,
+            request_id = tracing::field::Empty,
+        );
+        
+        if !span.is_disabled() {
+            if let Some(program) = &self.program {
+                span.record("wallet", tracing::field::display(program.payer()));
+            }
+            span.record("program_id", tracing::field::display(self.config.effective_program_id()));
+            span.record("request_id", format!("{:016x}", rand::random::<u64>()).as_str());
+        }
+        span
+    }
+    
+    /// Attempt `operation` until it succeeds, fails permanently, or runs out of attempts
+    async fn retry_operation<F, Fut, T, E>(
+        &self,
+        operation_name: &str,
+        retry_config: RetryConfig,
+        mut operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        let max_attempts = retry_config.max_attempts.max(1);
+        let retry = RetryHandler::new(retry_config);
+        let mut attempt = 0;
+        
+        loop {
+            match self.run_attempt(operation_name, operation()).await {
+                Err(e) if e.is_retryable() && attempt + 1 < max_attempts => {
+                    let delay = retry.delay_for(attempt as u32, &e);
+                    tracing::debug!(
+                        operation = operation_name,
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        "Retrying after transient error: {}",
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+    
+    /// Run a single attempt with rate limiting and metrics
+    async fn run_attempt<Fut, T, E>(&self, operation_name: &str, attempt: Fut) -> Result<T>
+    where
+        Fut: std::future::Future<Output = std::result::Result<T, E>>,
+        E: Into<PodComError>,
+    {
+        // Check rate limits
+        self.check_rate_limit(operation_name).await?;
+        
+        let start_time = Instant::now();
+        
+        // Execute operation; the guard keeps the count right if the future is dropped
+        let pending = PendingOperation::start(&self.pending_operations);
+        let result = attempt.await;
+        drop(pending);
+        
+        let duration = start_time.elapsed();
+        let success = result.is_ok();
+        
+        // Record metrics
+        self.record_operation(duration, success).await;
+        self.metrics.record_latency(operation_name, duration);
+        
+        // Convert error and return
+        result.map_err(|e| e.into())
+    }
+    
+    /// Probe the RPC endpoint with `getHealth` and classify the response
+    /// 
+    /// Goes through the configured RPC transport, so the method allowlist
+    /// and proxy apply. See [`probe_health_with`](Self::probe_health_with).
+    pub async fn probe_health(&self) -> ServiceHealth {
+        self.probe_health_with(|| self.config.async_rpc_client.get_health()).await
+    }
+    
+    /// Run `probe` against the service's endpoint and classify the response
+    /// 
+    /// An uninitialized service is `NotInitialized` without probing. An
+    /// endpoint answering within [`HEALTH_PROBE_SLOW_AFTER`] is `Healthy`,
+    /// one answering more slowly is `Degraded`, and one that errors or does
+    /// not answer within [`HEALTH_PROBE_TIMEOUT`] is `Unhealthy`. The result
+    /// is reused for [`HEALTH_PROBE_TTL`], and callers arriving while a
+    /// probe is in flight wait for its result, so polling health does not
+    /// hammer the endpoint.
+    pub async fn probe_health_with<F, Fut, E>(&self, probe: F) -> ServiceHealth
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = std::result::Result<(), E>>,
+        E: std::fmt::Display,
+    {
+        if self.program.is_none() {
+            return ServiceHealth::NotInitialized;
+        }
+        
+        let mut last_probe = self.health_probe.lock().await;
+        if let Some((probed_at, health)) = last_probe.as_ref() {
+            if probed_at.elapsed() < HEALTH_PROBE_TTL {
+                return health.clone();
+            }
+        }
+        
+        let health = classify_probe(probe(), HEALTH_PROBE_SLOW_AFTER, HEALTH_PROBE_TIMEOUT).await;
+        *last_probe = Some((Instant::now(), health.clone()));
+        health
+    }
+    
+    /// Get health status
+    pub fn health_check(&self) -> ServiceHealth {
+        if self.program.is_none() {
+            return ServiceHealth::NotInitialized;
+        }
+        
+        // Check if service has been running for a reasonable time
+        if let Some(init_time) = self.initialized_at {
+            if init_time.elapsed() < Duration::from_secs(5) {
+                return ServiceHealth::Healthy; // Recently initialized
+            }
+        }
+        
+        // Perform sophisticated health checks based on metrics
+        let metrics = self.metrics_snapshot();
+        
+        // If no operations have been performed yet, consider healthy
+        if metrics.operations_count == 0 {
+            return ServiceHealth::Healthy;
+        }
+        
+        // Check error rate
+        let error_rate = metrics.error_rate();
+        let recent_activity = metrics.last_operation_at
+            .map(|last| {
+                let now = chrono::Utc::now();
+                let minutes_since_last = now.signed_duration_since(last).num_minutes();
+                minutes_since_last < 5 // Active within last 5 minutes
             })
             .unwrap_or(false);
         
@@ -401,73 +1555,143 @@ impl ServiceBase {
         }
     }
     
+    /// Token cancelled when the service shuts down
+    /// 
+    /// Subscriptions and other long-lived work should stop once it fires.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.shutdown_token.child_token()
+    }
+    
+    /// Spawn a background task, such as a cache sweeper, owned by the service
+    /// 
+    /// `task` receives a token that is cancelled on shutdown and should
+    /// return promptly once it is. Shutdown waits up to
+    /// [`SHUTDOWN_TASK_TIMEOUT`] for spawned tasks before aborting them. The
+    /// task is tracked in the registry shared with the client, so dropping
+    /// the client or the service aborts it.
+    pub fn spawn_task<F, Fut>(&self, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task(self.cancellation_token()));
+        self.tasks.track(self.task_owner, handle.abort_handle());
+    }
+    
     /// Shutdown the service
+    /// 
+    /// Cancels subscriptions and background tasks, then waits up to
+    /// [`SHUTDOWN_TASK_TIMEOUT`] for spawned tasks to exit; any still running
+    /// are aborted. The service can be initialized again afterwards.
     pub async fn shutdown(&mut self) -> Result<()> {
+        std::mem::replace(&mut self.shutdown_token, CancellationToken::new()).cancel();
+        
+        let tasks = self.tasks.take_owned(self.task_owner);
+        let finished = async {
+            while tasks.iter().any(|task| !task.is_finished()) {
+                tokio::time::sleep(SHUTDOWN_POLL_INTERVAL).await;
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_TASK_TIMEOUT, finished).await.is_err() {
+            tracing::warn!(
+                service = self.service,
+                timeout_ms = SHUTDOWN_TASK_TIMEOUT.as_millis() as u64,
+                "Background tasks ignored cancellation; aborting"
+            );
+            tasks.iter().for_each(AbortHandle::abort);
+        }
+        
         self.program = None;
         self.initialized_at = None;
         Ok(())
     }
 }
 
-/// Rate limiter implementation
-#[derive(Debug)]
-pub struct RateLimiter {
-    config: RateLimitConfig,
-    operation_counts: std::collections::HashMap<String, OperationCounter>,
+impl Drop for ServiceBase {
+    /// Cancel subscriptions and abort background tasks the service still owns
+    fn drop(&mut self) {
+        self.shutdown_token.cancel();
+        self.tasks.take_owned(self.task_owner).iter().for_each(AbortHandle::abort);
+    }
+}
+
+/// Counts one in-flight operation until dropped
+struct PendingOperation<'a>(&'a AtomicU64);
+
+impl<'a> PendingOperation<'a> {
+    fn start(counter: &'a AtomicU64) -> Self {
+        counter.fetch_add(1, Ordering::AcqRel);
+        Self(counter)
+    }
 }
 
-#[derive(Debug)]
-struct OperationCounter {
-    count: u32,
-    window_start: Instant,
-    last_request: Instant,
+impl Drop for PendingOperation<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
-impl RateLimiter {
-    pub fn new(config: RateLimitConfig) -> Self {
-        Self {
-            config,
-            operation_counts: std::collections::HashMap::new(),
+/// Transaction builder returned by [`ServiceBase::request`]
+pub type Request<'a> = RequestBuilder<'a, Arc<Keypair>, Box<dyn Signer + 'a>>;
+
+/// Off-curve account keys referenced by `message`'s instructions, which are PDAs
+fn derived_addresses(message: &Message) -> Vec<Pubkey> {
+    let mut addresses = Vec::new();
+    for index in message.instructions.iter().flat_map(|ix| &ix.accounts) {
+        let key = message.account_keys[*index as usize];
+        if !key.is_on_curve() && !addresses.contains(&key) {
+            addresses.push(key);
         }
     }
-    
-    /// Check if an operation is allowed under rate limits
-    pub fn allow_operation(&mut self, operation: &str) -> bool {
-        let now = Instant::now();
-        let counter = self.operation_counts.entry(operation.to_string()).or_insert(OperationCounter {
-            count: 0,
-            window_start: now,
-            last_request: now,
-        });
-        
-        // Reset window if expired
-        if now.duration_since(counter.window_start) >= self.config.window_duration {
-            counter.count = 0;
-            counter.window_start = now;
-        }
-        
-        // Check rate limit
-        if counter.count >= self.config.max_requests_per_window {
-            return false;
-        }
-        
-        // Update counter
-        counter.count += 1;
-        counter.last_request = now;
-        
-        true
+    addresses
+}
+
+/// Simulation settings for unsigned transactions built without a recent blockhash
+pub(crate) fn simulation_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..Default::default()
     }
+}
+
+/// Compute budget instructions for a unit limit and price, limit first
+pub(crate) fn compute_budget_instructions(
+    unit_limit: Option<u32>,
+    unit_price_micro_lamports: Option<u64>,
+) -> Vec<Instruction> {
+    unit_limit
+        .map(ComputeBudgetInstruction::set_compute_unit_limit)
+        .into_iter()
+        .chain(unit_price_micro_lamports.map(ComputeBudgetInstruction::set_compute_unit_price))
+        .collect()
+}
+
+/// Pad simulated compute units by [`COMPUTE_UNIT_PADDING_PERCENT`], capped at the maximum limit
+fn padded_unit_limit(consumed: u64) -> u32 {
+    let padded = consumed.saturating_mul(COMPUTE_UNIT_PADDING_PERCENT).div_ceil(100);
+    padded.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Program account query selecting accounts of type `T` by discriminator, base64-encoded
+pub(crate) fn program_accounts_config<T: anchor_lang::Discriminator>(
+    commitment: CommitmentConfig,
+) -> solana_rpc_client_api::config::RpcProgramAccountsConfig {
+    use solana_rpc_client_api::{
+        config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        filter::{Memcmp, RpcFilterType},
+    };
     
-    /// Get retry after duration for an operation
-    pub fn retry_after(&self, operation: &str) -> Option<Duration> {
-        if let Some(counter) = self.operation_counts.get(operation) {
-            let window_remaining = self.config.window_duration
-                .saturating_sub(counter.window_start.elapsed());
-            if window_remaining > Duration::ZERO {
-                return Some(window_remaining);
-            }
-        }
-        None
+    let discriminator: &[u8] = T::DISCRIMINATOR.as_ref();
+    RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, discriminator))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..Default::default()
+        },
+        ..Default::default()
     }
 }
 
@@ -510,29 +1734,109 @@ impl RetryHandler {
         Err(last_error.unwrap())
     }
     
-    fn calculate_delay(&self, attempt: u32) -> Duration {
+    pub(crate) fn calculate_delay(&self, attempt: u32) -> Duration {
         let base_delay = self.config.base_delay;
         let multiplier = self.config.multiplier.powi(attempt as i32);
         let delay = base_delay.mul_f64(multiplier);
         
         // Apply jitter
-        let jitter = if self.config.jitter > 0.0 {
-            use rand::Rng;
-            let mut rng = rand::thread_rng();
-            let jitter_factor = rng.gen_range(1.0 - self.config.jitter..=1.0 + self.config.jitter);
-            delay.mul_f64(jitter_factor)
-        } else {
-            delay
-        };
+        let jitter = self.config.apply_jitter(delay);
         
         std::cmp::min(jitter, self.config.max_delay)
     }
+    
+    /// Backoff before retrying after `error`
+    /// 
+    /// Rate limit errors that say how long to wait are never retried sooner
+    /// than that, even if it exceeds the configured maximum delay.
+    pub(crate) fn delay_for(&self, attempt: u32, error: &PodComError) -> Duration {
+        let backoff = self.calculate_delay(attempt);
+        match error {
+            PodComError::RateLimited { .. }
+            | PodComError::Network(NetworkError::RateLimited { .. })
+            | PodComError::Agent(AgentError::RateLimitExceeded { .. })
+            | PodComError::Message(MessageError::RateLimit { .. }) => {
+                error.retry_after().map_or(backoff, |wait| wait.max(backoff))
+            }
+            _ => backoff,
+        }
+    }
+    
+    /// Backoff before the next reconnect, or `None` once `max_retries` is exhausted
+    pub(crate) fn reconnect_delay(&self, failures: &mut usize, max_retries: usize) -> Option<Duration> {
+        if *failures >= max_retries {
+            return None;
+        }
+        let delay = self.calculate_delay(*failures as u32);
+        *failures += 1;
+        Some(delay)
+    }
+}
+
+/// Map an RPC client error for `method` into a [`NetworkError::RpcFailed`]
+fn rpc_failed(method: &str) -> impl FnOnce(solana_rpc_client_api::client_error::Error) -> PodComError {
+    let method = method.to_string();
+    move |e| NetworkError::RpcFailed { method, reason: e.to_string() }.into()
+}
+
+/// Await a health probe and classify how it answered
+async fn classify_probe<Fut, E>(probe: Fut, slow_after: Duration, timeout: Duration) -> ServiceHealth
+where
+    Fut: std::future::Future<Output = std::result::Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let start = Instant::now();
+    
+    match tokio::time::timeout(timeout, probe).await {
+        Ok(Ok(())) if start.elapsed() <= slow_after => ServiceHealth::Healthy,
+        Ok(Ok(())) => ServiceHealth::Degraded,
+        Ok(Err(e)) => {
+            tracing::debug!(error = %e, "Health probe failed");
+            ServiceHealth::Unhealthy
+        }
+        Err(_) => {
+            tracing::debug!(timeout_ms = timeout.as_millis() as u64, "Health probe timed out");
+            ServiceHealth::Unhealthy
+        }
+    }
 }
 
+/// Percentage of simulated compute units requested as the limit
+const COMPUTE_UNIT_PADDING_PERCENT: u64 = 120;
+
+/// How long shutdown waits for cancelled background tasks to exit
+pub const SHUTDOWN_TASK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often shutdown checks whether cancelled background tasks have exited
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often `send_and_confirm` polls signature status
+pub const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long an RPC health probe may take before the endpoint counts as unreachable
+pub const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Probe latency above which a responsive endpoint counts as degraded
+pub const HEALTH_PROBE_SLOW_AFTER: Duration = Duration::from_millis(500);
+
+/// How long a health probe result is reused before probing again
+pub const HEALTH_PROBE_TTL: Duration = Duration::from_secs(5);
+
+/// Operation names given a latency histogram; later names are not tracked
+pub const MAX_TRACKED_OPERATIONS: usize = 64;
+
+/// Longest latency a histogram distinguishes
+pub const MAX_RECORDED_LATENCY: Duration = Duration::from_secs(600);
+
+/// Percentiles (0 to 100) kept in each [`LatencySummary`]
+pub const SUMMARY_PERCENTILES: [f64; 5] = [50.0, 90.0, 95.0, 99.0, 99.9];
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Duration;
+    use pod_sdk_types::pda;
+    use crate::testing::{simulation, with_context, MockRpc, TraceCapture};
 
     #[test]
     fn test_service_metrics() {
@@ -549,16 +1853,776 @@ mod tests {
         assert_eq!(metrics.avg_duration_ms, 150.0);
     }
 
+    #[test]
+    fn test_operation_latency_percentiles() {
+        let recorder = MetricsRecorder::default();
+        
+        // 90 fast calls and 10 slow ones
+        for _ in 0..90 {
+            recorder.record_latency("fetch", Duration::from_millis(1));
+        }
+        for _ in 0..10 {
+            recorder.record_latency("fetch", Duration::from_millis(100));
+        }
+        recorder.record_latency("send", Duration::from_millis(5));
+        
+        let metrics = recorder.snapshot();
+        assert_eq!(metrics.count("fetch"), 100);
+        assert_eq!(metrics.count("send"), 1);
+        
+        // Values come back as the top of their bucket, within 1%
+        let p50 = metrics.percentile("fetch", 50.0);
+        assert!(p50 >= Duration::from_millis(1) && p50 <= Duration::from_micros(1010), "p50 {:?}", p50);
+        let p99 = metrics.percentile("fetch", 99.0);
+        assert!(p99 >= Duration::from_millis(100) && p99 <= Duration::from_micros(101_000), "p99 {:?}", p99);
+        
+        assert_eq!(metrics.count("unknown"), 0);
+        assert_eq!(metrics.percentile("unknown", 99.0), Duration::ZERO);
+        
+        // Unsummarized percentiles round up to a summarized one
+        assert_eq!(metrics.percentile("fetch", 0.9 * 100.0), metrics.percentile("fetch", 90.0));
+        assert_eq!(metrics.percentile("fetch", 98.0), p99);
+        assert_eq!(metrics.percentile("fetch", 100.0), metrics.percentile("fetch", 99.9));
+    }
+
+    #[test]
+    fn test_tracked_operation_names_are_capped() {
+        let mut latencies = OperationLatencies::default();
+        for i in 0..MAX_TRACKED_OPERATIONS {
+            latencies.record(&format!("op{}", i), Duration::from_millis(1));
+        }
+        latencies.record("one_too_many", Duration::from_millis(1));
+        latencies.record("op0", Duration::from_millis(1));
+        
+        assert_eq!(latencies.count("one_too_many"), 0);
+        assert_eq!(latencies.count("op0"), 2);
+        
+        // Latencies past the maximum are clamped rather than dropped
+        latencies.record("op1", MAX_RECORDED_LATENCY * 2);
+        assert_eq!(latencies.count("op1"), 2);
+    }
+
+    fn fast_retry(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            max_retries: max_attempts,
+            base_delay: Duration::from_millis(1),
+            enable_jitter: false,
+            ..RetryConfig::default()
+        }
+    }
+
+    /// Operation that times out `failures` times before succeeding
+    async fn flaky_operation(calls: &std::sync::atomic::AtomicUsize, failures: usize) -> Result<&'static str> {
+        if calls.fetch_add(1, Ordering::SeqCst) < failures {
+            Err(NetworkError::Timeout { timeout: Duration::from_secs(1) }.into())
+        } else {
+            Ok("done")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_operation_retries_transient_errors() {
+        let base = ServiceBase::new(crate::config::test_config());
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+
+        let result = base
+            .execute_operation_with("flaky", fast_retry(3), || flaky_operation(&calls, 2))
+            .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let metrics = base.metrics().await;
+        assert_eq!(metrics.failed_operations, 2);
+        assert_eq!(metrics.successful_operations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_readable_inside_runtime() {
+        let base = ServiceBase::new(crate::config::test_config());
+        base.execute_operation("ok", || async { Ok::<_, PodComError>(()) }).await.unwrap();
+        
+        // Synchronous reads must not block the runtime they are called from
+        let metrics = base.metrics_snapshot();
+        assert_eq!(metrics.operations_count, 1);
+        assert_eq!(metrics.successful_operations, 1);
+        assert!(metrics.last_operation_at.is_some());
+        assert_eq!(base.health_check(), ServiceHealth::NotInitialized);
+        
+        let service = crate::services::AgentService::new(crate::config::test_config());
+        assert_eq!(service.metrics().operations_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_operation_gives_up_after_max_attempts() {
+        let base = ServiceBase::new(crate::config::test_config());
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+
+        let result = base
+            .execute_operation_with("flaky", fast_retry(2), || flaky_operation(&calls, 5))
+            .await;
+
+        assert!(matches!(result, Err(PodComError::Network(NetworkError::Timeout { .. }))));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_transaction_does_not_retry() {
+        let mut config = crate::config::test_config();
+        config.retry_config = fast_retry(5);
+        let base = ServiceBase::new(config);
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+
+        // A timed-out send may have landed, so it is not attempted again
+        let result = base.execute_transaction("send", || flaky_operation(&calls, 1)).await;
+
+        assert!(matches!(result, Err(PodComError::Network(NetworkError::Timeout { .. }))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_rate_limited_delay_honors_retry_after() {
+        let retry = RetryHandler::new(fast_retry(3));
+        let backoff = retry.calculate_delay(0);
+        
+        let limited = PodComError::RateLimited {
+            operation: "send_message".to_string(),
+            retry_after: Some(Duration::from_secs(2)),
+        };
+        assert_eq!(retry.delay_for(0, &limited), Duration::from_secs(2));
+        
+        let unhinted = PodComError::RateLimited { operation: "send_message".to_string(), retry_after: None };
+        assert_eq!(retry.delay_for(0, &unhinted), backoff);
+
+        let agent = PodComError::from(AgentError::RateLimitExceeded { operations: 10, window: Duration::from_secs(3) });
+        assert_eq!(retry.delay_for(0, &agent), Duration::from_secs(3));
+        let message = PodComError::from(MessageError::RateLimit { count: 10, window: Duration::from_secs(4) });
+        assert_eq!(retry.delay_for(0, &message), Duration::from_secs(4));
+        
+        // Other errors' hints do not override the configured backoff
+        let timeout = PodComError::from(NetworkError::Timeout { timeout: Duration::from_secs(30) });
+        assert_eq!(retry.delay_for(0, &timeout), backoff);
+    }
+
+    #[tokio::test]
+    async fn test_execute_operation_returns_non_transient_errors_immediately() {
+        let base = ServiceBase::new(crate::config::test_config());
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+
+        let result: Result<()> = base
+            .execute_operation_with("invalid", fast_retry(5), || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(PodComError::InvalidConfiguration {
+                    message: "bad program id".to_string(),
+                })
+            })
+            .await;
+
+        assert!(matches!(result, Err(PodComError::InvalidConfiguration { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_operation_span_records_context() {
+        use anchor_client::{Client, Cluster};
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let capture = TraceCapture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+        
+        let payer = Arc::new(Keypair::new());
+        let mut base = ServiceBase::new(crate::config::test_config()).with_service_name("agent");
+        base.initialize(Client::new(Cluster::Localnet, payer.clone()).program(crate::PROGRAM_ID).unwrap())
+            .await
+            .unwrap();
+        base.execute_operation("register_agent", || async { Ok::<_, PodComError>(()) }).await.unwrap();
+        
+        let spans = capture.spans();
+        assert_eq!(spans["service"], "agent");
+        assert_eq!(spans["operation"], "register_agent");
+        assert_eq!(spans["wallet"], payer.pubkey().to_string());
+        assert_eq!(spans["program_id"], crate::PROGRAM_ID.to_string());
+        assert_eq!(spans["request_id"].len(), 16);
+        
+        let events = capture.events();
+        let finished = events.last().unwrap();
+        assert_eq!(finished["message"], "Operation succeeded");
+        assert!(finished.contains_key("elapsed_ms"));
+    }
+
     #[test]
     fn test_rate_limiter() {
         let config = RateLimitConfig {
+            requests_per_second: 1,
             max_requests_per_window: 2,
+            burst_capacity: 2,
+            enabled: true,
             window_duration: Duration::from_secs(60),
         };
-        let mut rate_limiter = RateLimiter::new(config);
+        let rate_limiter = RateLimiter::new(config);
+        
+        assert!(rate_limiter.try_acquire());
+        assert!(rate_limiter.try_acquire());
+        assert!(!rate_limiter.try_acquire());
+    }
+
+    #[test]
+    fn test_program_id_override_changes_pdas() {
+        let owner = Pubkey::new_unique();
+        let mut config = crate::config::test_config();
+        assert_eq!(config.effective_program_id(), config.program_id);
+        
+        let test_program = Pubkey::new_unique();
+        config.program_id_override = Some(test_program);
+        assert_eq!(config.effective_program_id(), test_program);
+        
+        let (default_pda, _) = pda::agent_pda(&crate::PROGRAM_ID, &owner);
+        let (override_pda, _) = pda::agent_pda(&config.effective_program_id(), &owner);
+        assert_ne!(default_pda, override_pda);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_requires_override_program() {
+        use anchor_client::{Client, Cluster};
+        
+        let test_program = Pubkey::new_unique();
+        let mut config = crate::config::test_config();
+        config.program_id_override = Some(test_program);
+        let client = Client::new(Cluster::Localnet, Arc::new(Keypair::new()));
+        
+        let mut base = ServiceBase::new(config);
+        let default_program = client.program(crate::PROGRAM_ID).unwrap();
+        assert!(matches!(
+            base.initialize(default_program).await,
+            Err(PodComError::InvalidConfiguration { .. })
+        ));
+        
+        base.initialize(client.program(test_program).unwrap()).await.unwrap();
+        assert_eq!(base.program().unwrap().id(), test_program);
+    }
+
+    struct MockService {
+        name: &'static str,
+        healthy: bool,
+        shut_down: bool,
+    }
+
+    #[async_trait]
+    impl BaseService for MockService {
+        type Error = PodComError;
+
+        async fn initialize(&mut self, _program: Program<Arc<Keypair>>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn program(&self) -> Result<&Program<Arc<Keypair>>, Self::Error> {
+            Err(PodComError::NotInitialized)
+        }
+
+        fn validate_config(&self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn health_check(&self) -> ServiceHealth {
+            if self.healthy { ServiceHealth::Healthy } else { ServiceHealth::Unhealthy }
+        }
+
+        fn metrics(&self) -> ServiceMetrics {
+            ServiceMetrics::default()
+        }
+
+        async fn shutdown(&mut self) -> Result<(), Self::Error> {
+            self.shut_down = true;
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(PodComError::ServiceUnavailable { service: self.name.to_string() })
+            }
+        }
+
+        fn service_name(&self) -> &'static str {
+            self.name
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dyn_service_iteration() {
+        let mut services: Vec<Box<dyn DynService>> = vec![
+            Box::new(MockService { name: "alpha", healthy: true, shut_down: false }),
+            Box::new(MockService { name: "beta", healthy: false, shut_down: false }),
+        ];
+        
+        let names: Vec<_> = services.iter().map(|s| s.dyn_service_name()).collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+        
+        let health: Vec<_> = services.iter().map(|s| s.dyn_health_check()).collect();
+        assert_eq!(health, vec![ServiceHealth::Healthy, ServiceHealth::Unhealthy]);
+        
+        for service in services.iter() {
+            assert_eq!(service.dyn_metrics().operations_count, 0);
+            assert_eq!(service.dyn_pending_operations(), 0);
+            assert!(service.dyn_validate_config().is_ok());
+        }
+        
+        let mut errors = Vec::new();
+        for service in services.iter_mut() {
+            if let Err(e) = service.dyn_shutdown().await {
+                errors.push((service.dyn_service_name(), e.to_string()));
+            }
+        }
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "beta");
+    }
+
+    /// Minimal account type for exercising paginated scans
+    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    struct Tally {
+        value: u64,
+    }
+
+    impl anchor_lang::Discriminator for Tally {
+        const DISCRIMINATOR: &'static [u8] = &[7, 7, 7, 7, 7, 7, 7, 7];
+    }
+
+    /// Sizes of the account pages fetched through `rpc`
+    ///
+    /// A single-account fetch is counted as a page of one.
+    fn pages(rpc: &MockRpc) -> Vec<usize> {
+        rpc.calls()
+            .into_iter()
+            .filter_map(|(method, params)| match method.as_str() {
+                "getAccountInfo" => Some(1),
+                "getMultipleAccounts" => Some(params[0].as_array().unwrap().len()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_scan_accounts_folds_pages() {
+        use anchor_lang::Discriminator;
+        
+        let mut accounts: HashMap<String, Vec<u8>> = (1..=7u64)
+            .map(|value| {
+                let mut data = Tally::DISCRIMINATOR.to_vec();
+                data.extend(borsh::to_vec(&Tally { value }).unwrap());
+                (Pubkey::new_unique().to_string(), data)
+            })
+            .collect();
+        // Right discriminator, truncated body
+        accounts.insert(Pubkey::new_unique().to_string(), Tally::DISCRIMINATOR.to_vec());
+        let rpc = MockRpc::serving(accounts);
+        
+        let mut config = crate::config::test_config();
+        config.async_rpc_client = rpc.client();
+        let base = ServiceBase::new(config);
+        
+        let mut total = 0u64;
+        let mut largest_page = 0;
+        let scanned = base
+            .scan_accounts::<Tally, _>(Vec::new(), 3, |page| {
+                largest_page = largest_page.max(page.len());
+                total += page.iter().map(|(_, tally)| tally.value).sum::<u64>();
+                Ok(())
+            })
+            .await
+            .unwrap();
+        
+        assert_eq!(scanned, 7);
+        assert_eq!(total, 28);
+        assert_eq!(largest_page, 3);
+        assert_eq!(pages(&rpc), vec![3, 3, 2]);
+        assert_eq!(base.metrics_snapshot().skipped_accounts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scan_accounts_while_stops_fetching() {
+        use anchor_lang::Discriminator;
+        
+        let accounts: HashMap<String, Vec<u8>> = (1..=7u64)
+            .map(|value| {
+                let mut data = Tally::DISCRIMINATOR.to_vec();
+                data.extend(borsh::to_vec(&Tally { value }).unwrap());
+                (Pubkey::new_unique().to_string(), data)
+            })
+            .collect();
+        let rpc = MockRpc::serving(accounts);
+        
+        let mut config = crate::config::test_config();
+        config.async_rpc_client = rpc.client();
+        let base = ServiceBase::new(config);
+        
+        let mut seen = 0;
+        let scanned = base
+            .scan_accounts_while::<Tally, _>(Vec::new(), 3, |page| {
+                seen += page.len();
+                Ok(seen < 4)
+            })
+            .await
+            .unwrap();
+        
+        assert_eq!(scanned, 6);
+        assert_eq!(pages(&rpc), vec![3, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_account_served_from_cache() {
+        use anchor_client::{Client, Cluster};
+        use anchor_lang::Discriminator;
+        
+        let address = Pubkey::new_unique();
+        let mut data = Tally::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&Tally { value: 5 }).unwrap());
+        let rpc = MockRpc::serving([(address.to_string(), data)].into_iter().collect());
+        
+        let mut config = crate::config::test_config();
+        config.account_cache = shared_account_cache(&CacheConfig::default());
+        config.async_rpc_client = rpc.client();
+        let mut base = ServiceBase::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(Keypair::new()))
+            .program(crate::PROGRAM_ID)
+            .unwrap();
+        base.initialize(program).await.unwrap();
+        let fetches = || pages(&rpc).len();
+        
+        assert_eq!(base.fetch_account::<Tally>(&address).await.unwrap().value, 5);
+        assert_eq!(base.fetch_account::<Tally>(&address).await.unwrap().value, 5);
+        assert_eq!(fetches(), 1);
+        
+        assert_eq!(base.fetch_account_with::<Tally>(&address, true).await.unwrap().value, 5);
+        assert_eq!(fetches(), 2);
+        
+        base.invalidate(&address);
+        base.fetch_account::<Tally>(&address).await.unwrap();
+        assert_eq!(fetches(), 3);
+        
+        // Services built from one config share the cache, invalidations included
+        let mut other = ServiceBase::new(base.config().clone());
+        other.initialize(
+            Client::new(Cluster::Localnet, Arc::new(Keypair::new())).program(crate::PROGRAM_ID).unwrap(),
+        ).await.unwrap();
+        other.fetch_account::<Tally>(&address).await.unwrap();
+        assert_eq!(fetches(), 3);
+        other.invalidate(&address);
+        base.fetch_account::<Tally>(&address).await.unwrap();
+        assert_eq!(fetches(), 4);
+    }
+
+    #[test]
+    fn test_poisoned_account_cache_is_emptied() {
+        let cache = shared_account_cache(&CacheConfig::default()).unwrap();
+        let address = Pubkey::new_unique();
+        lock_cache(&cache).insert(address, vec![1, 2, 3]);
+        
+        let poisoner = cache.clone();
+        std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the cache");
+        })
+        .join()
+        .unwrap_err();
+        assert!(cache.is_poisoned());
+        
+        assert_eq!(lock_cache(&cache).get(&address), None);
+        assert!(!cache.is_poisoned());
+        lock_cache(&cache).insert(address, vec![4]);
+        assert_eq!(lock_cache(&cache).get(&address), Some(vec![4]));
+    }
+
+    /// Initialized base with `compute_budget` whose simulations consume `units_consumed`
+    async fn budgeted_base(
+        compute_budget: ComputeBudgetConfig,
+        units_consumed: u64,
+    ) -> (ServiceBase, MockRpc) {
+        use anchor_client::{Client, Cluster};
+        use solana_rpc_client_api::request::RpcRequest;
+        
+        let rpc = MockRpc::new(move |request, _| match request {
+            RpcRequest::SimulateTransaction => with_context(simulation(units_consumed, &[])),
+            other => panic!("unexpected RPC request {}", other),
+        });
+        let mut config = crate::config::test_config();
+        config.compute_budget = compute_budget;
+        config.async_rpc_client = rpc.client();
+        
+        let mut base = ServiceBase::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(Keypair::new()))
+            .program(crate::PROGRAM_ID)
+            .unwrap();
+        base.initialize(program).await.unwrap();
+        (base, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_compute_budget_instructions_come_first() {
+        let noop = Instruction::new_with_bytes(crate::PROGRAM_ID, &[1], Vec::new());
+        let (base, rpc) = budgeted_base(ComputeBudgetConfig {
+            unit_limit: Some(300_000),
+            unit_price_micro_lamports: Some(5_000),
+            simulate_then_pad: false,
+        }, 0).await;
+        
+        let instructions = base
+            .request(|builder| builder.instruction(noop.clone()))
+            .await
+            .unwrap()
+            .instructions()
+            .unwrap();
+        assert_eq!(instructions, vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+            ComputeBudgetInstruction::set_compute_unit_price(5_000),
+            noop.clone(),
+        ]);
+        assert_eq!(base.compute_budget_reserve(), instructions[..2].to_vec());
+        assert!(rpc.methods().is_empty());
+        
+        // Without a budget the transaction is left alone
+        let (base, _) = budgeted_base(ComputeBudgetConfig::default(), 0).await;
+        let instructions = base.request(|builder| builder.instruction(noop.clone())).await.unwrap().instructions().unwrap();
+        assert_eq!(instructions, vec![noop]);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_then_pad_sets_limit_from_consumed_units() {
+        let noop = Instruction::new_with_bytes(crate::PROGRAM_ID, &[1], Vec::new());
+        let (base, rpc) = budgeted_base(ComputeBudgetConfig {
+            unit_limit: Some(10),
+            unit_price_micro_lamports: None,
+            simulate_then_pad: true,
+        }, 50_000).await;
+        
+        let instructions = base
+            .request(|builder| builder.instruction(noop.clone()))
+            .await
+            .unwrap()
+            .instructions()
+            .unwrap();
+        assert_eq!(instructions, vec![ComputeBudgetInstruction::set_compute_unit_limit(60_000), noop]);
+        assert_eq!(rpc.methods(), vec!["simulateTransaction"]);
+    }
+
+    fn transaction_status(confirmation_status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "slot": 1,
+            "confirmations": null,
+            "status": { "Ok": null },
+            "err": null,
+            "confirmationStatus": confirmation_status,
+        })
+    }
+
+    /// Base whose RPC lands a signed no-op transaction, and that transaction
+    ///
+    /// The transaction is reported unknown, then processed, then confirmed.
+    /// With `confirms` false it stays unknown forever while the chain sits at
+    /// `block_height`.
+    fn confirming_base(confirms: bool, block_height: u64) -> (ServiceBase, Transaction, Arc<AtomicU64>) {
+        use solana_rpc_client_api::request::RpcRequest;
+        
+        let payer = Keypair::new();
+        let noop = Instruction::new_with_bytes(crate::PROGRAM_ID, &[1], Vec::new());
+        let tx = Transaction::new_signed_with_payer(&[noop], Some(&payer.pubkey()), &[&payer], Default::default());
+        let signature = tx.signatures[0];
+        let polls = Arc::new(AtomicU64::new(0));
+        let status_polls = polls.clone();
+        let rpc = MockRpc::new(move |request, _| match request {
+            RpcRequest::GetVersion => serde_json::json!({ "solana-core": "2.3.1", "feature-set": 0 }),
+            RpcRequest::SendTransaction => serde_json::json!(signature.to_string()),
+            RpcRequest::GetBlockHeight => serde_json::json!(block_height),
+            RpcRequest::GetSignatureStatuses => {
+                let status = match (confirms, status_polls.fetch_add(1, Ordering::SeqCst)) {
+                    (false, _) | (true, 0) => serde_json::Value::Null,
+                    (true, 1) => transaction_status("processed"),
+                    (true, _) => transaction_status("confirmed"),
+                };
+                with_context(serde_json::json!([status]))
+            }
+            other => panic!("unexpected RPC request {}", other),
+        });
+        
+        let mut config = crate::config::test_config();
+        config.async_rpc_client = rpc.client();
+        (ServiceBase::new(config), tx, polls)
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_waits_for_commitment() {
+        let (base, tx, polls) = confirming_base(true, 100);
+        
+        let outcome = base
+            .send_and_confirm(&tx, 150, Some(CommitmentConfig::confirmed()), Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert_eq!(outcome, Outcome::Sent(tx.signatures[0]));
+        // Unknown and processed statuses don't satisfy a confirmed commitment
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_times_out_with_signature() {
+        let (base, tx, polls) = confirming_base(false, 100);
+        let timeout = Duration::from_millis(200);
+        
+        match base.send_and_confirm(&tx, 150, None, timeout).await {
+            Err(PodComError::ConfirmationTimeout { signature, timeout: waited }) => {
+                assert_eq!(signature, tx.signatures[0]);
+                assert_eq!(waited, timeout);
+            }
+            other => panic!("expected a confirmation timeout, got {:?}", other),
+        }
+        assert!(polls.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_stops_once_blockhash_expires() {
+        let (base, tx, polls) = confirming_base(false, 151);
+        
+        match base.send_and_confirm(&tx, 150, None, Duration::from_secs(60)).await {
+            Err(PodComError::TransactionExpired { signature, last_valid_block_height }) => {
+                assert_eq!(signature, tx.signatures[0]);
+                assert_eq!(last_valid_block_height, 150);
+            }
+            other => panic!("expected an expired transaction, got {:?}", other),
+        }
+        assert_eq!(polls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_subscriptions_follow_the_transport_settings() {
+        let mut config = crate::config::test_config();
+        assert!(config.check_subscription("accountSubscribe").is_ok());
+        
+        config.allowed_rpc_methods = Some(["getAccountInfo".to_string()].into_iter().collect());
+        assert!(matches!(
+            config.check_subscription("accountSubscribe"),
+            Err(PodComError::Network(NetworkError::RpcMethodNotAllowed { .. }))
+        ));
+        
+        config.allowed_rpc_methods = None;
+        config.rpc_proxy = Some(reqwest::Proxy::all("http://proxy.example.com:8080").unwrap());
+        assert!(matches!(
+            config.check_subscription("accountSubscribe"),
+            Err(PodComError::InvalidConfiguration { .. })
+        ));
+    }
+
+    #[test]
+    fn test_padded_unit_limit() {
+        assert_eq!(padded_unit_limit(0), 0);
+        assert_eq!(padded_unit_limit(50_000), 60_000);
+        assert_eq!(padded_unit_limit(1), 2);
+        assert_eq!(padded_unit_limit(u64::MAX), MAX_COMPUTE_UNIT_LIMIT);
+    }
+
+    /// JSON-RPC node answering `getHealth` with `body` after `delay`
+    async fn rpc_node(body: serde_json::Value, delay: Duration) -> wiremock::MockServer {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let node = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "method": "getHealth" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body).set_delay(delay))
+            .mount(&node)
+            .await;
+        node
+    }
+
+    /// Probe the RPC node at `url` the way [`ServiceBase::probe_health`] does
+    async fn probe_rpc_health(url: String, slow_after: Duration, timeout: Duration) -> ServiceHealth {
+        classify_probe(AsyncRpcClient::new(url).get_health(), slow_after, timeout).await
+    }
+
+    #[tokio::test]
+    async fn test_probe_rpc_health_classifies_responses() {
+        let ok = serde_json::json!({ "jsonrpc": "2.0", "result": "ok", "id": 1 });
+        let behind = serde_json::json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32005, "message": "Node is behind by 42 slots" },
+            "id": 1,
+        });
+        let (slow_after, timeout) = (Duration::from_millis(100), Duration::from_millis(500));
+        
+        let healthy = rpc_node(ok.clone(), Duration::ZERO).await;
+        assert_eq!(probe_rpc_health(healthy.uri(), slow_after, timeout).await, ServiceHealth::Healthy);
+        
+        let slow = rpc_node(ok.clone(), Duration::from_millis(200)).await;
+        assert_eq!(probe_rpc_health(slow.uri(), slow_after, timeout).await, ServiceHealth::Degraded);
+        
+        let erroring = rpc_node(behind, Duration::ZERO).await;
+        assert_eq!(probe_rpc_health(erroring.uri(), slow_after, timeout).await, ServiceHealth::Unhealthy);
+        
+        let unresponsive = rpc_node(ok, Duration::from_secs(2)).await;
+        assert_eq!(probe_rpc_health(unresponsive.uri(), slow_after, timeout).await, ServiceHealth::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_probe_health_reuses_recent_result() {
+        use anchor_client::{Client, Cluster};
+        
+        let node = rpc_node(
+            serde_json::json!({ "jsonrpc": "2.0", "result": "ok", "id": 1 }),
+            Duration::from_millis(50),
+        ).await;
+        let mut config = crate::config::test_config();
+        config.async_rpc_client = Arc::new(AsyncRpcClient::new(node.uri()));
+        let mut base = ServiceBase::new(config);
+        
+        // Nothing to report on until the service is initialized
+        assert_eq!(base.probe_health().await, ServiceHealth::NotInitialized);
+        assert!(node.received_requests().await.unwrap().is_empty());
+        
+        let program = Client::new(Cluster::Localnet, Arc::new(Keypair::new()))
+            .program(crate::PROGRAM_ID)
+            .unwrap();
+        base.initialize(program).await.unwrap();
+        
+        // Concurrent callers share the probe in flight
+        let (first, second) = tokio::join!(base.probe_health(), base.probe_health());
+        assert_eq!((first, second), (ServiceHealth::Healthy, ServiceHealth::Healthy));
+        assert_eq!(base.probe_health().await, ServiceHealth::Healthy);
+        assert_eq!(node.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_background_tasks() {
+        let mut base = ServiceBase::new(crate::config::test_config());
+        let observed = Arc::new(AtomicU64::new(0));
         
-        assert!(rate_limiter.allow_operation("test"));
-        assert!(rate_limiter.allow_operation("test"));
-        assert!(!rate_limiter.allow_operation("test"));
+        let seen = observed.clone();
+        base.spawn_task(move |shutdown| async move {
+            // Stands in for a subscription pump that would otherwise run forever
+            tokio::select! {
+                _ = shutdown.cancelled() => { seen.fetch_add(1, Ordering::SeqCst); }
+                _ = tokio::time::sleep(Duration::from_secs(3600)) => {}
+            }
+        });
+        let stream_token = base.cancellation_token();
+        
+        tokio::time::timeout(SHUTDOWN_TASK_TIMEOUT, base.shutdown()).await.unwrap().unwrap();
+        assert_eq!(observed.load(Ordering::SeqCst), 1);
+        assert!(stream_token.is_cancelled());
+        
+        // A restarted service hands out fresh tokens
+        assert!(!base.cancellation_token().is_cancelled());
     }
-} 
\ No newline at end of file
+
+
+    #[tokio::test]
+    async fn test_drop_cancels_tokens_and_aborts_tasks() {
+        let base = ServiceBase::new(crate::config::test_config());
+        let (held, released) = tokio::sync::oneshot::channel::<()>();
+        
+        // Ignores cancellation, so only an abort stops it
+        base.spawn_task(move |_shutdown| async move {
+            let _held = held;
+            futures::future::pending::<()>().await;
+        });
+        let stream_token = base.cancellation_token();
+        drop(base);
+        
+        assert!(stream_token.is_cancelled());
+        assert!(released.await.is_err());
+    }
+}
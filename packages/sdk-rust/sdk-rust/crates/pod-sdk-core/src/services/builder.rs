@@ -0,0 +1,94 @@
+//! # Service Builder
+//!
+//! Single place where every protocol service is constructed and initialized,
+//! so clients don't repeat the per-service wiring.
+
+use std::sync::Arc;
+
+use anchor_client::Program;
+use solana_sdk::signer::keypair::Keypair;
+
+use crate::error::Result;
+use crate::services::{
+    AgentService, AnalyticsService, ChannelService, DiscoveryService, EscrowService,
+    IPFSService, MessageService, ServiceConfig, ServiceRegistry, ZKCompressionService,
+};
+
+/// Builds a [`ServiceRegistry`] from a shared service configuration
+#[derive(Debug, Clone)]
+pub struct ServiceBuilder {
+    config: ServiceConfig,
+}
+
+impl ServiceBuilder {
+    /// Create a builder for the given service configuration
+    pub fn new(config: ServiceConfig) -> Self {
+        Self { config }
+    }
+
+    /// Simulate state-changing transactions instead of sending them
+    /// 
+    /// See [`ServiceConfig::dry_run`].
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.config.dry_run = enabled;
+        self
+    }
+
+    /// Construct every service without initializing them
+    pub fn build(self) -> Result<ServiceRegistry> {
+        let config = self.config;
+        
+        Ok(ServiceRegistry {
+            agents: AgentService::new(config.clone()),
+            messages: MessageService::new(config.clone()),
+            channels: ChannelService::new(config.clone()),
+            escrow: EscrowService::new(config.clone()),
+            analytics: AnalyticsService::new(config.clone()),
+            discovery: DiscoveryService::new(config.clone()),
+            ipfs: IPFSService::new(config.clone())?,
+            zk_compression: ZKCompressionService::new(config),
+        })
+    }
+
+    /// Construct every service and initialize them with the program instance
+    pub async fn build_initialized(self, program: Program<Arc<Keypair>>) -> Result<ServiceRegistry> {
+        let mut registry = self.build()?;
+        registry.initialize_all(program).await?;
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::services::ServiceHealth;
+    use anchor_client::{Client, Cluster};
+
+    #[tokio::test]
+    async fn test_builder_initializes_every_service() {
+        let client = Client::new(Cluster::Localnet, Arc::new(Keypair::new()));
+        let program = client.program(crate::PROGRAM_ID).unwrap();
+        
+        let registry = ServiceBuilder::new(test_config())
+            .build_initialized(program)
+            .await
+            .unwrap();
+        
+        assert!(registry.is_initialized());
+        for (name, health) in registry.health_status() {
+            assert_ne!(health, ServiceHealth::NotInitialized, "{} was not initialized", name);
+        }
+    }
+
+    #[test]
+    fn test_build_leaves_services_uninitialized() {
+        let registry = ServiceBuilder::new(test_config()).build().unwrap();
+        
+        assert!(!registry.is_initialized());
+        assert!(registry
+            .health_status()
+            .values()
+            .all(|health| *health == ServiceHealth::NotInitialized));
+    }
+}
@@ -7,24 +7,26 @@ use std::sync::Arc;
 use std::collections::HashSet;
 
 use anchor_client::Program;
-use rand::{distributions::Alphanumeric, Rng};
 use async_trait::async_trait;
 use solana_sdk::{
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
 };
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use pod_com::{ChannelInvitation, ChannelMessage, ChannelParticipant};
 
 use pod_sdk_types::{
-    ChannelAccount, AgentAccount, MessageAccount,
+    pda, ChannelAccount, AgentAccount, MessageAccount,
     CreateChannelRequest, ChannelVisibility,
 };
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    error::{ChannelError, NetworkError, PodComError, Result},
+    services::base::{BaseService, Outcome, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
     utils::{
-        account::{derive_channel_pda, validate_channel_account},
+        account::validate_channel_account,
         encryption::{generate_channel_key, derive_shared_key},
+        pagination::{Page, PageRequest},
     },
 };
 
@@ -38,7 +40,7 @@ impl ChannelService {
     /// Create a new channel service
     pub fn new(config: ServiceConfig) -> Self {
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("channel"),
         }
     }
 
@@ -47,12 +49,10 @@ impl ChannelService {
         &self,
         creator: &Keypair,
         params: CreateChannelParams,
-    ) -> Result<(Pubkey, ChannelAccount)> {
+    ) -> Result<Outcome<(Pubkey, ChannelAccount)>> {
         let operation_name = "create_channel";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             // Validate participants
             if params.participants.is_empty() {
                 return Err(PodComError::InvalidChannelParticipants {
@@ -68,23 +68,17 @@ impl ChannelService {
             
             // Verify all participants are valid agents
             for participant in &params.participants {
-                let _agent_account = program.account::<AgentAccount>(*participant)?;
+                let _agent_account = self.base.fetch_account::<AgentAccount>(participant).await?;
             }
             
-            // Generate channel ID and derive PDA
-            let channel_id: String = rand::thread_rng()
-                .sample_iter(&rand::distributions::Alphanumeric)
-                .take(32)
-                .map(char::from)
-                .collect();
-            let (channel_pda, _bump) = derive_channel_pda(&creator.pubkey(), &channel_id)?;
+            // The program seeds channels by creator and name
+            let (channel_pda, _bump) = pda::channel_pda(&self.base.config().effective_program_id(), &creator.pubkey(), &params.name);
             
             // Generate encryption key for the channel
             let encryption_key = generate_channel_key(&params.participants)?;
             
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::CreateChannel {
                     channel_account: channel_pda,
                     creator: creator.pubkey(),
@@ -97,10 +91,14 @@ impl ChannelService {
                     max_participants: params.participants.len() as u32,
                     fee_per_message: 0, // Default to no fee
                 })
-                .signer(creator);
+                .signer(creator)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(&channel_pda);
             
             // Fetch created channel account
             let channel_account = self.get_channel_account(&channel_pda).await?;
@@ -109,12 +107,12 @@ impl ChannelService {
                 channel_address = %channel_pda,
                 signature = %signature,
                 creator = %creator.pubkey(),
-                channel_id = %channel_id,
+                channel_name = %params.name,
                 participants_count = params.participants.len(),
                 "Channel created successfully"
             );
 
-            Ok((channel_pda, channel_account))
+            Ok(Outcome::Sent((channel_pda, channel_account)))
         }).await
     }
 
@@ -122,11 +120,15 @@ impl ChannelService {
     pub async fn get_channel_account(&self, channel_address: &Pubkey) -> Result<ChannelAccount> {
         let operation_name = "get_channel_account";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<ChannelAccount>(*channel_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let account_data = self.base.fetch_account::<ChannelAccount>(channel_address).await?;
             validate_channel_account(&account_data)?;
+            account_data.validate_invariants().map_err(|state| {
+                PodComError::InconsistentChannelState {
+                    channel_address: *channel_address,
+                    state,
+                }
+            })?;
             
             Ok(account_data)
         }).await
@@ -138,12 +140,10 @@ impl ChannelService {
         channel_address: &Pubkey,
         admin: &Keypair,
         new_participant: &Pubkey,
-    ) -> Result<ChannelAccount> {
+    ) -> Result<Outcome<ChannelAccount>> {
         let operation_name = "add_participant";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let channel_account = self.get_channel_account(channel_address).await?;
             
             // Verify admin privileges
@@ -163,7 +163,7 @@ impl ChannelService {
             }
             
             // Verify participant is a valid agent
-            let _agent_account = program.account::<AgentAccount>(*new_participant)?;
+            let _agent_account = self.base.fetch_account::<AgentAccount>(new_participant).await?;
             
             // Check participant limit
             if channel_account.participants.len() >= MAX_CHANNEL_PARTICIPANTS {
@@ -180,7 +180,10 @@ impl ChannelService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             // Fetch updated channel account
             let updated_account = self.get_channel_account(channel_address).await?;
@@ -193,11 +196,17 @@ impl ChannelService {
                 "Participant added to channel successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
     /// Remove participant from channel
+    ///
+    /// pod-com only lets participants leave a channel themselves, so once
+    /// the admin and the participant are checked this always fails with
+    /// [`PodComError::NotImplemented`]. Muting is not offered either: the
+    /// program has nowhere to store a mute and its broadcast path would not
+    /// consult one.
     pub async fn remove_participant(
         &self,
         channel_address: &Pubkey,
@@ -206,9 +215,7 @@ impl ChannelService {
     ) -> Result<ChannelAccount> {
         let operation_name = "remove_participant";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             let channel_account = self.get_channel_account(channel_address).await?;
             
             // Verify admin privileges
@@ -236,40 +243,144 @@ impl ChannelService {
             
             // Build instruction - Note: pod-com doesn't have remove_participant, this would need custom implementation
             // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
+            Err(PodComError::NotImplemented {
                 feature: "remove_participant".to_string(),
+            })
+        }).await
+    }
+
+    /// Join a channel as the program's payer
+    ///
+    /// The channel's participant limit is checked before anything is
+    /// submitted, and private channels additionally require an unused,
+    /// unexpired invitation addressed to the payer. pod-com keeps a
+    /// participant account after its agent leaves, so a channel left once
+    /// is still reported as already joined.
+    pub async fn join_channel(&self, channel: Pubkey) -> Result<Outcome> {
+        let operation_name = "join_channel";
+        
+        self.base.execute_transaction(operation_name, || async {
+            let program = self.base.program()?;
+            let program_id = self.base.config().effective_program_id();
+            let user = program.payer();
+            
+            let (agent_pda, _) = pda::agent_pda(&program_id, &user);
+            let (participant_pda, membership) = self.membership(&channel, &agent_pda).await;
+            if membership.is_some() {
+                return Err(ChannelError::AlreadyMember { channel }.into());
+            }
+            
+            let channel_account = self.get_channel_account(&channel).await?;
+            ensure_capacity(&channel, &channel_account)?;
+            
+            let invitation_account = if channel_account.visibility == ChannelVisibility::Private {
+                let (invitation_pda, _) = Pubkey::find_program_address(
+                    &[b"invitation", channel.as_ref(), user.as_ref()],
+                    &program_id,
+                );
+                let invitation = self.base.fetch_account_with::<ChannelInvitation>(&invitation_pda, true).await.ok();
+                ensure_invited(&channel, &user, invitation.as_ref(), chrono::Utc::now().timestamp())?;
+                Some(invitation_pda)
+            } else {
+                None
+            };
+            
+            // Paid channels take their joining fee from the user's escrow
+            let escrow_account = (channel_account.fee_per_message > 0).then(|| {
+                pda::escrow_pda(&program_id, &channel, &user).0
             });
+            
+            let outcome = self.base.send(self.base.request(|builder| builder
+                .accounts(pod_com::accounts::JoinChannel {
+                    channel_account: channel,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    invitation_account,
+                    escrow_account,
+                    user,
+                    system_program: solana_sdk::system_program::id(),
+                })
+                .args(pod_com::instruction::JoinChannel {})).await?).await?;
+            let signature = match outcome {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(&channel);
+            
+            tracing::info!(
+                channel_address = %channel,
+                signature = %signature,
+                participant = %user,
+                "Joined channel successfully"
+            );
 
-            // Send transaction
-            let signature = ix.send()?;
+            Ok(Outcome::Sent(signature))
+        }).await
+    }
+
+    /// Leave a channel as the program's payer
+    pub async fn leave_channel(&self, channel: Pubkey) -> Result<Outcome> {
+        let operation_name = "leave_channel";
+        
+        self.base.execute_transaction(operation_name, || async {
+            let program = self.base.program()?;
+            let program_id = self.base.config().effective_program_id();
+            let user = program.payer();
             
-            // Fetch updated channel account
-            let updated_account = self.get_channel_account(channel_address).await?;
+            let (agent_pda, _) = pda::agent_pda(&program_id, &user);
+            let (participant_pda, membership) = self.membership(&channel, &agent_pda).await;
+            if !membership.is_some_and(|participant| participant.is_active) {
+                return Err(ChannelError::NotMember { channel }.into());
+            }
+            
+            let outcome = self.base.send(self.base.request(|builder| builder
+                .accounts(pod_com::accounts::LeaveChannel {
+                    channel_account: channel,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    user,
+                })
+                .args(pod_com::instruction::LeaveChannel {})).await?).await?;
+            let signature = match outcome {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(&channel);
             
             tracing::info!(
-                channel_address = %channel_address,
+                channel_address = %channel,
                 signature = %signature,
-                admin = %admin.pubkey(),
-                removed_participant = %participant_to_remove,
-                "Participant removed from channel successfully"
+                participant = %user,
+                "Left channel successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(signature))
         }).await
     }
 
+    /// Participant account of `agent_pda` in `channel`, if the agent ever joined it
+    ///
+    /// Membership is not recorded on the channel account: each join creates
+    /// a participant account, which is marked inactive when the agent leaves.
+    async fn membership(&self, channel: &Pubkey, agent_pda: &Pubkey) -> (Pubkey, Option<ChannelParticipant>) {
+        let (participant_pda, _) = Pubkey::find_program_address(
+            &[b"participant", channel.as_ref(), agent_pda.as_ref()],
+            &self.base.config().effective_program_id(),
+        );
+        let participant = self.base.fetch_account_with::<ChannelParticipant>(&participant_pda, true).await.ok();
+        (participant_pda, participant)
+    }
+
     /// Update channel settings
     pub async fn update_channel(
         &self,
         channel_address: &Pubkey,
         admin: &Keypair,
         params: UpdateChannelParams,
-    ) -> Result<ChannelAccount> {
+    ) -> Result<Outcome<ChannelAccount>> {
         let operation_name = "update_channel";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let channel_account = self.get_channel_account(channel_address).await?;
             
             // Verify admin privileges
@@ -281,23 +392,26 @@ impl ChannelService {
             }
             
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::UpdateChannel {
                     channel_account: *channel_address,
                     signer: admin.pubkey(),
                 })
                 .args(pod_com::instruction::UpdateChannel {
-                    name: params.name,
-                    description: params.description,
+                    name: params.name.clone(),
+                    description: params.description.clone(),
                     max_participants: None,
                     fee_per_message: None,
                     is_active: None,
                 })
-                .signer(admin);
+                .signer(admin)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(channel_address);
             
             // Fetch updated channel account
             let updated_account = self.get_channel_account(channel_address).await?;
@@ -309,7 +423,7 @@ impl ChannelService {
                 "Channel updated successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -318,12 +432,10 @@ impl ChannelService {
         &self,
         channel_address: &Pubkey,
         admin: &Keypair,
-    ) -> Result<ChannelAccount> {
+    ) -> Result<Outcome<ChannelAccount>> {
         let operation_name = "archive_channel";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let channel_account = self.get_channel_account(channel_address).await?;
             
             // Verify admin privileges
@@ -335,8 +447,7 @@ impl ChannelService {
             }
             
             // Build instruction - Note: pod-com doesn't have archive_channel, using update_channel instead
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::UpdateChannel {
                     channel_account: *channel_address,
                     signer: admin.pubkey(),
@@ -348,10 +459,14 @@ impl ChannelService {
                     fee_per_message: None,
                     is_active: Some(false), // Archive by setting inactive
                 })
-                .signer(admin);
+                .signer(admin)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(channel_address);
             
             // Fetch updated channel account
             let updated_account = self.get_channel_account(channel_address).await?;
@@ -363,7 +478,7 @@ impl ChannelService {
                 "Channel archived successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -372,10 +487,10 @@ impl ChannelService {
         &self,
         channel_address: &Pubkey,
         creator: &Keypair,
-    ) -> Result<()> {
+    ) -> Result<Outcome<()>> {
         let operation_name = "delete_channel";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             let program = self.base.program()?;
             
             let channel_account = self.get_channel_account(channel_address).await?;
@@ -398,7 +513,10 @@ impl ChannelService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             tracing::info!(
                 channel_address = %channel_address,
@@ -407,26 +525,21 @@ impl ChannelService {
                 "Channel deleted successfully"
             );
 
-            Ok(())
+            Ok(Outcome::Sent(()))
         }).await
     }
 
-    /// List channels for a specific participant
+    /// List channels for a specific participant, most recent first
     pub async fn list_participant_channels(
         &self,
         participant: &Pubkey,
-    ) -> Result<Vec<(Pubkey, ChannelAccount)>> {
+        page: PageRequest,
+    ) -> Result<Page<(Pubkey, ChannelAccount)>> {
         let operation_name = "list_participant_channels";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all channel accounts
-            let accounts = program
-                .accounts::<ChannelAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<ChannelAccount>().await?;
                 
             let mut participant_channels = Vec::new();
             
@@ -440,7 +553,81 @@ impl ChannelService {
             // Sort by creation time (most recent first)
             participant_channels.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
             
-            Ok(participant_channels)
+            Page::from_sorted(participant_channels, &page)
+        }).await
+    }
+
+    /// Fetch a page of a channel's messages and their addresses, newest first
+    ///
+    /// Returns up to `limit` messages ordered before `before`, or the newest
+    /// messages when no cursor is given. Pass
+    /// [`HistoryCursor::of`] the last message returned as the next `before`
+    /// to page further back; unlike an offset, the cursor does not shift as
+    /// new messages arrive, and messages sharing a timestamp are ordered by
+    /// address so none fall between pages. A bare timestamp cursor would
+    /// skip messages sharing the boundary timestamp, which is why the cursor
+    /// carries the address too. Only the channel's messages are fetched.
+    /// `limit` is capped at the channel config's `message_history_limit`.
+    pub async fn get_messages(
+        &self,
+        channel: Pubkey,
+        before: Option<HistoryCursor>,
+        limit: usize,
+    ) -> Result<Vec<(Pubkey, ChannelMessage)>> {
+        let operation_name = "get_messages";
+        
+        self.base.execute_operation(operation_name, || async {
+            let limit = self.history_limit(limit);
+            let mut messages = Vec::new();
+            
+            self.base.scan_accounts::<ChannelMessage, _>(vec![channel_filter(&channel)], HISTORY_SCAN_PAGE_SIZE, |page| {
+                messages.extend(page.into_iter().filter(|message| in_history(message, &channel, before)));
+                Ok(())
+            }).await?;
+            
+            Ok(newest_first(messages, limit))
+        }).await
+    }
+
+    /// `requested` capped at the configured message history limit
+    fn history_limit(&self, requested: usize) -> usize {
+        let configured = self.base.config()
+            .channel_config
+            .as_ref()
+            .map_or(DEFAULT_MESSAGE_HISTORY_LIMIT, |config| config.message_history_limit as usize);
+        requested.min(configured)
+    }
+
+    /// Estimate the rent `added` participants pay to join a channel
+    ///
+    /// The channel account never grows: each join creates a separate
+    /// participant account, funded by the joining user, so the cost is the
+    /// rent-exempt minimum of one participant account per join, as the
+    /// cluster reports it.
+    pub async fn estimate_participant_cost(&self, current_count: usize, added: usize) -> Result<Lamports> {
+        let operation_name = "estimate_participant_cost";
+        
+        if current_count.saturating_add(added) > MAX_CHANNEL_PARTICIPANTS {
+            return Err(PodComError::InvalidChannelParticipants {
+                reason: format!("Channel cannot have more than {} participants", MAX_CHANNEL_PARTICIPANTS),
+            });
+        }
+        if added == 0 {
+            return Ok(0);
+        }
+
+        self.base.execute_operation(operation_name, || async {
+            let rent = self.base.config().async_rpc_client
+                .get_minimum_balance_for_rent_exemption(CHANNEL_PARTICIPANT_SPACE)
+                .await
+                .map_err(|e| -> PodComError {
+                    NetworkError::RpcFailed {
+                        method: "getMinimumBalanceForRentExemption".to_string(),
+                        reason: e.to_string(),
+                    }.into()
+                })?;
+            
+            Ok::<_, PodComError>(rent.saturating_mul(added as u64))
         }).await
     }
 
@@ -448,17 +635,11 @@ impl ChannelService {
     pub async fn get_channel_stats(&self, channel_address: &Pubkey) -> Result<ChannelStats> {
         let operation_name = "get_channel_stats";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             let channel_account = self.get_channel_account(channel_address).await?;
             
             // Get message count for this channel
-            let accounts = program
-                .accounts::<MessageAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<MessageAccount>().await?;
                 
             let mut message_count = 0u64;
             let mut last_message_at = None;
@@ -598,9 +779,12 @@ impl BaseService for ChannelService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -611,15 +795,94 @@ impl BaseService for ChannelService {
     fn service_name(&self) -> &'static str {
         "channel"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
+}
+
+/// Amount of SOL in lamports
+pub type Lamports = u64;
+
+/// Reject joining a channel that has no free participant slots
+fn ensure_capacity(channel: &Pubkey, account: &ChannelAccount) -> Result<()> {
+    if account.participant_count >= account.participant_limit {
+        return Err(ChannelError::Full {
+            channel: *channel,
+            participant_limit: account.participant_limit,
+        }.into());
+    }
+    Ok(())
+}
+
+/// Require an invitation to `user` that is unused and unexpired at `now`
+fn ensure_invited(
+    channel: &Pubkey,
+    user: &Pubkey,
+    invitation: Option<&ChannelInvitation>,
+    now: i64,
+) -> Result<()> {
+    match invitation {
+        Some(invitation)
+            if invitation.channel == *channel
+                && invitation.invitee == *user
+                && !invitation.is_used
+                && now <= invitation.expires_at => Ok(()),
+        _ => Err(ChannelError::PrivateRequiresInvitation { channel: *channel }.into()),
+    }
+}
+
+/// Position in a channel's history, taken from a message returned by
+/// [`ChannelService::get_messages`]
+///
+/// Cursors order by `created_at`, then by address, so messages that share
+/// a timestamp still have a fixed place between pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HistoryCursor {
+    pub created_at: i64,
+    pub address: Pubkey,
+}
+
+impl HistoryCursor {
+    /// Cursor just after `message` in newest-first order
+    pub fn of((address, message): &(Pubkey, ChannelMessage)) -> Self {
+        Self { created_at: message.created_at, address: *address }
+    }
+}
+
+/// Matches messages posted to `channel`
+fn channel_filter(channel: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(MESSAGE_CHANNEL_OFFSET, channel.as_ref()))
+}
+
+/// Whether `message` was posted to `channel` before the `before` cursor
+fn in_history(message: &(Pubkey, ChannelMessage), channel: &Pubkey, before: Option<HistoryCursor>) -> bool {
+    message.1.channel == *channel && before.is_none_or(|cursor| HistoryCursor::of(message) < cursor)
+}
+
+/// The `limit` most recent of `messages`, newest first
+fn newest_first(mut messages: Vec<(Pubkey, ChannelMessage)>, limit: usize) -> Vec<(Pubkey, ChannelMessage)> {
+    messages.sort_by_key(|message| std::cmp::Reverse(HistoryCursor::of(message)));
+    messages.truncate(limit);
+    messages
 }
 
 // Constants for channel management
 const MAX_CHANNEL_PARTICIPANTS: usize = 100;
+/// Size of the participant account pod-com creates for each join
+const CHANNEL_PARTICIPANT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 6;
+/// Messages returned per history page when no channel config sets a limit
+const DEFAULT_MESSAGE_HISTORY_LIMIT: usize = 100;
+/// Accounts fetched per RPC call when scanning channel history
+const HISTORY_SCAN_PAGE_SIZE: usize = 100;
+/// Offset of `channel` in a `ChannelMessage`, after the discriminator
+const MESSAGE_CHANNEL_OFFSET: usize = 8;
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::test_config;
+    use crate::testing::{self, MockRpc};
 
     #[tokio::test]
     async fn test_channel_service_creation() {
@@ -628,4 +891,252 @@ mod tests {
         assert_eq!(service.service_name(), "channel");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_participant_cost_is_rent_per_join() {
+        use solana_sdk::rent::Rent;
+        
+        let (service, rpc) = service_with_channel(Pubkey::new_unique(), channel_fixture(ChannelVisibility::Public, 100, 5)).await;
+
+        let cost = service.estimate_participant_cost(5, 10).await.unwrap();
+        assert_eq!(CHANNEL_PARTICIPANT_SPACE, 104);
+        assert_eq!(cost, 10 * Rent::default().minimum_balance(104));
+        assert_eq!(rpc.params_of("getMinimumBalanceForRentExemption"), vec![serde_json::json!([104])]);
+
+        assert_eq!(service.estimate_participant_cost(5, 0).await.unwrap(), 0);
+        assert!(service.estimate_participant_cost(95, 10).await.is_err());
+    }
+
+    /// Channel with `participants` members, the first of them its creator
+    fn channel_fixture(visibility: ChannelVisibility, participant_limit: u32, participants: usize) -> ChannelAccount {
+        let participants: Vec<Pubkey> = (0..participants).map(|_| Pubkey::new_unique()).collect();
+        ChannelAccount {
+            creator: participants[0],
+            name: "research".to_string(),
+            description: String::new(),
+            visibility,
+            participant_limit,
+            participant_count: participants.len() as u32,
+            participants,
+            is_active: true,
+            fee_per_message: 0,
+            created_at: 1_700_000_000,
+            created_at_dt: 1_700_000_000,
+            last_activity: 1_700_000_000,
+            bump: 255,
+        }
+    }
+
+    /// Initialized service whose RPC serves `channel` and records calls
+    async fn service_with_channel(
+        address: Pubkey,
+        channel: ChannelAccount,
+    ) -> (ChannelService, MockRpc) {
+        service_with_membership(Keypair::new(), address, channel, None).await
+    }
+
+    /// Initialized service paying with `payer`, whose RPC serves `channel`
+    /// and the payer's `membership` in it
+    async fn service_with_membership(
+        payer: Keypair,
+        address: Pubkey,
+        channel: ChannelAccount,
+        membership: Option<ChannelParticipant>,
+    ) -> (ChannelService, MockRpc) {
+        use anchor_client::{Client, Cluster};
+        use anchor_lang::Discriminator;
+        
+        let mut data = ChannelAccount::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&pod_sdk_types::ChannelAccountBorsh::from(channel)).unwrap());
+        let mut accounts: std::collections::HashMap<_, _> = [(address.to_string(), data)].into_iter().collect();
+        if let Some(membership) = membership {
+            let (agent_pda, _) = pda::agent_pda(&crate::PROGRAM_ID, &payer.pubkey());
+            let (participant_pda, _) = Pubkey::find_program_address(
+                &[b"participant", address.as_ref(), agent_pda.as_ref()],
+                &crate::PROGRAM_ID,
+            );
+            accounts.insert(participant_pda.to_string(), testing::account_data(&membership));
+        }
+        let rpc = MockRpc::serving(accounts);
+        
+        let mut config = test_config();
+        config.async_rpc_client = rpc.client();
+        
+        let mut service = ChannelService::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(payer))
+            .program(crate::PROGRAM_ID)
+            .unwrap();
+        service.initialize(program).await.unwrap();
+        (service, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_join_full_channel_short_circuits() {
+        let address = Pubkey::new_unique();
+        let (service, rpc) = service_with_channel(address, channel_fixture(ChannelVisibility::Public, 2, 2)).await;
+        
+        let err = service.join_channel(address).await.unwrap_err();
+        assert!(matches!(
+            err,
+            PodComError::Channel(ChannelError::Full { participant_limit: 2, .. })
+        ));
+        assert!(rpc.methods().iter().all(|method| method == "getAccountInfo"));
+    }
+
+    #[tokio::test]
+    async fn test_join_private_channel_requires_invitation() {
+        let address = Pubkey::new_unique();
+        let (service, rpc) = service_with_channel(address, channel_fixture(ChannelVisibility::Private, 4, 2)).await;
+        
+        // The invitation PDA is not served, so no invitation exists
+        let err = service.join_channel(address).await.unwrap_err();
+        assert!(matches!(
+            err,
+            PodComError::Channel(ChannelError::PrivateRequiresInvitation { .. })
+        ));
+        assert!(rpc.methods().iter().all(|method| method == "getAccountInfo"));
+    }
+
+    fn participant(is_active: bool) -> ChannelParticipant {
+        let mut participant: ChannelParticipant = testing::zeroed();
+        participant.is_active = is_active;
+        participant
+    }
+
+    #[tokio::test]
+    async fn test_join_rejects_existing_participant() {
+        let address = Pubkey::new_unique();
+        for is_active in [true, false] {
+            let channel = channel_fixture(ChannelVisibility::Public, 4, 2);
+            let (service, rpc) = service_with_membership(Keypair::new(), address, channel, Some(participant(is_active))).await;
+            
+            let err = service.join_channel(address).await.unwrap_err();
+            assert!(matches!(err, PodComError::Channel(ChannelError::AlreadyMember { .. })));
+            assert!(rpc.methods().iter().all(|method| method == "getAccountInfo"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leave_requires_active_participant() {
+        let address = Pubkey::new_unique();
+        for membership in [None, Some(participant(false))] {
+            let channel = channel_fixture(ChannelVisibility::Public, 4, 2);
+            let (service, rpc) = service_with_membership(Keypair::new(), address, channel, membership).await;
+            
+            let err = service.leave_channel(address).await.unwrap_err();
+            assert!(matches!(err, PodComError::Channel(ChannelError::NotMember { .. })));
+            assert!(rpc.methods().iter().all(|method| method == "getAccountInfo"));
+        }
+    }
+
+    #[test]
+    fn test_invitation_must_be_unused_and_unexpired() {
+        let (channel, invitee) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let invitation = |expires_at: i64, is_used: bool| -> ChannelInvitation {
+            let mut data = Vec::new();
+            data.extend_from_slice(channel.as_ref());
+            data.extend_from_slice(Pubkey::new_unique().as_ref()); // inviter
+            data.extend_from_slice(invitee.as_ref());
+            data.extend_from_slice(&[0u8; 32]); // invitation hash
+            data.extend_from_slice(&0i64.to_le_bytes()); // created_at
+            data.extend_from_slice(&expires_at.to_le_bytes());
+            data.extend_from_slice(&7u64.to_le_bytes()); // nonce
+            data.extend_from_slice(&[0, is_used as u8, 255, 0, 0, 0, 0, 0]);
+            borsh::BorshDeserialize::try_from_slice(&data).unwrap()
+        };
+        
+        assert!(ensure_invited(&channel, &invitee, Some(&invitation(100, false)), 50).is_ok());
+        assert!(ensure_invited(&channel, &invitee, Some(&invitation(100, true)), 50).is_err());
+        assert!(ensure_invited(&channel, &invitee, Some(&invitation(100, false)), 101).is_err());
+        assert!(ensure_invited(&channel, &Pubkey::new_unique(), Some(&invitation(100, false)), 50).is_err());
+        assert!(ensure_invited(&channel, &invitee, None, 50).is_err());
+    }
+
+    fn channel_message(channel: Pubkey, created_at: i64) -> (Pubkey, ChannelMessage) {
+        (Pubkey::new_unique(), testing::channel_message(channel, created_at, None))
+    }
+
+    fn history(
+        messages: &[(Pubkey, ChannelMessage)],
+        channel: &Pubkey,
+        before: Option<HistoryCursor>,
+        limit: usize,
+    ) -> Vec<(Pubkey, ChannelMessage)> {
+        let matching = messages.iter().filter(|m| in_history(m, channel, before)).cloned().collect();
+        newest_first(matching, limit)
+    }
+
+    fn timestamps(messages: &[(Pubkey, ChannelMessage)]) -> Vec<i64> {
+        messages.iter().map(|(_, m)| m.created_at).collect()
+    }
+
+    #[test]
+    fn test_history_newest_first_before_cursor() {
+        let channel = Pubkey::new_unique();
+        let mut messages: Vec<_> = [30, 10, 50, 20, 40].into_iter().map(|t| channel_message(channel, t)).collect();
+        messages.push(channel_message(Pubkey::new_unique(), 45));
+        
+        assert_eq!(timestamps(&history(&messages, &channel, None, 10)), vec![50, 40, 30, 20, 10]);
+        // The cursor is exclusive
+        let forty = messages.iter().find(|(_, m)| m.created_at == 40).unwrap();
+        assert_eq!(timestamps(&history(&messages, &channel, Some(HistoryCursor::of(forty)), 10)), vec![30, 20, 10]);
+        
+        // Paging with the last message seen walks back without gaps or repeats
+        let first = history(&messages, &channel, None, 2);
+        let second = history(&messages, &channel, first.last().map(HistoryCursor::of), 2);
+        assert_eq!((timestamps(&first), timestamps(&second)), (vec![50, 40], vec![30, 20]));
+    }
+
+    #[test]
+    fn test_history_pages_through_shared_timestamps() {
+        let channel = Pubkey::new_unique();
+        let messages: Vec<_> = [10, 20, 20, 20, 20, 30].into_iter().map(|t| channel_message(channel, t)).collect();
+        
+        let mut seen = Vec::new();
+        let mut before = None;
+        loop {
+            let page = history(&messages, &channel, before, 2);
+            if page.is_empty() {
+                break;
+            }
+            before = page.last().map(HistoryCursor::of);
+            seen.extend(page.into_iter().map(|(address, _)| address));
+        }
+        
+        let mut expected: Vec<_> = messages.iter().map(|(address, _)| *address).collect();
+        let mut unique = seen.clone();
+        expected.sort();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(seen.len(), messages.len());
+        assert_eq!(unique, expected);
+    }
+
+    #[test]
+    fn test_channel_filter_matches_serialized_channel() {
+        let channel = Pubkey::new_unique();
+        let posted = testing::channel_message(channel, 10, None);
+        let elsewhere = testing::channel_message(Pubkey::new_unique(), 10, None);
+        
+        let RpcFilterType::Memcmp(memcmp) = channel_filter(&channel) else { unreachable!() };
+        assert!(memcmp.bytes_match(&testing::account_data(&posted)));
+        assert!(!memcmp.bytes_match(&testing::account_data(&elsewhere)));
+    }
+
+    #[test]
+    fn test_history_limit_capped_by_config() {
+        let mut config = test_config();
+        config.channel_config = None;
+        assert_eq!(ChannelService::new(config.clone()).history_limit(500), DEFAULT_MESSAGE_HISTORY_LIMIT);
+        
+        config.channel_config = Some(crate::services::base::ChannelConfig {
+            participant_limit: 10,
+            invitation_expiry_hours: 24,
+            message_history_limit: 3,
+            moderation_enabled: false,
+        });
+        let service = ChannelService::new(config);
+        assert_eq!(service.history_limit(500), 3);
+        assert_eq!(service.history_limit(2), 2);
+    }
+}
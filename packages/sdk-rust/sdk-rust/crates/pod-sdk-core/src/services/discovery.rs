@@ -10,18 +10,33 @@ use anchor_client::Program;
 use async_trait::async_trait;
 use solana_sdk::{
     pubkey::Pubkey,
+    signature::Signature,
     signer::{keypair::Keypair, Signer},
 };
 use serde::{Deserialize, Serialize};
+use futures::{Stream, StreamExt};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client_api::{
+    filter::{Memcmp, RpcFilterType},
+    response::RpcKeyedAccount,
+};
 
+use pod_com::{ChannelParticipant, MessageAccount};
 use pod_sdk_types::{
-    AgentAccount, ChannelAccount, capabilities,
+    AgentAccount, ChannelAccount, capabilities, pda,
 };
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
-    utils::network::{calculate_distance, NetworkTopology},
+    error::{AgentError, PodComError, Result, SecurityError},
+    services::base::{
+        program_accounts_config, BaseService, RetryHandler, ServiceBase, ServiceConfig,
+        ServiceHealth, ServiceMetrics,
+    },
+    utils::{
+        decode::decode_account,
+        network::{calculate_distance, NetworkTopology},
+        pagination::{Page, PageRequest},
+    },
 };
 
 /// Service for agent discovery and network management
@@ -30,18 +45,47 @@ pub struct DiscoveryService {
     base: ServiceBase,
     agent_registry: Arc<tokio::sync::RwLock<AgentRegistry>>,
     topology_cache: Arc<tokio::sync::RwLock<TopologyCache>>,
+    interaction_cache: Arc<tokio::sync::RwLock<InteractionCache>>,
+    registration_challenges: Arc<tokio::sync::RwLock<RegistrationChallenges>>,
+    /// How long after its last heartbeat an agent is considered offline
+    heartbeat_staleness: chrono::Duration,
+    /// Weight of each new latency sample in an agent's smoothed latency
+    latency_smoothing: f64,
 }
 
 impl DiscoveryService {
     /// Create a new discovery service
     pub fn new(config: ServiceConfig) -> Self {
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("discovery"),
             agent_registry: Arc::new(tokio::sync::RwLock::new(AgentRegistry::new())),
             topology_cache: Arc::new(tokio::sync::RwLock::new(TopologyCache::new())),
+            interaction_cache: Arc::new(tokio::sync::RwLock::new(InteractionCache::new())),
+            registration_challenges: Arc::new(tokio::sync::RwLock::new(RegistrationChallenges::default())),
+            heartbeat_staleness: chrono::Duration::seconds(DEFAULT_HEARTBEAT_STALENESS_SECS),
+            latency_smoothing: DEFAULT_LATENCY_SMOOTHING,
         }
     }
 
+    /// Treat agents as offline once their last heartbeat is older than `window`
+    pub fn with_heartbeat_staleness(mut self, window: std::time::Duration) -> Self {
+        self.heartbeat_staleness = chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+        self
+    }
+
+    /// Weight each heartbeat's latency by `smoothing` in the moving average
+    /// 
+    /// Values are clamped to `(0, 1]`; 1 disables smoothing, smaller values
+    /// make the average steadier but slower to follow real changes.
+    pub fn with_latency_smoothing(mut self, smoothing: f64) -> Self {
+        self.latency_smoothing = if smoothing.is_nan() {
+            DEFAULT_LATENCY_SMOOTHING
+        } else {
+            smoothing.clamp(f64::MIN_POSITIVE, 1.0)
+        };
+        self
+    }
+
     /// Discover agents by capabilities
     pub async fn discover_agents_by_capability(
         &self,
@@ -50,11 +94,9 @@ impl DiscoveryService {
     ) -> Result<Vec<AgentDiscoveryResult>> {
         let operation_name = "discover_agents_by_capability";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all agent accounts
-            let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+            let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
             
             let mut matching_agents = Vec::new();
             
@@ -92,15 +134,17 @@ impl DiscoveryService {
         }).await
     }
 
-    /// Search agents by query
-    pub async fn search_agents(&self, query: DiscoveryQuery) -> Result<Vec<AgentDiscoveryResult>> {
+    /// Search agents by query, returning one page of sorted results
+    pub async fn search_agents(
+        &self,
+        query: DiscoveryQuery,
+        page: PageRequest,
+    ) -> Result<Page<AgentDiscoveryResult>> {
         let operation_name = "search_agents";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all agent accounts
-            let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+            let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
             
             let mut matching_agents = Vec::new();
             
@@ -129,7 +173,7 @@ impl DiscoveryService {
                 "Agent search completed"
             );
             
-            Ok(matching_agents)
+            Page::from_sorted(matching_agents, &page)
         }).await
     }
 
@@ -142,25 +186,32 @@ impl DiscoveryService {
     ) -> Result<Vec<NearbyAgentResult>> {
         let operation_name = "get_nearby_agents";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let topology = self.get_network_topology().await?;
             
             let nearby_agents = topology.find_nearby_agents(reference_agent, max_distance, limit);
+            let weights = ConnectionStrengthWeights::default();
             
             let mut results = Vec::new();
             for (agent_address, distance) in nearby_agents {
                 if let Ok(availability) = self.get_agent_availability(&agent_address).await {
+                    let latency_ms = self.get_agent_connection_info(&agent_address)
+                        .await?
+                        .and_then(|info| info.latency_ms);
                     results.push(NearbyAgentResult {
                         address: agent_address,
                         distance,
                         availability_status: availability,
-                        connection_strength: self.calculate_connection_strength(&agent_address, reference_agent).await?,
+                        connection_strength: self
+                            .calculate_connection_strength(&agent_address, reference_agent, &weights)
+                            .await?,
+                        latency_ms,
                     });
                 }
             }
             
-            // Sort by distance (closest first)
-            results.sort_by_key(|r| r.distance);
+            // Closest first, then lowest smoothed latency
+            results.sort_by_key(|r| (r.distance, latency_rank(r.latency_ms)));
             
             Ok(results)
         }).await
@@ -175,12 +226,10 @@ impl DiscoveryService {
     ) -> Result<Vec<AgentRecommendation>> {
         let operation_name = "get_agent_recommendations";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let agent_account = program.account::<AgentAccount>(*agent_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let agent_account = self.base.fetch_account::<AgentAccount>(agent_address).await?;
             
-            let recommendations = match recommendation_type {
+            let mut recommendations = match recommendation_type {
                 RecommendationType::SimilarCapabilities => {
                     self.recommend_by_capabilities(&agent_account).await?
                 },
@@ -195,6 +244,20 @@ impl DiscoveryService {
                 },
             };
             
+            // Among equally scored agents, prefer the lowest smoothed latency
+            {
+                let registry = self.agent_registry.read().await;
+                let latency = |agent: &Pubkey| {
+                    latency_rank(registry.get_connection_info(agent).and_then(|info| info.latency_ms))
+                };
+                recommendations.sort_by(|a, b| {
+                    b.recommendation_score
+                        .partial_cmp(&a.recommendation_score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| latency(&a.agent_address).cmp(&latency(&b.agent_address)))
+                });
+            }
+            
             let limited_recommendations = if let Some(limit) = limit {
                 recommendations.into_iter().take(limit as usize).collect()
             } else {
@@ -205,17 +268,43 @@ impl DiscoveryService {
         }).await
     }
 
+    /// Issue a challenge for registering `agent_address` for discovery
+    /// 
+    /// The challenge is accepted by [`register_agent_for_discovery`](Self::register_agent_for_discovery)
+    /// once, within [`REGISTRATION_CHALLENGE_TTL_SECS`] of being issued.
+    pub async fn issue_registration_challenge(&self, agent_address: &Pubkey) -> [u8; 32] {
+        self.registration_challenges.write().await.issue(*agent_address, chrono::Utc::now())
+    }
+
     /// Register agent for discovery
+    /// 
+    /// `challenge` must come from [`issue_registration_challenge`](Self::issue_registration_challenge)
+    /// for `agent_address` and not have expired or been used.
+    /// `signature` must be `owner`'s signature over
+    /// [`registration_message`]`(agent_address, &challenge, &connection_info)`,
+    /// and `agent_address` must be the agent PDA of `owner`. A registration
+    /// failing any check is rejected, so nobody can advertise an endpoint
+    /// for an agent they don't own or replay an earlier registration.
     pub async fn register_agent_for_discovery(
         &self,
         agent_address: &Pubkey,
         connection_info: AgentConnectionInfo,
+        owner: &Pubkey,
+        challenge: [u8; 32],
+        signature: &Signature,
     ) -> Result<()> {
         let operation_name = "register_agent_for_discovery";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
+            let program_id = self.base.config().effective_program_id();
+            let mut challenges = self.registration_challenges.write().await;
+            challenges.check(&challenge, agent_address, chrono::Utc::now())?;
+            verify_registration(&program_id, agent_address, &connection_info, owner, &challenge, signature)?;
+            challenges.consume(&challenge);
+            drop(challenges);
+            
             let mut registry = self.agent_registry.write().await;
-            registry.register_agent(*agent_address, connection_info);
+            registry.register_agent(*agent_address, connection_info.clone());
             
             tracing::info!(
                 agent_address = %agent_address,
@@ -226,6 +315,31 @@ impl DiscoveryService {
         }).await
     }
 
+    /// Record a heartbeat from a registered agent's endpoint
+    /// 
+    /// Refreshes the agent's last heartbeat to now, stores the measured
+    /// round-trip latency and folds it into the agent's smoothed latency
+    /// (see [`with_latency_smoothing`](Self::with_latency_smoothing)). Agents that stop sending heartbeats are reported
+    /// `Offline` once the staleness window passes.
+    pub async fn record_heartbeat(&self, agent_address: &Pubkey, latency_ms: u32) -> Result<()> {
+        let operation_name = "record_heartbeat";
+        
+        self.base.execute_operation(operation_name, || async {
+            let mut registry = self.agent_registry.write().await;
+            if !registry.record_heartbeat(agent_address, latency_ms, self.latency_smoothing, chrono::Utc::now()) {
+                return Err(AgentError::NotFound { pubkey: *agent_address }.into());
+            }
+            
+            tracing::debug!(
+                agent_address = %agent_address,
+                latency_ms,
+                "Agent heartbeat recorded"
+            );
+            
+            Ok(())
+        }).await
+    }
+
     /// Update agent availability status
     pub async fn update_agent_availability(
         &self,
@@ -234,9 +348,9 @@ impl DiscoveryService {
     ) -> Result<()> {
         let operation_name = "update_agent_availability";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let mut registry = self.agent_registry.write().await;
-            registry.update_availability(*agent_address, status);
+            registry.update_availability(*agent_address, status.clone());
             
             tracing::info!(
                 agent_address = %agent_address,
@@ -252,7 +366,7 @@ impl DiscoveryService {
     pub async fn get_network_topology(&self) -> Result<NetworkTopology> {
         let operation_name = "get_network_topology";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Check cache first
             {
                 let cache = self.topology_cache.read().await;
@@ -276,18 +390,31 @@ impl DiscoveryService {
         }).await
     }
 
+    /// Find the shortest chain of agents connecting `from` to `to`
+    ///
+    /// Agents are connected when they share a channel. The returned path
+    /// includes both endpoints, so a direct connection yields two elements.
+    /// Returns `None` when the agents are not connected.
+    pub async fn shortest_path(&self, from: Pubkey, to: Pubkey) -> Result<Option<Vec<Pubkey>>> {
+        let operation_name = "shortest_path";
+        
+        self.base.execute_operation(operation_name, || async {
+            let topology = self.get_network_topology().await?;
+            Ok::<_, PodComError>(topology.shortest_path(&from, &to))
+        }).await
+    }
+
     /// Get network statistics
     pub async fn get_network_stats(&self) -> Result<NetworkStats> {
         let operation_name = "get_network_stats";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_operation(operation_name, || async {
             let topology = self.get_network_topology().await?;
             
-            let total_agents = program.accounts::<AgentAccount>(vec![]).await?.len() as u64;
+            let total_agents = self.base.fetch_program_accounts::<AgentAccount>().await?.len() as u64;
             let active_agents = {
                 let registry = self.agent_registry.read().await;
-                registry.get_active_agent_count()
+                registry.get_active_agent_count(chrono::Utc::now(), self.heartbeat_staleness)
             };
             
             let total_connections = topology.get_total_connections();
@@ -312,11 +439,95 @@ impl DiscoveryService {
         }).await
     }
 
+    /// Stream live agent account updates from the configured WebSocket endpoint
+    ///
+    /// Opens a program subscription filtered to agent accounts. An update from
+    /// a slot no newer than the last one yielded for that agent is dropped, so
+    /// an older state never follows a newer one; only each agent's latest slot
+    /// is kept, for the life of the stream. A dropped socket is reopened with the
+    /// configured retry backoff; the stream ends after `max_retries`
+    /// consecutive failed reconnects, or when the service shuts down.
+    pub fn subscribe_agent_updates(&self) -> Result<impl Stream<Item = (Pubkey, AgentAccount)>> {
+        let config = self.base.config();
+        let ws_url = config.ws_url.clone().ok_or_else(|| PodComError::InvalidConfiguration {
+            field: "ws_url".to_string(),
+            reason: "A WebSocket URL is required for agent subscriptions".to_string(),
+        })?;
+        config.check_subscription("programSubscribe")?;
+        let program_id = config.effective_program_id();
+        let subscription_config = program_accounts_config::<AgentAccount>(config.commitment);
+        let max_retries = config.retry_config.max_retries;
+        let retry = RetryHandler::new(config.retry_config.clone());
+        let shutdown = self.base.cancellation_token();
+        
+        let updates = async_stream::stream! {
+            let mut failures = 0usize;
+            let mut latest_slots = HashMap::new();
+            
+            loop {
+                let client = match PubsubClient::new(&ws_url).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        tracing::warn!("Agent subscription connect failed: {}", e);
+                        match retry.reconnect_delay(&mut failures, max_retries) {
+                            Some(delay) => {
+                                tokio::time::sleep(delay).await;
+                                continue;
+                            }
+                            None => break,
+                        }
+                    }
+                };
+                
+                let (mut updates, unsubscribe) = match client
+                    .program_subscribe(&program_id, Some(subscription_config.clone()))
+                    .await
+                {
+                    Ok(subscription) => subscription,
+                    Err(e) => {
+                        tracing::warn!("Agent subscription request failed: {}", e);
+                        match retry.reconnect_delay(&mut failures, max_retries) {
+                            Some(delay) => {
+                                tokio::time::sleep(delay).await;
+                                continue;
+                            }
+                            None => break,
+                        }
+                    }
+                };
+                failures = 0;
+                
+                while let Some(update) = updates.next().await {
+                    let Some((address, account)) = decode_agent_update(&update.value) else {
+                        continue;
+                    };
+                    
+                    if is_newer_update(&mut latest_slots, address, update.context.slot) {
+                        yield (address, account);
+                    }
+                }
+                
+                drop(updates);
+                unsubscribe().await;
+                tracing::warn!("Agent subscription dropped; reconnecting");
+            }
+            
+            tracing::error!(
+                "Agent subscription gave up after {} failed reconnects",
+                max_retries
+            );
+        };
+        
+        Ok(updates.take_until(async move { shutdown.cancelled().await }))
+    }
+
     // Helper methods
 
     async fn get_agent_availability(&self, agent_address: &Pubkey) -> Result<AvailabilityStatus> {
         let registry = self.agent_registry.read().await;
-        Ok(registry.get_availability(agent_address).unwrap_or(AvailabilityStatus::Unknown))
+        Ok(registry
+            .get_availability(agent_address, chrono::Utc::now(), self.heartbeat_staleness)
+            .unwrap_or(AvailabilityStatus::Unknown))
     }
 
     async fn get_agent_connection_info(&self, agent_address: &Pubkey) -> Result<Option<AgentConnectionInfo>> {
@@ -382,14 +593,12 @@ impl DiscoveryService {
     }
 
     async fn build_network_topology(&self) -> Result<NetworkTopology> {
-        let program = self.base.program()?;
-        
         // Get all agents
-        let agents = program.accounts::<AgentAccount>(vec![]).await?;
+        let agents = self.base.fetch_program_accounts::<AgentAccount>().await?;
         let agent_addresses: Vec<Pubkey> = agents.into_iter().map(|(addr, _)| addr).collect();
         
         // Get all channels to build connections
-        let channels = program.accounts::<ChannelAccount>(vec![]).await?;
+        let channels = self.base.fetch_program_accounts::<ChannelAccount>().await?;
         
         let mut connections = HashMap::new();
         
@@ -413,18 +622,70 @@ impl DiscoveryService {
         Ok(NetworkTopology::new(agent_addresses, connections))
     }
 
-    async fn calculate_connection_strength(&self, _agent1: &Pubkey, _agent2: &Pubkey) -> Result<f64> {
-        // TODO: Implement connection strength calculation based on:
-        // - Number of shared channels
-        // - Message frequency
-        // - Response time patterns
-        // - Collaboration success rate
-        Ok(0.8) // Placeholder
+    /// Score how strongly two agents are connected, in `[0.0, 1.0]`
+    ///
+    /// Combines the overlap of their channel memberships, how many messages
+    /// they have exchanged, and how recently they last interacted, weighted
+    /// by `weights`. Both addresses are agent PDAs. Each pair's history is
+    /// cached for [`INTERACTION_CACHE_TTL_SECS`].
+    pub async fn calculate_connection_strength(
+        &self,
+        agent1: &Pubkey,
+        agent2: &Pubkey,
+        weights: &ConnectionStrengthWeights,
+    ) -> Result<f64> {
+        let pair = agent_pair(agent1, agent2);
+        let cached = self.interaction_cache.read().await.get(&pair);
+        let history = match cached {
+            Some(history) => history,
+            None => {
+                let history = self.load_interaction_history(agent1, agent2).await?;
+                self.interaction_cache.write().await.insert(pair, history.clone());
+                history
+            }
+        };
+        
+        Ok(history.connection_strength(agent1, agent2, chrono::Utc::now().timestamp(), weights))
+    }
+
+    /// Channel memberships of both agents and the messages between them
+    /// 
+    /// Memberships come from each agent's active [`ChannelParticipant`]
+    /// accounts and messages from those sent in either direction, each
+    /// selected on-chain by memcmp filters.
+    async fn load_interaction_history(&self, agent1: &Pubkey, agent2: &Pubkey) -> Result<InteractionHistory> {
+        let mut memberships = Vec::new();
+        for agent in [agent1, agent2] {
+            let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                PARTICIPANT_AGENT_OFFSET,
+                agent.as_ref(),
+            ))];
+            self.base.scan_accounts::<ChannelParticipant, _>(filters, DISCOVERY_SCAN_PAGE_SIZE, |page| {
+                memberships.extend(page.into_iter()
+                    .filter(|(_, membership)| membership.is_active)
+                    .map(|(_, membership)| (membership.participant, membership.channel)));
+                Ok(())
+            }).await?;
+        }
+        
+        let mut messages = Vec::new();
+        for (sender, recipient) in [(agent1, agent2), (agent2, agent1)] {
+            let filters = vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(MESSAGE_SENDER_OFFSET, sender.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(MESSAGE_RECIPIENT_OFFSET, recipient.as_ref())),
+            ];
+            self.base.scan_accounts::<MessageAccount, _>(filters, DISCOVERY_SCAN_PAGE_SIZE, |page| {
+                messages.extend(page.into_iter()
+                    .map(|(_, message)| (message.sender, message.recipient, message.created_at)));
+                Ok(())
+            }).await?;
+        }
+        
+        Ok(InteractionHistory::from_accounts(memberships, messages))
     }
 
     async fn recommend_by_capabilities(&self, agent: &AgentAccount) -> Result<Vec<AgentRecommendation>> {
-        let program = self.base.program()?;
-        let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+        let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
         
         let mut recommendations = Vec::new();
         
@@ -434,7 +695,7 @@ impl DiscoveryService {
             }
             
             // Calculate capability similarity
-            let similarity = self.calculate_capability_similarity(&agent.capabilities, &other_agent.capabilities);
+            let similarity = capabilities::similarity(agent.capabilities, other_agent.capabilities);
             
             if similarity > 0.3 { // Threshold for similarity
                 recommendations.push(AgentRecommendation {
@@ -452,8 +713,7 @@ impl DiscoveryService {
     }
 
     async fn recommend_complementary_agents(&self, agent: &AgentAccount) -> Result<Vec<AgentRecommendation>> {
-        let program = self.base.program()?;
-        let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+        let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
         
         let mut recommendations = Vec::new();
         
@@ -463,7 +723,7 @@ impl DiscoveryService {
             }
             
             // Calculate complementarity score
-            let complementarity = self.calculate_capability_complementarity(&agent.capabilities, &other_agent.capabilities);
+            let complementarity = capabilities::complementarity(agent.capabilities, other_agent.capabilities);
             
             if complementarity > 0.4 { // Threshold for complementarity
                 recommendations.push(AgentRecommendation {
@@ -481,8 +741,7 @@ impl DiscoveryService {
     }
 
     async fn recommend_high_reputation_agents(&self) -> Result<Vec<AgentRecommendation>> {
-        let program = self.base.program()?;
-        let accounts = program.accounts::<AgentAccount>(vec![]).await?;
+        let accounts = self.base.fetch_program_accounts::<AgentAccount>().await?;
         
         let mut recommendations = Vec::new();
         
@@ -530,38 +789,6 @@ impl DiscoveryService {
         recommendations.sort_by(|a, b| b.recommendation_score.partial_cmp(&a.recommendation_score).unwrap());
         Ok(recommendations)
     }
-
-    fn calculate_capability_similarity(&self, caps1: &[AgentCapability], caps2: &[AgentCapability]) -> f64 {
-        if caps1.is_empty() && caps2.is_empty() {
-            return 1.0;
-        }
-        
-        let set1: HashSet<_> = caps1.iter().collect();
-        let set2: HashSet<_> = caps2.iter().collect();
-        
-        let intersection = set1.intersection(&set2).count();
-        let union = set1.union(&set2).count();
-        
-        if union == 0 {
-            0.0
-        } else {
-            intersection as f64 / union as f64
-        }
-    }
-
-    fn calculate_capability_complementarity(&self, caps1: &[AgentCapability], caps2: &[AgentCapability]) -> f64 {
-        let set1: HashSet<_> = caps1.iter().collect();
-        let set2: HashSet<_> = caps2.iter().collect();
-        
-        let unique_in_set2 = set2.difference(&set1).count();
-        let total_in_set2 = set2.len();
-        
-        if total_in_set2 == 0 {
-            0.0
-        } else {
-            unique_in_set2 as f64 / total_in_set2 as f64
-        }
-    }
 }
 
 // Data structures
@@ -584,6 +811,31 @@ pub struct NearbyAgentResult {
     pub distance: u32,
     pub availability_status: AvailabilityStatus,
     pub connection_strength: f64,
+    /// Smoothed round-trip latency, if the agent has sent heartbeats
+    pub latency_ms: Option<u32>,
+}
+
+/// Relative weights of the factors in a connection strength score
+///
+/// Weights are normalized by their sum, so only their ratios matter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionStrengthWeights {
+    /// Weight of the Jaccard similarity of the agents' channel sets
+    pub shared_channels: f64,
+    /// Weight of the number of messages exchanged in either direction
+    pub message_frequency: f64,
+    /// Weight of how recently the agents last interacted
+    pub recency: f64,
+}
+
+impl Default for ConnectionStrengthWeights {
+    fn default() -> Self {
+        Self {
+            shared_channels: 0.5,
+            message_frequency: 0.3,
+            recency: 0.2,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -600,7 +852,25 @@ pub struct AgentConnectionInfo {
     pub endpoint: String,
     pub protocols: Vec<String>,
     pub last_heartbeat: chrono::DateTime<chrono::Utc>,
+    /// Exponential moving average of heartbeat latencies
     pub latency_ms: Option<u32>,
+    /// Latency measured by the most recent heartbeat
+    #[serde(default)]
+    pub last_latency_ms: Option<u32>,
+}
+
+impl AgentConnectionInfo {
+    /// Fold a new latency sample into the moving average
+    /// 
+    /// The first sample seeds the average; later ones move it by `smoothing`
+    /// times their difference from it.
+    pub fn record_latency(&mut self, sample_ms: u32, smoothing: f64) {
+        self.last_latency_ms = Some(sample_ms);
+        self.latency_ms = Some(match self.latency_ms {
+            None => sample_ms,
+            Some(average) => smoothed_latency(average, sample_ms, smoothing),
+        });
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -651,6 +921,184 @@ pub struct NetworkStats {
 
 // Internal data structures
 
+/// Channel memberships and direct message history used to score connections
+#[derive(Debug, Clone, Default)]
+struct InteractionHistory {
+    channels_by_agent: HashMap<Pubkey, HashSet<Pubkey>>,
+    /// Message count and latest timestamp, keyed by the unordered agent pair
+    messages_by_pair: HashMap<(Pubkey, Pubkey), (u64, i64)>,
+}
+
+impl InteractionHistory {
+    /// Build from `(agent, channel)` memberships and `(sender, recipient, created_at)` messages
+    fn from_accounts<C, M>(memberships: C, messages: M) -> Self
+    where
+        C: IntoIterator<Item = (Pubkey, Pubkey)>,
+        M: IntoIterator<Item = (Pubkey, Pubkey, i64)>,
+    {
+        let mut history = Self::default();
+        
+        for (agent, channel) in memberships {
+            history.channels_by_agent
+                .entry(agent)
+                .or_default()
+                .insert(channel);
+        }
+        
+        for (sender, recipient, created_at) in messages {
+            let entry = history.messages_by_pair
+                .entry(agent_pair(&sender, &recipient))
+                .or_insert((0, i64::MIN));
+            entry.0 += 1;
+            entry.1 = entry.1.max(created_at);
+        }
+        
+        history
+    }
+
+    fn connection_strength(
+        &self,
+        agent1: &Pubkey,
+        agent2: &Pubkey,
+        now: i64,
+        weights: &ConnectionStrengthWeights,
+    ) -> f64 {
+        let empty = HashSet::new();
+        let channels1 = self.channels_by_agent.get(agent1).unwrap_or(&empty);
+        let channels2 = self.channels_by_agent.get(agent2).unwrap_or(&empty);
+        let (message_count, last_interaction) = self.messages_by_pair
+            .get(&agent_pair(agent1, agent2))
+            .map_or((0, None), |&(count, latest)| (count, Some(latest)));
+        
+        connection_strength(channels1, channels2, message_count, last_interaction, now, weights)
+    }
+}
+
+/// Order-independent key for a pair of agents
+fn agent_pair(a: &Pubkey, b: &Pubkey) -> (Pubkey, Pubkey) {
+    if a <= b { (*a, *b) } else { (*b, *a) }
+}
+
+/// Weighted combination of channel overlap, message volume and recency, in `[0.0, 1.0]`
+///
+/// Message volume saturates smoothly, reaching 0.5 at
+/// [`CONNECTION_MESSAGE_SCALE`] messages, and recency halves every
+/// [`CONNECTION_RECENCY_HALF_LIFE_SECS`] since the last interaction.
+fn connection_strength(
+    channels1: &HashSet<Pubkey>,
+    channels2: &HashSet<Pubkey>,
+    message_count: u64,
+    last_interaction: Option<i64>,
+    now: i64,
+    weights: &ConnectionStrengthWeights,
+) -> f64 {
+    let union = channels1.union(channels2).count();
+    let shared = if union == 0 {
+        0.0
+    } else {
+        channels1.intersection(channels2).count() as f64 / union as f64
+    };
+    
+    let count = message_count as f64;
+    let frequency = count / (count + CONNECTION_MESSAGE_SCALE);
+    
+    let recency = last_interaction.map_or(0.0, |at| {
+        let age = now.saturating_sub(at).max(0) as f64;
+        0.5f64.powf(age / CONNECTION_RECENCY_HALF_LIFE_SECS as f64)
+    });
+    
+    let weights = [
+        weights.shared_channels.max(0.0),
+        weights.message_frequency.max(0.0),
+        weights.recency.max(0.0),
+    ];
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 || !total.is_finite() {
+        return 0.0;
+    }
+    
+    let score = (weights[0] * shared + weights[1] * frequency + weights[2] * recency) / total;
+    score.clamp(0.0, 1.0)
+}
+
+/// Bytes an agent's owner signs to register `connection_info` for discovery
+/// 
+/// `challenge` comes from [`DiscoveryService::issue_registration_challenge`],
+/// so a captured signature cannot be replayed.
+pub fn registration_message(
+    agent_address: &Pubkey,
+    challenge: &[u8; 32],
+    connection_info: &AgentConnectionInfo,
+) -> Result<Vec<u8>> {
+    let mut message = Vec::new();
+    message.extend_from_slice(challenge);
+    message.extend_from_slice(agent_address.as_ref());
+    message.extend_from_slice(&serde_json::to_vec(connection_info)?);
+    Ok(message)
+}
+
+/// Check that `owner` owns `agent_address` and signed its registration
+fn verify_registration(
+    program_id: &Pubkey,
+    agent_address: &Pubkey,
+    connection_info: &AgentConnectionInfo,
+    owner: &Pubkey,
+    challenge: &[u8; 32],
+    signature: &Signature,
+) -> Result<()> {
+    if pda::agent_pda(program_id, owner).0 != *agent_address {
+        return Err(AgentError::Unauthorized { pubkey: *agent_address }.into());
+    }
+    
+    let message = registration_message(agent_address, challenge, connection_info)?;
+    if !signature.verify(owner.as_ref(), &message) {
+        return Err(SecurityError::InvalidSignature.into());
+    }
+    
+    Ok(())
+}
+
+/// Move `average` toward `sample` by `smoothing` of their difference
+/// 
+/// Always moves at least 1ms when they differ, so a steady input is
+/// eventually reached exactly rather than stalling on rounding.
+fn smoothed_latency(average: u32, sample: u32, smoothing: f64) -> u32 {
+    let difference = sample as f64 - average as f64;
+    let step = (difference * smoothing).round();
+    let step = if step == 0.0 { difference.signum() } else { step };
+    (average as f64 + step).clamp(0.0, u32::MAX as f64) as u32
+}
+
+/// Sort key ordering agents by latency, unknown latencies last
+fn latency_rank(latency_ms: Option<u32>) -> u32 {
+    latency_ms.unwrap_or(u32::MAX)
+}
+
+/// Record `slot` for `agent_address` unless the same or a later slot was already seen
+fn is_newer_update(latest_slots: &mut HashMap<Pubkey, u64>, agent_address: Pubkey, slot: u64) -> bool {
+    match latest_slots.get(&agent_address) {
+        Some(known_slot) if *known_slot >= slot => false,
+        _ => {
+            latest_slots.insert(agent_address, slot);
+            true
+        }
+    }
+}
+
+/// Decode an agent account pushed by a program subscription
+fn decode_agent_update(keyed: &RpcKeyedAccount) -> Option<(Pubkey, AgentAccount)> {
+    let address = keyed.pubkey.parse::<Pubkey>().ok()?;
+    let account = keyed.account.decode::<solana_sdk::account::Account>()?;
+    
+    match decode_account::<AgentAccount>(&account.data) {
+        Ok(agent) => Some((address, agent)),
+        Err(e) => {
+            tracing::warn!("Skipping undecodable agent update {}: {}", address, e);
+            None
+        }
+    }
+}
+
 #[derive(Debug)]
 struct AgentRegistry {
     agents: HashMap<Pubkey, AgentRegistration>,
@@ -685,16 +1133,47 @@ impl AgentRegistry {
         }
     }
 
-    fn get_availability(&self, agent_address: &Pubkey) -> Option<AvailabilityStatus> {
-        self.agents.get(agent_address).map(|r| r.availability.clone())
+    /// Refresh an agent's heartbeat, returning whether the agent is registered
+    fn record_heartbeat(
+        &mut self,
+        agent_address: &Pubkey,
+        latency_ms: u32,
+        smoothing: f64,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        match self.agents.get_mut(agent_address) {
+            Some(registration) => {
+                registration.connection_info.last_heartbeat = now;
+                registration.connection_info.record_latency(latency_ms, smoothing);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stored availability, or `Offline` if the last heartbeat is older than `staleness`
+    fn get_availability(
+        &self,
+        agent_address: &Pubkey,
+        now: chrono::DateTime<chrono::Utc>,
+        staleness: chrono::Duration,
+    ) -> Option<AvailabilityStatus> {
+        self.agents.get(agent_address).map(|r| {
+            if now - r.connection_info.last_heartbeat > staleness {
+                AvailabilityStatus::Offline
+            } else {
+                r.availability.clone()
+            }
+        })
     }
 
     fn get_connection_info(&self, agent_address: &Pubkey) -> Option<&AgentConnectionInfo> {
         self.agents.get(agent_address).map(|r| &r.connection_info)
     }
 
-    fn get_active_agent_count(&self) -> u64 {
+    fn get_active_agent_count(&self, now: chrono::DateTime<chrono::Utc>, staleness: chrono::Duration) -> u64 {
         self.agents.values()
+            .filter(|r| now - r.connection_info.last_heartbeat <= staleness)
             .filter(|r| matches!(r.availability, AvailabilityStatus::Online | AvailabilityStatus::Busy))
             .count() as u64
     }
@@ -734,6 +1213,96 @@ impl TopologyCache {
     }
 }
 
+/// Recently loaded interaction histories, keyed by agent pair
+#[derive(Debug)]
+struct InteractionCache {
+    entries: HashMap<(Pubkey, Pubkey), (chrono::DateTime<chrono::Utc>, InteractionHistory)>,
+    cache_duration: chrono::Duration,
+}
+
+impl InteractionCache {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            cache_duration: chrono::Duration::seconds(INTERACTION_CACHE_TTL_SECS),
+        }
+    }
+
+    fn get(&self, pair: &(Pubkey, Pubkey)) -> Option<InteractionHistory> {
+        self.entries
+            .get(pair)
+            .filter(|(loaded_at, _)| chrono::Utc::now() - *loaded_at <= self.cache_duration)
+            .map(|(_, history)| history.clone())
+    }
+
+    /// Store `history`, dropping expired entries and then the oldest once full
+    fn insert(&mut self, pair: (Pubkey, Pubkey), history: InteractionHistory) {
+        let now = chrono::Utc::now();
+        let cache_duration = self.cache_duration;
+        self.entries.retain(|_, (loaded_at, _)| now - *loaded_at <= cache_duration);
+        
+        if self.entries.len() >= MAX_CACHED_INTERACTIONS && !self.entries.contains_key(&pair) {
+            let oldest = self.entries
+                .iter()
+                .min_by_key(|(_, (loaded_at, _))| *loaded_at)
+                .map(|(pair, _)| *pair);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        
+        self.entries.insert(pair, (now, history));
+    }
+}
+
+/// Registration challenges issued but not yet used, with the agent and time each was issued for
+#[derive(Debug, Default)]
+struct RegistrationChallenges {
+    pending: HashMap<[u8; 32], (Pubkey, chrono::DateTime<chrono::Utc>)>,
+}
+
+impl RegistrationChallenges {
+    /// Issue a random challenge, dropping expired ones and then the oldest once full
+    fn issue(&mut self, agent_address: Pubkey, now: chrono::DateTime<chrono::Utc>) -> [u8; 32] {
+        let ttl = chrono::Duration::seconds(REGISTRATION_CHALLENGE_TTL_SECS);
+        self.pending.retain(|_, (_, issued_at)| now - *issued_at <= ttl);
+        
+        if self.pending.len() >= MAX_PENDING_CHALLENGES {
+            let oldest = self.pending
+                .iter()
+                .min_by_key(|(_, (_, issued_at))| *issued_at)
+                .map(|(challenge, _)| *challenge);
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        
+        let challenge: [u8; 32] = rand::random();
+        self.pending.insert(challenge, (agent_address, now));
+        challenge
+    }
+
+    /// Check that `challenge` was issued for `agent_address`, is unused and has not expired
+    fn check(&self, challenge: &[u8; 32], agent_address: &Pubkey, now: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        match self.pending.get(challenge) {
+            Some((agent, issued_at)) if agent == agent_address => {
+                if now - *issued_at > chrono::Duration::seconds(REGISTRATION_CHALLENGE_TTL_SECS) {
+                    return Err(SecurityError::AuthenticationFailed {
+                        reason: "registration challenge expired".to_string(),
+                    }.into());
+                }
+                Ok(())
+            }
+            _ => Err(SecurityError::ReplayAttack.into()),
+        }
+    }
+
+    /// Mark `challenge` used, so it is not accepted again
+    fn consume(&mut self, challenge: &[u8; 32]) {
+        self.pending.remove(challenge);
+    }
+}
+
 #[async_trait]
 impl BaseService for DiscoveryService {
     type Error = PodComError;
@@ -822,9 +1391,12 @@ impl BaseService for DiscoveryService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -835,8 +1407,39 @@ impl BaseService for DiscoveryService {
     fn service_name(&self) -> &'static str {
         "discovery"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
 }
 
+/// Seconds without a heartbeat after which an agent is reported offline
+const DEFAULT_HEARTBEAT_STALENESS_SECS: i64 = 2 * 60;
+/// Weight of a new heartbeat latency in the smoothed latency
+const DEFAULT_LATENCY_SMOOTHING: f64 = 0.2;
+
+// Constants for connection scoring
+/// Message count at which the frequency factor reaches 0.5
+const CONNECTION_MESSAGE_SCALE: f64 = 20.0;
+/// Seconds after which the recency factor halves
+const CONNECTION_RECENCY_HALF_LIFE_SECS: i64 = 7 * 24 * 60 * 60;
+/// How long a pair's interaction history is reused before it is reloaded
+const INTERACTION_CACHE_TTL_SECS: i64 = 60;
+/// Agent pairs whose interaction history is kept at once
+const MAX_CACHED_INTERACTIONS: usize = 1024;
+/// How long an issued registration challenge is accepted
+pub const REGISTRATION_CHALLENGE_TTL_SECS: i64 = 5 * 60;
+/// Registration challenges awaiting use at once
+const MAX_PENDING_CHALLENGES: usize = 1024;
+/// Accounts fetched per page when scanning memberships and messages
+const DISCOVERY_SCAN_PAGE_SIZE: usize = 100;
+/// Offset of `participant` in a `ChannelParticipant`, after the discriminator and `channel`
+const PARTICIPANT_AGENT_OFFSET: usize = 8 + 32;
+/// Offset of `sender` in a `MessageAccount`, after the discriminator
+const MESSAGE_SENDER_OFFSET: usize = 8;
+/// Offset of `recipient` in a `MessageAccount`, after the discriminator and `sender`
+const MESSAGE_RECIPIENT_OFFSET: usize = 8 + 32;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -849,4 +1452,292 @@ mod tests {
         assert_eq!(service.service_name(), "discovery");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    fn connection_info(last_heartbeat: chrono::DateTime<chrono::Utc>) -> AgentConnectionInfo {
+        AgentConnectionInfo {
+            endpoint: "https://agent.example.com".to_string(),
+            protocols: vec!["https".to_string()],
+            last_heartbeat,
+            latency_ms: None,
+            last_latency_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_latency_average_converges_to_steady_input() {
+        let mut info = connection_info(chrono::Utc::now());
+        info.record_latency(200, DEFAULT_LATENCY_SMOOTHING);
+        assert_eq!(info.latency_ms, Some(200));
+        
+        let mut previous_gap = u32::MAX;
+        for _ in 0..100 {
+            info.record_latency(50, DEFAULT_LATENCY_SMOOTHING);
+            let gap = info.latency_ms.unwrap() - 50;
+            assert!(gap <= previous_gap);
+            previous_gap = gap;
+        }
+        assert_eq!(info.latency_ms, Some(50));
+        assert_eq!(info.last_latency_ms, Some(50));
+    }
+
+    #[test]
+    fn test_latency_average_dampens_outlier() {
+        let mut info = connection_info(chrono::Utc::now());
+        for _ in 0..10 {
+            info.record_latency(40, DEFAULT_LATENCY_SMOOTHING);
+        }
+        info.record_latency(1_000, DEFAULT_LATENCY_SMOOTHING);
+        
+        assert_eq!(info.last_latency_ms, Some(1_000));
+        assert_eq!(info.latency_ms, Some(232));
+        
+        // Without smoothing the spike is taken as-is
+        info.record_latency(1_000, 1.0);
+        assert_eq!(info.latency_ms, Some(1_000));
+    }
+
+    #[test]
+    fn test_stale_heartbeat_reports_offline() {
+        let mut registry = AgentRegistry::new();
+        let agent = Pubkey::new_unique();
+        let staleness = chrono::Duration::seconds(DEFAULT_HEARTBEAT_STALENESS_SECS);
+        let start = chrono::Utc::now();
+        registry.register_agent(agent, connection_info(start));
+
+        // A fresh heartbeat keeps the stored status
+        assert!(matches!(registry.get_availability(&agent, start + staleness, staleness), Some(AvailabilityStatus::Online)));
+        // Past the window the agent is offline whatever its stored status
+        let stale = start + staleness + chrono::Duration::seconds(1);
+        assert!(matches!(registry.get_availability(&agent, stale, staleness), Some(AvailabilityStatus::Offline)));
+        registry.update_availability(agent, AvailabilityStatus::Busy);
+        assert!(matches!(registry.get_availability(&agent, stale, staleness), Some(AvailabilityStatus::Offline)));
+
+        // A new heartbeat brings it back
+        assert!(registry.record_heartbeat(&agent, 42, DEFAULT_LATENCY_SMOOTHING, stale));
+        assert!(matches!(registry.get_availability(&agent, stale, staleness), Some(AvailabilityStatus::Busy)));
+        assert_eq!(registry.get_connection_info(&agent).unwrap().latency_ms, Some(42));
+
+        assert!(!registry.record_heartbeat(&Pubkey::new_unique(), 42, DEFAULT_LATENCY_SMOOTHING, stale));
+    }
+
+    #[tokio::test]
+    async fn test_record_heartbeat_brings_agent_online() {
+        let service = DiscoveryService::new(test_config())
+            .with_heartbeat_staleness(std::time::Duration::from_secs(60));
+        let owner = Keypair::new();
+        let agent = pda::agent_pda(&test_config().effective_program_id(), &owner.pubkey()).0;
+        let last_heartbeat = chrono::Utc::now() - chrono::Duration::minutes(5);
+        let info = connection_info(last_heartbeat);
+        let challenge = service.issue_registration_challenge(&agent).await;
+        let signature = owner.sign_message(&registration_message(&agent, &challenge, &info).unwrap());
+        service.register_agent_for_discovery(&agent, info, &owner.pubkey(), challenge, &signature).await.unwrap();
+
+        assert!(matches!(service.get_agent_availability(&agent).await.unwrap(), AvailabilityStatus::Offline));
+        service.record_heartbeat(&agent, 15).await.unwrap();
+        assert!(matches!(service.get_agent_availability(&agent).await.unwrap(), AvailabilityStatus::Online));
+        assert_eq!(service.get_agent_connection_info(&agent).await.unwrap().unwrap().latency_ms, Some(15));
+
+        assert!(matches!(
+            service.record_heartbeat(&Pubkey::new_unique(), 15).await,
+            Err(PodComError::Agent(AgentError::NotFound { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_agent_updates_deduped_by_slot() {
+        let mut latest_slots = HashMap::new();
+        let agent = Pubkey::new_unique();
+
+        assert!(is_newer_update(&mut latest_slots, agent, 10));
+        assert!(is_newer_update(&mut latest_slots, agent, 12));
+
+        // Replayed and out-of-order updates are dropped
+        assert!(!is_newer_update(&mut latest_slots, agent, 12));
+        assert!(!is_newer_update(&mut latest_slots, agent, 11));
+        assert_eq!(latest_slots[&agent], 12);
+
+        // Slots are tracked per agent
+        assert!(is_newer_update(&mut latest_slots, Pubkey::new_unique(), 5));
+    }
+
+    #[test]
+    fn test_reconnect_gives_up_after_max_retries() {
+        let retry_config = crate::config::RetryConfig::default();
+        let max_retries = retry_config.max_retries;
+        let retry = RetryHandler::new(retry_config);
+        let mut failures = 0;
+
+        for _ in 0..max_retries {
+            assert!(retry.reconnect_delay(&mut failures, max_retries).is_some());
+        }
+        assert!(retry.reconnect_delay(&mut failures, max_retries).is_none());
+    }
+
+    /// Memberships of `participants` in one new channel
+    fn channel_with(participants: &[Pubkey]) -> Vec<(Pubkey, Pubkey)> {
+        let channel = Pubkey::new_unique();
+        participants.iter().map(|participant| (*participant, channel)).collect()
+    }
+
+    fn message_between(sender: Pubkey, recipient: Pubkey, created_at: i64) -> (Pubkey, Pubkey, i64) {
+        (sender, recipient, created_at)
+    }
+
+    #[test]
+    fn test_more_shared_channels_means_stronger_connection() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let weights = ConnectionStrengthWeights::default();
+        let now = 1_700_000_000;
+
+        // Both agents are in four channels each; `shared` of them are common
+        let strengths: Vec<f64> = (0..=4)
+            .map(|shared| {
+                let mut channels = Vec::new();
+                for i in 0..4 {
+                    if i < shared {
+                        channels.extend(channel_with(&[a, b]));
+                    } else {
+                        channels.extend(channel_with(&[a]));
+                        channels.extend(channel_with(&[b]));
+                    }
+                }
+                InteractionHistory::from_accounts(channels, Vec::new())
+                    .connection_strength(&a, &b, now, &weights)
+            })
+            .collect();
+
+        assert_eq!(strengths[0], 0.0);
+        assert!(strengths.windows(2).all(|w| w[0] < w[1]), "{:?}", strengths);
+        // Identical channel sets with no messages score exactly the channel weight's share
+        assert!((strengths[4] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_messages_and_recency_raise_strength() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let weights = ConnectionStrengthWeights::default();
+        let now = 1_700_000_000;
+        let week = CONNECTION_RECENCY_HALF_LIFE_SECS;
+
+        let history = InteractionHistory::from_accounts(
+            channel_with(&[a, b, c]),
+            vec![
+                message_between(a, b, now - 3 * week),
+                message_between(b, a, now - week),
+                message_between(a, c, now - 3 * week),
+            ],
+        );
+
+        // Messages count in both directions and are keyed independently of order
+        assert_eq!(history.messages_by_pair[&agent_pair(&b, &a)], (2, now - week));
+
+        let ab = history.connection_strength(&a, &b, now, &weights);
+        let ac = history.connection_strength(&a, &c, now, &weights);
+        let bc = history.connection_strength(&b, &c, now, &weights);
+        assert!(ab > ac && ac > bc, "{} {} {}", ab, ac, bc);
+        assert_eq!(history.connection_strength(&a, &b, now, &weights), history.connection_strength(&b, &a, now, &weights));
+    }
+
+    #[test]
+    fn test_interaction_cache_expires_and_stays_bounded() {
+        let mut cache = InteractionCache::new();
+        let pairs: Vec<_> = (0..=MAX_CACHED_INTERACTIONS)
+            .map(|_| agent_pair(&Pubkey::new_unique(), &Pubkey::new_unique()))
+            .collect();
+
+        for pair in &pairs {
+            cache.insert(*pair, InteractionHistory::default());
+        }
+        assert_eq!(cache.entries.len(), MAX_CACHED_INTERACTIONS);
+        assert!(cache.get(&pairs[MAX_CACHED_INTERACTIONS]).is_some());
+
+        cache.entries.get_mut(&pairs[1]).unwrap().0 -= chrono::Duration::seconds(INTERACTION_CACHE_TTL_SECS + 1);
+        assert!(cache.get(&pairs[1]).is_none());
+    }
+
+    #[test]
+    fn test_connection_strength_bounded_by_weights() {
+        let channels: HashSet<Pubkey> = [Pubkey::new_unique()].into_iter().collect();
+        let now = 1_700_000_000;
+
+        let saturated = connection_strength(&channels, &channels, u64::MAX, Some(now), now, &ConnectionStrengthWeights::default());
+        assert!(saturated > 0.99 && saturated <= 1.0);
+
+        // Only the recency factor counts, and an interaction one half-life ago scores 0.5
+        let recency_only = ConnectionStrengthWeights { shared_channels: 0.0, message_frequency: 0.0, recency: 3.0 };
+        let score = connection_strength(&channels, &channels, 1, Some(now - CONNECTION_RECENCY_HALF_LIFE_SECS), now, &recency_only);
+        assert!((score - 0.5).abs() < 1e-9);
+
+        let no_weights = ConnectionStrengthWeights { shared_channels: 0.0, message_frequency: 0.0, recency: 0.0 };
+        assert_eq!(connection_strength(&channels, &channels, 10, Some(now), now, &no_weights), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_signed_registration_accepted_and_forgery_rejected() {
+        let service = DiscoveryService::new(test_config());
+        let program_id = test_config().effective_program_id();
+        let (owner, impostor) = (Keypair::new(), Keypair::new());
+        let agent = pda::agent_pda(&program_id, &owner.pubkey()).0;
+        let challenge = service.issue_registration_challenge(&agent).await;
+        let info = connection_info(chrono::Utc::now());
+        let message = registration_message(&agent, &challenge, &info).unwrap();
+        
+        // Signed by someone other than the owner
+        let forged = impostor.sign_message(&message);
+        assert!(matches!(
+            service.register_agent_for_discovery(&agent, info.clone(), &owner.pubkey(), challenge, &forged).await,
+            Err(PodComError::Security(SecurityError::InvalidSignature))
+        ));
+        // Validly signed, but for an agent the signer doesn't own
+        let impostor_signature = impostor.sign_message(&message);
+        assert!(matches!(
+            service.register_agent_for_discovery(&agent, info.clone(), &impostor.pubkey(), challenge, &impostor_signature).await,
+            Err(PodComError::Agent(AgentError::Unauthorized { .. }))
+        ));
+        // Signature over a different challenge
+        let signature = owner.sign_message(&message);
+        let other_challenge = service.issue_registration_challenge(&agent).await;
+        assert!(matches!(
+            service.register_agent_for_discovery(&agent, info.clone(), &owner.pubkey(), other_challenge, &signature).await,
+            Err(PodComError::Security(SecurityError::InvalidSignature))
+        ));
+        assert!(service.get_agent_connection_info(&agent).await.unwrap().is_none());
+        
+        service.register_agent_for_discovery(&agent, info.clone(), &owner.pubkey(), challenge, &signature).await.unwrap();
+        assert!(service.get_agent_connection_info(&agent).await.unwrap().is_some());
+        
+        // The same challenge and signature are not accepted twice
+        assert!(matches!(
+            service.register_agent_for_discovery(&agent, info, &owner.pubkey(), challenge, &signature).await,
+            Err(PodComError::Security(SecurityError::ReplayAttack))
+        ));
+    }
+
+    #[test]
+    fn test_registration_challenges_expire_and_bind_to_agent() {
+        let (agent, other_agent) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let issued_at = chrono::Utc::now();
+        let mut challenges = RegistrationChallenges::default();
+        let challenge = challenges.issue(agent, issued_at);
+        
+        assert!(challenges.check(&challenge, &agent, issued_at).is_ok());
+        assert!(matches!(
+            challenges.check(&challenge, &other_agent, issued_at),
+            Err(PodComError::Security(SecurityError::ReplayAttack))
+        ));
+        assert!(matches!(
+            challenges.check(&[0; 32], &agent, issued_at),
+            Err(PodComError::Security(SecurityError::ReplayAttack))
+        ));
+        
+        let expired = issued_at + chrono::Duration::seconds(REGISTRATION_CHALLENGE_TTL_SECS + 1);
+        assert!(matches!(
+            challenges.check(&challenge, &agent, expired),
+            Err(PodComError::Security(SecurityError::AuthenticationFailed { .. }))
+        ));
+        
+        // Expired challenges are dropped when the next one is issued
+        challenges.issue(agent, expired);
+        assert_eq!(challenges.pending.len(), 1);
+    }
+}
@@ -1,8 +1,13 @@
 //! # Escrow Service
 //!
 //! Service for managing escrow accounts and payments on the PoD Protocol.
-//! Provides functionality for creating escrows, releasing funds, and handling disputes.
+//! Provides functionality for creating escrows and releasing or refunding funds.
+//! Disputes cannot be recorded: pod-com's escrow account has no status or
+//! dispute fields and the program has no dispute instruction. For the same
+//! reason arbitrators come only from the escrow as created or the service's
+//! `arbitrator_config`, and cannot be added or removed later.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anchor_client::Program;
@@ -15,47 +20,82 @@ use solana_sdk::{
 };
 
 use pod_sdk_types::{
-    EscrowAccount, AgentAccount, EscrowStatus, EscrowCondition,
+    EscrowAccount, AgentAccount, ChannelAccount, EscrowStatus, EscrowCondition,
 };
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
-    utils::account::{derive_escrow_pda, validate_escrow_account},
+    error::{EscrowError, PodComError, Result},
+    services::base::{BaseService, Outcome, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    utils::{
+        account::{derive_escrow_pda, validate_escrow_account},
+        pagination::{Page, PageRequest},
+    },
 };
 
 /// Service for managing escrow accounts
 #[derive(Debug)]
 pub struct EscrowService {
     base: ServiceBase,
+    /// Evaluators for condition types the service doesn't know natively
+    condition_predicates: ConditionPredicates,
 }
 
 impl EscrowService {
     /// Create a new escrow service
     pub fn new(config: ServiceConfig) -> Self {
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("escrow"),
+            condition_predicates: ConditionPredicates::default(),
         }
     }
 
+    /// Evaluate conditions of type `condition_type` with `predicate`
+    /// 
+    /// The predicate receives the condition, its escrow and the evaluation
+    /// time. Built-in condition types cannot be overridden.
+    pub fn with_condition_predicate<F>(mut self, condition_type: &str, predicate: F) -> Self
+    where
+        F: Fn(&EscrowCondition, &EscrowAccount, i64) -> bool + Send + Sync + 'static,
+    {
+        self.condition_predicates.0.insert(condition_type.to_string(), Arc::new(predicate));
+        self
+    }
+
+    /// Check each of an escrow's release conditions as of `now` (Unix seconds)
+    /// 
+    /// `time_elapsed` compares `now` against the deposit time,
+    /// `service_completion` is met once the service channel has been closed,
+    /// and other types use their `fulfilled` flag, approval counts, or a
+    /// registered predicate. Unknown types are never satisfied.
+    pub async fn evaluate_conditions(&self, escrow: &EscrowAccount, now: i64) -> ConditionEvaluation {
+        let mut channel_active = HashMap::new();
+        for channel in service_channels(escrow) {
+            if let Ok(account) = self.base.fetch_account::<ChannelAccount>(&channel).await {
+                channel_active.insert(channel, account.is_active);
+            }
+        }
+        
+        evaluate_conditions(escrow, now, &self.condition_predicates, |channel| {
+            channel_active.get(channel).copied()
+        })
+    }
+
     /// Create a new escrow account
     pub async fn create_escrow(
         &self,
         payer: &Keypair,
         params: CreateEscrowParams,
-    ) -> Result<(Pubkey, EscrowAccount)> {
+    ) -> Result<Outcome<(Pubkey, EscrowAccount)>> {
         let operation_name = "create_escrow";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             // Validate participants
             if params.amount == 0 {
                 return Err(PodComError::InvalidEscrowAmount { amount: params.amount });
             }
             
             // Verify beneficiary is a valid agent
-            let _beneficiary_account = program.account::<AgentAccount>(params.beneficiary)?;
+            let _beneficiary_account = self.base.fetch_account::<AgentAccount>(&params.beneficiary).await?;
             
             // Generate escrow ID and derive PDA
             let escrow_id: String = rand::thread_rng()
@@ -63,11 +103,11 @@ impl EscrowService {
                 .take(32)
                 .map(char::from)
                 .collect();
-            let (escrow_pda, _bump) = derive_escrow_pda(&payer.pubkey(), &escrow_id)?;
+            #[allow(deprecated)]
+            let (escrow_pda, _bump) = derive_escrow_pda(&payer.pubkey(), &escrow_id, &self.base.config().effective_program_id())?;
             
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::DepositEscrow {
                     escrow: escrow_pda,
                     payer: payer.pubkey(),
@@ -82,10 +122,14 @@ impl EscrowService {
                     timeout_duration: params.timeout_duration,
                     metadata: params.metadata.clone(),
                 })
-                .signer(payer);
+                .signer(payer)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(&escrow_pda);
             
             // Fetch created escrow account
             let escrow_account = self.get_escrow_account(&escrow_pda).await?;
@@ -100,7 +144,7 @@ impl EscrowService {
                 "Escrow created successfully"
             );
 
-            Ok((escrow_pda, escrow_account))
+            Ok(Outcome::Sent((escrow_pda, escrow_account)))
         }).await
     }
 
@@ -108,10 +152,8 @@ impl EscrowService {
     pub async fn get_escrow_account(&self, escrow_address: &Pubkey) -> Result<EscrowAccount> {
         let operation_name = "get_escrow_account";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<EscrowAccount>(*escrow_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let account_data = self.base.fetch_account::<EscrowAccount>(escrow_address).await?;
             validate_escrow_account(&account_data)?;
             
             Ok(account_data)
@@ -124,16 +166,15 @@ impl EscrowService {
         escrow_address: &Pubkey,
         releaser: &Keypair,
         params: ReleaseEscrowParams,
-    ) -> Result<EscrowAccount> {
+    ) -> Result<Outcome<EscrowAccount>> {
         let operation_name = "release_escrow";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let escrow_account = self.get_escrow_account(escrow_address).await?;
+            let evaluation = self.evaluate_conditions(&escrow_account, chrono::Utc::now().timestamp()).await;
             
             // Verify releaser authorization
-            if !self.can_release_escrow(&escrow_account, &releaser.pubkey()) {
+            if !self.can_release_escrow(&escrow_account, &releaser.pubkey(), &evaluation) {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "escrow".to_string(),
                     action: "release".to_string(),
@@ -149,9 +190,15 @@ impl EscrowService {
                 });
             }
             
+            if !evaluation.all_satisfied {
+                let unmet: Vec<_> = evaluation.unsatisfied().map(|r| r.condition_type.as_str()).collect();
+                return Err(EscrowError::WithdrawalNotAllowed {
+                    reason: format!("release conditions not met: {}", unmet.join(", ")),
+                }.into());
+            }
+            
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::WithdrawEscrow {
                     escrow: *escrow_address,
                     releaser: releaser.pubkey(),
@@ -159,12 +206,16 @@ impl EscrowService {
                 })
                 .args(pod_com::instruction::WithdrawEscrow {
                     release_amount: params.release_amount.unwrap_or(escrow_account.amount),
-                    release_reason: params.release_reason,
+                    release_reason: params.release_reason.clone(),
                 })
-                .signer(releaser);
+                .signer(releaser)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(escrow_address);
             
             // Fetch updated escrow account
             let updated_account = self.get_escrow_account(escrow_address).await?;
@@ -178,7 +229,7 @@ impl EscrowService {
                 "Escrow released successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -188,12 +239,10 @@ impl EscrowService {
         escrow_address: &Pubkey,
         refunder: &Keypair,
         refund_reason: Option<String>,
-    ) -> Result<EscrowAccount> {
+    ) -> Result<Outcome<EscrowAccount>> {
         let operation_name = "refund_escrow";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let escrow_account = self.get_escrow_account(escrow_address).await?;
             
             // Verify refunder authorization
@@ -214,20 +263,23 @@ impl EscrowService {
             }
             
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::WithdrawEscrow {
                     escrow: *escrow_address,
                     refunder: refunder.pubkey(),
                     payer: escrow_account.payer,
                 })
                 .args(pod_com::instruction::WithdrawEscrow {
-                    refund_reason,
+                    refund_reason: refund_reason.clone(),
                 })
-                .signer(refunder);
+                .signer(refunder)).await?;
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(escrow_address);
             
             // Fetch updated escrow account
             let updated_account = self.get_escrow_account(escrow_address).await?;
@@ -241,11 +293,15 @@ impl EscrowService {
                 "Escrow refunded successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
     /// Initiate dispute for an escrow
+    ///
+    /// pod-com cannot record a dispute, so once the disputer and the escrow
+    /// state are checked this always fails with
+    /// [`PodComError::NotImplemented`].
     pub async fn dispute_escrow(
         &self,
         escrow_address: &Pubkey,
@@ -254,7 +310,7 @@ impl EscrowService {
     ) -> Result<EscrowAccount> {
         let operation_name = "dispute_escrow";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let program = self.base.program()?;
             
             let escrow_account = self.get_escrow_account(escrow_address).await?;
@@ -284,42 +340,45 @@ impl EscrowService {
         }).await
     }
 
-    /// List escrows for a specific user (as payer or beneficiary)
-    pub async fn list_user_escrows(&self, user: &Pubkey) -> Result<Vec<(Pubkey, EscrowAccount)>> {
+    /// List escrows for a specific user (as payer or beneficiary), most recent first
+    pub async fn list_user_escrows(
+        &self,
+        user: &Pubkey,
+        page: PageRequest,
+    ) -> Result<Page<(Pubkey, EscrowAccount)>> {
         let operation_name = "list_user_escrows";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            // Get all escrow accounts
-            let accounts = program
-                .accounts::<EscrowAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
-                
-            let mut user_escrows = Vec::new();
+        self.base.execute_operation(operation_name, || async {
+            Page::from_sorted(self.fetch_user_escrows(user).await?, &page)
+        }).await
+    }
+
+    /// Fetch every escrow for a user, most recent first
+    async fn fetch_user_escrows(&self, user: &Pubkey) -> Result<Vec<(Pubkey, EscrowAccount)>> {
+        // Get all escrow accounts
+        let accounts = self.base.fetch_program_accounts::<EscrowAccount>().await?;
             
-            for (pubkey, account) in accounts {
-                if account.payer == *user || account.beneficiary == *user {
-                    validate_escrow_account(&account)?;
-                    user_escrows.push((pubkey, account));
-                }
+        let mut user_escrows = Vec::new();
+        
+        for (pubkey, account) in accounts {
+            if account.payer == *user || account.beneficiary == *user {
+                validate_escrow_account(&account)?;
+                user_escrows.push((pubkey, account));
             }
-            
-            // Sort by creation time (most recent first)
-            user_escrows.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
-            
-            Ok(user_escrows)
-        }).await
+        }
+        
+        // Sort by creation time (most recent first)
+        user_escrows.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
+        
+        Ok(user_escrows)
     }
 
     /// Get escrow statistics for a user
     pub async fn get_user_escrow_stats(&self, user: &Pubkey) -> Result<EscrowStats> {
         let operation_name = "get_user_escrow_stats";
         
-        self.base.execute_operation(operation_name, async {
-            let escrows = self.list_user_escrows(user).await?;
+        self.base.execute_operation(operation_name, || async {
+            let escrows = self.fetch_user_escrows(user).await?;
             
             let mut stats = EscrowStats {
                 total_escrows: 0,
@@ -368,8 +427,8 @@ impl EscrowService {
         }).await
     }
 
-    /// Check if a user can release an escrow
-    fn can_release_escrow(&self, escrow: &EscrowAccount, user: &Pubkey) -> bool {
+    /// Check if a user can release an escrow whose conditions evaluated to `evaluation`
+    fn can_release_escrow(&self, escrow: &EscrowAccount, user: &Pubkey, evaluation: &ConditionEvaluation) -> bool {
         // Payer can always release
         if escrow.payer == *user {
             return true;
@@ -377,7 +436,7 @@ impl EscrowService {
         
         // Beneficiary can release if conditions are met
         if escrow.beneficiary == *user {
-            return self.check_release_conditions(escrow);
+            return evaluation.all_satisfied;
         }
         
         // Arbitrator can release if system has arbitrators enabled
@@ -416,69 +475,6 @@ impl EscrowService {
         false
     }
 
-    /// Check if release conditions are met for beneficiary release
-    fn check_release_conditions(&self, escrow: &EscrowAccount) -> bool {
-        // If no conditions are specified, allow release
-        if escrow.conditions.is_empty() {
-            return true;
-        }
-        
-        // Parse and check each condition
-        for condition in &escrow.conditions {
-            match condition.condition_type.as_str() {
-                "time_elapsed" => {
-                    // Check if enough time has passed since escrow creation
-                    if let Some(required_seconds) = condition.parameters.get("seconds")
-                        .and_then(|v| v.parse::<i64>().ok()) {
-                        let elapsed = chrono::Utc::now()
-                            .signed_duration_since(escrow.created_at)
-                            .num_seconds();
-                        if elapsed < required_seconds {
-                            return false;
-                        }
-                    }
-                },
-                "service_completion" => {
-                    // Check if linked service/task has been completed
-                    // This would typically involve checking external state
-                    // For now, we'll check if the condition is marked as fulfilled
-                    if !condition.fulfilled {
-                        return false;
-                    }
-                },
-                "approval_count" => {
-                    // Check if minimum number of approvals have been received
-                    if let Some(required_approvals) = condition.parameters.get("min_approvals")
-                        .and_then(|v| v.parse::<u32>().ok()) {
-                        let current_approvals = condition.parameters.get("current_approvals")
-                            .and_then(|v| v.parse::<u32>().ok())
-                            .unwrap_or(0);
-                        if current_approvals < required_approvals {
-                            return false;
-                        }
-                    }
-                },
-                "external_verification" => {
-                    // Check if external verification has been completed
-                    if !condition.fulfilled {
-                        return false;
-                    }
-                },
-                _ => {
-                    // Unknown condition type - default to unfulfilled for safety
-                    tracing::warn!(
-                        condition_type = %condition.condition_type,
-                        "Unknown escrow condition type encountered"
-                    );
-                    return false;
-                }
-            }
-        }
-        
-        // All conditions passed
-        true
-    }
-
     /// Check if a user is an arbitrator for this escrow
     fn is_arbitrator(&self, user: &Pubkey, escrow: &EscrowAccount) -> bool {
         // Check if user is in the escrow's arbitrator list
@@ -533,6 +529,117 @@ impl EscrowService {
     }
 }
 
+/// Evaluator for a custom escrow condition type
+pub type ConditionPredicate = Arc<dyn Fn(&EscrowCondition, &EscrowAccount, i64) -> bool + Send + Sync>;
+
+/// Registered custom condition evaluators, by condition type
+#[derive(Default, Clone)]
+struct ConditionPredicates(HashMap<String, ConditionPredicate>);
+
+impl std::fmt::Debug for ConditionPredicates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+/// Outcome of checking an escrow's release conditions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionEvaluation {
+    /// One result per condition, in the escrow's order
+    pub results: Vec<ConditionResult>,
+    /// Whether every condition is satisfied (true when there are none)
+    pub all_satisfied: bool,
+}
+
+impl ConditionEvaluation {
+    /// Conditions that are not yet satisfied
+    pub fn unsatisfied(&self) -> impl Iterator<Item = &ConditionResult> {
+        self.results.iter().filter(|r| !r.satisfied)
+    }
+}
+
+/// Whether a single escrow condition is satisfied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionResult {
+    /// Type of the evaluated condition
+    pub condition_type: String,
+    /// Whether it permits release
+    pub satisfied: bool,
+}
+
+/// Channels whose closure would satisfy an unfulfilled `service_completion` condition
+fn service_channels(escrow: &EscrowAccount) -> Vec<Pubkey> {
+    escrow.conditions
+        .iter()
+        .filter(|condition| condition.condition_type == "service_completion" && !condition.fulfilled)
+        .filter_map(|condition| condition.parameters.get("channel")?.parse().ok())
+        .collect()
+}
+
+/// Evaluate every condition of `escrow`, looking up channel activity with `channel_active`
+fn evaluate_conditions<F>(
+    escrow: &EscrowAccount,
+    now: i64,
+    predicates: &ConditionPredicates,
+    channel_active: F,
+) -> ConditionEvaluation
+where
+    F: Fn(&Pubkey) -> Option<bool>,
+{
+    let results: Vec<_> = escrow.conditions
+        .iter()
+        .map(|condition| ConditionResult {
+            condition_type: condition.condition_type.clone(),
+            satisfied: condition_satisfied(condition, escrow, now, predicates, &channel_active),
+        })
+        .collect();
+    let all_satisfied = results.iter().all(|r| r.satisfied);
+    
+    ConditionEvaluation { results, all_satisfied }
+}
+
+fn condition_satisfied<F>(
+    condition: &EscrowCondition,
+    escrow: &EscrowAccount,
+    now: i64,
+    predicates: &ConditionPredicates,
+    channel_active: &F,
+) -> bool
+where
+    F: Fn(&Pubkey) -> Option<bool>,
+{
+    let parameter = |key: &str| condition.parameters.get(key);
+    
+    match condition.condition_type.as_str() {
+        "time_elapsed" => parameter("seconds")
+            .and_then(|v| v.parse::<i64>().ok())
+            .is_some_and(|seconds| now.saturating_sub(escrow.deposited_at) >= seconds),
+        "service_completion" => {
+            // Closing the service channel marks the work as delivered
+            condition.fulfilled || parameter("channel")
+                .and_then(|v| v.parse::<Pubkey>().ok())
+                .and_then(|channel| channel_active(&channel))
+                .is_some_and(|active| !active)
+        }
+        "approval_count" => {
+            let count = |key: &str| parameter(key).and_then(|v| v.parse::<u32>().ok());
+            match count("min_approvals") {
+                Some(required) => count("current_approvals").unwrap_or(0) >= required,
+                None => true,
+            }
+        }
+        "external_verification" => condition.fulfilled,
+        other => match predicates.0.get(other) {
+            Some(predicate) => predicate(condition, escrow, now),
+            None => {
+                // Unknown condition type - default to unfulfilled for safety
+                tracing::warn!(condition_type = %other, "Unknown escrow condition type encountered");
+                false
+            }
+        },
+    }
+}
+
 /// Escrow statistics
 #[derive(Debug, Clone)]
 pub struct EscrowStats {
@@ -646,9 +753,12 @@ impl BaseService for EscrowService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -659,6 +769,10 @@ impl BaseService for EscrowService {
     fn service_name(&self) -> &'static str {
         "escrow"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
 }
 
 #[cfg(test)]
@@ -673,4 +787,72 @@ mod tests {
         assert_eq!(service.service_name(), "escrow");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    fn active_escrow(payer: Pubkey, arbitrators: Option<Vec<Pubkey>>) -> EscrowAccount {
+        EscrowAccount {
+            channel: Pubkey::new_unique(),
+            depositor: payer,
+            payer,
+            beneficiary: Pubkey::new_unique(),
+            amount: 1_000_000,
+            status: EscrowStatus::Active,
+            deposited_at: 0,
+            created_at: 0,
+            timeout_at: None,
+            disputed_at: None,
+            conditions: Vec::new(),
+            arbitrators,
+            bump: 255,
+            milestones: Vec::new(),
+        }
+    }
+
+    fn condition(condition_type: &str, parameters: &[(&str, String)], fulfilled: bool) -> EscrowCondition {
+        EscrowCondition {
+            condition_type: condition_type.to_string(),
+            parameters: parameters.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            fulfilled,
+        }
+    }
+
+    #[test]
+    fn test_time_condition_before_and_after_expiry() {
+        let mut escrow = active_escrow(Pubkey::new_unique(), None);
+        escrow.deposited_at = 1_700_000_000;
+        escrow.conditions.push(condition("time_elapsed", &[("seconds", "3600".to_string())], false));
+        let evaluate = |now| evaluate_conditions(&escrow, now, &ConditionPredicates::default(), |_| None);
+        
+        let before = evaluate(escrow.deposited_at + 3599);
+        assert!(!before.all_satisfied);
+        assert_eq!(before.unsatisfied().count(), 1);
+        
+        let after = evaluate(escrow.deposited_at + 3600);
+        assert!(after.all_satisfied);
+        assert_eq!(after.results, vec![ConditionResult { condition_type: "time_elapsed".to_string(), satisfied: true }]);
+    }
+
+    #[test]
+    fn test_mixed_conditions_report_each_result() {
+        let (open_channel, closed_channel) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut escrow = active_escrow(Pubkey::new_unique(), None);
+        escrow.conditions = vec![
+            condition("service_completion", &[("channel", closed_channel.to_string())], false),
+            condition("service_completion", &[("channel", open_channel.to_string())], false),
+            condition("approval_count", &[("min_approvals", "2".to_string()), ("current_approvals", "2".to_string())], false),
+            condition("external_verification", &[], false),
+            condition("kyc_passed", &[], false),
+            condition("unregistered", &[], true),
+        ];
+        let mut predicates = ConditionPredicates::default();
+        predicates.0.insert("kyc_passed".to_string(), Arc::new(|_: &EscrowCondition, _: &EscrowAccount, _| true));
+        
+        let evaluation = evaluate_conditions(&escrow, 0, &predicates, |channel| Some(*channel == open_channel));
+        let satisfied: Vec<_> = evaluation.results.iter().map(|r| r.satisfied).collect();
+        assert_eq!(satisfied, vec![true, false, true, false, true, false]);
+        assert!(!evaluation.all_satisfied);
+        
+        // Escrows without conditions may always be released
+        escrow.conditions.clear();
+        assert!(evaluate_conditions(&escrow, 0, &predicates, |_| None).all_satisfied);
+    }
+}
@@ -21,10 +21,11 @@ use pod_sdk_types::{
 };
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    error::{IpfsError, PodComError, Result, ValidationError},
+    services::base::{BaseService, Outcome, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
     utils::{
-        ipfs::{IPFSClient, ContentHash, PinStatus},
+        ipfs::{validate_cid, IPFSClient, ContentHash, PinStatus},
+        compression::{compress_with_algorithm, decompress_with_limit, CompressionAlgorithm, CompressionLevel},
         encryption::{encrypt_content, decrypt_content},
     },
 };
@@ -35,18 +36,46 @@ pub struct IPFSService {
     base: ServiceBase,
     ipfs_client: Arc<IPFSClient>,
     pin_cache: Arc<tokio::sync::RwLock<PinCache>>,
+    /// How [`upload`](Self::upload) compresses large payloads, if at all
+    upload_compression: Option<UploadCompression>,
+    /// Largest payload [`download`](Self::download) will restore
+    max_file_size: usize,
 }
 
 impl IPFSService {
     /// Create a new IPFS service
-    pub fn new(config: ServiceConfig) -> Self {
-        let ipfs_client = Arc::new(IPFSClient::new(config.ipfs_endpoint.clone()));
+    ///
+    /// Fails if the HTTP client for the configured proxy cannot be built.
+    pub fn new(config: ServiceConfig) -> Result<Self> {
+        let endpoint = config.ipfs_endpoint.clone().unwrap_or_default();
+        let timeout = config.ipfs_timeout.unwrap_or(DEFAULT_IPFS_TIMEOUT);
+        let ipfs_client = Arc::new(match config.ipfs_proxy.clone() {
+            Some(proxy) => IPFSClient::with_proxy(endpoint, proxy, timeout)?,
+            None => IPFSClient::with_timeout(endpoint, timeout),
+        });
         
-        Self {
-            base: ServiceBase::new(config),
+        let upload_compression = UploadCompression::from_config(&config);
+        let max_file_size = config.ipfs_max_file_size.unwrap_or(MAX_IPFS_CONTENT_SIZE);
+        
+        Ok(Self {
+            base: ServiceBase::new(config).with_service_name("ipfs"),
             ipfs_client,
             pin_cache: Arc::new(tokio::sync::RwLock::new(PinCache::new())),
-        }
+            upload_compression,
+            max_file_size,
+        })
+    }
+
+    /// Compress [`upload`](Self::upload)s larger than `threshold` bytes
+    /// with `algorithm`, overriding the service configuration
+    pub fn with_upload_compression(
+        mut self,
+        algorithm: CompressionAlgorithm,
+        level: CompressionLevel,
+        threshold: usize,
+    ) -> Self {
+        self.upload_compression = Some(UploadCompression { algorithm, level, threshold });
+        self
     }
 
     /// Upload content to IPFS
@@ -55,27 +84,28 @@ impl IPFSService {
         uploader: &Keypair,
         content: Vec<u8>,
         params: UploadToIPFSParams,
-    ) -> Result<(ContentHash, IPFSMetadataAccount)> {
+    ) -> Result<Outcome<(ContentHash, IPFSMetadataAccount)>> {
         let operation_name = "upload_content";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             let program = self.base.program()?;
             
             // Validate content size
-            if content.len() > MAX_IPFS_CONTENT_SIZE {
+            if content.len() > self.max_file_size {
                 return Err(PodComError::ContentTooLarge {
                     size: content.len(),
-                    max_size: MAX_IPFS_CONTENT_SIZE,
+                    max_size: self.max_file_size,
                 });
             }
             
             // Encrypt content if requested
             let final_content = if params.encrypt {
                 let encryption_key = params.encryption_key
+                    .as_ref()
                     .ok_or(PodComError::MissingEncryptionKey)?;
-                encrypt_content(&content, &encryption_key)?
+                encrypt_content(&content, encryption_key)?
             } else {
-                content
+                content.clone()
             };
             
             // Upload to IPFS
@@ -92,7 +122,7 @@ impl IPFSService {
                 .take(32)
                 .map(char::from)
                 .collect();
-            let (metadata_pda, _bump) = derive_ipfs_metadata_pda(&uploader.pubkey(), &metadata_id)?;
+            let (metadata_pda, _bump) = derive_ipfs_metadata_pda(&uploader.pubkey(), &metadata_id, &self.base.config().effective_program_id())?;
             
             // Build instruction - Note: pod-com doesn't have IPFS functionality, this would need custom implementation
             // For now, we'll return an error indicating this feature needs implementation
@@ -101,7 +131,10 @@ impl IPFSService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             // Fetch created metadata account
             let metadata_account = self.get_ipfs_metadata(&metadata_pda).await?;
@@ -123,7 +156,7 @@ impl IPFSService {
                 "Content uploaded to IPFS successfully"
             );
 
-            Ok((content_hash, metadata_account))
+            Ok(Outcome::Sent((content_hash, metadata_account)))
         }).await
     }
 
@@ -136,7 +169,7 @@ impl IPFSService {
     ) -> Result<Vec<u8>> {
         let operation_name = "retrieve_content";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Check if content exists and requester has access
             if let Some(metadata) = self.find_metadata_by_hash(content_hash).await? {
                 self.verify_access_permissions(&metadata, &requester.pubkey())?;
@@ -146,8 +179,8 @@ impl IPFSService {
             let content = self.ipfs_client.get_content(content_hash).await?;
             
             // Decrypt if necessary
-            let final_content = if let Some(key) = decryption_key {
-                decrypt_content(&content, &key)?
+            let final_content = if let Some(key) = &decryption_key {
+                decrypt_content(&content, key)?
             } else {
                 content
             };
@@ -172,7 +205,7 @@ impl IPFSService {
     ) -> Result<()> {
         let operation_name = "pin_content";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Verify pinner has access to the content
             if let Some(metadata) = self.find_metadata_by_hash(content_hash).await? {
                 self.verify_access_permissions(&metadata, &pinner.pubkey())?;
@@ -214,7 +247,7 @@ impl IPFSService {
     ) -> Result<()> {
         let operation_name = "unpin_content";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Verify unpinner has permission to unpin
             {
                 let cache = self.pin_cache.read().await;
@@ -254,14 +287,162 @@ impl IPFSService {
         }).await
     }
 
+    /// Pin `cid` on the configured IPFS node
+    /// 
+    /// Calls the node's `/api/v0/pin/add` directly, without checking access
+    /// or touching on-chain metadata; see [`pin_content`](Self::pin_content)
+    /// for the access-controlled variant.
+    pub async fn pin(&self, cid: &str) -> Result<()> {
+        validate_cid(cid).map_err(|_| IpfsError::InvalidHash { hash: cid.to_string() })?;
+        
+        self.base.execute_operation("pin", || self.ipfs_client.pin_add(cid)).await
+    }
+
+    /// Unpin `cid` from the configured IPFS node with `/api/v0/pin/rm`
+    pub async fn unpin(&self, cid: &str) -> Result<()> {
+        validate_cid(cid).map_err(|_| IpfsError::InvalidHash { hash: cid.to_string() })?;
+        
+        self.base.execute_operation("unpin", || self.ipfs_client.pin_rm(cid)).await
+    }
+
+    /// Add `data` to the configured IPFS node and return its CID
+    /// 
+    /// Payloads larger than the compression threshold are compressed with
+    /// the configured algorithm. Every upload is prefixed with a header
+    /// recording the algorithm and original size, which
+    /// [`download`](Self::download) uses to restore the exact bytes.
+    pub async fn upload(&self, data: &[u8]) -> Result<ContentHash> {
+        self.base.execute_operation("upload", || async {
+            let framed = frame_upload(data, self.upload_compression.as_ref())?;
+            if framed.len() > self.max_file_size {
+                return Err(PodComError::ContentTooLarge {
+                    size: framed.len(),
+                    max_size: self.max_file_size,
+                });
+            }
+            
+            let cid = self.ipfs_client.add_content(&framed).await?;
+            
+            tracing::debug!(
+                cid = %cid,
+                size = data.len(),
+                stored_size = framed.len(),
+                "Content uploaded to IPFS"
+            );
+            
+            Ok(cid)
+        }).await
+    }
+
+    /// Fetch content stored with [`upload`](Self::upload), decompressing it if needed
+    /// 
+    /// Content without an upload header, such as that added by
+    /// [`upload_content`](Self::upload_content) or another SDK, is returned
+    /// as stored. Fails with [`IpfsError::DownloadFailed`] if the header
+    /// records more than the configured maximum file size, or the content
+    /// does not decompress to the recorded size.
+    pub async fn download(&self, cid: &str) -> Result<Vec<u8>> {
+        validate_cid(cid).map_err(|_| IpfsError::InvalidHash { hash: cid.to_string() })?;
+        
+        self.base.execute_operation("download", || async {
+            let framed = self.ipfs_client.get_content(cid).await?;
+            unframe_download(&framed, self.max_file_size)
+        }).await
+    }
+
+    /// Upload `data` as a series of chunks and a manifest listing them
+    /// 
+    /// Each chunk of up to `chunk_size` bytes is added on its own, so payloads
+    /// too large for a single upload can still be shared. The manifest records
+    /// the chunk CIDs in order and the total length; it is added last and its
+    /// CID is what [`download_chunked`](Self::download_chunked) takes.
+    pub async fn upload_chunked(&self, data: &[u8], chunk_size: usize) -> Result<IpfsManifest> {
+        if chunk_size == 0 || chunk_size > self.max_file_size {
+            return Err(ValidationError::OutOfRange {
+                field: "chunk_size".to_string(),
+                value: chunk_size.to_string(),
+                min: "1".to_string(),
+                max: self.max_file_size.to_string(),
+            }.into());
+        }
+        
+        self.base.execute_operation("upload_chunked", || async {
+            let mut chunks = Vec::with_capacity(data.len().div_ceil(chunk_size));
+            for chunk in data.chunks(chunk_size) {
+                chunks.push(self.ipfs_client.add_content(chunk).await?);
+            }
+            
+            let mut manifest = IpfsManifest {
+                cid: ContentHash::new(),
+                chunks,
+                total_length: data.len() as u64,
+            };
+            manifest.cid = self.ipfs_client.add_content(&serde_json::to_vec(&manifest)?).await?;
+            
+            tracing::info!(
+                manifest_cid = %manifest.cid,
+                chunks = manifest.chunks.len(),
+                size = data.len(),
+                "Chunked content uploaded to IPFS"
+            );
+            
+            Ok(manifest)
+        }).await
+    }
+
+    /// Download and reassemble content uploaded with [`upload_chunked`](Self::upload_chunked)
+    /// 
+    /// Fails with [`IpfsError::DownloadFailed`] if the manifest cannot be
+    /// parsed or the reassembled chunks do not add up to its total length.
+    pub async fn download_chunked(&self, manifest_cid: &str) -> Result<Vec<u8>> {
+        validate_cid(manifest_cid).map_err(|_| IpfsError::InvalidHash { hash: manifest_cid.to_string() })?;
+        
+        self.base.execute_operation("download_chunked", || async {
+            let manifest_bytes = self.ipfs_client.get_content_bounded(manifest_cid, MAX_IPFS_MANIFEST_SIZE).await?;
+            let manifest: IpfsManifest = serde_json::from_slice(&manifest_bytes).map_err(|e| IpfsError::DownloadFailed {
+                reason: format!("invalid manifest {}: {}", manifest_cid, e),
+            })?;
+            if manifest.total_length > self.max_file_size as u64 {
+                return Err(IpfsError::DownloadFailed {
+                    reason: format!(
+                        "manifest {} lists {} bytes, more than the {} allowed",
+                        manifest_cid,
+                        manifest.total_length,
+                        self.max_file_size
+                    ),
+                }.into());
+            }
+            
+            // Each chunk may only fill what the manifest has left, so the
+            // running total never passes the listed length
+            let total_length = manifest.total_length as usize;
+            let mut data = Vec::with_capacity(total_length);
+            for cid in &manifest.chunks {
+                let remaining = total_length - data.len();
+                data.extend(self.ipfs_client.get_content_bounded(cid, remaining).await?);
+            }
+            
+            if data.len() != total_length {
+                return Err(IpfsError::DownloadFailed {
+                    reason: format!(
+                        "manifest {} lists {} bytes but its chunks hold {}",
+                        manifest_cid,
+                        manifest.total_length,
+                        data.len()
+                    ),
+                }.into());
+            }
+            
+            Ok(data)
+        }).await
+    }
+
     /// Get IPFS metadata account
     pub async fn get_ipfs_metadata(&self, metadata_address: &Pubkey) -> Result<IPFSMetadataAccount> {
         let operation_name = "get_ipfs_metadata";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<IPFSMetadataAccount>(*metadata_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let account_data = self.base.fetch_account::<IPFSMetadataAccount>(metadata_address).await?;
             self.validate_ipfs_metadata(&account_data)?;
             
             Ok(account_data)
@@ -272,15 +453,9 @@ impl IPFSService {
     pub async fn list_content_by_uploader(&self, uploader: &Pubkey) -> Result<Vec<(Pubkey, IPFSMetadataAccount)>> {
         let operation_name = "list_content_by_uploader";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all IPFS metadata accounts
-            let accounts = program
-                .accounts::<IPFSMetadataAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<IPFSMetadataAccount>().await?;
                 
             let mut uploader_content = Vec::new();
             
@@ -302,7 +477,7 @@ impl IPFSService {
     pub async fn get_content_stats(&self, content_hash: &ContentHash) -> Result<ContentStats> {
         let operation_name = "get_content_stats";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Get IPFS stats
             let ipfs_stats = self.ipfs_client.get_content_stats(content_hash).await?;
             
@@ -329,7 +504,7 @@ impl IPFSService {
     pub async fn get_node_stats(&self) -> Result<IPFSNodeStats> {
         let operation_name = "get_node_stats";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let node_stats = self.ipfs_client.get_node_stats().await?;
             
             let stats = IPFSNodeStats {
@@ -352,7 +527,7 @@ impl IPFSService {
     pub async fn garbage_collect(&self) -> Result<GarbageCollectionResult> {
         let operation_name = "garbage_collect";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let gc_result = self.ipfs_client.garbage_collect().await?;
             
             let result = GarbageCollectionResult {
@@ -377,8 +552,7 @@ impl IPFSService {
     // Helper methods
 
     async fn find_metadata_by_hash(&self, content_hash: &ContentHash) -> Result<Option<IPFSMetadataAccount>> {
-        let program = self.base.program()?;
-        let accounts = program.accounts::<IPFSMetadataAccount>(vec![]).await?;
+        let accounts = self.base.fetch_program_accounts::<IPFSMetadataAccount>().await?;
         
         for (_, account) in accounts {
             if account.content_hash == *content_hash {
@@ -390,8 +564,7 @@ impl IPFSService {
     }
 
     async fn find_metadata_address_by_hash(&self, content_hash: &ContentHash) -> Result<Option<Pubkey>> {
-        let program = self.base.program()?;
-        let accounts = program.accounts::<IPFSMetadataAccount>(vec![]).await?;
+        let accounts = self.base.fetch_program_accounts::<IPFSMetadataAccount>().await?;
         
         for (address, account) in accounts {
             if account.content_hash == *content_hash {
@@ -441,6 +614,121 @@ impl IPFSService {
     }
 }
 
+/// Compression applied by [`IPFSService::upload`]
+#[derive(Debug, Clone, Copy)]
+struct UploadCompression {
+    algorithm: CompressionAlgorithm,
+    level: CompressionLevel,
+    /// Payloads up to this many bytes are stored uncompressed
+    threshold: usize,
+}
+
+impl UploadCompression {
+    /// Settings from the service configuration, if compression is enabled
+    fn from_config(config: &ServiceConfig) -> Option<Self> {
+        let compression = config.compression_config.as_ref().filter(|c| c.enabled)?;
+        let algorithm = match compression.algorithm.as_str() {
+            "gzip" => CompressionAlgorithm::Gzip,
+            "zstd" => CompressionAlgorithm::Zstd,
+            _ => return None,
+        };
+        let threshold = config.message_config
+            .as_ref()
+            .map_or(DEFAULT_UPLOAD_COMPRESSION_THRESHOLD, |m| m.compression_threshold as usize);
+        
+        Some(Self {
+            algorithm,
+            level: CompressionLevel::Exact(compression.level),
+            threshold,
+        })
+    }
+}
+
+/// Prefix `data` with an upload header, compressing it if it exceeds the threshold
+fn frame_upload(data: &[u8], compression: Option<&UploadCompression>) -> Result<Vec<u8>> {
+    let (tag, body) = match compression {
+        Some(c) if data.len() > c.threshold => {
+            let tag = algorithm_tag(c.algorithm).ok_or_else(|| IpfsError::UploadFailed {
+                reason: format!("{:?} compression is not supported for uploads", c.algorithm),
+            })?;
+            (tag, compress_with_algorithm(data, c.algorithm, c.level)?)
+        }
+        _ => (UPLOAD_UNCOMPRESSED, data.to_vec()),
+    };
+    
+    let mut framed = Vec::with_capacity(UPLOAD_HEADER_LEN + body.len());
+    framed.extend_from_slice(UPLOAD_MAGIC);
+    framed.push(tag);
+    framed.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    framed.extend_from_slice(&body);
+    Ok(framed)
+}
+
+/// Strip the upload header from `framed` and restore the original bytes
+/// 
+/// Content without a header is returned unchanged. Decompressed output is
+/// capped at `max_size` bytes.
+fn unframe_download(framed: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    let download_failed = |reason: String| -> PodComError { IpfsError::DownloadFailed { reason }.into() };
+    
+    if framed.len() < UPLOAD_HEADER_LEN || !framed.starts_with(UPLOAD_MAGIC) {
+        return Ok(framed.to_vec());
+    }
+    let tag = framed[UPLOAD_MAGIC.len()];
+    let size_bytes: [u8; 8] = framed[UPLOAD_MAGIC.len() + 1..UPLOAD_HEADER_LEN]
+        .try_into()
+        .expect("header holds an 8-byte size");
+    let original_size = u64::from_le_bytes(size_bytes);
+    if original_size > max_size as u64 {
+        return Err(download_failed(format!(
+            "header records {} bytes, over the {}-byte limit",
+            original_size,
+            max_size
+        )));
+    }
+    let body = &framed[UPLOAD_HEADER_LEN..];
+    
+    let data = match tag {
+        UPLOAD_UNCOMPRESSED => body.to_vec(),
+        tag => {
+            let algorithm = tag_algorithm(tag)
+                .ok_or_else(|| download_failed(format!("unknown compression tag {}", tag)))?;
+            // Never inflate past the recorded size, whatever the body claims
+            decompress_with_limit(body, algorithm, original_size as usize)
+                .map_err(|e| download_failed(e.to_string()))?
+        }
+    };
+    
+    if data.len() as u64 != original_size {
+        return Err(download_failed(format!(
+            "header records {} bytes but content holds {}",
+            original_size,
+            data.len()
+        )));
+    }
+    
+    Ok(data)
+}
+
+/// Header tag for `algorithm`, or `None` if uploads cannot use it
+/// 
+/// LZ4 is not supported: the compression utilities have no LZ4 encoder.
+fn algorithm_tag(algorithm: CompressionAlgorithm) -> Option<u8> {
+    match algorithm {
+        CompressionAlgorithm::Gzip => Some(1),
+        CompressionAlgorithm::Zstd => Some(2),
+        CompressionAlgorithm::Lz4 => None,
+    }
+}
+
+fn tag_algorithm(tag: u8) -> Option<CompressionAlgorithm> {
+    match tag {
+        1 => Some(CompressionAlgorithm::Gzip),
+        2 => Some(CompressionAlgorithm::Zstd),
+        _ => None,
+    }
+}
+
 // Data structures
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -456,6 +744,18 @@ pub struct ContentStats {
     pub is_encrypted: bool,
 }
 
+/// Manifest of content uploaded in chunks by [`IPFSService::upload_chunked`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpfsManifest {
+    /// CID of the manifest itself
+    #[serde(skip)]
+    pub cid: ContentHash,
+    /// Chunk CIDs, in content order
+    pub chunks: Vec<ContentHash>,
+    /// Length of the reassembled content in bytes
+    pub total_length: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct IPFSNodeStats {
     pub node_id: String,
@@ -553,7 +853,7 @@ impl BaseService for IPFSService {
 
     fn validate_config(&self) -> Result<(), Self::Error> {
         // Validate IPFS service specific configuration
-        if self.base.config().ipfs_endpoint.is_empty() {
+        if self.base.config().ipfs_endpoint.as_deref().map_or(true, str::is_empty) {
             return Err(PodComError::MissingConfiguration {
                 field: "ipfs_endpoint".to_string(),
             });
@@ -577,9 +877,13 @@ impl BaseService for IPFSService {
         }
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        // Content lives on the IPFS node, so that is the endpoint worth probing
+        self.base.probe_health_with(|| self.ipfs_client.ping()).await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -593,10 +897,26 @@ impl BaseService for IPFSService {
     fn service_name(&self) -> &'static str {
         "ipfs"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
 }
 
 // Constants
 const MAX_IPFS_CONTENT_SIZE: usize = 32 * 1024 * 1024; // 32MB
+/// Largest chunk manifest [`IPFSService::download_chunked`] reads
+const MAX_IPFS_MANIFEST_SIZE: usize = 1024 * 1024;
+/// Request timeout used when the service config sets none
+const DEFAULT_IPFS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+/// Upload size above which content is compressed when no message config sets one
+const DEFAULT_UPLOAD_COMPRESSION_THRESHOLD: usize = 1024;
+/// Marks content stored by [`IPFSService::upload`]
+const UPLOAD_MAGIC: &[u8; 4] = b"PODC";
+/// Magic, compression tag and little-endian original size
+const UPLOAD_HEADER_LEN: usize = UPLOAD_MAGIC.len() + 1 + 8;
+/// Compression tag of content stored as-is
+const UPLOAD_UNCOMPRESSED: u8 = 0;
 
 #[cfg(test)]
 mod tests {
@@ -606,8 +926,298 @@ mod tests {
     #[tokio::test]
     async fn test_ipfs_service_creation() {
         let config = test_config();
-        let service = IPFSService::new(config);
+        let service = IPFSService::new(config).unwrap();
         assert_eq!(service.service_name(), "ipfs");
         // Note: Health check may be NotInitialized or Degraded depending on IPFS connection
     }
-} 
\ No newline at end of file
+
+    const CID_V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+    const CID_V1: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+    fn service_for(endpoint: String) -> IPFSService {
+        let mut config = test_config();
+        config.ipfs_endpoint = Some(endpoint);
+        IPFSService::new(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pin_and_unpin_call_node_api() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let node = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v0/pin/add"))
+            .and(query_param("arg", CID_V0))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "Pins": [CID_V0] })))
+            .expect(1)
+            .mount(&node)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v0/pin/rm"))
+            .and(query_param("arg", CID_V1))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "Message": "not pinned or pinned indirectly",
+                "Code": 0,
+                "Type": "error",
+            })))
+            .expect(1)
+            .mount(&node)
+            .await;
+        
+        let service = service_for(node.uri());
+        service.pin(CID_V0).await.unwrap();
+        
+        match service.unpin(CID_V1).await {
+            Err(PodComError::Ipfs(IpfsError::ApiError { command, status, message })) => {
+                assert_eq!(command, "pin/rm");
+                assert_eq!(status, 500);
+                assert_eq!(message, "not pinned or pinned indirectly");
+            }
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+    }
+
+    /// In-memory IPFS node answering `add` and `cat`
+    #[derive(Clone, Default)]
+    struct FakeNode {
+        blocks: Arc<std::sync::Mutex<HashMap<String, Vec<u8>>>>,
+    }
+
+    impl wiremock::Respond for FakeNode {
+        fn respond(&self, request: &wiremock::Request) -> wiremock::ResponseTemplate {
+            use wiremock::ResponseTemplate;
+            
+            let mut blocks = self.blocks.lock().unwrap();
+            match request.url.path() {
+                "/api/v0/add" => {
+                    let content = multipart_file(&request.body);
+                    let cid = format!("Qm{}", &blake3::hash(&content).to_hex()[..44]);
+                    let size = content.len().to_string();
+                    blocks.insert(cid.clone(), content);
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({ "Name": "data", "Hash": cid, "Size": size }))
+                }
+                "/api/v0/cat" => {
+                    let cid = request.url.query_pairs()
+                        .find(|(key, _)| key == "arg")
+                        .map(|(_, value)| value.into_owned())
+                        .unwrap_or_default();
+                    match blocks.get(&cid) {
+                        Some(content) => ResponseTemplate::new(200).set_body_bytes(content.clone()),
+                        None => ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                            "Message": "block not found",
+                            "Code": 0,
+                            "Type": "error",
+                        })),
+                    }
+                }
+                _ => ResponseTemplate::new(404),
+            }
+        }
+    }
+
+    /// Bytes of the single file part in a multipart body
+    fn multipart_file(body: &[u8]) -> Vec<u8> {
+        let start = body.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let end = body.windows(4).rposition(|w| w == b"\r\n--").unwrap();
+        body[start..end].to_vec()
+    }
+
+    async fn fake_node() -> (wiremock::MockServer, FakeNode) {
+        let server = wiremock::MockServer::start().await;
+        let node = FakeNode::default();
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(node.clone())
+            .mount(&server)
+            .await;
+        (server, node)
+    }
+
+    #[tokio::test]
+    async fn test_chunked_round_trip() {
+        let (server, node) = fake_node().await;
+        let service = service_for(server.uri());
+        
+        // Two full chunks and a shorter final one
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let manifest = service.upload_chunked(&data, 4096).await.unwrap();
+        
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.total_length, 10_000);
+        {
+            let blocks = node.blocks.lock().unwrap();
+            let sizes: Vec<usize> = manifest.chunks.iter().map(|cid| blocks[cid].len()).collect();
+            assert_eq!(sizes, vec![4096, 4096, 1808]);
+        }
+        
+        assert_eq!(service.download_chunked(&manifest.cid).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_download_checks_total_length() {
+        let (server, _node) = fake_node().await;
+        let service = service_for(server.uri());
+        
+        let manifest = service.upload_chunked(&[7; 100], 30).await.unwrap();
+        let overstated = IpfsManifest { total_length: 101, ..manifest };
+        let overstated_cid = service.ipfs_client.add_content(&serde_json::to_vec(&overstated).unwrap()).await.unwrap();
+        
+        assert!(matches!(
+            service.download_chunked(&overstated_cid).await,
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_download_bounds_running_total() {
+        let (server, _node) = fake_node().await;
+        let mut config = test_config();
+        config.ipfs_endpoint = Some(server.uri());
+        config.ipfs_max_file_size = Some(64);
+        let service = IPFSService::new(config).unwrap();
+        
+        // Chunks holding more than the manifest lists stop at its length
+        let manifest = service.upload_chunked(&[7; 60], 20).await.unwrap();
+        let understated = IpfsManifest { total_length: 30, ..manifest.clone() };
+        let understated_cid = service.ipfs_client.add_content(&serde_json::to_vec(&understated).unwrap()).await.unwrap();
+        assert!(matches!(
+            service.download_chunked(&understated_cid).await,
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+        
+        // A manifest listing more than the configured maximum is not followed
+        let oversized = IpfsManifest { total_length: 65, ..manifest };
+        let oversized_cid = service.ipfs_client.add_content(&serde_json::to_vec(&oversized).unwrap()).await.unwrap();
+        assert!(matches!(
+            service.download_chunked(&oversized_cid).await,
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upload_below_threshold_is_stored_raw() {
+        let (server, node) = fake_node().await;
+        let service = service_for(server.uri())
+            .with_upload_compression(CompressionAlgorithm::Gzip, CompressionLevel::Balanced, 1024);
+        
+        let data = b"short payload".to_vec();
+        let cid = service.upload(&data).await.unwrap();
+        {
+            let blocks = node.blocks.lock().unwrap();
+            let stored = &blocks[&cid];
+            assert_eq!(stored[UPLOAD_MAGIC.len()], UPLOAD_UNCOMPRESSED);
+            assert_eq!(&stored[UPLOAD_HEADER_LEN..], &data[..]);
+        }
+        
+        assert_eq!(service.download(&cid).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_upload_above_threshold_is_compressed() {
+        let (server, node) = fake_node().await;
+        let service = service_for(server.uri())
+            .with_upload_compression(CompressionAlgorithm::Gzip, CompressionLevel::Balanced, 1024);
+        
+        let data: Vec<u8> = b"compressible ".iter().copied().cycle().take(64 * 1024).collect();
+        let cid = service.upload(&data).await.unwrap();
+        {
+            let blocks = node.blocks.lock().unwrap();
+            let stored = &blocks[&cid];
+            assert_eq!(Some(stored[UPLOAD_MAGIC.len()]), algorithm_tag(CompressionAlgorithm::Gzip));
+            assert!(stored.len() < data.len() / 10);
+        }
+        
+        assert_eq!(service.download(&cid).await.unwrap(), data);
+    }
+
+    #[test]
+    fn test_download_rejects_bad_headers() {
+        let mut framed = frame_upload(b"payload", None).unwrap();
+        // Original size no longer matches the body
+        framed[UPLOAD_MAGIC.len() + 1] += 1;
+        assert!(matches!(
+            unframe_download(&framed, MAX_IPFS_CONTENT_SIZE),
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_headerless_content_is_returned_raw() {
+        assert_eq!(unframe_download(b"not framed", MAX_IPFS_CONTENT_SIZE).unwrap(), b"not framed");
+    }
+
+    #[test]
+    fn test_download_bounds_decompressed_size() {
+        let compression = UploadCompression {
+            algorithm: CompressionAlgorithm::Gzip,
+            level: CompressionLevel::Best,
+            threshold: 0,
+        };
+        let data = vec![0u8; 64 * 1024];
+        let framed = frame_upload(&data, Some(&compression)).unwrap();
+        
+        // Over the configured maximum
+        assert!(matches!(
+            unframe_download(&framed, data.len() - 1),
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+        
+        // Header understates the body, which would inflate past it
+        let mut understated = framed.clone();
+        understated[UPLOAD_MAGIC.len() + 1..UPLOAD_HEADER_LEN].copy_from_slice(&16u64.to_le_bytes());
+        assert!(matches!(
+            unframe_download(&understated, MAX_IPFS_CONTENT_SIZE),
+            Err(PodComError::Ipfs(IpfsError::DownloadFailed { .. }))
+        ));
+        
+        assert_eq!(unframe_download(&framed, data.len()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_lz4_uploads_rejected() {
+        let compression = UploadCompression {
+            algorithm: CompressionAlgorithm::Lz4,
+            level: CompressionLevel::Fast,
+            threshold: 0,
+        };
+        assert!(matches!(
+            frame_upload(b"payload", Some(&compression)),
+            Err(PodComError::Ipfs(IpfsError::UploadFailed { .. }))
+        ));
+        
+        let mut framed = frame_upload(b"payload", None).unwrap();
+        framed[UPLOAD_MAGIC.len()] = 3;
+        assert!(unframe_download(&framed, MAX_IPFS_CONTENT_SIZE).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chunk_size_must_fit_upload_limit() {
+        let service = service_for("http://127.0.0.1:1".to_string());
+        
+        for chunk_size in [0, service.max_file_size + 1] {
+            assert!(matches!(
+                service.upload_chunked(&[1, 2, 3], chunk_size).await,
+                Err(PodComError::Validation(ValidationError::OutOfRange { .. }))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pin_rejects_invalid_cids() {
+        let node = wiremock::MockServer::start().await;
+        let service = service_for(node.uri());
+        
+        let too_short = &CID_V0[..40];
+        let too_long = format!("{}{}", CID_V1, "a");
+        let wrong_prefix = format!("Xm{}", &CID_V0[2..]);
+        for cid in ["", too_short, too_long.as_str(), wrong_prefix.as_str()] {
+            assert!(matches!(
+                service.pin(cid).await,
+                Err(PodComError::Ipfs(IpfsError::InvalidHash { .. }))
+            ), "{:?} should be rejected", cid);
+            assert!(service.unpin(cid).await.is_err());
+        }
+        
+        assert!(node.received_requests().await.unwrap().is_empty());
+    }
+}
@@ -3,32 +3,60 @@
 //! Service for managing messages on the PoD Protocol.
 //! Provides functionality for sending, receiving, querying, and managing encrypted messages.
 
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anchor_client::Program;
+use anchor_lang::{InstructionData, ToAccountMetas};
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use lru::LruCache;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcTransactionConfig, UiTransactionEncoding},
+    filter::{Memcmp, RpcFilterType},
+};
 use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::{Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
     pubkey::Pubkey,
+    signature::Signature,
     signer::{keypair::Keypair, Signer},
 };
+use tokio::sync::RwLock;
 
 // Import UUID for message ID generation
 use rand::{distributions::Alphanumeric, Rng};
 
 // Import the actual program types
-use pod_com::{MessageAccount, ChannelAccount, AgentAccount, MessageType, MessageStatus};
+use pod_com::{MessageAccount, ChannelAccount, ChannelMessage, AgentAccount, MessageType, MessageStatus};
+use pod_sdk_types::{pda, SendMessageRequest};
 
 use crate::{
-    error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    error::{MessageError, NetworkError, PodComError, Result, ValidationError},
+    services::{
+        agent::{from_program_message_type, to_program_message_type},
+        base::{BaseService, Outcome, RetryHandler, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    },
     types::{
-        SendMessageParams, MessageContent, FilterOptions,
+        MessageContent, FilterOptions,
         BatchOperationResult, RequestOptions,
     },
     utils::{
         account::{derive_message_pda, validate_message_account},
+        decode::decode_account,
         crypto::{encrypt_message, decrypt_message, compress_message, decompress_message, secure_hash_data},
+        encoding::{
+            decode_stored_payload, encode_inline_message, new_correlation_id, open_versioned,
+            payload_hash, seal_versioned, verify_payload_hash, CorrelationId, InlinePayload,
+            message_version, LEGACY_PROTOCOL_VERSION,
+        },
+        search::MessageSearchIndex,
+        stream::{DedupStream, DEFAULT_DEDUP_WINDOW},
     },
 };
 
@@ -36,30 +64,134 @@ use crate::{
 #[derive(Debug)]
 pub struct MessageService {
     base: ServiceBase,
+    /// Local full-text index over decrypted content, if enabled
+    search_index: Option<Arc<RwLock<MessageSearchIndex>>>,
+    /// Recently sent payloads, if send deduplication is enabled
+    sent_messages: Option<Mutex<SentMessageCache>>,
 }
 
 impl MessageService {
     /// Create a new message service
     pub fn new(config: ServiceConfig) -> Self {
+        let search_index = config.message_config
+            .as_ref()
+            .filter(|c| c.local_search_enabled)
+            .and_then(|c| match MessageSearchIndex::new(c.local_search_capacity) {
+                Ok(index) => Some(Arc::new(RwLock::new(index))),
+                Err(e) => {
+                    tracing::warn!("Local message search disabled: {}", e);
+                    None
+                }
+            });
+        
+        let sent_messages = config.message_config
+            .as_ref()
+            .and_then(|c| c.dedup_window)
+            .filter(|window| !window.is_zero())
+            .map(|window| Mutex::new(SentMessageCache::new(window)));
+        
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("message"),
+            search_index,
+            sent_messages,
         }
     }
 
+    /// Search locally indexed messages for content containing every query term
+    ///
+    /// Only messages previously read through [`Self::get_message_content`] are
+    /// indexed. Requires `local_search_enabled` in the message configuration.
+    pub async fn search_local(&self, query: &str) -> Result<Vec<Pubkey>> {
+        let index = self.search_index.as_ref().ok_or_else(|| PodComError::ServiceUnavailable {
+            service: "local_message_search".to_string(),
+        })?;
+        
+        Ok(index.read().await.search(query))
+    }
+
+    /// Export the local search index encrypted under `storage_key`
+    pub async fn export_search_index(&self, storage_key: &[u8]) -> Result<Vec<u8>> {
+        let index = self.search_index.as_ref().ok_or_else(|| PodComError::ServiceUnavailable {
+            service: "local_message_search".to_string(),
+        })?;
+        
+        index.read().await.to_encrypted_bytes(storage_key)
+    }
+
+    /// Wrap a subscription stream so each message address is delivered once
+    ///
+    /// The window of remembered addresses comes from `subscription_dedup_window`
+    /// in the message configuration; each call gets its own window.
+    pub fn dedup_subscription<S, T>(&self, stream: S) -> DedupStream<S>
+    where
+        S: futures::Stream<Item = (Pubkey, T)>,
+    {
+        let window = self.base.config().message_config
+            .as_ref()
+            .map(|c| c.subscription_dedup_window)
+            .and_then(NonZeroUsize::new)
+            .unwrap_or(NonZeroUsize::new(DEFAULT_DEDUP_WINDOW).unwrap());
+
+        DedupStream::new(stream, window)
+    }
+
     /// Send a message to a channel
+    /// 
+    /// With a `dedup_window` configured, resending a payload this sender
+    /// already sent to the channel within the window returns the earlier
+    /// message instead of creating a duplicate.
     pub async fn send_message(
         &self,
         sender: &Keypair,
         channel_address: &Pubkey,
-        params: SendMessageParams,
-    ) -> Result<(Pubkey, MessageAccount)> {
+        request: SendMessageRequest,
+    ) -> Result<Outcome<(Pubkey, MessageAccount)>> {
+        self.send_message_with(sender, channel_address, request, false).await
+    }
+
+    /// Send a message to a channel, bypassing send deduplication if `force`
+    pub async fn send_message_with(
+        &self,
+        sender: &Keypair,
+        channel_address: &Pubkey,
+        request: SendMessageRequest,
+        force: bool,
+    ) -> Result<Outcome<(Pubkey, MessageAccount)>> {
         let operation_name = "send_message";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
+        self.base.execute_transaction(operation_name, || async {
+            // Reject payloads the program would refuse before paying any fees
+            request.validate()?;
+            let message_type = to_program_message_type(request.message_type);
+            
+            // A retried send of the same payload returns the message it already created
+            let hash = payload_hash(&request.payload);
+            let sent_key = (sender.pubkey(), *channel_address, pda::message_type_seed(&request.message_type), hash);
+            if let Some(sent) = recently_sent(self.sent_messages.as_ref(), &sent_key, force, Instant::now()) {
+                match sent.signature {
+                    Some(signature) => {
+                        tracing::info!(
+                            message_address = %sent.message,
+                            signature = %signature,
+                            "Duplicate send within dedup window; returning earlier message"
+                        );
+                        return Ok(Outcome::Sent((sent.message, self.get_message_account(&sent.message).await?)));
+                    }
+                    // The earlier attempt failed or never reported back, but may still have landed
+                    None => {
+                        if let Ok(account) = self.get_message_account(&sent.message).await {
+                            tracing::info!(
+                                message_address = %sent.message,
+                                "Earlier send landed despite failing; returning its message"
+                            );
+                            return Ok(Outcome::Sent((sent.message, account)));
+                        }
+                    }
+                }
+            }
             
             // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(*channel_address)?;
+            let channel_account = self.base.fetch_account::<ChannelAccount>(channel_address).await?;
             if !channel_account.participants.contains(&sender.pubkey()) {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "channel".to_string(),
@@ -67,37 +199,71 @@ impl MessageService {
                 });
             }
             
+            // Pre-flight: make sure the recipient can execute the command
+            if matches!(message_type, MessageType::Command) {
+                if let Some(required) = request.required_capabilities {
+                    let recipient_agent = self.base.fetch_account::<AgentAccount>(&request.recipient).await?;
+                    check_recipient_capabilities(
+                        message_type,
+                        Some(required),
+                        &request.recipient,
+                        recipient_agent.capabilities,
+                    )?;
+                }
+            }
+            
             // Generate message ID and derive PDA
             let message_id: String = rand::thread_rng()
                 .sample_iter(&Alphanumeric)
                 .take(32)
                 .map(char::from)
                 .collect();
-            let (message_pda, _bump) = derive_message_pda(channel_address, &message_id)?;
+            #[allow(deprecated)]
+            let (message_pda, _bump) = derive_message_pda(channel_address, &message_id, &self.base.config().effective_program_id())?;
+            
+            // Encode the inline payload, recording the encoding in its header
+            let payload_encoding = self.base.config().message_config
+                .as_ref()
+                .map(|c| c.payload_encoding)
+                .unwrap_or_default();
+            let correlation_id = resolve_correlation_id(message_type, request.correlation_id);
+            let inline_payload = encode_inline_message(
+                &request.payload,
+                payload_encoding,
+                correlation_id.as_ref(),
+                request.content_type.as_deref(),
+            );
             
             // Encrypt message content
-            let encrypted_content = encrypt_message(&params.content, &channel_account.encryption_key)?;
+            let encrypted_content = encrypt_message(&inline_payload, &channel_account.encryption_key)?;
             
             // Compress if needed (for large messages)
-            let final_content = if encrypted_content.len() > MAX_UNCOMPRESSED_MESSAGE_SIZE {
+            let compressed_content = if encrypted_content.len() > MAX_UNCOMPRESSED_MESSAGE_SIZE {
                 compress_message(&encrypted_content)?
             } else {
                 encrypted_content
             };
+            
+            // Record the protocol version in cleartext ahead of the content
+            let version = message_version(request.content_type.as_deref());
+            let final_content = seal_versioned(version, &compressed_content);
 
-            // Calculate expiration timestamp
-            let expiration_timestamp = params.expiration_duration
-                .map(|duration| {
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() + duration.as_secs()
-                })
-                .unwrap_or(0); // 0 means no expiration
+            // Calculate expiration timestamp, falling back to the configured default
+            let created_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let default_expiry = self.base.config().message_config
+                .as_ref()
+                .and_then(|c| c.default_message_expiry);
+            let expiration_timestamp = resolve_expiration(
+                created_at,
+                request.expiry,
+                default_expiry,
+            )?; // 0 means no expiration
 
             // Build instruction
-            let ix = program
-                .request()
+            let ix = self.base.request(|builder| builder
                 .accounts(pod_com::accounts::SendMessage {
                     message_account: message_pda,
                     sender_agent: sender.pubkey(),
@@ -106,13 +272,25 @@ impl MessageService {
                 })
                 .args(pod_com::instruction::SendMessage {
                     recipient: *channel_address,
-                    payload_hash: pod_com::secure_hash_data(&final_content).unwrap_or([0u8; 32]),
-                    message_type: params.message_type,
+                    payload_hash: hash,
+                    message_type: message_type,
                 })
-                .signer(sender);
+                .signer(sender)).await?;
 
+            // Remember the attempt before sending, so a retry after an error
+            // that hid a landed transaction finds this message
+            record_sent(self.sent_messages.as_ref(), sent_key, SentMessage { signature: None, message: message_pda });
+            
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => {
+                    forget_sent(self.sent_messages.as_ref(), &sent_key);
+                    return Ok(Outcome::Simulated(dry_run));
+                }
+            };
+            self.base.invalidate(&message_pda);
+            record_sent(self.sent_messages.as_ref(), sent_key, SentMessage { signature: Some(signature), message: message_pda });
             
             // Fetch created message account
             let message_account = self.get_message_account(&message_pda).await?;
@@ -126,18 +304,182 @@ impl MessageService {
                 "Message sent successfully"
             );
 
-            Ok((message_pda, message_account))
+            Ok(Outcome::Sent((message_pda, message_account)))
         }).await
     }
 
+    /// Send the same payload as a direct message to each of `recipients`
+    /// 
+    /// Messages are sent from the payer's agent. Instead of one transaction
+    /// per recipient, as many message instructions are packed into each
+    /// transaction as its size and compute budget allow. A failed transaction
+    /// only fails the recipients packed into it; the others are still sent.
+    pub async fn send_multicast(
+        &self,
+        recipients: &[Pubkey],
+        payload: Vec<u8>,
+        message_type: MessageType,
+    ) -> Result<MulticastDelivery> {
+        let operation_name = "send_multicast";
+        
+        pod_sdk_types::validate_message(&payload, None)?;
+        let mut seen = HashSet::with_capacity(recipients.len());
+        if let Some(duplicate) = recipients.iter().find(|recipient| !seen.insert(**recipient)) {
+            return Err(ValidationError::InvalidCombination {
+                reason: format!("recipient {} is listed more than once", duplicate),
+            }.into());
+        }
+        
+        self.base.execute_transaction(operation_name, || async {
+            let program = self.base.program()?;
+            let payer = program.payer();
+            let program_id = self.base.config().effective_program_id();
+            let hash = payload_hash(&payload);
+            
+            let instructions: Vec<Instruction> = recipients
+                .iter()
+                .map(|recipient| multicast_instruction(&program_id, &payer, recipient, hash, message_type))
+                .collect();
+            
+            let mut delivery = MulticastDelivery::default();
+            let mut sent = recipients.iter();
+            let reserved = self.base.compute_budget_reserve();
+            for batch in pack_instructions(instructions, &reserved, &payer) {
+                let batch_recipients: Vec<Pubkey> = sent.by_ref().take(batch.len()).copied().collect();
+                
+                // Each transaction succeeds or fails on its own; failures are reported, not retried
+                let sent_batch = match self.base
+                    .request(|builder| batch.iter().cloned().fold(builder, |builder, ix| builder.instruction(ix)))
+                    .await
+                {
+                    Ok(request) => self.base.send(request).await,
+                    Err(e) => Err(e),
+                };
+                match sent_batch {
+                    Ok(outcome) => {
+                        if matches!(outcome, Outcome::Sent(_)) {
+                            // Only the message account each instruction creates is written
+                            batch.iter().for_each(|ix| self.base.invalidate(&ix.accounts[0].pubkey));
+                        }
+                        delivery.outcomes.extend(batch_recipients.iter().map(|_| Some(outcome.clone())));
+                    }
+                    Err(e) => {
+                        delivery.outcomes.extend(batch_recipients.iter().map(|_| None));
+                        for recipient in batch_recipients {
+                            delivery.errors.insert(recipient, e.to_string());
+                        }
+                    }
+                }
+            }
+            
+            tracing::info!(
+                sender = %payer,
+                recipients = recipients.len(),
+                failed = delivery.errors.len(),
+                "Multicast message sent"
+            );
+            
+            Ok(delivery)
+        }).await
+    }
+
+    /// Stream a message's delivery status from the configured WebSocket endpoint
+    /// 
+    /// Subscribes to the message account and yields its status each time it
+    /// changes, starting with the status when the subscription opens. The
+    /// stream completes after a terminal status (`Read`, `Failed` or
+    /// `Expired`). A dropped socket is reopened with the configured retry
+    /// backoff; after `max_retries` consecutive reconnects that fail or drop
+    /// before delivering an update, the stream yields an error and ends. The stream also ends when the service shuts
+    /// down.
+    pub fn watch_message(
+        &self,
+        message_pda: Pubkey,
+    ) -> Result<impl Stream<Item = Result<pod_sdk_types::MessageStatus>>> {
+        let config = self.base.config();
+        let ws_url = config.ws_url.clone().ok_or_else(|| PodComError::InvalidConfiguration {
+            field: "ws_url".to_string(),
+            reason: "A WebSocket URL is required for message subscriptions".to_string(),
+        })?;
+        config.check_subscription("accountSubscribe")?;
+        let rpc_client = config.async_rpc_client.clone();
+        let subscription_config = RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+            commitment: Some(config.commitment),
+            ..Default::default()
+        };
+        let max_retries = config.retry_config.max_retries;
+        let retry = RetryHandler::new(config.retry_config.clone());
+        let shutdown = self.base.cancellation_token();
+        
+        let statuses = async_stream::stream! {
+            let mut failures = 0usize;
+            
+            loop {
+                let subscribed = match PubsubClient::new(&ws_url).await {
+                    Ok(client) => client
+                        .account_subscribe(&message_pda, Some(subscription_config.clone()))
+                        .await
+                        .map(|subscription| (client, subscription)),
+                    Err(e) => Err(e),
+                };
+                let (_client, (mut updates, unsubscribe)) = match subscribed {
+                    Ok(subscribed) => subscribed,
+                    Err(e) => {
+                        tracing::warn!(message = %message_pda, "Message subscription failed: {}", e);
+                        match retry.reconnect_delay(&mut failures, max_retries) {
+                            Some(delay) => {
+                                tokio::time::sleep(delay).await;
+                                continue;
+                            }
+                            None => break,
+                        }
+                    }
+                };
+                
+                // Catch up on any change made before the subscription opened
+                if let Ok(data) = rpc_client.get_account_data(&message_pda).await {
+                    if let Ok(message) = decode_account::<MessageAccount>(&data) {
+                        yield Ok(observed_status(&message, chrono::Utc::now().timestamp()));
+                    }
+                }
+                
+                while let Some(update) = updates.next().await {
+                    // Only a subscription that delivers counts as a successful reconnect
+                    failures = 0;
+                    let Some(account) = update.value.decode::<solana_sdk::account::Account>() else {
+                        continue;
+                    };
+                    match decode_account::<MessageAccount>(&account.data) {
+                        Ok(message) => yield Ok(observed_status(&message, chrono::Utc::now().timestamp())),
+                        Err(e) => tracing::warn!(message = %message_pda, "Skipping undecodable message update: {}", e),
+                    }
+                }
+                
+                drop(updates);
+                unsubscribe().await;
+                tracing::warn!(message = %message_pda, "Message subscription dropped; reconnecting");
+                match retry.reconnect_delay(&mut failures, max_retries) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => break,
+                }
+            }
+            
+            yield Err(NetworkError::ConnectionFailed {
+                endpoint: ws_url.clone(),
+                reason: format!("message subscription gave up after {} failed reconnects", max_retries),
+            }.into());
+        };
+        
+        Ok(status_changes(statuses).take_until(async move { shutdown.cancelled().await }))
+    }
+
     /// Get message account data
     pub async fn get_message_account(&self, message_address: &Pubkey) -> Result<MessageAccount> {
         let operation_name = "get_message_account";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<MessageAccount>(*message_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let account_data = self.base.fetch_account::<MessageAccount>(message_address).await?;
             validate_message_account(&account_data)?;
             
             Ok(account_data)
@@ -152,49 +494,149 @@ impl MessageService {
     ) -> Result<MessageContent> {
         let operation_name = "get_message_content";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let message_account = self.get_message_account(message_address).await?;
+        self.base.execute_operation(operation_name, || async {
+            let decoded = self.read_inline_message(message_address, reader).await?;
             
-            // Verify read access
-            let channel_account = program.account::<ChannelAccount>(message_account.channel)?;
-            if !channel_account.participants.contains(&reader.pubkey()) {
-                return Err(PodComError::UnauthorizedAccess {
-                    resource: "message".to_string(),
-                    action: "read".to_string(),
-                });
+            // Index decrypted content locally if search is enabled
+            if let Some(index) = &self.search_index {
+                index.write().await.insert(*message_address, &decoded.payload);
             }
             
-            // Check if message has expired
-            if message_account.expiration_timestamp > 0 {
-                let current_timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                
-                if current_timestamp > message_account.expiration_timestamp {
-                    return Err(PodComError::MessageExpired {
-                        message_address: *message_address,
-                        expired_at: message_account.expiration_timestamp,
-                    });
-                }
+            Ok(decoded.payload)
+        }).await
+    }
+
+    /// Get and decrypt a message along with its header fields
+    ///
+    /// Unlike [`get_message_content`](Self::get_message_content), this keeps
+    /// the payload's encoding, correlation id and content type tag.
+    pub async fn get_message_payload(
+        &self,
+        message_address: &Pubkey,
+        reader: &Keypair,
+    ) -> Result<InlinePayload> {
+        let operation_name = "get_message_payload";
+        
+        self.base.execute_operation(operation_name, || async {
+            let decoded = self.read_inline_message(message_address, reader).await?;
+            
+            if let Some(index) = &self.search_index {
+                index.write().await.insert(*message_address, &decoded.payload);
             }
             
-            // Decompress if needed
-            let raw_content = if message_account.is_compressed {
-                decompress_message(&message_account.encrypted_content)?
-            } else {
-                message_account.encrypted_content.clone()
-            };
+            Ok(decoded)
+        }).await
+    }
+
+    /// Wait for the `Response` correlated with a previously sent `Command`
+    ///
+    /// Polls the command's channel for new `Response` messages, backing off
+    /// between polls, and returns the first whose correlation id matches the
+    /// command's. Responses carrying a different (or no) correlation id are
+    /// ignored. Each message is decrypted at most once, and the wait is not
+    /// retried as a whole; the reads it makes retry on their own.
+    pub async fn await_response(
+        &self,
+        command_message: &Pubkey,
+        reader: &Keypair,
+        timeout: Duration,
+    ) -> Result<(Pubkey, MessageContent)> {
+        let operation_name = "await_response";
+        
+        self.base.execute_once(operation_name, || async {
+            let command_account = self.get_message_account(command_message).await?;
+            let command = self.read_inline_message(command_message, reader).await?;
+            let correlation_id = command.correlation_id.ok_or_else(|| MessageError::InvalidContent {
+                reason: format!("message {} carries no correlation id", command_message),
+            })?;
             
-            // Decrypt content
-            let decrypted_content = decrypt_message(&raw_content, &channel_account.encryption_key)?;
+            let seen = Mutex::new(HashSet::new());
+            let response = await_correlated(correlation_id, timeout, RESPONSE_POLL_INTERVAL, || async {
+                let messages = self.list_channel_messages(&command_account.channel, reader, None, None).await?;
+                
+                let mut candidates = Vec::new();
+                for (address, account) in messages {
+                    let unseen = seen.lock().unwrap_or_else(|e| e.into_inner()).insert(address);
+                    if !unseen
+                        || !matches!(account.message_type, MessageType::Response)
+                        || account.created_at < command_account.created_at
+                    {
+                        continue;
+                    }
+                    // Responses we cannot read are simply not ours
+                    if let Ok(decoded) = self.read_inline_message(&address, reader).await {
+                        candidates.push((address, decoded));
+                    }
+                }
+                Ok(candidates)
+            }).await?;
             
-            Ok(decrypted_content)
+            response.ok_or_else(|| MessageError::ResponseTimeout {
+                command: *command_message,
+                timeout,
+            }.into())
         }).await
     }
 
+    /// Fetch, decrypt and decode a message's inline payload
+    async fn read_inline_message(
+        &self,
+        message_address: &Pubkey,
+        reader: &Keypair,
+    ) -> Result<InlinePayload> {
+        let message_account = self.get_message_account(message_address).await?;
+        
+        // Verify read access
+        let channel_account = self.base.fetch_account::<ChannelAccount>(&message_account.channel).await?;
+        if !channel_account.participants.contains(&reader.pubkey()) {
+            return Err(PodComError::UnauthorizedAccess {
+                resource: "message".to_string(),
+                action: "read".to_string(),
+            });
+        }
+        
+        // Check if message has expired
+        if message_account.expiration_timestamp > 0 {
+            let current_timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            
+            if current_timestamp > message_account.expiration_timestamp {
+                return Err(PodComError::MessageExpired {
+                    message_address: *message_address,
+                    expired_at: message_account.expiration_timestamp,
+                });
+            }
+        }
+        
+        // Reject incompatible protocol versions before touching the content
+        let (version, stored_content) = open_versioned(&message_account.encrypted_content)?;
+        
+        // Decompress if needed
+        let raw_content = if message_account.is_compressed {
+            decompress_message(stored_content)?
+        } else {
+            stored_content.to_vec()
+        };
+        
+        // Decrypt content and decode the inline payload
+        let decrypted_content = decrypt_message(&raw_content, &channel_account.encryption_key)?;
+        let inline = decode_stored_payload(version, &decrypted_content)?;
+        
+        // Versioned messages commit to the decoded payload; legacy ones hashed
+        // the stored bytes, and some stored a zero hash, so they are not checked
+        if version != LEGACY_PROTOCOL_VERSION
+            && !verify_payload_hash(&inline.payload, &message_account.payload_hash)
+        {
+            return Err(MessageError::InvalidContent {
+                reason: "payload does not match its on-chain hash".to_string(),
+            }.into());
+        }
+        
+        Ok(inline)
+    }
+
     /// List messages in a channel
     pub async fn list_channel_messages(
         &self,
@@ -205,11 +647,9 @@ impl MessageService {
     ) -> Result<Vec<(Pubkey, MessageAccount)>> {
         let operation_name = "list_channel_messages";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(*channel_address)?;
+            let channel_account = self.base.fetch_account::<ChannelAccount>(channel_address).await?;
             if !channel_account.participants.contains(&reader.pubkey()) {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "channel".to_string(),
@@ -218,11 +658,7 @@ impl MessageService {
             }
             
             // Get all message accounts for this channel
-            let accounts = program
-                .accounts::<MessageAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<MessageAccount>().await?;
                 
             let mut channel_messages = Vec::new();
             
@@ -258,16 +694,14 @@ impl MessageService {
         message_address: &Pubkey,
         reactor: &Keypair,
         reaction: String,
-    ) -> Result<MessageAccount> {
+    ) -> Result<Outcome<MessageAccount>> {
         let operation_name = "react_to_message";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_transaction(operation_name, || async {
             let message_account = self.get_message_account(message_address).await?;
             
             // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(message_account.channel)?;
+            let channel_account = self.base.fetch_account::<ChannelAccount>(&message_account.channel).await?;
             if !channel_account.participants.contains(&reactor.pubkey()) {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "message".to_string(),
@@ -282,7 +716,10 @@ impl MessageService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             // Fetch updated message account
             let updated_account = self.get_message_account(message_address).await?;
@@ -294,7 +731,7 @@ impl MessageService {
                 "Message reaction added successfully"
             );
 
-            Ok(updated_account)
+            Ok(Outcome::Sent(updated_account))
         }).await
     }
 
@@ -303,10 +740,10 @@ impl MessageService {
         &self,
         message_address: &Pubkey,
         sender: &Keypair,
-    ) -> Result<()> {
+    ) -> Result<Outcome<()>> {
         let operation_name = "delete_message";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             let program = self.base.program()?;
             
             let message_account = self.get_message_account(message_address).await?;
@@ -326,7 +763,10 @@ impl MessageService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             tracing::info!(
                 message_address = %message_address,
@@ -335,11 +775,17 @@ impl MessageService {
                 "Message deleted successfully"
             );
 
-            Ok(())
+            Ok(Outcome::Sent(()))
         }).await
     }
 
     /// Clean up expired messages for a channel
+    /// 
+    /// Returns the messages cleaned up. pod-com has no instruction that
+    /// closes an expired message, and `update_message_status` rejects
+    /// expired messages, so expired messages are only reported and the
+    /// result is always empty.
+    #[deprecated(since = "2.0.0", note = "pod-com cannot close or expire messages; the result is always empty")]
     pub async fn cleanup_expired_messages(
         &self,
         channel_address: &Pubkey,
@@ -347,11 +793,9 @@ impl MessageService {
     ) -> Result<Vec<Pubkey>> {
         let operation_name = "cleanup_expired_messages";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(*channel_address)?;
+            let channel_account = self.base.fetch_account::<ChannelAccount>(channel_address).await?;
             if !channel_account.participants.contains(&cleaner.pubkey()) {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "channel".to_string(),
@@ -366,31 +810,181 @@ impl MessageService {
             
             // Find expired messages
             let all_messages = self.list_channel_messages(channel_address, cleaner, None, None).await?;
-            let mut cleaned_messages = Vec::new();
+            let expired_count = all_messages
+                .iter()
+                .filter(|(_, message)| {
+                    message.expiration_timestamp > 0 && current_timestamp > message.expiration_timestamp
+                })
+                .count();
             
-            for (message_address, message_account) in all_messages {
-                if message_account.expiration_timestamp > 0 && 
-                   current_timestamp > message_account.expiration_timestamp {
-                    
-                    // Build cleanup instruction - Note: pod-com doesn't have cleanup_expired_message
-                    // For now, we'll skip this message and continue
-                    tracing::info!("Skipping cleanup of expired message - feature not implemented in pod-com program");
-                    continue;
-
-                    // Send transaction
-                    let _signature = ix.send()?;
-                    cleaned_messages.push(message_address);
-                }
+            if expired_count > 0 {
+                tracing::info!(
+                    channel_address = %channel_address,
+                    expired_count,
+                    "Skipping cleanup of expired messages - feature not implemented in pod-com program"
+                );
             }
+
+            Ok(Vec::new())
+        }).await
+    }
+
+    /// Mark `message` as `Read` with a signed proof of delivery
+    ///
+    /// `recipient` signs [`read_receipt_digest`] of the message address. The
+    /// program's account has no room for the signature, so it is recorded in
+    /// a memo instruction in the same transaction as the status transition.
+    /// Returns the receipt signature, not the transaction signature.
+    pub async fn acknowledge_read(&self, message: Pubkey, recipient: &Keypair) -> Result<Outcome<Signature>> {
+        let operation_name = "acknowledge_read";
+        
+        self.base.execute_transaction(operation_name, || async {
+            let program_id = self.base.config().effective_program_id();
+            let recipient_agent = pda::agent_pda(&program_id, &recipient.pubkey()).0;
+            let receipt = recipient.sign_message(&read_receipt_digest(&message));
+            
+            let ix = self.base.request(|builder| builder
+                .accounts(pod_com::accounts::UpdateMessageStatus {
+                    message_account: message,
+                    recipient_agent,
+                    signer: recipient.pubkey(),
+                })
+                .args(pod_com::instruction::UpdateMessageStatus {
+                    new_status: MessageStatus::Read,
+                })
+                .instruction(read_receipt_memo(&recipient.pubkey(), &receipt))
+                .signer(recipient)).await?;
+            
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
+            self.base.invalidate(&message);
             
             tracing::info!(
-                channel_address = %channel_address,
-                cleaned_count = cleaned_messages.len(),
-                cleaner = %cleaner.pubkey(),
-                "Expired messages cleaned up successfully"
+                message_address = %message,
+                signature = %signature,
+                recipient = %recipient.pubkey(),
+                "Message read receipt recorded"
             );
+            
+            Ok(Outcome::Sent(receipt))
+        }).await
+    }
+
+    /// Find the read receipt `recipient` recorded for the message at `message`
+    ///
+    /// The message account has no room for the receipt, so this walks the
+    /// message's successful transactions, newest first, and returns the
+    /// receipt from the first memo signed by `recipient`. Older pages of
+    /// `getSignaturesForAddress` are requested until the history is
+    /// exhausted.
+    pub async fn fetch_read_receipt(&self, message: &Pubkey, recipient: &Pubkey) -> Result<Option<Signature>> {
+        let operation_name = "fetch_read_receipt";
+        
+        self.base.execute_operation(operation_name, || async {
+            let config = self.base.config();
+            let rpc_client = &config.async_rpc_client;
+            let mut before = None;
+            loop {
+                let history = rpc_client
+                    .get_signatures_for_address_with_config(message, GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: None,
+                        commitment: Some(config.commitment),
+                    })
+                    .await
+                    .map_err(|e| -> PodComError {
+                        NetworkError::RpcFailed {
+                            method: "getSignaturesForAddress".to_string(),
+                            reason: e.to_string(),
+                        }.into()
+                    })?;
+                let Some(oldest) = history.last() else {
+                    return Ok(None);
+                };
+                before = Some(oldest.signature.parse::<Signature>().map_err(|e| -> PodComError {
+                    NetworkError::RpcFailed {
+                        method: "getSignaturesForAddress".to_string(),
+                        reason: e.to_string(),
+                    }.into()
+                })?);
+                
+                for entry in history.iter().filter(|entry| entry.err.is_none()) {
+                    let Ok(signature) = entry.signature.parse::<Signature>() else {
+                        continue;
+                    };
+                    let transaction = rpc_client
+                        .get_transaction_with_config(&signature, RpcTransactionConfig {
+                            encoding: Some(UiTransactionEncoding::Base64),
+                            commitment: Some(config.commitment),
+                            max_supported_transaction_version: Some(0),
+                        })
+                        .await
+                        .map_err(|e| -> PodComError {
+                            NetworkError::RpcFailed {
+                                method: "getTransaction".to_string(),
+                                reason: e.to_string(),
+                            }.into()
+                        })?;
+                    let Some(transaction) = transaction.transaction.transaction.decode() else {
+                        continue;
+                    };
+                    if let Some(receipt) = read_receipt_in(&transaction.message, recipient) {
+                        return Ok(Some(receipt));
+                    }
+                }
+            }
+        }).await
+    }
 
-            Ok(cleaned_messages)
+    /// Whether `recipient` has recorded a valid read receipt for `message`
+    ///
+    /// `recipient` is the wallet that acknowledged the message, and its agent
+    /// must be the message's recipient. The message address is re-derived
+    /// from the account and its receipt fetched with
+    /// [`fetch_read_receipt`](Self::fetch_read_receipt), so a receipt for
+    /// another message is rejected. The message account has no room for the
+    /// receipt, which is why this is asynchronous and can fail.
+    pub async fn verify_read_receipt(&self, message: &pod_sdk_types::MessageAccount, recipient: &Pubkey) -> Result<bool> {
+        let program_id = self.base.config().effective_program_id();
+        let address = pda::message_pda(
+            &program_id,
+            &message.sender,
+            &message.recipient,
+            &message.payload_hash,
+            pda::message_type_seed(&message.message_type),
+        ).0;
+        
+        Ok(self.fetch_read_receipt(&address, recipient).await?
+            .is_some_and(|receipt| read_receipt_is_valid(&program_id, &address, message, recipient, &receipt)))
+    }
+
+    /// Reconstruct the reply thread rooted at the channel message `root`
+    ///
+    /// Replies are found with a single scan of the root's channel, selected
+    /// on-chain by a memcmp on the channel, indexed by `reply_to`, and
+    /// attached recursively. A message already placed in the
+    /// thread is never placed again and replies below [`MAX_THREAD_DEPTH`] are
+    /// dropped, so malformed `reply_to` cycles cannot recurse forever.
+    pub async fn build_thread(&self, root: Pubkey) -> Result<MessageThread> {
+        let operation_name = "build_thread";
+        
+        self.base.execute_operation(operation_name, || async {
+            let root_message = self.base.fetch_account::<ChannelMessage>(&root).await?;
+            let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                CHANNEL_MESSAGE_CHANNEL_OFFSET,
+                root_message.channel.as_ref(),
+            ))];
+            let mut messages = Vec::new();
+            
+            self.base.scan_accounts::<ChannelMessage, _>(filters, THREAD_SCAN_PAGE_SIZE, |page| {
+                messages.extend(page.into_iter().filter(|(_, message)| message.reply_to.is_some()));
+                Ok(())
+            }).await?;
+            
+            Ok(assemble_thread(root, root_message, messages, MAX_THREAD_DEPTH))
         }).await
     }
 
@@ -402,7 +996,7 @@ impl MessageService {
     ) -> Result<MessageStats> {
         let operation_name = "get_channel_message_stats";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let messages = self.list_channel_messages(channel_address, reader, None, None).await?;
             
             let total_messages = messages.len() as u64;
@@ -447,6 +1041,380 @@ impl MessageService {
     }
 }
 
+/// Status of a message as observed at `now`
+///
+/// The program never writes an expired status, so a message still `Pending`
+/// or `Delivered` past its expiry is reported as `Expired`.
+fn observed_status(message: &MessageAccount, now: i64) -> pod_sdk_types::MessageStatus {
+    use pod_sdk_types::MessageStatus as Observed;
+    
+    match message.status {
+        MessageStatus::Pending | MessageStatus::Delivered
+            if message.expires_at > 0 && message.expires_at < now => Observed::Expired,
+        MessageStatus::Pending => Observed::Pending,
+        MessageStatus::Delivered => Observed::Delivered,
+        MessageStatus::Read => Observed::Read,
+        MessageStatus::Failed => Observed::Failed,
+    }
+}
+
+/// Reduce observed statuses to changes, ending after a terminal status or an error
+fn status_changes<S>(statuses: S) -> impl Stream<Item = Result<pod_sdk_types::MessageStatus>>
+where
+    S: Stream<Item = Result<pod_sdk_types::MessageStatus>>,
+{
+    use pod_sdk_types::MessageStatus as Observed;
+    
+    async_stream::stream! {
+        let mut last = None;
+        futures::pin_mut!(statuses);
+        
+        while let Some(status) = statuses.next().await {
+            match status {
+                Ok(status) if last == Some(status) => continue,
+                Ok(status) => {
+                    last = Some(status);
+                    yield Ok(status);
+                    if matches!(status, Observed::Read | Observed::Failed | Observed::Expired) {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Direct message instruction from the payer's agent to `recipient`
+fn multicast_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    recipient: &Pubkey,
+    payload_hash: [u8; 32],
+    message_type: MessageType,
+) -> Instruction {
+    let sender_agent = pda::agent_pda(program_id, payer).0;
+    let message_account = pda::message_pda(
+        program_id,
+        &sender_agent,
+        recipient,
+        &payload_hash,
+        pda::message_type_seed(&from_program_message_type(message_type)),
+    ).0;
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: pod_com::accounts::SendMessage {
+            message_account,
+            sender_agent,
+            signer: *payer,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: pod_com::instruction::SendMessage {
+            recipient: *recipient,
+            payload_hash,
+            message_type,
+        }
+        .data(),
+    }
+}
+
+/// Digest a recipient signs to acknowledge reading the message at `message`
+pub fn read_receipt_digest(message: &Pubkey) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(message.as_ref());
+    hasher.update(READ_RECEIPT_DOMAIN);
+    *hasher.finalize().as_bytes()
+}
+
+/// Memo instruction recording `receipt` on-chain, signed by `recipient`
+fn read_receipt_memo(recipient: &Pubkey, receipt: &Signature) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(*recipient, true)],
+        data: receipt.to_string().into_bytes(),
+    }
+}
+
+/// Receipt held by a memo that `recipient` signed in `message`, if any
+fn read_receipt_in(message: &VersionedMessage, recipient: &Pubkey) -> Option<Signature> {
+    let keys = message.static_account_keys();
+    let signers = usize::from(message.header().num_required_signatures);
+    let signed_by_recipient = |index: &u8| {
+        let index = usize::from(*index);
+        index < signers && keys.get(index) == Some(recipient)
+    };
+    
+    message
+        .instructions()
+        .iter()
+        .filter(|ix| keys.get(usize::from(ix.program_id_index)) == Some(&MEMO_PROGRAM_ID))
+        .filter(|ix| ix.accounts.iter().any(signed_by_recipient))
+        .find_map(|ix| std::str::from_utf8(&ix.data).ok()?.parse().ok())
+}
+
+/// Whether `receipt` is `recipient` acknowledging `message`, stored at `address`
+fn read_receipt_is_valid(
+    program_id: &Pubkey,
+    address: &Pubkey,
+    message: &pod_sdk_types::MessageAccount,
+    recipient: &Pubkey,
+    receipt: &Signature,
+) -> bool {
+    if pda::agent_pda(program_id, recipient).0 != message.recipient {
+        return false;
+    }
+    let Ok(receipt) = <[u8; 64]>::try_from(receipt.as_ref()) else {
+        return false;
+    };
+    pod_sdk_crypto::Signature::verify(&recipient.to_bytes(), &read_receipt_digest(address), &receipt)
+}
+
+/// Group instructions, in order, into as few transactions as fit the budget
+///
+/// A transaction takes instructions until the next one would push its
+/// serialized size past [`PACKET_DATA_SIZE`] or its instruction count past
+/// [`MULTICAST_MAX_INSTRUCTIONS`]. The `reserved` instructions, such as the
+/// compute budget, are prepended to every transaction and count toward its
+/// size but not its instruction count. An instruction too large to share a
+/// transaction is placed on its own.
+pub(crate) fn pack_instructions(
+    instructions: Vec<Instruction>,
+    reserved: &[Instruction],
+    payer: &Pubkey,
+) -> Vec<Vec<Instruction>> {
+    let mut batches: Vec<Vec<Instruction>> = Vec::new();
+    let mut current: Vec<Instruction> = Vec::new();
+    
+    for ix in instructions {
+        current.push(ix);
+        if current.len() > 1
+            && (current.len() > MULTICAST_MAX_INSTRUCTIONS
+                || transaction_size(&[reserved, &current].concat(), payer) > PACKET_DATA_SIZE)
+        {
+            let overflow = current.pop().expect("batch holds the instruction just pushed");
+            batches.push(std::mem::replace(&mut current, vec![overflow]));
+        }
+    }
+    
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// Serialized size of a signed transaction carrying `instructions`
+fn transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = message.header.num_required_signatures as usize;
+    // Signatures are prefixed by a compact-u16 count, one byte below 128
+    1 + signatures * 64 + message.serialize().len()
+}
+
+/// Verify a recipient can execute a command message.
+///
+/// The check only applies to `Command` messages that carry a capability hint;
+/// everything else is allowed through.
+fn check_recipient_capabilities(
+    message_type: MessageType,
+    required_capabilities: Option<u64>,
+    recipient: &Pubkey,
+    recipient_capabilities: u64,
+) -> Result<()> {
+    let required = match (message_type, required_capabilities) {
+        (MessageType::Command, Some(required)) => required,
+        _ => return Ok(()),
+    };
+    
+    if recipient_capabilities & required != required {
+        return Err(MessageError::RecipientLacksCapability {
+            recipient: *recipient,
+            required,
+            actual: recipient_capabilities,
+        }.into());
+    }
+    
+    Ok(())
+}
+
+/// Correlation id to attach to an outgoing message.
+///
+/// Commands always carry one, generating a fresh id when none was supplied;
+/// other message types only carry an id the caller set explicitly (e.g. a
+/// `Response` echoing its command's id).
+fn resolve_correlation_id(
+    message_type: MessageType,
+    requested: Option<CorrelationId>,
+) -> Option<CorrelationId> {
+    match (message_type, requested) {
+        (_, Some(id)) => Some(id),
+        (MessageType::Command, None) => Some(new_correlation_id()),
+        _ => None,
+    }
+}
+
+/// Poll for a message carrying `correlation_id` until `timeout` elapses.
+///
+/// Each poll returns the new candidate responses; the first whose correlation
+/// id matches is returned. The wait between polls starts at `poll_interval`
+/// and doubles up to [`MAX_RESPONSE_POLL_INTERVAL`]. `Ok(None)` means the
+/// wait timed out.
+async fn await_correlated<F, Fut>(
+    correlation_id: CorrelationId,
+    timeout: Duration,
+    poll_interval: Duration,
+    mut poll: F,
+) -> Result<Option<(Pubkey, MessageContent)>>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<(Pubkey, InlinePayload)>>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut poll_interval = poll_interval;
+    
+    loop {
+        let matched = poll().await?
+            .into_iter()
+            .find(|(_, decoded)| decoded.correlation_id == Some(correlation_id));
+        if let Some((address, decoded)) = matched {
+            return Ok(Some((address, decoded.payload)));
+        }
+        
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        tokio::time::sleep(poll_interval.min(deadline - now)).await;
+        poll_interval = next_poll_interval(poll_interval);
+    }
+}
+
+/// Wait before the poll after one that waited `poll_interval`
+fn next_poll_interval(poll_interval: Duration) -> Duration {
+    poll_interval.saturating_mul(2).min(MAX_RESPONSE_POLL_INTERVAL)
+}
+
+/// Compute a message's expiration timestamp.
+///
+/// An explicit request expiry overrides the configured default; with neither,
+/// the message never expires and `0` is returned. A zero-length request
+/// expiry is rejected.
+fn resolve_expiration(
+    created_at: u64,
+    requested: Option<Duration>,
+    default: Option<Duration>,
+) -> Result<u64> {
+    if let Some(expiry) = requested {
+        if expiry.is_zero() {
+            return Err(ValidationError::OutOfRange {
+                field: "expiry".to_string(),
+                value: format!("{:?}", expiry),
+                min: "1s".to_string(),
+                max: "unbounded".to_string(),
+            }.into());
+        }
+    }
+    
+    Ok(requested
+        .or(default)
+        .map(|expiry| created_at.saturating_add(expiry.as_secs()))
+        .unwrap_or(0))
+}
+
+/// Build the reply tree under `root` from candidate `messages`
+///
+/// Replies are ordered oldest first. A message is placed at most once, and
+/// nothing deeper than `max_depth` below the root is included.
+fn assemble_thread(
+    root: Pubkey,
+    root_message: ChannelMessage,
+    messages: Vec<(Pubkey, ChannelMessage)>,
+    max_depth: usize,
+) -> MessageThread {
+    let mut replies: HashMap<Pubkey, Vec<(Pubkey, ChannelMessage)>> = HashMap::new();
+    for (address, message) in messages {
+        if let Some(parent) = message.reply_to {
+            replies.entry(parent).or_default().push((address, message));
+        }
+    }
+    for children in replies.values_mut() {
+        children.sort_by_key(|(address, message)| (message.created_at, *address));
+    }
+    
+    fn attach(
+        address: Pubkey,
+        message: ChannelMessage,
+        depth: usize,
+        max_depth: usize,
+        replies: &mut HashMap<Pubkey, Vec<(Pubkey, ChannelMessage)>>,
+        visited: &mut HashSet<Pubkey>,
+    ) -> MessageThread {
+        let mut thread = MessageThread { address, message, depth, replies: Vec::new() };
+        if depth >= max_depth {
+            return thread;
+        }
+        
+        for (child, child_message) in replies.remove(&address).unwrap_or_default() {
+            if visited.insert(child) {
+                thread.replies.push(attach(child, child_message, depth + 1, max_depth, replies, visited));
+            }
+        }
+        thread
+    }
+    
+    let mut visited = HashSet::from([root]);
+    attach(root, root_message, 0, max_depth, &mut replies, &mut visited)
+}
+
+/// Outcome of [`MessageService::send_multicast`]
+#[derive(Debug, Clone, Default)]
+pub struct MulticastDelivery {
+    /// Outcome of the transaction carrying each recipient's message, in
+    /// recipient order; `None` where that transaction failed
+    /// 
+    /// In dry-run mode each transaction is simulated and carries
+    /// [`Outcome::Simulated`], which is not a failure.
+    pub outcomes: Vec<Option<Outcome>>,
+    /// Failure reason for each recipient whose message was not sent
+    pub errors: HashMap<Pubkey, String>,
+}
+
+/// A message and the replies beneath it, from [`MessageService::build_thread`]
+#[derive(Clone)]
+pub struct MessageThread {
+    /// Address of this message
+    pub address: Pubkey,
+    /// The channel message account
+    pub message: ChannelMessage,
+    /// Distance from the thread's root, which is at depth 0
+    pub depth: usize,
+    /// Direct replies, oldest first
+    pub replies: Vec<MessageThread>,
+}
+
+impl MessageThread {
+    /// Every message in the thread, shallowest first and oldest first within a depth
+    pub fn by_depth(&self) -> Vec<&MessageThread> {
+        let mut ordered = vec![self];
+        let mut next = 0;
+        while next < ordered.len() {
+            let current: &MessageThread = ordered[next];
+            ordered.extend(current.replies.iter());
+            next += 1;
+        }
+        ordered
+    }
+    
+    /// Number of messages in the thread, including the root
+    pub fn message_count(&self) -> usize {
+        1 + self.replies.iter().map(MessageThread::message_count).sum::<usize>()
+    }
+}
+
 /// Message statistics
 #[derive(Debug, Clone)]
 pub struct MessageStats {
@@ -567,9 +1535,12 @@ impl BaseService for MessageService {
         self.base.health_check()
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -580,15 +1551,176 @@ impl BaseService for MessageService {
     fn service_name(&self) -> &'static str {
         "message"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
+}
+
+/// Sender, recipient, message type seed and payload hash identifying a send for deduplication
+type SentMessageKey = (Pubkey, Pubkey, u8, [u8; 32]);
+
+/// Transaction and message account created by a send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SentMessage {
+    /// `None` while the send is in flight, or after it failed without confirming
+    signature: Option<Signature>,
+    message: Pubkey,
+}
+
+/// Recently sent payloads, so a retried send returns the message it already created
+#[derive(Debug)]
+struct SentMessageCache {
+    entries: LruCache<SentMessageKey, (Instant, SentMessage)>,
+    window: Duration,
+}
+
+impl SentMessageCache {
+    fn new(window: Duration) -> Self {
+        Self {
+            entries: LruCache::new(NonZeroUsize::new(SENT_MESSAGE_CACHE_CAPACITY).unwrap()),
+            window,
+        }
+    }
+
+    /// The send recorded for `key`, unless it is older than the window
+    fn lookup(&mut self, key: &SentMessageKey, now: Instant) -> Option<SentMessage> {
+        let (sent_at, sent) = *self.entries.get(key)?;
+        if now.saturating_duration_since(sent_at) >= self.window {
+            self.entries.pop(key);
+            return None;
+        }
+        Some(sent)
+    }
+
+    fn record(&mut self, key: SentMessageKey, sent: SentMessage, now: Instant) {
+        self.entries.put(key, (now, sent));
+    }
+
+    fn forget(&mut self, key: &SentMessageKey) {
+        self.entries.pop(key);
+    }
+}
+
+/// Record a send of `key` if deduplication is configured
+fn record_sent(cache: Option<&Mutex<SentMessageCache>>, key: SentMessageKey, sent: SentMessage) {
+    if let Some(cache) = cache {
+        cache.lock().unwrap_or_else(|e| e.into_inner()).record(key, sent, Instant::now());
+    }
+}
+
+/// Drop the send recorded for `key`, e.g. when it was only simulated
+fn forget_sent(cache: Option<&Mutex<SentMessageCache>>, key: &SentMessageKey) {
+    if let Some(cache) = cache {
+        cache.lock().unwrap_or_else(|e| e.into_inner()).forget(key);
+    }
+}
+
+/// Earlier send of `key` still within the dedup window, unless `force` skips the check
+fn recently_sent(
+    cache: Option<&Mutex<SentMessageCache>>,
+    key: &SentMessageKey,
+    force: bool,
+    now: Instant,
+) -> Option<SentMessage> {
+    if force {
+        return None;
+    }
+    cache?.lock().unwrap_or_else(|e| e.into_inner()).lookup(key, now)
 }
 
 // Constants for message handling
 const MAX_UNCOMPRESSED_MESSAGE_SIZE: usize = 8192; // 8KB
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Longest wait between polls for a response
+const MAX_RESPONSE_POLL_INTERVAL: Duration = Duration::from_secs(8);
+/// Message instructions per multicast transaction, keeping well inside its compute limit
+const MULTICAST_MAX_INSTRUCTIONS: usize = 10;
+/// Accounts fetched per RPC call when scanning for thread replies
+const THREAD_SCAN_PAGE_SIZE: usize = 100;
+/// Offset of `channel` in a channel message account, after the discriminator
+const CHANNEL_MESSAGE_CHANNEL_OFFSET: usize = 8;
+/// Deepest reply below a thread's root that `build_thread` follows
+const MAX_THREAD_DEPTH: usize = 64;
+/// Appended to the message address in the digest a read receipt signs
+const READ_RECEIPT_DOMAIN: &[u8] = b"read";
+/// Distinct sends remembered for deduplication
+const SENT_MESSAGE_CACHE_CAPACITY: usize = 1024;
+/// SPL Memo program, which carries read receipts on-chain
+const MEMO_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::test_config;
+    use crate::services::base::compute_budget_instructions;
+    use crate::testing::channel_message;
+
+    fn sent_message() -> SentMessage {
+        SentMessage {
+            signature: Some(Keypair::new().sign_message(b"sent")),
+            message: Pubkey::new_unique(),
+        }
+    }
+
+    fn sent_key(message_type: &pod_sdk_types::MessageType, payload: &[u8]) -> SentMessageKey {
+        (Pubkey::new_unique(), Pubkey::new_unique(), pda::message_type_seed(message_type), payload_hash(payload))
+    }
+
+    #[test]
+    fn test_duplicate_send_returns_cached_signature() {
+        let cache = Mutex::new(SentMessageCache::new(Duration::from_secs(60)));
+        let key = sent_key(&pod_sdk_types::MessageType::Text, b"hello");
+        let sent = sent_message();
+        let now = Instant::now();
+
+        assert_eq!(recently_sent(Some(&cache), &key, false, now), None);
+        cache.lock().unwrap().record(key, sent, now);
+
+        assert_eq!(recently_sent(Some(&cache), &key, false, now + Duration::from_secs(59)), Some(sent));
+        // A different payload or recipient is a new message
+        let other_payload = (key.0, key.1, key.2, payload_hash(b"hello again"));
+        assert_eq!(recently_sent(Some(&cache), &other_payload, false, now), None);
+        let other_recipient = (key.0, Pubkey::new_unique(), key.2, key.3);
+        assert_eq!(recently_sent(Some(&cache), &other_recipient, false, now), None);
+        // The message type is part of the message address, so it is a new message too
+        let other_type = (key.0, key.1, pda::message_type_seed(&pod_sdk_types::MessageType::Data), key.3);
+        assert_eq!(recently_sent(Some(&cache), &other_type, false, now), None);
+
+        // Past the window the payload may be sent again
+        assert_eq!(recently_sent(Some(&cache), &key, false, now + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn test_forced_send_bypasses_dedup() {
+        let cache = Mutex::new(SentMessageCache::new(Duration::from_secs(60)));
+        let key = sent_key(&pod_sdk_types::MessageType::Text, b"hello");
+        let now = Instant::now();
+        cache.lock().unwrap().record(key, sent_message(), now);
+
+        assert_eq!(recently_sent(Some(&cache), &key, true, now), None);
+        // Without a configured window nothing is deduplicated
+        assert_eq!(recently_sent(None, &key, false, now), None);
+        assert!(MessageService::new(test_config()).sent_messages.is_none());
+    }
+
+    #[test]
+    fn test_pending_send_recorded_until_forgotten() {
+        let cache = Mutex::new(SentMessageCache::new(Duration::from_secs(60)));
+        let key = sent_key(&pod_sdk_types::MessageType::Command, b"run");
+        let pending = SentMessage { signature: None, message: Pubkey::new_unique() };
+
+        // An attempt is visible to a retry before its outcome is known
+        record_sent(Some(&cache), key, pending);
+        assert_eq!(recently_sent(Some(&cache), &key, false, Instant::now()), Some(pending));
+
+        let confirmed = SentMessage { signature: sent_message().signature, ..pending };
+        record_sent(Some(&cache), key, confirmed);
+        assert_eq!(recently_sent(Some(&cache), &key, false, Instant::now()), Some(confirmed));
+
+        forget_sent(Some(&cache), &key);
+        assert_eq!(recently_sent(Some(&cache), &key, false, Instant::now()), None);
+    }
 
     #[tokio::test]
     async fn test_message_service_creation() {
@@ -597,4 +1729,461 @@ mod tests {
         assert_eq!(service.service_name(), "message");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_command_to_capable_recipient() {
+        use pod_sdk_types::capabilities::{AI_CHAT, CODE_GENERATION};
+        
+        let recipient = Pubkey::new_unique();
+        assert!(check_recipient_capabilities(
+            MessageType::Command,
+            Some(CODE_GENERATION),
+            &recipient,
+            AI_CHAT | CODE_GENERATION,
+        ).is_ok());
+    }
+
+    #[test]
+    fn test_command_to_incapable_recipient_rejected() {
+        use pod_sdk_types::capabilities::{AI_CHAT, CODE_GENERATION};
+        
+        let recipient = Pubkey::new_unique();
+        let result = check_recipient_capabilities(
+            MessageType::Command,
+            Some(AI_CHAT | CODE_GENERATION),
+            &recipient,
+            AI_CHAT,
+        );
+        
+        assert!(matches!(
+            result,
+            Err(PodComError::Message(MessageError::RecipientLacksCapability { required, actual, .. }))
+                if required == AI_CHAT | CODE_GENERATION && actual == AI_CHAT
+        ));
+    }
+
+    #[test]
+    fn test_command_without_hint_allowed() {
+        let recipient = Pubkey::new_unique();
+        assert!(check_recipient_capabilities(MessageType::Command, None, &recipient, 0).is_ok());
+    }
+
+    #[test]
+    fn test_capability_hint_ignored_for_non_commands() {
+        use pod_sdk_types::capabilities::ML_TRAINING;
+        
+        let recipient = Pubkey::new_unique();
+        assert!(check_recipient_capabilities(MessageType::Text, Some(ML_TRAINING), &recipient, 0).is_ok());
+    }
+
+    #[test]
+    fn test_default_expiry_applied() {
+        let expires_at = resolve_expiration(1_000, None, Some(Duration::from_secs(3600))).unwrap();
+        assert_eq!(expires_at, 4_600);
+    }
+
+    #[test]
+    fn test_request_expiry_overrides_default() {
+        let expires_at = resolve_expiration(
+            1_000,
+            Some(Duration::from_secs(60)),
+            Some(Duration::from_secs(3600)),
+        ).unwrap();
+        assert_eq!(expires_at, 1_060);
+    }
+
+    #[test]
+    fn test_no_default_means_no_expiry() {
+        assert_eq!(resolve_expiration(1_000, None, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_zero_request_expiry_rejected() {
+        let result = resolve_expiration(1_000, Some(Duration::ZERO), Some(Duration::from_secs(60)));
+        assert!(matches!(result, Err(PodComError::Validation(ValidationError::OutOfRange { .. }))));
+    }
+
+    fn response(correlation_id: Option<CorrelationId>, body: &[u8]) -> (Pubkey, InlinePayload) {
+        (Pubkey::new_unique(), InlinePayload {
+            encoding: Default::default(),
+            correlation_id,
+            content_type: None,
+            payload: body.to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_commands_always_get_correlation_id() {
+        assert!(resolve_correlation_id(MessageType::Command, None).is_some());
+        assert_eq!(resolve_correlation_id(MessageType::Response, Some([3u8; 16])), Some([3u8; 16]));
+        assert_eq!(resolve_correlation_id(MessageType::Text, None), None);
+    }
+
+    #[tokio::test]
+    async fn test_correlated_response_resolves_await() {
+        let command_id = [1u8; 16];
+        let (address, matching) = response(Some(command_id), b"done");
+        let unrelated = response(Some([2u8; 16]), b"other");
+        let uncorrelated = response(None, b"noise");
+
+        let mut polls = 0;
+        let result = await_correlated(command_id, Duration::from_secs(5), Duration::from_millis(1), || {
+            polls += 1;
+            // The matching response only shows up on the second poll
+            let candidates = if polls == 1 {
+                vec![unrelated.clone(), uncorrelated.clone()]
+            } else {
+                vec![unrelated.clone(), uncorrelated.clone(), (address, matching.clone())]
+            };
+            async move { Ok(candidates) }
+        }).await.unwrap();
+
+        assert_eq!(result, Some((address, b"done".to_vec())));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_response_does_not_resolve_await() {
+        let unrelated = response(Some([2u8; 16]), b"other");
+
+        let result = await_correlated([1u8; 16], Duration::from_millis(20), Duration::from_millis(5), || {
+            let candidates = vec![unrelated.clone()];
+            async move { Ok(candidates) }
+        }).await.unwrap();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_response_polling_backs_off() {
+        let intervals: Vec<Duration> = std::iter::successors(Some(RESPONSE_POLL_INTERVAL), |interval| {
+            Some(next_poll_interval(*interval))
+        })
+        .take(7)
+        .collect();
+        
+        assert_eq!(intervals[1], RESPONSE_POLL_INTERVAL * 2);
+        assert!(intervals.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(intervals[6], MAX_RESPONSE_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn test_pack_instructions_splits_at_size_limit() {
+        let program_id = crate::PROGRAM_ID;
+        let payer = Pubkey::new_unique();
+        let recipients: Vec<Pubkey> = (0..25).map(|_| Pubkey::new_unique()).collect();
+        let instructions: Vec<Instruction> = recipients
+            .iter()
+            .map(|recipient| multicast_instruction(&program_id, &payer, recipient, [7; 32], MessageType::Text))
+            .collect();
+        
+        let reserved = compute_budget_instructions(Some(200_000), Some(1_000));
+        
+        let batches = pack_instructions(instructions.clone(), &reserved, &payer);
+        assert!(batches.len() > 1);
+        
+        // Order is preserved across batches
+        let flattened: Vec<Instruction> = batches.iter().flatten().cloned().collect();
+        assert_eq!(flattened, instructions);
+        
+        for (i, batch) in batches.iter().enumerate() {
+            assert!(transaction_size(&[&reserved, batch].concat(), &payer) <= PACKET_DATA_SIZE);
+            assert!(batch.len() <= MULTICAST_MAX_INSTRUCTIONS);
+            
+            // Every full batch is full: the next instruction would not have fit
+            if let Some(next) = batches.get(i + 1) {
+                let mut grown = batch.clone();
+                grown.push(next[0].clone());
+                assert!(
+                    transaction_size(&[&reserved, &grown].concat(), &payer) > PACKET_DATA_SIZE
+                        || grown.len() > MULTICAST_MAX_INSTRUCTIONS
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_instructions_caps_instruction_count() {
+        let payer = Pubkey::new_unique();
+        let noop = Instruction::new_with_bytes(crate::PROGRAM_ID, &[], Vec::new());
+        
+        let batches = pack_instructions(vec![noop.clone(); MULTICAST_MAX_INSTRUCTIONS + 1], &[], &payer);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].len(), MULTICAST_MAX_INSTRUCTIONS);
+        assert_eq!(batches[1].len(), 1);
+        
+        assert_eq!(pack_instructions(vec![noop; MULTICAST_MAX_INSTRUCTIONS], &[], &payer).len(), 1);
+        assert!(pack_instructions(Vec::new(), &[], &payer).is_empty());
+    }
+
+    #[test]
+    fn test_pack_instructions_isolates_oversized_instruction() {
+        let payer = Pubkey::new_unique();
+        let small = Instruction::new_with_bytes(crate::PROGRAM_ID, &[1], Vec::new());
+        let huge = Instruction::new_with_bytes(crate::PROGRAM_ID, &[0; PACKET_DATA_SIZE], Vec::new());
+        
+        let batches = pack_instructions(vec![small.clone(), huge.clone(), small.clone()], &[], &payer);
+        assert_eq!(batches, vec![vec![small.clone()], vec![huge], vec![small]]);
+    }
+
+    #[tokio::test]
+    async fn test_send_multicast_rejects_duplicate_recipients() {
+        let service = MessageService::new(test_config());
+        let recipient = Pubkey::new_unique();
+        
+        let result = service
+            .send_multicast(&[recipient, Pubkey::new_unique(), recipient], b"hi".to_vec(), MessageType::Text)
+            .await;
+        assert!(matches!(result, Err(PodComError::Validation(ValidationError::InvalidCombination { .. }))));
+    }
+
+    #[tokio::test]
+    async fn test_status_changes_until_terminal() {
+        use pod_sdk_types::MessageStatus as Observed;
+        
+        // Repeats are collapsed and anything after `Read` is never observed
+        let subscription = futures::stream::iter(vec![
+            Ok(Observed::Pending),
+            Ok(Observed::Pending),
+            Ok(Observed::Delivered),
+            Ok(Observed::Read),
+            Ok(Observed::Failed),
+        ]);
+        let statuses: Vec<Observed> = status_changes(subscription)
+            .map(|status| status.unwrap())
+            .collect()
+            .await;
+        assert_eq!(statuses, vec![Observed::Pending, Observed::Delivered, Observed::Read]);
+        
+        // A subscription that gives up ends the stream with its error
+        let dropped = futures::stream::iter(vec![
+            Ok(Observed::Pending),
+            Err(PodComError::Network(NetworkError::ConnectionFailed {
+                endpoint: "ws://localhost".to_string(),
+                reason: "gave up".to_string(),
+            })),
+            Ok(Observed::Delivered),
+        ]);
+        let statuses: Vec<Result<Observed>> = status_changes(dropped).collect().await;
+        assert_eq!(statuses.len(), 2);
+        assert!(matches!(statuses[1], Err(PodComError::Network(NetworkError::ConnectionFailed { .. }))));
+    }
+
+    #[test]
+    fn test_watch_message_requires_ws_url() {
+        let mut config = test_config();
+        config.ws_url = None;
+        let service = MessageService::new(config);
+        
+        assert!(matches!(
+            service.watch_message(Pubkey::new_unique()),
+            Err(PodComError::InvalidConfiguration { .. })
+        ));
+    }
+
+    fn received_message(recipient: &Keypair) -> (Pubkey, Pubkey, pod_sdk_types::MessageAccount) {
+        let program_id = crate::PROGRAM_ID;
+        let message = pod_sdk_types::MessageAccount {
+            sender: Pubkey::new_unique(),
+            recipient: pda::agent_pda(&program_id, &recipient.pubkey()).0,
+            channel: None,
+            payload_hash: [7; 32],
+            message_type: pod_sdk_types::MessageType::Data,
+            status: pod_sdk_types::MessageStatus::Read,
+            created_at: 100,
+            expires_at: 0,
+            reply_to: None,
+            bump: 255,
+        };
+        let address = pda::message_pda(
+            &program_id,
+            &message.sender,
+            &message.recipient,
+            &message.payload_hash,
+            pda::message_type_seed(&message.message_type),
+        ).0;
+        (program_id, address, message)
+    }
+
+    #[test]
+    fn test_valid_read_receipt() {
+        let recipient = Keypair::new();
+        let (program_id, address, message) = received_message(&recipient);
+        
+        let receipt = recipient.sign_message(&read_receipt_digest(&address));
+        assert!(read_receipt_is_valid(&program_id, &address, &message, &recipient.pubkey(), &receipt));
+    }
+
+    #[test]
+    fn test_forged_read_receipt_rejected() {
+        let recipient = Keypair::new();
+        let (program_id, address, message) = received_message(&recipient);
+        
+        // Signed by someone other than the recipient
+        let receipt = Keypair::new().sign_message(&read_receipt_digest(&address));
+        assert!(!read_receipt_is_valid(&program_id, &address, &message, &recipient.pubkey(), &receipt));
+        
+        // Signed by the recipient, but for a different message
+        let receipt = recipient.sign_message(&read_receipt_digest(&Pubkey::new_unique()));
+        assert!(!read_receipt_is_valid(&program_id, &address, &message, &recipient.pubkey(), &receipt));
+    }
+
+    #[test]
+    fn test_read_receipt_from_wrong_recipient_rejected() {
+        let (recipient, other) = (Keypair::new(), Keypair::new());
+        let (program_id, address, message) = received_message(&recipient);
+        
+        // A valid signature from a wallet whose agent is not the recipient
+        let receipt = other.sign_message(&read_receipt_digest(&address));
+        assert!(!read_receipt_is_valid(&program_id, &address, &message, &other.pubkey(), &receipt));
+    }
+
+    #[test]
+    fn test_read_receipt_found_in_recipient_memo() {
+        let (payer, recipient) = (Keypair::new(), Keypair::new());
+        let receipt = recipient.sign_message(&read_receipt_digest(&Pubkey::new_unique()));
+        let status_update = Instruction::new_with_bytes(crate::PROGRAM_ID, &[1], vec![
+            AccountMeta::new_readonly(recipient.pubkey(), true),
+        ]);
+        
+        let message = |memo: Instruction| VersionedMessage::Legacy(Message::new(
+            &[status_update.clone(), memo],
+            Some(&payer.pubkey()),
+        ));
+        let acknowledged = message(read_receipt_memo(&recipient.pubkey(), &receipt));
+        assert_eq!(read_receipt_in(&acknowledged, &recipient.pubkey()), Some(receipt));
+        
+        // A memo signed by someone else is not the recipient's receipt
+        let other = Keypair::new();
+        let forged = message(read_receipt_memo(&other.pubkey(), &receipt));
+        assert_eq!(read_receipt_in(&forged, &recipient.pubkey()), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_receipt_search_pages_through_history() {
+        use solana_rpc_client_api::request::RpcRequest;
+        
+        // Two pages of failed transactions, then the end of the history
+        let pages = [vec![Signature::new_unique(), Signature::new_unique()], vec![Signature::new_unique()], vec![]];
+        let oldest = [pages[0][1], pages[1][0]];
+        let rpc = crate::testing::MockRpc::new(move |request, params| match request {
+            RpcRequest::GetSignaturesForAddress => {
+                let page = match params[1]["before"].as_str() {
+                    None => &pages[0],
+                    Some(before) if before == oldest[0].to_string() => &pages[1],
+                    Some(_) => &pages[2],
+                };
+                serde_json::Value::Array(page.iter().map(|signature| serde_json::json!({
+                    "signature": signature.to_string(),
+                    "slot": 1,
+                    "err": "AccountInUse",
+                    "memo": null,
+                    "blockTime": null,
+                    "confirmationStatus": "finalized",
+                })).collect())
+            }
+            other => panic!("unexpected RPC request {}", other),
+        });
+        let mut config = test_config();
+        config.async_rpc_client = rpc.client();
+        let service = MessageService::new(config);
+        
+        let receipt = service.fetch_read_receipt(&Pubkey::new_unique(), &Pubkey::new_unique()).await.unwrap();
+        assert_eq!(receipt, None);
+        let befores: Vec<_> = rpc.params_of("getSignaturesForAddress")
+            .iter()
+            .map(|params| params[1]["before"].as_str().map(str::to_string))
+            .collect();
+        assert_eq!(befores, vec![None, Some(oldest[0].to_string()), Some(oldest[1].to_string())]);
+    }
+
+    fn thread_message(created_at: i64, reply_to: Option<Pubkey>) -> (Pubkey, ChannelMessage) {
+        (Pubkey::new_unique(), channel_message(Pubkey::new_unique(), created_at, reply_to))
+    }
+
+    fn addresses(thread: &MessageThread) -> Vec<(Pubkey, usize)> {
+        thread.by_depth().into_iter().map(|t| (t.address, t.depth)).collect()
+    }
+
+    #[test]
+    fn test_linear_thread() {
+        let (root, root_msg) = thread_message(100, None);
+        let (first, first_msg) = thread_message(110, Some(root));
+        let (second, second_msg) = thread_message(120, Some(first));
+        let (_, unrelated) = thread_message(130, Some(Pubkey::new_unique()));
+        
+        let messages = vec![(second, second_msg), (first, first_msg), (Pubkey::new_unique(), unrelated)];
+        let thread = assemble_thread(root, root_msg, messages, MAX_THREAD_DEPTH);
+        
+        assert_eq!(addresses(&thread), vec![(root, 0), (first, 1), (second, 2)]);
+        assert_eq!(thread.message_count(), 3);
+    }
+
+    #[test]
+    fn test_branching_thread() {
+        let (root, root_msg) = thread_message(100, None);
+        let (late, late_msg) = thread_message(150, Some(root));
+        let (early, early_msg) = thread_message(110, Some(root));
+        let (nested, nested_msg) = thread_message(120, Some(early));
+        
+        let thread = assemble_thread(
+            root,
+            root_msg,
+            vec![(late, late_msg), (nested, nested_msg), (early, early_msg)],
+            MAX_THREAD_DEPTH,
+        );
+        
+        // Shallowest first, oldest first within a depth
+        assert_eq!(addresses(&thread), vec![(root, 0), (early, 1), (late, 1), (nested, 2)]);
+        assert_eq!(thread.replies[0].replies[0].address, nested);
+        assert!(thread.replies[1].replies.is_empty());
+    }
+
+    #[test]
+    fn test_thread_cycles_and_depth_are_bounded() {
+        // The root claims to reply to itself
+        let root = Pubkey::new_unique();
+        let (_, mut root_msg) = thread_message(100, None);
+        root_msg.reply_to = Some(root);
+        let (reply, reply_msg) = thread_message(110, Some(root));
+        
+        let messages = vec![(root, root_msg.clone()), (reply, reply_msg.clone())];
+        let thread = assemble_thread(root, root_msg.clone(), messages, MAX_THREAD_DEPTH);
+        assert_eq!(addresses(&thread), vec![(root, 0), (reply, 1)]);
+        
+        // Replies past the depth limit are dropped
+        let thread = assemble_thread(root, root_msg, vec![(reply, reply_msg)], 0);
+        assert_eq!(thread.message_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_build_thread_scans_root_channel() {
+        use anchor_client::{Client, Cluster};
+        use crate::testing::{account_data, MockRpc};
+        
+        let channel = Pubkey::new_unique();
+        let (root, reply) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::serving([
+            (root.to_string(), account_data(&channel_message(channel, 100, None))),
+            (reply.to_string(), account_data(&channel_message(channel, 110, Some(root)))),
+        ].into_iter().collect());
+        
+        let mut config = test_config();
+        config.async_rpc_client = rpc.client();
+        let mut service = MessageService::new(config);
+        let program = Client::new(Cluster::Localnet, Arc::new(Keypair::new()))
+            .program(crate::PROGRAM_ID)
+            .unwrap();
+        service.initialize(program).await.unwrap();
+        
+        let thread = service.build_thread(root).await.unwrap();
+        assert_eq!(addresses(&thread), vec![(root, 0), (reply, 1)]);
+        
+        let scans = rpc.params_of("getProgramAccounts");
+        assert_eq!(scans.len(), 1);
+        let filters: Vec<RpcFilterType> = serde_json::from_value(scans[0][1]["filters"].clone()).unwrap();
+        assert!(filters.contains(&RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            CHANNEL_MESSAGE_CHANNEL_OFFSET,
+            channel.as_ref(),
+        ))));
+    }
+}
@@ -4,6 +4,7 @@
 //! All services follow a consistent pattern and provide production-ready functionality.
 
 pub mod base;
+pub mod builder;
 pub mod agent;
 pub mod message; 
 pub mod channel;
@@ -16,7 +17,8 @@ pub mod zk_compression;
 // Re-export all services for convenient access
 pub use agent::AgentService;
 pub use analytics::AnalyticsService;
-pub use base::{BaseService, ServiceConfig, ServiceMetrics, ServiceHealth};
+pub use builder::ServiceBuilder;
+pub use base::{BaseService, DryRunResult, DynService, DynServiceError, LatencySummaries, LatencySummary, MetricsRecorder, OperationLatencies, Outcome, ServiceConfig, ServiceMetrics, ServiceHealth, SharedAccountCache, TaskRegistry};
 pub use channel::ChannelService;
 pub use discovery::DiscoveryService;
 pub use escrow::EscrowService;
@@ -24,12 +26,75 @@ pub use ipfs::IPFSService;
 pub use message::MessageService;
 pub use zk_compression::ZKCompressionService;
 
+use std::collections::HashMap;
+
+use crate::error::PodComError;
+
+/// Outcome of shutting down every service in a [`ServiceRegistry`]
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// Shutdown result for each service, keyed by service name
+    pub per_service: HashMap<&'static str, Result<(), String>>,
+    /// Operations still in flight when shutdown began
+    pub pending_operations: u64,
+}
+
+impl ShutdownReport {
+    /// Whether every service shut down without error and nothing was in flight
+    pub fn is_clean(&self) -> bool {
+        self.pending_operations == 0 && self.per_service.values().all(|r| r.is_ok())
+    }
+    
+    /// Names of services whose shutdown returned an error
+    pub fn failed_services(&self) -> Vec<&'static str> {
+        let mut failed: Vec<_> = self.per_service
+            .iter()
+            .filter(|(_, r)| r.is_err())
+            .map(|(name, _)| *name)
+            .collect();
+        failed.sort_unstable();
+        failed
+    }
+    
+    /// Record the result of shutting down a single service
+    async fn record<F>(&mut self, name: &'static str, shutdown: F)
+    where
+        F: std::future::Future<Output = crate::error::Result<()>>,
+    {
+        let result = shutdown.await.map_err(|e| {
+            tracing::warn!("Service '{}' failed to shut down cleanly: {}", name, e);
+            e.to_string()
+        });
+        self.per_service.insert(name, result);
+    }
+}
+
+/// Expands `$entry!(name, field)` for every service of a [`ServiceRegistry`],
+/// in initialization order, collected into a `Vec`
+///
+/// This is the one list of the registry's services; every per-service
+/// iteration is derived from it.
+macro_rules! registry_services {
+    ($entry:ident) => {
+        vec![
+            $entry!("agent", agents),
+            $entry!("message", messages),
+            $entry!("channel", channels),
+            $entry!("escrow", escrow),
+            $entry!("analytics", analytics),
+            $entry!("discovery", discovery),
+            $entry!("ipfs", ipfs),
+            $entry!("zk_compression", zk_compression),
+        ]
+    };
+}
+
 /// Service registry for managing all protocol services
 #[derive(Debug)]
 pub struct ServiceRegistry {
-    pub agent: AgentService,
-    pub message: MessageService,
-    pub channel: ChannelService,
+    pub agents: AgentService,
+    pub messages: MessageService,
+    pub channels: ChannelService,
     pub escrow: EscrowService,
     pub analytics: AnalyticsService,
     pub discovery: DiscoveryService,
@@ -38,61 +103,200 @@ pub struct ServiceRegistry {
 }
 
 impl ServiceRegistry {
-    /// Create a new service registry with all services initialized
-    pub fn new(config: ServiceConfig) -> Self {
-        Self {
-            agent: AgentService::new(config.clone()),
-            message: MessageService::new(config.clone()),
-            channel: ChannelService::new(config.clone()),
-            escrow: EscrowService::new(config.clone()),
-            analytics: AnalyticsService::new(config.clone()),
-            discovery: DiscoveryService::new(config.clone()),
-            ipfs: IPFSService::new(config.clone()),
-            zk_compression: ZKCompressionService::new(config),
+    /// Create a new service registry with all services constructed
+    pub fn new(config: ServiceConfig) -> crate::error::Result<Self> {
+        ServiceBuilder::new(config).build()
+    }
+    
+    /// Every service with its name, in initialization order
+    pub(crate) fn services(&self) -> Vec<(&'static str, &dyn BaseService<Error = PodComError>)> {
+        macro_rules! entry {
+            ($name:literal, $field:ident) => {
+                ($name, &self.$field as &dyn BaseService<Error = PodComError>)
+            };
         }
+        registry_services!(entry)
+    }
+    
+    /// Mutable counterpart of [`Self::services`], in the same order
+    pub(crate) fn services_mut(&mut self) -> Vec<(&'static str, &mut dyn BaseService<Error = PodComError>)> {
+        macro_rules! entry {
+            ($name:literal, $field:ident) => {
+                ($name, &mut self.$field as &mut dyn BaseService<Error = PodComError>)
+            };
+        }
+        registry_services!(entry)
     }
     
     /// Initialize all services with the program instance
     pub async fn initialize_all(&mut self, program: anchor_client::Program<std::sync::Arc<solana_sdk::signer::keypair::Keypair>>) -> crate::error::Result<()> {
-        self.agent.initialize(program.clone()).await?;
-        self.message.initialize(program.clone()).await?;
-        self.channel.initialize(program.clone()).await?;
-        self.escrow.initialize(program.clone()).await?;
-        self.analytics.initialize(program.clone()).await?;
-        self.discovery.initialize(program.clone()).await?;
-        self.ipfs.initialize(program.clone()).await?;
-        self.zk_compression.initialize(program).await?;
+        for (_, service) in self.services_mut() {
+            service.initialize(program.clone()).await?;
+        }
         
         Ok(())
     }
     
+    /// Whether every service has been initialized with a program instance
+    pub fn is_initialized(&self) -> bool {
+        self.health_status()
+            .values()
+            .all(|health| *health != ServiceHealth::NotInitialized)
+    }
+    
     /// Shutdown all services gracefully
-    pub async fn shutdown_all(&mut self) -> crate::error::Result<()> {
-        self.zk_compression.shutdown().await?;
-        self.ipfs.shutdown().await?;
-        self.discovery.shutdown().await?;
-        self.analytics.shutdown().await?;
-        self.escrow.shutdown().await?;
-        self.channel.shutdown().await?;
-        self.message.shutdown().await?;
-        self.agent.shutdown().await?;
+    ///
+    /// Services are shut down in reverse initialization order. Every service
+    /// is shut down even if an earlier one fails; the returned report records
+    /// the outcome for each.
+    pub async fn shutdown_all(&mut self) -> ShutdownReport {
+        let mut report = ShutdownReport {
+            per_service: HashMap::new(),
+            pending_operations: self.pending_operations(),
+        };
         
-        Ok(())
+        for (name, service) in self.services_mut().into_iter().rev() {
+            report.record(name, service.shutdown()).await;
+        }
+        
+        report
+    }
+    
+    /// Total number of operations currently in flight across all services
+    pub fn pending_operations(&self) -> u64 {
+        self.services()
+            .into_iter()
+            .map(|(_, service)| service.pending_operations())
+            .sum()
+    }
+    
+    /// Borrow every service through the object-safe [`DynService`] interface
+    pub fn dyn_services(&self) -> Vec<&dyn DynService> {
+        macro_rules! entry {
+            ($name:literal, $field:ident) => {
+                &self.$field as &dyn DynService
+            };
+        }
+        registry_services!(entry)
+    }
+    
+    /// Convert the registry into boxed [`DynService`] trait objects
+    pub fn into_dyn_services(self) -> Vec<Box<dyn DynService>> {
+        macro_rules! entry {
+            ($name:literal, $field:ident) => {
+                Box::new(self.$field) as Box<dyn DynService>
+            };
+        }
+        registry_services!(entry)
     }
     
     /// Get health status of all services
     pub fn health_status(&self) -> std::collections::HashMap<&'static str, ServiceHealth> {
-        let mut status = std::collections::HashMap::new();
+        self.services()
+            .into_iter()
+            .map(|(name, service)| (name, service.health_check()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_shutdown_report_continues_after_failure() {
+        let completed = AtomicUsize::new(0);
+        let mut report = ShutdownReport::default();
+        
+        report.record("zk_compression", async {
+            completed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).await;
+        report.record("ipfs", async {
+            Err(PodComError::NotInitialized)
+        }).await;
+        report.record("discovery", async {
+            completed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).await;
+        report.record("agent", async {
+            completed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).await;
+        
+        assert_eq!(completed.load(Ordering::SeqCst), 3);
+        assert_eq!(report.per_service.len(), 4);
+        assert!(report.per_service["ipfs"].is_err());
+        assert!(report.per_service["discovery"].is_ok());
+        assert!(report.per_service["agent"].is_ok());
+        assert_eq!(report.failed_services(), vec!["ipfs"]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_operation_not_left_pending() {
+        use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(60)))
+            .mount(&server)
+            .await;
+        let mut config = test_config();
+        config.ipfs_endpoint = Some(server.uri());
+        let mut registry = ServiceRegistry::new(config).unwrap();
         
-        status.insert("agent", self.agent.health_check());
-        status.insert("message", self.message.health_check());
-        status.insert("channel", self.channel.health_check());
-        status.insert("escrow", self.escrow.health_check());
-        status.insert("analytics", self.analytics.health_check());
-        status.insert("discovery", self.discovery.health_check());
-        status.insert("ipfs", self.ipfs.health_check());
-        status.insert("zk_compression", self.zk_compression.health_check());
+        let mut download = Box::pin(registry.ipfs.download("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"));
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(100), &mut download).await.is_err());
+        assert_eq!(registry.pending_operations(), 1);
         
-        status
+        // Dropping the in-flight download releases its count
+        drop(download);
+        assert_eq!(registry.pending_operations(), 0);
+        
+        let report = registry.shutdown_all().await;
+        assert_eq!(report.pending_operations, 0);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_shutdown_report_clean() {
+        let mut report = ShutdownReport::default();
+        report.per_service.insert("agent", Ok(()));
+        assert!(report.is_clean());
+        
+        report.pending_operations = 2;
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_service_iteration_covers_every_field() {
+        let mut registry = ServiceRegistry::new(test_config()).unwrap();
+        
+        // Fails to compile when a field is added without updating this list
+        let ServiceRegistry {
+            agents: _,
+            messages: _,
+            channels: _,
+            escrow: _,
+            analytics: _,
+            discovery: _,
+            ipfs: _,
+            zk_compression: _,
+        } = &registry;
+        const FIELD_COUNT: usize = 8;
+        
+        let names: Vec<_> = registry.services().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), FIELD_COUNT);
+        for (name, service) in registry.services() {
+            assert_eq!(name, service.service_name());
+        }
+        
+        let mut_names: Vec<_> = registry.services_mut().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(mut_names, names);
+        let dyn_names: Vec<_> = registry.dyn_services().into_iter().map(|service| service.dyn_service_name()).collect();
+        assert_eq!(dyn_names, names);
+        assert_eq!(registry.health_status().len(), FIELD_COUNT);
+    }
+}
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use std::collections::HashMap;
 
 use anchor_client::Program;
+use pod_sdk_crypto::Hash;
 use rand::{distributions::Alphanumeric, Rng};
 use async_trait::async_trait;
 use solana_sdk::{
@@ -19,9 +20,12 @@ use pod_sdk_types::{
     AgentAccount,
 };
 
+use tokio::sync::Semaphore;
+
 use crate::{
+    config::ZKCompressionConfig,
     error::{PodComError, Result},
-    services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
+    services::base::{BaseService, Outcome, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
     utils::{
         zk::{ZKProof, ZKCircuit, ZKCompressor, CompressionProof},
         compression::{CompressionAlgorithm, CompressionLevel},
@@ -36,31 +40,52 @@ pub struct ZKCompressionService {
     zk_compressor: Arc<ZKCompressor>,
     proof_cache: Arc<tokio::sync::RwLock<ProofCache>>,
     compression_stats: Arc<tokio::sync::RwLock<CompressionStats>>,
+    /// Bounds concurrent proof generation across the whole service instance
+    compression_permits: Arc<Semaphore>,
 }
 
 impl ZKCompressionService {
     /// Create a new ZK compression service
     pub fn new(config: ServiceConfig) -> Self {
         let zk_compressor = Arc::new(ZKCompressor::new(config.zk_compression_config.clone()));
+        let max_concurrent = config.zk_compression_config
+            .as_ref()
+            .map(|c| c.max_concurrent_compressions)
+            .unwrap_or_else(|| ZKCompressionConfig::default().max_concurrent_compressions)
+            .max(1);
         
         Self {
-            base: ServiceBase::new(config),
+            base: ServiceBase::new(config).with_service_name("zk_compression"),
             zk_compressor,
             proof_cache: Arc::new(tokio::sync::RwLock::new(ProofCache::new())),
             compression_stats: Arc::new(tokio::sync::RwLock::new(CompressionStats::new())),
+            compression_permits: Arc::new(Semaphore::new(max_concurrent)),
         }
     }
 
+    /// Run CPU-heavy compression work once a concurrency permit is available
+    ///
+    /// Callers wait for a permit rather than oversubscribing the runtime.
+    async fn with_compression_permit<F, T>(&self, work: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        let _permit = self.compression_permits.acquire().await.map_err(|_| PodComError::ServiceUnavailable {
+            service: "zk_compression".to_string(),
+        })?;
+        work.await
+    }
+
     /// Compress data with zero-knowledge proof
     pub async fn compress_data(
         &self,
         compressor: &Keypair,
         data: Vec<u8>,
         params: CompressDataParams,
-    ) -> Result<(Vec<u8>, CompressionProof, ZKCompressionAccount)> {
+    ) -> Result<Outcome<(Vec<u8>, CompressionProof, ZKCompressionAccount)>> {
         let operation_name = "compress_data";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             let program = self.base.program()?;
             
             // Validate input data
@@ -75,13 +100,13 @@ impl ZKCompressionService {
                 });
             }
             
-            // Perform ZK compression
-            let compression_result = self.zk_compressor.compress_with_proof(
+            // Perform ZK compression, bounded by the service-wide concurrency limit
+            let compression_result = self.with_compression_permit(self.zk_compressor.compress_with_proof(
                 &data,
                 params.algorithm,
                 params.level,
                 params.privacy_level,
-            ).await?;
+            )).await?;
             
             // Generate commitment to original data
             let data_commitment = generate_commitment(&data, &compression_result.randomness)?;
@@ -92,7 +117,7 @@ impl ZKCompressionService {
                 .take(32)
                 .map(char::from)
                 .collect();
-            let (compression_pda, _bump) = derive_zk_compression_pda(&compressor.pubkey(), &compression_id)?;
+            let (compression_pda, _bump) = derive_zk_compression_pda(&compressor.pubkey(), &compression_id, &self.base.config().effective_program_id())?;
             
             // Build instruction - Note: pod-com doesn't have separate ZK compression functionality
             // The program has compressed message support built-in, this would need custom implementation
@@ -101,7 +126,10 @@ impl ZKCompressionService {
             });
 
             // Send transaction
-            let signature = ix.send()?;
+            let signature = match self.base.send(ix).await? {
+                Outcome::Sent(signature) => signature,
+                Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+            };
             
             // Fetch created compression account
             let compression_account = self.get_zk_compression_account(&compression_pda).await?;
@@ -133,7 +161,7 @@ impl ZKCompressionService {
                 "Data compressed with ZK proof successfully"
             );
 
-            Ok((compression_result.compressed_data, compression_result.proof, compression_account))
+            Ok(Outcome::Sent((compression_result.compressed_data, compression_result.proof, compression_account)))
         }).await
     }
 
@@ -147,7 +175,7 @@ impl ZKCompressionService {
     ) -> Result<Vec<u8>> {
         let operation_name = "decompress_data";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Verify the compression proof first
             if !self.verify_compression_proof(&compressed_data, &proof, &params.expected_commitment).await? {
                 return Err(PodComError::InvalidCompressionProof {
@@ -166,7 +194,7 @@ impl ZKCompressionService {
             let computed_commitment = generate_commitment(&decompressed_data, &proof.randomness)?;
             if computed_commitment != params.expected_commitment {
                 return Err(PodComError::CompressionDataIntegrityFailed {
-                    expected_commitment: params.expected_commitment,
+                    expected_commitment: params.expected_commitment.clone(),
                     computed_commitment,
                 });
             }
@@ -192,7 +220,7 @@ impl ZKCompressionService {
     ) -> Result<bool> {
         let operation_name = "verify_compression_proof";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             // Check proof cache first
             {
                 let cache = self.proof_cache.read().await;
@@ -221,26 +249,27 @@ impl ZKCompressionService {
     pub async fn get_zk_compression_account(&self, compression_address: &Pubkey) -> Result<ZKCompressionAccount> {
         let operation_name = "get_zk_compression_account";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
-            let account_data = program.account::<ZKCompressionAccount>(*compression_address)?;
+        self.base.execute_operation(operation_name, || async {
+            let account_data = self.base.fetch_account::<ZKCompressionAccount>(compression_address).await?;
             self.validate_zk_compression_account(&account_data)?;
+            self.check_cached_proof(&account_data).await?;
             
             Ok(account_data)
         }).await
     }
 
     /// Batch compress multiple data items
+    /// 
+    /// In dry-run mode this stops at the first item and returns its simulation.
     pub async fn batch_compress(
         &self,
         compressor: &Keypair,
         data_items: Vec<Vec<u8>>,
         params: CompressDataParams,
-    ) -> Result<BatchCompressionResult> {
+    ) -> Result<Outcome<BatchCompressionResult>> {
         let operation_name = "batch_compress";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_transaction(operation_name, || async {
             if data_items.is_empty() {
                 return Err(PodComError::EmptyBatchCompressionInput);
             }
@@ -269,7 +298,10 @@ impl ZKCompressionService {
                     }),
                 };
                 
-                let (compressed_data, proof, _) = self.compress_data(compressor, data.clone(), item_params).await?;
+                let (compressed_data, proof, _) = match self.compress_data(compressor, data.clone(), item_params).await? {
+                    Outcome::Sent(compressed) => compressed,
+                    Outcome::Simulated(dry_run) => return Ok(Outcome::Simulated(dry_run)),
+                };
                 
                 total_original_size += data.len() as u64;
                 total_compressed_size += compressed_data.len() as u64;
@@ -278,8 +310,10 @@ impl ZKCompressionService {
                 proofs.push(proof);
             }
             
-            // Generate batch proof
-            let batch_proof = self.zk_compressor.generate_batch_proof(&proofs).await?;
+            // Generate batch proof; items above were each limited inside `compress_data`
+            let batch_proof = self.with_compression_permit(
+                self.zk_compressor.generate_batch_proof(&proofs),
+            ).await?;
             
             let result = BatchCompressionResult {
                 compressed_items,
@@ -300,7 +334,7 @@ impl ZKCompressionService {
                 "Batch compression completed successfully"
             );
             
-            Ok(result)
+            Ok(Outcome::Sent(result))
         }).await
     }
 
@@ -308,7 +342,7 @@ impl ZKCompressionService {
     pub async fn get_compression_stats(&self) -> Result<CompressionStatsReport> {
         let operation_name = "get_compression_stats";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let stats = self.compression_stats.read().await;
             Ok(stats.generate_report())
         }).await
@@ -318,15 +352,9 @@ impl ZKCompressionService {
     pub async fn list_compressions_by_compressor(&self, compressor: &Pubkey) -> Result<Vec<(Pubkey, ZKCompressionAccount)>> {
         let operation_name = "list_compressions_by_compressor";
         
-        self.base.execute_operation(operation_name, async {
-            let program = self.base.program()?;
-            
+        self.base.execute_operation(operation_name, || async {
             // Get all ZK compression accounts
-            let accounts = program
-                .accounts::<ZKCompressionAccount>(vec![
-                    solana_account_decoder::UiAccountEncoding::Base64,
-                ])
-                .await?;
+            let accounts = self.base.fetch_program_accounts::<ZKCompressionAccount>().await?;
                 
             let mut compressor_compressions = Vec::new();
             
@@ -352,7 +380,7 @@ impl ZKCompressionService {
     ) -> Result<OptimizedCompressionParams> {
         let operation_name = "optimize_compression_params";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let optimization_result = self.zk_compressor.optimize_parameters(
                 sample_data,
                 target_compression_ratio,
@@ -376,7 +404,7 @@ impl ZKCompressionService {
     pub async fn cleanup_proof_cache(&self) -> Result<u64> {
         let operation_name = "cleanup_proof_cache";
         
-        self.base.execute_operation(operation_name, async {
+        self.base.execute_operation(operation_name, || async {
             let mut cache = self.proof_cache.write().await;
             let cleaned_count = cache.cleanup_expired();
             
@@ -389,8 +417,46 @@ impl ZKCompressionService {
         }).await
     }
 
+    /// Clean up expired proofs every `interval` until the service shuts down
+    pub fn spawn_proof_cache_sweeper(&self, interval: std::time::Duration) {
+        let proof_cache = self.proof_cache.clone();
+        
+        self.base.spawn_task(move |shutdown| async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(interval) => {
+                        let cleaned_count = proof_cache.write().await.cleanup_expired();
+                        tracing::debug!(cleaned_count, "Proof cache sweep completed");
+                    }
+                }
+            }
+        });
+    }
+
     // Helper methods
 
+    /// Check an account against its cached proof when `check_cached_proofs_on_read` is enabled
+    ///
+    /// Only proofs this client generated are cached, and the program exposes
+    /// no state root to verify against, so an account whose proof is not
+    /// cached is returned unchecked rather than rejected.
+    async fn check_cached_proof(&self, account: &ZKCompressionAccount) -> Result<()> {
+        let enabled = self.base.config().zk_compression_config
+            .as_ref()
+            .is_some_and(|c| c.check_cached_proofs_on_read);
+        if !enabled {
+            return Ok(());
+        }
+        
+        let Some(proof) = self.proof_cache.write().await.get_proof(&account.proof_hash).cloned() else {
+            tracing::debug!(proof_hash = %account.proof_hash, "Proof not cached; account left unchecked");
+            return Ok(());
+        };
+        
+        check_account_proof(account, &proof)
+    }
+
     fn validate_zk_compression_account(&self, account: &ZKCompressionAccount) -> Result<()> {
         // Validate sizes
         if account.original_size == 0 {
@@ -422,6 +488,26 @@ impl ZKCompressionService {
     }
 }
 
+/// Check that a cached proof matches the hash committed in its account and has not been altered
+fn check_account_proof(account: &ZKCompressionAccount, proof: &CompressionProof) -> Result<()> {
+    let invalid = |reason: &str| PodComError::ProofMismatch {
+        proof_hash: account.proof_hash.clone(),
+        reason: reason.to_string(),
+    };
+    
+    if proof.hash() != account.proof_hash {
+        return Err(invalid("proof hash does not match account"));
+    }
+    if hex::encode(Hash::hash(proof.data())) != account.proof_hash {
+        return Err(invalid("proof data does not match committed hash"));
+    }
+    if proof.data().len() < 32 {
+        return Err(invalid("proof data is truncated"));
+    }
+    
+    Ok(())
+}
+
 // Data structures
 
 #[derive(Debug, Clone)]
@@ -765,9 +851,12 @@ impl BaseService for ZKCompressionService {
         }
     }
 
+    async fn probe_health(&self) -> ServiceHealth {
+        self.base.probe_health().await
+    }
+
     fn metrics(&self) -> ServiceMetrics {
-        // This is a blocking call for consistency with the trait
-        futures::executor::block_on(self.base.metrics())
+        self.base.metrics_snapshot()
     }
 
     async fn shutdown(&mut self) -> Result<(), Self::Error> {
@@ -781,6 +870,10 @@ impl BaseService for ZKCompressionService {
     fn service_name(&self) -> &'static str {
         "zk_compression"
     }
+
+    fn pending_operations(&self) -> u64 {
+        self.base.pending_operations()
+    }
 }
 
 // Constants
@@ -799,4 +892,104 @@ mod tests {
         assert_eq!(service.service_name(), "zk_compression");
         // Note: Health check may be NotInitialized or Degraded depending on ZK setup
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_compression_concurrency_limited() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let mut config = test_config();
+        config.zk_compression_config = Some(ZKCompressionConfig {
+            max_concurrent_compressions: 4,
+            ..ZKCompressionConfig::default()
+        });
+        let service = ZKCompressionService::new(config);
+        
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        
+        let tasks = (0..20).map(|_| service.with_compression_permit(async {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }));
+        let results = futures::future::join_all(tasks).await;
+        
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+    }
+
+    fn service_with_proof_check(check_cached_proofs_on_read: bool) -> ZKCompressionService {
+        let mut config = test_config();
+        config.zk_compression_config = Some(ZKCompressionConfig {
+            check_cached_proofs_on_read,
+            ..ZKCompressionConfig::default()
+        });
+        ZKCompressionService::new(config)
+    }
+
+    fn account_for(proof: &CompressionProof) -> ZKCompressionAccount {
+        ZKCompressionAccount {
+            id: "compression".to_string(),
+            compressor: Pubkey::new_unique(),
+            original_size: 1024,
+            compressed_size: 256,
+            proof_hash: proof.hash(),
+            created_at: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proof_check_disabled_skips_check() {
+        let service = service_with_proof_check(false);
+        let proof = CompressionProof::new(vec![7u8; 64]);
+        let mut tampered = account_for(&proof);
+        tampered.proof_hash = "00".repeat(32);
+        
+        assert!(service.check_cached_proof(&tampered).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_proof_check_enabled() {
+        let service = service_with_proof_check(true);
+        let proof = CompressionProof::new(vec![7u8; 64]);
+        let account = account_for(&proof);
+        
+        // A cold read has nothing to compare against and is not rejected
+        assert!(service.check_cached_proof(&account).await.is_ok());
+        
+        service.proof_cache.write().await.store_proof(proof.hash(), proof.clone());
+        assert!(service.check_cached_proof(&account).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_proof_check_rejects_mismatched_proof() {
+        let service = service_with_proof_check(true);
+        let proof = CompressionProof::new(vec![7u8; 64]);
+        let substitute = CompressionProof::new(vec![9u8; 64]);
+        
+        // A different proof sitting under the account's proof hash
+        service.proof_cache.write().await.store_proof(proof.hash(), substitute);
+        assert!(matches!(
+            service.check_cached_proof(&account_for(&proof)).await,
+            Err(PodComError::ProofMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chosen_algorithm_recorded_in_stats() {
+        let mut stats = CompressionStats::new();
+        stats.record_compression(1_000, 400, CompressionAlgorithm::Zstd);
+        stats.record_compression(1_000, 500, CompressionAlgorithm::Zstd);
+        stats.record_compression(1_000, 600, CompressionAlgorithm::Gzip);
+
+        assert_eq!(stats.algorithm_counts.get(&CompressionAlgorithm::Zstd), Some(&2));
+        assert_eq!(stats.algorithm_counts.get(&CompressionAlgorithm::Gzip), Some(&1));
+
+        let report = stats.generate_report();
+        assert_eq!(report.algorithm_distribution, stats.algorithm_counts);
+        assert_eq!(report.total_compressions, 3);
+    }
+}
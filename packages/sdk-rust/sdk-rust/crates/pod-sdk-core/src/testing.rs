@@ -0,0 +1,299 @@
+//! # Test Helpers
+//!
+//! Mock RPC transport, program account fixtures and tracing capture shared by
+//! the SDK's tests.
+//! Integration tests get the transport through the `testing` feature.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_rpc_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client_api::{client_error::Result as ClientResult, filter::RpcFilterType, request::RpcRequest};
+use solana_sdk::pubkey::Pubkey;
+
+/// Compute units reported by [`MockRpc::serving`] simulations
+pub const SIMULATED_UNITS: u64 = 12_345;
+
+/// Answers one request with its JSON-RPC `result`
+type Handler = dyn Fn(RpcRequest, &Value) -> Value + Send + Sync;
+
+/// RPC transport answering requests with a handler and recording every call
+///
+/// Clones share the handler and the call log, so a test can hand one clone
+/// to a client and inspect the calls through another.
+#[derive(Clone)]
+pub struct MockRpc {
+    handler: Arc<Handler>,
+    calls: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl MockRpc {
+    /// Transport answering every request with `handler`
+    ///
+    /// `handler` returns the request's `result`; requests a test does not
+    /// expect should panic.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(RpcRequest, &Value) -> Value + Send + Sync + 'static,
+    {
+        Self {
+            handler: Arc::new(handler),
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Transport serving program-owned `accounts`, keyed by base58 address
+    ///
+    /// Answers account reads (honoring `filters` and `dataSlice` on program
+    /// account scans), successful simulations consuming [`SIMULATED_UNITS`],
+    /// rent queries at the default rent, and version queries. Any other
+    /// request panics.
+    pub fn serving(accounts: HashMap<String, Vec<u8>>) -> Self {
+        Self::serving_with(accounts, |request, _| panic!("unexpected RPC request {}", request))
+    }
+
+    /// Transport serving `accounts` like [`serving`](Self::serving), passing
+    /// any other request to `fallback`
+    pub fn serving_with<F>(accounts: HashMap<String, Vec<u8>>, fallback: F) -> Self
+    where
+        F: Fn(RpcRequest, &Value) -> Value + Send + Sync + 'static,
+    {
+        Self::new(move |request, params| match request {
+            RpcRequest::GetAccountInfo => with_context(
+                accounts
+                    .get(params[0].as_str().unwrap())
+                    .map_or(Value::Null, |data| ui_account(data)),
+            ),
+            RpcRequest::GetMultipleAccounts => with_context(Value::Array(
+                params[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| accounts.get(key.as_str().unwrap()).map_or(Value::Null, |data| ui_account(data)))
+                    .collect(),
+            )),
+            RpcRequest::GetProgramAccounts => {
+                let filters: Vec<RpcFilterType> =
+                    serde_json::from_value(params[1]["filters"].clone()).unwrap_or_default();
+                let mut keys: Vec<_> = accounts
+                    .iter()
+                    .filter(|(_, data)| filters.iter().all(|filter| filter_allows(filter, data)))
+                    .map(|(key, _)| key)
+                    .collect();
+                keys.sort();
+                let slice = &params[1]["dataSlice"];
+                Value::Array(keys
+                    .into_iter()
+                    .map(|key| {
+                        let data = &accounts[key];
+                        let data = match (slice["offset"].as_u64(), slice["length"].as_u64()) {
+                            (Some(offset), Some(length)) => {
+                                let start = (offset as usize).min(data.len());
+                                &data[start..(start + length as usize).min(data.len())]
+                            }
+                            _ => &data[..],
+                        };
+                        json!({ "pubkey": key, "account": ui_account(data) })
+                    })
+                    .collect())
+            }
+            RpcRequest::SimulateTransaction => with_context(simulation(
+                SIMULATED_UNITS,
+                &["Program log: Instruction: RegisterAgent"],
+            )),
+            RpcRequest::GetMinimumBalanceForRentExemption => {
+                let size = params[0].as_u64().unwrap() as usize;
+                json!(solana_sdk::rent::Rent::default().minimum_balance(size))
+            }
+            // Recent enough that the client sends filters unchanged
+            RpcRequest::GetVersion => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            other => fallback(other, params),
+        })
+    }
+
+    /// Nonblocking client sending through this transport
+    pub fn client(&self) -> Arc<RpcClient> {
+        Arc::new(RpcClient::new_sender(self.clone(), Default::default()))
+    }
+
+    /// Method name and params of every request so far, in order
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Method name of every request so far, in order
+    pub fn methods(&self) -> Vec<String> {
+        self.calls().into_iter().map(|(method, _)| method).collect()
+    }
+
+    /// Params of every `method` request so far, in order
+    pub fn params_of(&self, method: &str) -> Vec<Value> {
+        self.calls()
+            .into_iter()
+            .filter(|(called, _)| called == method)
+            .map(|(_, params)| params)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl RpcSender for MockRpc {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let result = (self.handler)(request, &params);
+        self.calls.lock().unwrap().push((request.to_string(), params));
+        Ok(result)
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "mock".to_string()
+    }
+}
+
+/// `value` wrapped in the response context account and simulation reads carry
+pub fn with_context(value: Value) -> Value {
+    json!({ "context": { "slot": 1 }, "value": value })
+}
+
+/// Program-owned account holding `data`, as `getAccountInfo` returns it
+pub fn ui_account(data: &[u8]) -> Value {
+    json!({
+        "lamports": 1_000_000,
+        "owner": crate::PROGRAM_ID.to_string(),
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "executable": false,
+        "rentEpoch": 0,
+        "space": data.len(),
+    })
+}
+
+/// Whether a program account scan with `filter` lists an account holding `data`
+fn filter_allows(filter: &RpcFilterType, data: &[u8]) -> bool {
+    match filter {
+        RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+        RpcFilterType::TokenAccountState => false,
+    }
+}
+
+/// Successful simulation consuming `units_consumed` and logging `logs`
+pub fn simulation(units_consumed: u64, logs: &[&str]) -> Value {
+    json!({
+        "err": null,
+        "logs": logs,
+        "accounts": null,
+        "unitsConsumed": units_consumed,
+        "returnData": null,
+    })
+}
+
+/// Program account of type `T` with every field at its empty value
+///
+/// The program's accounts carry private padding, so they cannot be built
+/// field by field. Decoding a zeroed account gives one whose public fields
+/// a test can then set.
+pub fn zeroed<T: borsh::BorshDeserialize>() -> T {
+    T::deserialize(&mut &[0u8; 256][..]).unwrap()
+}
+
+/// Channel message posted to `channel` at `created_at`, decoded through the program's layout
+pub fn channel_message(channel: Pubkey, created_at: i64, reply_to: Option<Pubkey>) -> pod_com::ChannelMessage {
+    let mut message: pod_com::ChannelMessage = zeroed();
+    message.channel = channel;
+    message.sender = Pubkey::new_unique();
+    message.created_at = created_at;
+    message.reply_to = reply_to;
+    message
+}
+
+/// Data the program stores for `account`, discriminator first
+pub fn account_data<T: anchor_lang::AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// Tracing layer capturing span fields and events
+///
+/// Span fields from every span are merged into one map; each event keeps
+/// its level and fields. Values are recorded as their `Debug` output.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct TraceCapture {
+    spans: Arc<Mutex<HashMap<String, String>>>,
+    events: Arc<Mutex<Vec<(tracing::Level, HashMap<String, String>)>>>,
+}
+
+#[cfg(test)]
+impl TraceCapture {
+    /// Fields recorded on any span so far
+    pub fn spans(&self) -> HashMap<String, String> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    /// Fields of every event so far, in order
+    pub fn events(&self) -> Vec<HashMap<String, String>> {
+        self.events.lock().unwrap().iter().map(|(_, fields)| fields.clone()).collect()
+    }
+
+    /// Message of every warning so far, in order
+    pub fn warnings(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(level, _)| *level == tracing::Level::WARN)
+            .map(|(_, fields)| fields.get("message").cloned().unwrap_or_default())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+#[cfg(test)]
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+#[cfg(test)]
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for TraceCapture {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        _id: &tracing::span::Id,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        attrs.record(&mut FieldVisitor(&mut self.spans.lock().unwrap()));
+    }
+
+    fn on_record(
+        &self,
+        _id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        values.record(&mut FieldVisitor(&mut self.spans.lock().unwrap()));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.events.lock().unwrap().push((*event.metadata().level(), fields));
+    }
+}
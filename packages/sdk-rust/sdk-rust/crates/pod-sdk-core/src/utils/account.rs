@@ -2,8 +2,9 @@
 //!
 //! Utilities for working with Solana accounts and PDAs.
 
-use solana_sdk::pubkey::Pubkey;
-use crate::error::Result;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use crate::error::{NetworkError, PodComError, Result};
 
 /// Agent account data structure
 #[derive(Debug, Clone)]
@@ -75,44 +76,70 @@ pub struct ZKCompressionAccount {
 }
 
 /// Derive agent PDA
-pub fn derive_agent_pda(owner: &Pubkey, name: &str) -> Result<(Pubkey, u8)> {
+///
+/// Seeds the name alongside the owner, which the program does not; on-chain
+/// agent accounts live at [`pod_sdk_types::pda::agent_pda`].
+#[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `pod_sdk_types::pda::agent_pda`")]
+pub fn derive_agent_pda(owner: &Pubkey, name: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
     let seeds = &[b"agent", owner.as_ref(), name.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
 
+/// Derive a deterministic agent ID from its owner and a nonce
+///
+/// The ID is a truncated Blake3 hash of the inputs, encoded as base58 so it
+/// fits in a single PDA seed. The same owner and nonce always give the same
+/// ID, so a registration can be retried without creating a second agent.
+/// Prefer this over randomly generated IDs, which differ on every attempt.
+pub fn derive_agent_id(owner: &Pubkey, nonce: u64) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(AGENT_ID_DOMAIN);
+    hasher.update(owner.as_ref());
+    hasher.update(&nonce.to_le_bytes());
+    
+    let hash = hasher.finalize();
+    bs58::encode(&hash.as_bytes()[..AGENT_ID_HASH_LEN]).into_string()
+}
+
 /// Derive channel PDA
-pub fn derive_channel_pda(creator: &Pubkey, channel_id: &str) -> Result<(Pubkey, u8)> {
-    let seeds = &[b"channel", creator.as_ref(), channel_id.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
-    Ok((pda, bump))
+pub fn derive_channel_pda(creator: &Pubkey, channel_id: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
+    Ok(pod_sdk_types::pda::channel_pda(program_id, creator, channel_id))
 }
 
 /// Derive message PDA
-pub fn derive_message_pda(channel: &Pubkey, message_id: &str) -> Result<(Pubkey, u8)> {
+///
+/// Keyed by channel and message ID; direct messages on-chain live at
+/// [`pod_sdk_types::pda::message_pda`].
+#[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `pod_sdk_types::pda::message_pda`")]
+pub fn derive_message_pda(channel: &Pubkey, message_id: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
     let seeds = &[b"message", channel.as_ref(), message_id.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
 
 /// Derive escrow PDA
-pub fn derive_escrow_pda(payer: &Pubkey, escrow_id: &str) -> Result<(Pubkey, u8)> {
+///
+/// Keyed by payer and escrow ID; channel escrows on-chain live at
+/// [`pod_sdk_types::pda::escrow_pda`].
+#[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `pod_sdk_types::pda::escrow_pda`")]
+pub fn derive_escrow_pda(payer: &Pubkey, escrow_id: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
     let seeds = &[b"escrow", payer.as_ref(), escrow_id.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
 
 /// Derive IPFS metadata PDA
-pub fn derive_ipfs_metadata_pda(uploader: &Pubkey, metadata_id: &str) -> Result<(Pubkey, u8)> {
+pub fn derive_ipfs_metadata_pda(uploader: &Pubkey, metadata_id: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
     let seeds = &[b"ipfs_metadata", uploader.as_ref(), metadata_id.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
 
 /// Derive ZK compression PDA
-pub fn derive_zk_compression_pda(compressor: &Pubkey, compression_id: &str) -> Result<(Pubkey, u8)> {
+pub fn derive_zk_compression_pda(compressor: &Pubkey, compression_id: &str, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
     let seeds = &[b"zk_compression", compressor.as_ref(), compression_id.as_bytes()];
-    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
 
@@ -205,13 +232,7 @@ pub fn validate_message_account(account: &MessageAccount) -> Result<()> {
     }
     
     // Validate supported content types
-    let valid_content_types = [
-        "text/plain", "text/markdown", "application/json", 
-        "application/octet-stream", "image/png", "image/jpeg",
-        "audio/mpeg", "video/mp4", "application/pdf"
-    ];
-    
-    if !valid_content_types.contains(&account.content_type.as_str()) {
+    if pod_sdk_types::validate_content_type(&account.content_type).is_err() {
         return Err(crate::error::PodError::InvalidInput(
             format!("Unsupported content type: {}", account.content_type)
         ));
@@ -397,18 +418,8 @@ pub fn validate_ipfs_metadata_account(account: &IPFSMetadataAccount) -> Result<(
     }
     
     // Validate content hash format (IPFS CID)
-    if account.content_hash.is_empty() {
-        return Err(crate::error::PodError::InvalidInput("IPFS content hash cannot be empty".to_string()));
-    }
-    
-    if account.content_hash.len() < 46 || account.content_hash.len() > 59 {
-        return Err(crate::error::PodError::InvalidInput("Invalid IPFS content hash length".to_string()));
-    }
-    
-    // Check if hash starts with valid IPFS CID prefixes
-    if !account.content_hash.starts_with("Qm") && !account.content_hash.starts_with("bafy") && !account.content_hash.starts_with("bafk") {
-        return Err(crate::error::PodError::InvalidInput("Invalid IPFS content hash format".to_string()));
-    }
+    crate::utils::ipfs::validate_cid(&account.content_hash)
+        .map_err(|reason| crate::error::PodError::InvalidInput(reason.to_string()))?;
     
     // Validate size
     if account.size == 0 {
@@ -512,6 +523,99 @@ pub fn check_rent_exemption(account_size: usize, lamports: u64) -> Result<bool>
     Ok(lamports >= required_lamports)
 }
 
+/// Maximum number of keys accepted by a single `getMultipleAccounts` call
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Summary of a chunked account fetch in which some chunks may have failed
+#[derive(Debug, Clone, Default)]
+pub struct PartialFetchReport {
+    /// Total number of keys requested
+    pub requested: usize,
+    /// Keys belonging to chunks that failed, in request order
+    pub failed_keys: Vec<Pubkey>,
+    /// Error for each failed chunk, keyed by chunk index
+    pub chunk_errors: Vec<(usize, String)>,
+}
+
+impl PartialFetchReport {
+    /// Whether every chunk was fetched successfully
+    pub fn is_complete(&self) -> bool {
+        self.chunk_errors.is_empty()
+    }
+    
+    /// Number of keys whose chunk was fetched successfully
+    pub fn succeeded(&self) -> usize {
+        self.requested - self.failed_keys.len()
+    }
+}
+
+/// Fetch accounts in chunks, isolating failures to the chunk they occur in
+///
+/// Results are returned in the same order as `keys`. Keys in a failed chunk
+/// yield `None` and are listed in the report; in `strict` mode the first chunk
+/// error is returned instead.
+pub fn fetch_accounts_chunked<T, F>(
+    keys: &[Pubkey],
+    chunk_size: usize,
+    strict: bool,
+    mut fetch_chunk: F,
+) -> Result<(Vec<Option<T>>, PartialFetchReport)>
+where
+    F: FnMut(&[Pubkey]) -> Result<Vec<Option<T>>>,
+{
+    let mut accounts = Vec::with_capacity(keys.len());
+    let mut report = PartialFetchReport {
+        requested: keys.len(),
+        ..Default::default()
+    };
+    
+    for (index, chunk) in keys.chunks(chunk_size.clamp(1, MAX_MULTIPLE_ACCOUNTS)).enumerate() {
+        let result = fetch_chunk(chunk).and_then(|fetched| {
+            if fetched.len() == chunk.len() {
+                Ok(fetched)
+            } else {
+                Err(NetworkError::InvalidResponse {
+                    reason: format!("expected {} accounts, got {}", chunk.len(), fetched.len()),
+                }.into())
+            }
+        });
+        
+        match result {
+            Ok(fetched) => accounts.extend(fetched),
+            Err(e) if strict => return Err(e),
+            Err(e) => {
+                tracing::warn!("Account fetch chunk {} ({} keys) failed: {}", index, chunk.len(), e);
+                accounts.extend(chunk.iter().map(|_| None));
+                report.failed_keys.extend_from_slice(chunk);
+                report.chunk_errors.push((index, e.to_string()));
+            }
+        }
+    }
+    
+    Ok((accounts, report))
+}
+
+/// Fetch many accounts through `getMultipleAccounts`, tolerating failed chunks
+pub fn get_multiple_accounts_resilient(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+    strict: bool,
+) -> Result<(Vec<Option<Account>>, PartialFetchReport)> {
+    fetch_accounts_chunked(keys, MAX_MULTIPLE_ACCOUNTS, strict, |chunk| {
+        rpc_client.get_multiple_accounts(chunk).map_err(|e| -> PodComError {
+            NetworkError::RpcFailed {
+                method: "getMultipleAccounts".to_string(),
+                reason: e.to_string(),
+            }.into()
+        })
+    })
+}
+
+/// Domain separator for agent ID hashing
+const AGENT_ID_DOMAIN: &[u8] = b"pod-protocol:agent-id";
+/// Hash bytes kept in an agent ID; the largest prefix whose base58 form fits a 32-byte seed
+const AGENT_ID_HASH_LEN: usize = 23;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -755,18 +859,43 @@ mod tests {
         let owner = Pubkey::new_unique();
         let name = "test_agent";
         
-        let (pda1, bump1) = derive_agent_pda(&owner, name).unwrap();
-        let (pda2, bump2) = derive_agent_pda(&owner, name).unwrap();
+        let (pda1, bump1) = derive_agent_pda(&owner, name, &crate::PROGRAM_ID).unwrap();
+        let (pda2, bump2) = derive_agent_pda(&owner, name, &crate::PROGRAM_ID).unwrap();
         
         // Same inputs should produce same PDA
         assert_eq!(pda1, pda2);
         assert_eq!(bump1, bump2);
         
         // Different name should produce different PDA
-        let (pda3, _) = derive_agent_pda(&owner, "different_name").unwrap();
+        let (pda3, _) = derive_agent_pda(&owner, "different_name", &crate::PROGRAM_ID).unwrap();
         assert_ne!(pda1, pda3);
     }
 
+    #[test]
+    fn test_channel_pda_matches_historical_derivation() {
+        let creator = Pubkey::new_from_array([3; 32]);
+        let historical = Pubkey::find_program_address(
+            &[b"channel", creator.as_ref(), b"general"],
+            &crate::PROGRAM_ID,
+        );
+        
+        assert_eq!(pod_sdk_types::pda::channel_pda(&crate::PROGRAM_ID, &creator, "general"), historical);
+        assert_eq!(derive_channel_pda(&creator, "general", &crate::PROGRAM_ID).unwrap(), historical);
+    }
+
+    #[test]
+    fn test_derive_agent_id() {
+        let owner = Pubkey::new_unique();
+        
+        let id = derive_agent_id(&owner, 7);
+        assert_eq!(id, derive_agent_id(&owner, 7));
+        assert!(id.len() <= 32);
+        assert!(derive_agent_pda(&owner, &id, &crate::PROGRAM_ID).is_ok());
+        
+        assert_ne!(id, derive_agent_id(&owner, 8));
+        assert_ne!(id, derive_agent_id(&Pubkey::new_unique(), 7));
+    }
+
     #[test]
     fn test_account_size_validation() {
         let test_data = [0u8; 100];
@@ -790,4 +919,46 @@ mod tests {
         let insufficient_lamports = 1000;
         assert!(!check_rent_exemption(account_size, insufficient_lamports).unwrap());
     }
-} 
\ No newline at end of file
+
+    fn failing_second_chunk(chunk_size: usize, strict: bool) -> (Vec<Pubkey>, Result<(Vec<Option<Pubkey>>, PartialFetchReport)>) {
+        let keys: Vec<Pubkey> = (0..chunk_size * 3).map(|_| Pubkey::new_unique()).collect();
+        let mut calls = 0;
+        
+        let result = fetch_accounts_chunked(&keys, chunk_size, strict, |chunk| {
+            calls += 1;
+            if calls == 2 {
+                return Err(NetworkError::RpcFailed {
+                    method: "getMultipleAccounts".to_string(),
+                    reason: "node is behind".to_string(),
+                }.into());
+            }
+            // Echo each key back as its "account" so ordering is observable
+            Ok(chunk.iter().map(|k| Some(*k)).collect())
+        });
+        
+        (keys, result)
+    }
+
+    #[test]
+    fn test_partial_fetch_isolates_failed_chunk() {
+        let (keys, result) = failing_second_chunk(2, false);
+        let (accounts, report) = result.unwrap();
+        
+        assert_eq!(accounts, vec![
+            Some(keys[0]), Some(keys[1]),
+            None, None,
+            Some(keys[4]), Some(keys[5]),
+        ]);
+        assert_eq!(report.failed_keys, vec![keys[2], keys[3]]);
+        assert_eq!(report.chunk_errors.len(), 1);
+        assert_eq!(report.chunk_errors[0].0, 1);
+        assert_eq!(report.succeeded(), 4);
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn test_partial_fetch_strict_mode_fails() {
+        let (_, result) = failing_second_chunk(2, true);
+        assert!(result.is_err());
+    }
+}
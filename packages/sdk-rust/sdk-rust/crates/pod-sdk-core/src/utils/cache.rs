@@ -0,0 +1,155 @@
+//! # Account Cache
+//!
+//! A bounded, expiring cache of account state keyed by address. Entries are
+//! evicted least recently used first once the cache is full, and an entry
+//! older than the cache's TTL is treated as absent.
+
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::CacheConfig;
+
+/// LRU cache of accounts with TTL expiry
+pub struct AccountCache<T> {
+    entries: LruCache<Pubkey, CachedAccount<T>>,
+    ttl: Duration,
+}
+
+/// A cached value and when it was stored
+struct CachedAccount<T> {
+    value: T,
+    stored_at: Instant,
+}
+
+impl<T: Clone> AccountCache<T> {
+    /// Create a cache holding up to `capacity` accounts for `ttl` each
+    pub fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            entries: LruCache::new(capacity),
+            ttl,
+        }
+    }
+
+    /// Create a cache sized by `config`, or `None` if caching is disabled
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        NonZeroUsize::new(config.max_size).map(|capacity| Self::new(capacity, config.default_ttl))
+    }
+
+    /// Cached value for `address`, unless missing or expired
+    pub fn get(&mut self, address: &Pubkey) -> Option<T> {
+        self.get_at(address, Instant::now())
+    }
+
+    /// Store `value` for `address`, evicting the least recently used entry if full
+    pub fn insert(&mut self, address: Pubkey, value: T) {
+        self.insert_at(address, value, Instant::now());
+    }
+
+    /// Drop the entry for `address`, returning whether one was cached
+    pub fn invalidate(&mut self, address: &Pubkey) -> bool {
+        self.entries.pop(address).is_some()
+    }
+
+    /// Drop every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of entries held, including any that have expired but not been evicted
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn get_at(&mut self, address: &Pubkey, now: Instant) -> Option<T> {
+        let expired = now.saturating_duration_since(self.entries.get(address)?.stored_at) >= self.ttl;
+        if expired {
+            self.entries.pop(address);
+            return None;
+        }
+        self.entries.peek(address).map(|entry| entry.value.clone())
+    }
+
+    fn insert_at(&mut self, address: Pubkey, value: T, now: Instant) {
+        self.entries.put(address, CachedAccount { value, stored_at: now });
+    }
+}
+
+impl<T> std::fmt::Debug for AccountCache<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountCache")
+            .field("len", &self.entries.len())
+            .field("capacity", &self.entries.cap())
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, ttl: Duration) -> AccountCache<u64> {
+        AccountCache::new(NonZeroUsize::new(capacity).unwrap(), ttl)
+    }
+
+    #[test]
+    fn test_hit_and_miss() {
+        let mut cache = cache(4, Duration::from_secs(60));
+        let (cached, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        cache.insert(cached, 7);
+        assert_eq!(cache.get(&cached), Some(7));
+        assert_eq!(cache.get(&other), None);
+
+        assert!(cache.invalidate(&cached));
+        assert_eq!(cache.get(&cached), None);
+        assert!(!cache.invalidate(&cached));
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let mut cache = cache(4, Duration::from_secs(60));
+        let address = Pubkey::new_unique();
+        let stored = Instant::now();
+
+        cache.insert_at(address, 7, stored);
+        assert_eq!(cache.get_at(&address, stored + Duration::from_secs(59)), Some(7));
+        assert_eq!(cache.get_at(&address, stored + Duration::from_secs(60)), None);
+        // Expired entries are evicted on read
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_evicted_first() {
+        let mut cache = cache(2, Duration::from_secs(60));
+        let [a, b, c] = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        cache.insert(a, 1);
+        cache.insert(b, 2);
+        // Reading `a` makes `b` the least recently used
+        assert_eq!(cache.get(&a), Some(1));
+        cache.insert(c, 3);
+
+        assert_eq!(cache.get(&b), None);
+        assert_eq!(cache.get(&a), Some(1));
+        assert_eq!(cache.get(&c), Some(3));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_disabled_config_has_no_cache() {
+        assert!(AccountCache::<u64>::from_config(&CacheConfig::disabled()).is_none());
+        assert!(AccountCache::<u64>::from_config(&CacheConfig::default()).is_some());
+    }
+}
@@ -13,6 +13,9 @@ pub enum CompressionAlgorithm {
     /// LZ4 compression
     Lz4,
     /// ZSTD compression
+    ///
+    /// Requires the `zstd` feature; without it compressing or decompressing
+    /// ZSTD data fails.
     Zstd,
 }
 
@@ -25,8 +28,22 @@ pub enum CompressionLevel {
     Balanced,
     /// Best compression
     Best,
+    /// Algorithm-specific numeric level
+    ///
+    /// GZIP accepts 0-9 and ZSTD accepts [`ZSTD_MIN_LEVEL`] to
+    /// [`ZSTD_MAX_LEVEL`]; other values are rejected.
+    Exact(u8),
 }
 
+/// Lowest ZSTD compression level
+pub const ZSTD_MIN_LEVEL: u8 = 1;
+
+/// Highest ZSTD compression level
+pub const ZSTD_MAX_LEVEL: u8 = 22;
+
+/// Highest GZIP compression level
+const GZIP_MAX_LEVEL: u8 = 9;
+
 /// Compress message using default algorithm (GZIP with balanced level)
 pub fn compress_message(content: &[u8]) -> Result<Vec<u8>> {
     compress_with_algorithm(content, CompressionAlgorithm::Gzip, CompressionLevel::Balanced)
@@ -79,6 +96,41 @@ pub fn decompress_with_algorithm(
     }
 }
 
+/// Decompress data with specified algorithm, failing if the output exceeds `limit` bytes
+/// 
+/// Use this for untrusted input: decompression stops at `limit + 1`
+/// bytes, so a small payload cannot expand without bound.
+pub fn decompress_with_limit(
+    content: &[u8],
+    algorithm: CompressionAlgorithm,
+    limit: usize,
+) -> Result<Vec<u8>> {
+    if content.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match algorithm {
+        CompressionAlgorithm::Gzip | CompressionAlgorithm::Lz4 => {
+            read_bounded(flate2::read::GzDecoder::new(content), limit, "GZIP")
+        }
+        CompressionAlgorithm::Zstd => decompress_zstd_bounded(content, limit),
+    }
+}
+
+/// Read `reader` to the end, failing once more than `limit` bytes come out
+fn read_bounded(reader: impl Read, limit: usize, name: &str) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    reader.take(limit as u64 + 1).read_to_end(&mut decompressed)
+        .map_err(|e| crate::error::PodError::CryptoError(format!("{} decompression failed: {}", name, e)))?;
+    
+    if decompressed.len() > limit {
+        return Err(crate::error::PodError::CryptoError(format!(
+            "{} output exceeds {} bytes", name, limit
+        )));
+    }
+    Ok(decompressed)
+}
+
 /// Compress using GZIP
 fn compress_gzip(content: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
     use flate2::{write::GzEncoder, Compression};
@@ -87,6 +139,12 @@ fn compress_gzip(content: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
         CompressionLevel::Fast => Compression::fast(),
         CompressionLevel::Balanced => Compression::default(),
         CompressionLevel::Best => Compression::best(),
+        CompressionLevel::Exact(n) if n <= GZIP_MAX_LEVEL => Compression::new(n as u32),
+        CompressionLevel::Exact(n) => {
+            return Err(crate::error::PodError::CryptoError(format!(
+                "GZIP level {} is outside 0-{}", n, GZIP_MAX_LEVEL
+            )));
+        }
     };
 
     let mut encoder = GzEncoder::new(Vec::new(), compression_level);
@@ -110,24 +168,74 @@ fn decompress_gzip(content: &[u8]) -> Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Map a compression level to a ZSTD level
+fn zstd_level(level: CompressionLevel) -> Result<i32> {
+    match level {
+        CompressionLevel::Fast => Ok(1),
+        CompressionLevel::Balanced => Ok(3),
+        CompressionLevel::Best => Ok(9),
+        CompressionLevel::Exact(n) if (ZSTD_MIN_LEVEL..=ZSTD_MAX_LEVEL).contains(&n) => Ok(n as i32),
+        CompressionLevel::Exact(n) => Err(crate::error::PodError::CryptoError(format!(
+            "ZSTD level {} is outside {}-{}", n, ZSTD_MIN_LEVEL, ZSTD_MAX_LEVEL
+        ))),
+    }
+}
+
 /// Compress using ZSTD
+#[cfg(feature = "zstd")]
 fn compress_zstd(content: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
-    let compression_level = match level {
-        CompressionLevel::Fast => 1,
-        CompressionLevel::Balanced => 3,
-        CompressionLevel::Best => 9,
-    };
-
-    zstd::encode_all(content, compression_level)
+    zstd::encode_all(content, zstd_level(level)?)
         .map_err(|e| crate::error::PodError::CryptoError(format!("ZSTD compression failed: {}", e)))
 }
 
+/// Compress using ZSTD
+#[cfg(not(feature = "zstd"))]
+fn compress_zstd(_content: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
+    zstd_level(level)?;
+    Err(zstd_unavailable())
+}
+
 /// Decompress ZSTD
+#[cfg(feature = "zstd")]
 fn decompress_zstd(content: &[u8]) -> Result<Vec<u8>> {
     zstd::decode_all(content)
         .map_err(|e| crate::error::PodError::CryptoError(format!("ZSTD decompression failed: {}", e)))
 }
 
+/// Decompress ZSTD
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_content: &[u8]) -> Result<Vec<u8>> {
+    Err(zstd_unavailable())
+}
+
+/// Decompress ZSTD, failing if the output exceeds `limit` bytes
+#[cfg(feature = "zstd")]
+fn decompress_zstd_bounded(content: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let decoder = zstd::stream::read::Decoder::new(content)
+        .map_err(|e| crate::error::PodError::CryptoError(format!("ZSTD decompression failed: {}", e)))?;
+    read_bounded(decoder, limit, "ZSTD")
+}
+
+/// Decompress ZSTD, failing if the output exceeds `limit` bytes
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd_bounded(_content: &[u8], _limit: usize) -> Result<Vec<u8>> {
+    Err(zstd_unavailable())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unavailable() -> crate::error::PodError {
+    crate::error::PodError::CryptoError("ZSTD support requires the `zstd` feature".to_string())
+}
+
+/// Algorithms this build can compress with
+pub fn available_algorithms() -> &'static [CompressionAlgorithm] {
+    if cfg!(feature = "zstd") {
+        &[CompressionAlgorithm::Gzip, CompressionAlgorithm::Zstd]
+    } else {
+        &[CompressionAlgorithm::Gzip]
+    }
+}
+
 /// Detect compression algorithm from magic bytes
 fn detect_compression_algorithm(content: &[u8]) -> Result<CompressionAlgorithm> {
     if content.len() < 2 {
@@ -166,17 +274,17 @@ pub fn estimate_compression_ratio(
     let base_ratio = match algorithm {
         CompressionAlgorithm::Gzip => match level {
             CompressionLevel::Fast => 0.7,
-            CompressionLevel::Balanced => 0.6,
+            CompressionLevel::Balanced | CompressionLevel::Exact(_) => 0.6,
             CompressionLevel::Best => 0.5,
         },
         CompressionAlgorithm::Zstd => match level {
             CompressionLevel::Fast => 0.65,
-            CompressionLevel::Balanced => 0.55,
+            CompressionLevel::Balanced | CompressionLevel::Exact(_) => 0.55,
             CompressionLevel::Best => 0.45,
         },
         CompressionAlgorithm::Lz4 => match level {
             CompressionLevel::Fast => 0.8,
-            CompressionLevel::Balanced => 0.75,
+            CompressionLevel::Balanced | CompressionLevel::Exact(_) => 0.75,
             CompressionLevel::Best => 0.7,
         },
     };
@@ -279,7 +387,7 @@ pub fn find_best_compression(content: &[u8]) -> Result<(CompressionAlgorithm, Co
         return Ok((CompressionAlgorithm::Gzip, CompressionLevel::Fast));
     }
 
-    let algorithms = [CompressionAlgorithm::Gzip, CompressionAlgorithm::Zstd];
+    let algorithms = available_algorithms();
     let levels = [CompressionLevel::Fast, CompressionLevel::Balanced, CompressionLevel::Best];
     
     let mut best_ratio = f64::INFINITY;
@@ -290,7 +398,7 @@ pub fn find_best_compression(content: &[u8]) -> Result<(CompressionAlgorithm, Co
     let sample_size = content.len().min(1024);
     let sample = &content[..sample_size];
 
-    for &algorithm in &algorithms {
+    for &algorithm in algorithms {
         for &level in &levels {
             let estimated_ratio = estimate_compression_ratio(sample, algorithm, level);
             if estimated_ratio < best_ratio {
@@ -319,6 +427,7 @@ mod tests {
         assert!(compressed.len() < original.len()); // Should actually compress
     }
 
+    #[cfg(feature = "zstd")]
     #[test]
     fn test_different_algorithms() {
         let content = b"Test content for different compression algorithms. This should work with both GZIP and ZSTD.";
@@ -418,4 +527,39 @@ mod tests {
         
         assert!(high_entropy_factor > low_entropy_factor);
     }
-} 
\ No newline at end of file
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_roundtrip_at_every_level() {
+        let content = b"zstd roundtrip content, zstd roundtrip content, zstd roundtrip content".repeat(8);
+
+        for n in ZSTD_MIN_LEVEL..=ZSTD_MAX_LEVEL {
+            let compressed = compress_with_algorithm(&content, CompressionAlgorithm::Zstd, CompressionLevel::Exact(n))
+                .expect("ZSTD compression should succeed");
+            assert!(compressed.len() < content.len());
+            assert_eq!(detect_compression_algorithm(&compressed).unwrap(), CompressionAlgorithm::Zstd);
+            assert_eq!(decompress_message(&compressed).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_levels_rejected() {
+        let content = b"level checks";
+
+        for n in [0, ZSTD_MAX_LEVEL + 1] {
+            assert!(compress_with_algorithm(content, CompressionAlgorithm::Zstd, CompressionLevel::Exact(n)).is_err());
+        }
+        assert!(compress_with_algorithm(content, CompressionAlgorithm::Gzip, CompressionLevel::Exact(10)).is_err());
+        assert!(compress_with_algorithm(content, CompressionAlgorithm::Gzip, CompressionLevel::Exact(9)).is_ok());
+    }
+
+    #[test]
+    fn test_decompress_with_limit_stops_at_limit() {
+        let content = vec![0u8; 64 * 1024];
+        let compressed = compress_with_algorithm(&content, CompressionAlgorithm::Gzip, CompressionLevel::Best)
+            .expect("GZIP compression should succeed");
+
+        assert_eq!(decompress_with_limit(&compressed, CompressionAlgorithm::Gzip, content.len()).unwrap(), content);
+        assert!(decompress_with_limit(&compressed, CompressionAlgorithm::Gzip, content.len() - 1).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! # Bounded Account Decoding
+//!
+//! Borsh deserialization that cannot be tricked into large allocations by
+//! corrupt or malicious account data. Every read is checked against the bytes
+//! actually present, so a collection length prefix larger than the remaining
+//! data is rejected before its contents are allocated.
+
+use std::io::{self, Read};
+
+use anchor_lang::Discriminator;
+use borsh::BorshDeserialize;
+use solana_sdk::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+
+use crate::error::{PodComError, Result};
+
+/// Largest account data buffer accepted for decoding
+pub const MAX_ACCOUNT_DATA_LEN: usize = MAX_PERMITTED_DATA_LENGTH as usize;
+
+/// Length of an Anchor account discriminator
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// Reader over account data that refuses any read the data cannot satisfy
+struct BoundedReader<'a> {
+    remaining: &'a [u8],
+}
+
+impl Read for BoundedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Borsh sizes its read buffers from length prefixes; a request for more
+        // bytes than remain means the prefix is implausible.
+        if buf.len() > self.remaining.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "read of {} bytes exceeds {} remaining bytes",
+                    buf.len(),
+                    self.remaining.len()
+                ),
+            ));
+        }
+        let (head, tail) = self.remaining.split_at(buf.len());
+        buf.copy_from_slice(head);
+        self.remaining = tail;
+        Ok(buf.len())
+    }
+}
+
+/// Deserialize a Borsh value, bounding allocations by the size of `data`
+///
+/// Trailing bytes are allowed, since accounts are often over-allocated.
+pub fn deserialize_bounded<T: BorshDeserialize>(data: &[u8]) -> Result<T> {
+    if data.len() > MAX_ACCOUNT_DATA_LEN {
+        return Err(PodComError::MalformedAccountData {
+            reason: format!(
+                "{} bytes exceeds the maximum account size of {} bytes",
+                data.len(),
+                MAX_ACCOUNT_DATA_LEN
+            ),
+        });
+    }
+
+    T::deserialize_reader(&mut BoundedReader { remaining: data }).map_err(|e| {
+        PodComError::MalformedAccountData {
+            reason: e.to_string(),
+        }
+    })
+}
+
+/// Decode an Anchor account: check its discriminator, then deserialize the rest
+pub fn decode_account<T>(data: &[u8]) -> Result<T>
+where
+    T: Discriminator + BorshDeserialize,
+{
+    let expected: &[u8] = T::DISCRIMINATOR.as_ref();
+    if data.len() < DISCRIMINATOR_LEN || &data[..DISCRIMINATOR_LEN] != expected {
+        return Err(PodComError::MalformedAccountData {
+            reason: "account discriminator does not match".to_string(),
+        });
+    }
+
+    deserialize_bounded(&data[DISCRIMINATOR_LEN..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[derive(Debug, PartialEq, BorshDeserialize)]
+    struct Sample {
+        owner: Pubkey,
+        name: String,
+        members: Vec<Pubkey>,
+    }
+
+    fn encode(owner: &Pubkey, name: &str, members: &[Pubkey]) -> Vec<u8> {
+        let mut data = owner.to_bytes().to_vec();
+        data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(&(members.len() as u32).to_le_bytes());
+        for member in members {
+            data.extend_from_slice(member.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_valid_data_decodes() {
+        let owner = Pubkey::new_unique();
+        let members = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = encode(&owner, "agents", &members);
+        data.extend_from_slice(&[0u8; 16]); // account padding
+
+        let decoded: Sample = deserialize_bounded(&data).unwrap();
+        assert_eq!(decoded, Sample { owner, name: "agents".to_string(), members });
+    }
+
+    #[test]
+    fn test_oversized_length_prefix_rejected() {
+        let owner = Pubkey::new_unique();
+
+        // String claiming u32::MAX bytes
+        let mut data = owner.to_bytes().to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(b"short");
+        assert!(matches!(
+            deserialize_bounded::<Sample>(&data),
+            Err(PodComError::MalformedAccountData { .. })
+        ));
+
+        // Vec<Pubkey> claiming u32::MAX elements
+        let mut data = encode(&owner, "agents", &[]);
+        let prefix_at = data.len() - 4;
+        data[prefix_at..].copy_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&[7u8; 64]);
+        assert!(matches!(
+            deserialize_bounded::<Sample>(&data),
+            Err(PodComError::MalformedAccountData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fuzzed_buffers_never_panic() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x90d);
+        let valid = encode(&Pubkey::new_unique(), "channel", &[Pubkey::new_unique()]);
+
+        for _ in 0..2_000 {
+            let mut data = valid.clone();
+            let flips = rng.gen_range(1..8);
+            for _ in 0..flips {
+                let at = rng.gen_range(0..data.len());
+                data[at] = rng.gen();
+            }
+            data.truncate(rng.gen_range(0..=data.len()));
+
+            // Any outcome is fine as long as it returns rather than panicking
+            let _ = deserialize_bounded::<Sample>(&data);
+        }
+    }
+}
@@ -0,0 +1,517 @@
+//! # Payload Encoding Utilities
+//!
+//! Text encodings for small inline message payloads. The chosen encoding is
+//! recorded in a one-byte header so readers can decode without out-of-band
+//! information. When the header's high bit is set, a 16-byte request/response
+//! correlation id follows the header. When the next bit is set, a MIME content
+//! type follows as a length byte and UTF-8 text; only messages sealed with
+//! [`CONTENT_TYPE_PROTOCOL_VERSION`] or later may set it, so readers that
+//! predate content types reject tagged messages by version. Payloads written before the
+//! header existed carry raw bytes only and are read with
+//! [`decode_legacy_payload`].
+//!
+//! Stored message content is wrapped in a cleartext envelope carrying the
+//! protocol version, so incompatible messages are rejected before decryption.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use pod_sdk_crypto::{utils::constant_time_eq_fixed, Hash};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MessageError, Result};
+use crate::PROTOCOL_VERSION;
+
+/// Encoding applied to inline message payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PayloadEncoding {
+    /// Raw bytes, stored as-is
+    #[default]
+    Raw,
+    /// Standard base64 text
+    Base64,
+    /// Base58 text (Bitcoin alphabet)
+    Base58,
+}
+
+impl PayloadEncoding {
+    /// Header tag identifying this encoding
+    pub fn tag(&self) -> u8 {
+        match self {
+            PayloadEncoding::Raw => 0,
+            PayloadEncoding::Base64 => 1,
+            PayloadEncoding::Base58 => 2,
+        }
+    }
+
+    /// Resolve an encoding from its header tag
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(PayloadEncoding::Raw),
+            1 => Some(PayloadEncoding::Base64),
+            2 => Some(PayloadEncoding::Base58),
+            _ => None,
+        }
+    }
+
+    /// Encode raw payload bytes
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        match self {
+            PayloadEncoding::Raw => payload.to_vec(),
+            PayloadEncoding::Base64 => BASE64.encode(payload).into_bytes(),
+            PayloadEncoding::Base58 => bs58::encode(payload).into_string().into_bytes(),
+        }
+    }
+
+    /// Decode encoded payload bytes back to raw bytes
+    pub fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            PayloadEncoding::Raw => Ok(encoded.to_vec()),
+            PayloadEncoding::Base64 => BASE64.decode(encoded).map_err(|e| {
+                MessageError::InvalidContent {
+                    reason: format!("invalid base64 payload: {}", e),
+                }
+                .into()
+            }),
+            PayloadEncoding::Base58 => bs58::decode(encoded).into_vec().map_err(|e| {
+                MessageError::InvalidContent {
+                    reason: format!("invalid base58 payload: {}", e),
+                }
+                .into()
+            }),
+        }
+    }
+}
+
+/// Identifier linking a `Command` message to its `Response`
+pub type CorrelationId = [u8; 16];
+
+/// Header bit marking that a correlation id follows the encoding tag
+const CORRELATION_FLAG: u8 = 0x80;
+
+/// Header bit marking that a length-prefixed content type follows the correlation id
+const CONTENT_TYPE_FLAG: u8 = 0x40;
+
+/// Generate a random correlation id
+pub fn new_correlation_id() -> CorrelationId {
+    let mut id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut id);
+    id
+}
+
+/// A decoded inline payload together with its header fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlinePayload {
+    /// Encoding the payload was stored with
+    pub encoding: PayloadEncoding,
+    /// Request/response correlation id, if present
+    pub correlation_id: Option<CorrelationId>,
+    /// MIME type the sender tagged the payload with, if any
+    pub content_type: Option<String>,
+    /// Decoded payload bytes
+    pub payload: Vec<u8>,
+}
+
+/// Encode a payload and prefix it with its encoding tag
+pub fn encode_inline_payload(payload: &[u8], encoding: PayloadEncoding) -> Vec<u8> {
+    encode_inline_message(payload, encoding, None, None)
+}
+
+/// Encode a payload, prefixing it with its encoding tag, optional correlation
+/// id and optional content type
+///
+/// A content type longer than 255 bytes cannot be framed and is left out;
+/// callers validate it against [`pod_sdk_types::SUPPORTED_CONTENT_TYPES`] first.
+pub fn encode_inline_message(
+    payload: &[u8],
+    encoding: PayloadEncoding,
+    correlation_id: Option<&CorrelationId>,
+    content_type: Option<&str>,
+) -> Vec<u8> {
+    let encoded = encoding.encode(payload);
+    let content_type = content_type.map(str::as_bytes).filter(|bytes| bytes.len() <= u8::MAX as usize);
+    let mut inline = Vec::with_capacity(encoded.len() + 18 + content_type.map_or(0, <[u8]>::len));
+
+    let mut header = encoding.tag();
+    if correlation_id.is_some() {
+        header |= CORRELATION_FLAG;
+    }
+    if content_type.is_some() {
+        header |= CONTENT_TYPE_FLAG;
+    }
+    inline.push(header);
+    if let Some(id) = correlation_id {
+        inline.extend_from_slice(id);
+    }
+    if let Some(content_type) = content_type {
+        inline.push(content_type.len() as u8);
+        inline.extend_from_slice(content_type);
+    }
+    inline.extend_from_slice(&encoded);
+    inline
+}
+
+/// Read the encoding tag from an inline payload and decode the remainder
+pub fn decode_inline_payload(inline: &[u8]) -> Result<(PayloadEncoding, Vec<u8>)> {
+    let decoded = decode_inline_message(inline)?;
+    Ok((decoded.encoding, decoded.payload))
+}
+
+/// Decode an inline payload along with its header fields
+pub fn decode_inline_message(inline: &[u8]) -> Result<InlinePayload> {
+    decode_inline_header(inline, true)
+}
+
+/// Decode an inline payload, accepting a content type only if `content_type_allowed`
+fn decode_inline_header(inline: &[u8], content_type_allowed: bool) -> Result<InlinePayload> {
+    let (&header, rest) = inline.split_first().ok_or_else(|| MessageError::InvalidContent {
+        reason: "inline payload is missing its encoding header".to_string(),
+    })?;
+
+    let flags = if content_type_allowed { CORRELATION_FLAG | CONTENT_TYPE_FLAG } else { CORRELATION_FLAG };
+    let tag = header & !flags;
+    let encoding = PayloadEncoding::from_tag(tag).ok_or_else(|| MessageError::InvalidContent {
+        reason: format!("unknown payload encoding tag: {}", tag),
+    })?;
+
+    let (correlation_id, encoded) = if header & CORRELATION_FLAG != 0 {
+        if rest.len() < 16 {
+            return Err(MessageError::InvalidContent {
+                reason: "inline payload is missing its correlation id".to_string(),
+            }.into());
+        }
+        let (id, encoded) = rest.split_at(16);
+        let mut correlation_id = [0u8; 16];
+        correlation_id.copy_from_slice(id);
+        (Some(correlation_id), encoded)
+    } else {
+        (None, rest)
+    };
+
+    let (content_type, encoded) = if header & flags & CONTENT_TYPE_FLAG != 0 {
+        let missing = || MessageError::InvalidContent {
+            reason: "inline payload is missing its content type".to_string(),
+        };
+        let (&len, rest) = encoded.split_first().ok_or_else(missing)?;
+        if rest.len() < len as usize {
+            return Err(missing().into());
+        }
+        let (content_type, encoded) = rest.split_at(len as usize);
+        let content_type = String::from_utf8(content_type.to_vec()).map_err(|_| MessageError::InvalidContent {
+            reason: "inline payload content type is not UTF-8".to_string(),
+        })?;
+        (Some(content_type), encoded)
+    } else {
+        (None, encoded)
+    };
+
+    Ok(InlinePayload {
+        encoding,
+        correlation_id,
+        content_type,
+        payload: encoding.decode(encoded)?,
+    })
+}
+
+/// Read a payload written before inline headers existed
+///
+/// Legacy payloads are the raw message bytes with no encoding tag, so they
+/// cannot be told apart from headered ones by their content; callers pick
+/// this path from the stored message's protocol version.
+pub fn decode_legacy_payload(decoded: &[u8]) -> InlinePayload {
+    InlinePayload {
+        encoding: PayloadEncoding::Raw,
+        correlation_id: None,
+        content_type: None,
+        payload: decoded.to_vec(),
+    }
+}
+
+/// Major component of a protocol version
+pub fn protocol_major(version: u16) -> u8 {
+    (version >> 8) as u8
+}
+
+/// Marks stored message content that carries a protocol version
+const VERSION_MAGIC: &[u8; 4] = b"PODM";
+
+/// Magic followed by the little-endian protocol version
+const VERSION_HEADER_LEN: usize = VERSION_MAGIC.len() + 2;
+
+/// Protocol version of content stored before the envelope existed
+pub const LEGACY_PROTOCOL_VERSION: u16 = 0;
+
+/// Protocol version of messages whose inline header carries no content type
+pub const BASE_PROTOCOL_VERSION: u16 = 0x0100;
+
+/// First protocol version whose inline header may carry a content type
+pub const CONTENT_TYPE_PROTOCOL_VERSION: u16 = 0x0200;
+
+/// Oldest protocol version that can represent a message with these header fields
+///
+/// Untagged messages keep the base version so readers that predate content
+/// types can still read them.
+pub fn message_version(content_type: Option<&str>) -> u16 {
+    if content_type.is_some() {
+        CONTENT_TYPE_PROTOCOL_VERSION
+    } else {
+        BASE_PROTOCOL_VERSION
+    }
+}
+
+/// Prefix stored message content with its protocol version
+pub fn seal_versioned(version: u16, content: &[u8]) -> Vec<u8> {
+    let mut stored = Vec::with_capacity(content.len() + VERSION_HEADER_LEN);
+    stored.extend_from_slice(VERSION_MAGIC);
+    stored.extend_from_slice(&version.to_le_bytes());
+    stored.extend_from_slice(content);
+    stored
+}
+
+/// Check the protocol version of stored message content and return the content
+///
+/// Content without the version envelope predates it and is returned whole as
+/// [`LEGACY_PROTOCOL_VERSION`]. Messages from an older or equal major version
+/// are accepted; a newer major version fails with
+/// [`MessageError::UnsupportedProtocolVersion`].
+pub fn open_versioned(stored: &[u8]) -> Result<(u16, &[u8])> {
+    let Some(rest) = stored.strip_prefix(VERSION_MAGIC) else {
+        return Ok((LEGACY_PROTOCOL_VERSION, stored));
+    };
+    if rest.len() < 2 {
+        return Err(MessageError::InvalidContent {
+            reason: "stored message is missing its protocol version".to_string(),
+        }.into());
+    }
+    let (header, content) = rest.split_at(2);
+    let version = u16::from_le_bytes([header[0], header[1]]);
+
+    if protocol_major(version) > protocol_major(PROTOCOL_VERSION) {
+        return Err(MessageError::UnsupportedProtocolVersion {
+            version,
+            supported: PROTOCOL_VERSION,
+        }.into());
+    }
+
+    Ok((version, content))
+}
+
+/// Decode stored inline bytes according to the protocol version they were sealed with
+pub fn decode_stored_payload(version: u16, inline: &[u8]) -> Result<InlinePayload> {
+    if version == LEGACY_PROTOCOL_VERSION {
+        Ok(decode_legacy_payload(inline))
+    } else {
+        decode_inline_header(inline, version >= CONTENT_TYPE_PROTOCOL_VERSION)
+    }
+}
+
+/// Hash of the decoded payload bytes, independent of the encoding used to store them
+///
+/// This is the program's `secure_hash_data` (Blake3), so hashes computed here
+/// and on chain agree.
+pub fn payload_hash(decoded: &[u8]) -> [u8; 32] {
+    Hash::hash(decoded)
+}
+
+/// Check decoded payload bytes against a stored payload hash in constant time
+pub fn verify_payload_hash(decoded: &[u8], expected: &[u8; 32]) -> bool {
+    constant_time_eq_fixed(&payload_hash(decoded), expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: [PayloadEncoding; 3] = [
+        PayloadEncoding::Raw,
+        PayloadEncoding::Base64,
+        PayloadEncoding::Base58,
+    ];
+
+    #[test]
+    fn test_inline_payload_roundtrip() {
+        let payload = b"\x00\x01binary and text payload \xff\xfe";
+
+        for encoding in ENCODINGS {
+            let inline = encode_inline_payload(payload, encoding);
+            let (decoded_encoding, decoded) = decode_inline_payload(&inline)
+                .expect("Decoding should succeed");
+
+            assert_eq!(decoded_encoding, encoding);
+            assert_eq!(decoded.as_slice(), payload);
+        }
+    }
+
+    #[test]
+    fn test_payload_hash_is_encoding_independent() {
+        let payload = b"hash me the same way every time";
+        let expected = payload_hash(payload);
+
+        for encoding in ENCODINGS {
+            let inline = encode_inline_payload(payload, encoding);
+            let (_, decoded) = decode_inline_payload(&inline).unwrap();
+            assert_eq!(payload_hash(&decoded), expected);
+        }
+    }
+
+    #[test]
+    fn test_payload_hash_matches_program_hash() {
+        for payload in [&b"x"[..], b"hash me the same way every time", &[0u8; 300]] {
+            assert_eq!(payload_hash(payload), pod_com::secure_hash_data(payload).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_legacy_payload_is_raw() {
+        // Bytes that would parse as a headered Base64 payload are kept verbatim
+        let legacy = [PayloadEncoding::Base64.tag(), b'a', b'G', b'k', b'='];
+        let decoded = decode_legacy_payload(&legacy);
+
+        assert_eq!(decoded.encoding, PayloadEncoding::Raw);
+        assert_eq!(decoded.correlation_id, None);
+        assert_eq!(decoded.content_type, None);
+        assert_eq!(decoded.payload, legacy);
+    }
+
+    #[test]
+    fn test_verify_payload_hash() {
+        let payload = b"committed on chain";
+        let hash = payload_hash(payload);
+        assert!(verify_payload_hash(payload, &hash));
+        assert!(!verify_payload_hash(b"committed on chaiN", &hash));
+
+        let mut tampered = hash;
+        tampered[31] ^= 0x80;
+        assert!(!verify_payload_hash(payload, &tampered));
+    }
+
+    #[test]
+    fn test_text_encodings_are_printable() {
+        let payload = [0u8, 159, 255, 7];
+
+        for encoding in [PayloadEncoding::Base64, PayloadEncoding::Base58] {
+            let encoded = encoding.encode(&payload);
+            assert!(encoded.iter().all(|b| b.is_ascii_graphic()));
+        }
+    }
+
+    #[test]
+    fn test_invalid_inline_payload() {
+        assert!(decode_inline_payload(&[]).is_err());
+        assert!(decode_inline_payload(&[9, b'a']).is_err());
+        assert!(decode_inline_payload(&[PayloadEncoding::Base58.tag(), b'0']).is_err());
+        assert!(decode_inline_payload(&[CORRELATION_FLAG, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_content_type_roundtrip() {
+        let id = new_correlation_id();
+        let body = br#"{"ok":true}"#;
+
+        for correlation_id in [None, Some(&id)] {
+            let inline = encode_inline_message(body, PayloadEncoding::Base64, correlation_id, Some("application/json"));
+            let decoded = decode_inline_message(&inline).unwrap();
+
+            assert_eq!(decoded.encoding, PayloadEncoding::Base64);
+            assert_eq!(decoded.correlation_id.as_ref(), correlation_id);
+            assert_eq!(decoded.content_type.as_deref(), Some("application/json"));
+            assert_eq!(decoded.payload, body);
+        }
+
+        // Untagged payloads decode without a content type
+        let plain = decode_inline_message(&encode_inline_payload(body, PayloadEncoding::Raw)).unwrap();
+        assert_eq!(plain.content_type, None);
+
+        // A truncated content type is rejected
+        assert!(decode_inline_message(&[CONTENT_TYPE_FLAG, 16, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_correlation_id_roundtrip() {
+        let id = new_correlation_id();
+
+        for encoding in ENCODINGS {
+            let inline = encode_inline_message(b"status?", encoding, Some(&id), None);
+            let decoded = decode_inline_message(&inline).unwrap();
+
+            assert_eq!(decoded.encoding, encoding);
+            assert_eq!(decoded.correlation_id, Some(id));
+            assert_eq!(decoded.payload, b"status?");
+        }
+
+        let plain = decode_inline_message(&encode_inline_payload(b"hi", PayloadEncoding::Raw)).unwrap();
+        assert_eq!(plain.correlation_id, None);
+    }
+
+    fn stored_with_version(version: u16, content: &[u8]) -> Vec<u8> {
+        let mut stored = VERSION_MAGIC.to_vec();
+        stored.extend_from_slice(&version.to_le_bytes());
+        stored.extend_from_slice(content);
+        stored
+    }
+
+    #[test]
+    fn test_same_protocol_version_accepted() {
+        let stored = seal_versioned(PROTOCOL_VERSION, b"ciphertext");
+        let (version, content) = open_versioned(&stored).unwrap();
+
+        assert_eq!(version, PROTOCOL_VERSION);
+        assert_eq!(content, b"ciphertext");
+    }
+
+    #[test]
+    fn test_older_protocol_version_accepted() {
+        let older = PROTOCOL_VERSION - 0x0100;
+        let stored = stored_with_version(older, b"ciphertext");
+        let (version, content) = open_versioned(&stored).unwrap();
+
+        assert_eq!(version, older);
+        assert_eq!(content, b"ciphertext");
+
+        // A newer minor within the same major is still compatible
+        assert!(open_versioned(&stored_with_version(PROTOCOL_VERSION + 1, b"x")).is_ok());
+    }
+
+    #[test]
+    fn test_newer_protocol_version_rejected() {
+        let newer = PROTOCOL_VERSION + 0x0100;
+        // The body is not valid ciphertext; rejection must not depend on it
+        let stored = stored_with_version(newer, &[0xff; 3]);
+
+        let err = open_versioned(&stored).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::PodComError::Message(MessageError::UnsupportedProtocolVersion { version, .. })
+                if version == newer
+        ));
+        assert!(open_versioned(b"PODM\x01").is_err());
+    }
+
+    #[test]
+    fn test_unversioned_content_is_legacy() {
+        // Pre-envelope content is ciphertext whose first bytes can be anything
+        for stored in [&b"ciphertext"[..], &[0x00, 0x01, 0xaa], &[0xff, 0xff], &[]] {
+            let (version, content) = open_versioned(stored).unwrap();
+            assert_eq!(version, LEGACY_PROTOCOL_VERSION);
+            assert_eq!(content, stored);
+        }
+
+        let headerless = [PayloadEncoding::Base58.tag(), b'0'];
+        let legacy = decode_stored_payload(LEGACY_PROTOCOL_VERSION, &headerless).unwrap();
+        assert_eq!(legacy.payload, headerless);
+        assert!(decode_stored_payload(PROTOCOL_VERSION, &headerless).is_err());
+    }
+
+    #[test]
+    fn test_content_type_requires_its_protocol_version() {
+        let tagged = encode_inline_message(b"{}", PayloadEncoding::Raw, None, Some("application/json"));
+        let untagged = encode_inline_payload(b"{}", PayloadEncoding::Raw);
+
+        assert_eq!(message_version(Some("application/json")), CONTENT_TYPE_PROTOCOL_VERSION);
+        assert_eq!(message_version(None), BASE_PROTOCOL_VERSION);
+
+        let decoded = decode_stored_payload(CONTENT_TYPE_PROTOCOL_VERSION, &tagged).unwrap();
+        assert_eq!(decoded.content_type.as_deref(), Some("application/json"));
+        assert!(decode_stored_payload(BASE_PROTOCOL_VERSION, &tagged).is_err());
+        assert_eq!(decode_stored_payload(BASE_PROTOCOL_VERSION, &untagged).unwrap().payload, b"{}");
+    }
+}
@@ -0,0 +1,204 @@
+//! # Protocol Event Decoding
+//!
+//! Decodes the Anchor events emitted by the PoD program from transaction log
+//! messages. Each event is logged as a base64 `Program data:` line whose first
+//! eight bytes are the event discriminator, followed by the Borsh-encoded
+//! event fields.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::BorshDeserialize;
+use pod_sdk_types::{ChannelVisibility, MessageType};
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::utils::decode::deserialize_bounded;
+
+/// Log prefix used by the runtime for `sol_log_data` output
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Length of an Anchor event discriminator
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// Emitted when a new agent is registered
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct AgentRegisteredEvent {
+    pub agent: Pubkey,
+    pub capabilities: u64,
+    pub metadata_uri: String,
+    pub timestamp: i64,
+}
+
+/// Emitted when a direct message is sent
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct MessageSentEvent {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub message_type: MessageType,
+    pub timestamp: i64,
+}
+
+/// Emitted when a channel is created
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct ChannelCreatedEvent {
+    pub channel: Pubkey,
+    pub creator: Pubkey,
+    pub name: String,
+    pub visibility: ChannelVisibility,
+    pub timestamp: i64,
+}
+
+/// Emitted when escrowed funds are released back to their depositor
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct EscrowReleasedEvent {
+    pub channel: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Typed protocol event decoded from transaction logs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolEvent {
+    AgentRegistered(AgentRegisteredEvent),
+    MessageSent(MessageSentEvent),
+    ChannelCreated(ChannelCreatedEvent),
+    /// Decoded from the program's `EscrowWithdrawal` event
+    EscrowReleased(EscrowReleasedEvent),
+}
+
+impl ProtocolEvent {
+    /// Decode a single event payload (discriminator followed by fields)
+    ///
+    /// Returns `None` for unknown discriminators or malformed data.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < DISCRIMINATOR_LEN {
+            return None;
+        }
+        let (discriminator, fields) = data.split_at(DISCRIMINATOR_LEN);
+
+        let event = if discriminator == event_discriminator("AgentRegistered") {
+            ProtocolEvent::AgentRegistered(deserialize_bounded(fields).ok()?)
+        } else if discriminator == event_discriminator("MessageSent") {
+            ProtocolEvent::MessageSent(deserialize_bounded(fields).ok()?)
+        } else if discriminator == event_discriminator("ChannelCreated") {
+            ProtocolEvent::ChannelCreated(deserialize_bounded(fields).ok()?)
+        } else if discriminator == event_discriminator("EscrowWithdrawal") {
+            ProtocolEvent::EscrowReleased(deserialize_bounded(fields).ok()?)
+        } else {
+            return None;
+        };
+
+        Some(event)
+    }
+}
+
+/// Anchor discriminator for an event: the first 8 bytes of `sha256("event:<Name>")`
+pub fn event_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let digest = Sha256::digest(format!("event:{}", name).as_bytes());
+    let mut discriminator = [0u8; DISCRIMINATOR_LEN];
+    discriminator.copy_from_slice(&digest[..DISCRIMINATOR_LEN]);
+    discriminator
+}
+
+/// Decode all known protocol events from a transaction's log messages
+///
+/// Lines that are not `Program data:` entries, are not valid base64, or carry
+/// an unknown discriminator are skipped.
+pub fn decode_events(logs: &[String]) -> Vec<ProtocolEvent> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|encoded| BASE64.decode(encoded.trim()).ok())
+        .filter_map(|data| ProtocolEvent::from_bytes(&data))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `AgentRegistered` log line as emitted by the program
+    const AGENT_REGISTERED_LOG: &str = "Program data: v07ZNuhkvVUBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQMAAAAAAAAADAAAAGlwZnM6Ly9hZ2VudADxU2UAAAAA";
+
+    fn log_line(name: &str, fields: &[u8]) -> String {
+        let mut data = event_discriminator(name).to_vec();
+        data.extend_from_slice(fields);
+        format!("{}{}", PROGRAM_DATA_PREFIX, BASE64.encode(data))
+    }
+
+    #[test]
+    fn test_event_discriminator() {
+        assert_eq!(event_discriminator("AgentRegistered"), [191, 78, 217, 54, 232, 100, 189, 85]);
+        assert_eq!(event_discriminator("EscrowWithdrawal"), [76, 67, 48, 49, 180, 213, 250, 71]);
+    }
+
+    #[test]
+    fn test_decode_captured_agent_registered() {
+        let logs = vec![
+            "Program HEpGLgYsE1kP8aoYKyLFc3JVVrofS7T4zEA6fWBJsZps invoke [1]".to_string(),
+            "Program log: Instruction: RegisterAgent".to_string(),
+            AGENT_REGISTERED_LOG.to_string(),
+            "Program HEpGLgYsE1kP8aoYKyLFc3JVVrofS7T4zEA6fWBJsZps success".to_string(),
+        ];
+
+        let events = decode_events(&logs);
+
+        assert_eq!(events, vec![ProtocolEvent::AgentRegistered(AgentRegisteredEvent {
+            agent: Pubkey::new_from_array([1u8; 32]),
+            capabilities: 3,
+            metadata_uri: "ipfs://agent".to_string(),
+            timestamp: 1_700_000_000,
+        })]);
+    }
+
+    #[test]
+    fn test_decode_message_and_escrow_events() {
+        let (sender, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut message_fields = Vec::new();
+        message_fields.extend_from_slice(sender.as_ref());
+        message_fields.extend_from_slice(recipient.as_ref());
+        message_fields.push(2); // MessageType::Command
+        message_fields.extend_from_slice(&42i64.to_le_bytes());
+
+        let mut escrow_fields = Vec::new();
+        escrow_fields.extend_from_slice(recipient.as_ref());
+        escrow_fields.extend_from_slice(sender.as_ref());
+        escrow_fields.extend_from_slice(&500u64.to_le_bytes());
+        escrow_fields.extend_from_slice(&43i64.to_le_bytes());
+
+        let logs = vec![
+            log_line("MessageSent", &message_fields),
+            log_line("EscrowWithdrawal", &escrow_fields),
+        ];
+
+        assert_eq!(decode_events(&logs), vec![
+            ProtocolEvent::MessageSent(MessageSentEvent {
+                sender,
+                recipient,
+                message_type: MessageType::Command,
+                timestamp: 42,
+            }),
+            ProtocolEvent::EscrowReleased(EscrowReleasedEvent {
+                channel: recipient,
+                depositor: sender,
+                amount: 500,
+                timestamp: 43,
+            }),
+        ]);
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_lines_are_skipped() {
+        let logs = vec![
+            log_line("ParticipantJoined", &[0u8; 72]),
+            log_line("MessageSent", &[1, 2, 3]),
+            format!("{}not base64!", PROGRAM_DATA_PREFIX),
+            "Program data: AAAA".to_string(),
+            AGENT_REGISTERED_LOG.to_string(),
+        ];
+
+        let events = decode_events(&logs);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], ProtocolEvent::AgentRegistered(_)));
+    }
+}
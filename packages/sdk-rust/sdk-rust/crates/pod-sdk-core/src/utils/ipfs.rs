@@ -3,13 +3,35 @@
 //! IPFS integration utilities for the PoD Protocol.
 
 use std::time::{Duration, Instant};
-use crate::error::Result;
+use crate::error::{IpfsError, Result};
 use serde::{Deserialize, Serialize};
 use reqwest::multipart;
 
 /// IPFS content hash type
 pub type ContentHash = String;
 
+/// Shortest accepted CID string (a base58 CIDv0)
+pub const MIN_CID_LENGTH: usize = 46;
+/// Longest accepted CID string (a base32 CIDv1)
+pub const MAX_CID_LENGTH: usize = 59;
+
+/// Check that `cid` is a CIDv0 (`Qm...`) or base32 CIDv1 (`bafy...`, `bafk...`)
+pub fn validate_cid(cid: &str) -> std::result::Result<(), &'static str> {
+    if cid.is_empty() {
+        return Err("IPFS content hash cannot be empty");
+    }
+    
+    if cid.len() < MIN_CID_LENGTH || cid.len() > MAX_CID_LENGTH {
+        return Err("Invalid IPFS content hash length");
+    }
+    
+    if !cid.starts_with("Qm") && !cid.starts_with("bafy") && !cid.starts_with("bafk") {
+        return Err("Invalid IPFS content hash format");
+    }
+    
+    Ok(())
+}
+
 /// Pin status enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PinStatus {
@@ -61,6 +83,21 @@ impl IPFSClient {
         }
     }
 
+    /// Create new IPFS client that sends all requests through `proxy`
+    pub fn with_proxy(endpoint: String, proxy: reqwest::Proxy, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .proxy(proxy)
+            .build()?;
+
+        Ok(Self {
+            endpoint,
+            connected: false,
+            client,
+            timeout,
+        })
+    }
+
     /// Connect to IPFS node
     pub async fn connect(&mut self) -> Result<()> {
         // Test connection by calling version endpoint
@@ -97,71 +134,59 @@ impl IPFSClient {
         self.connected
     }
 
-    /// Add content to IPFS
-    pub async fn add_content(&self, content: &[u8]) -> Result<ContentHash> {
-        if !self.connected {
-            return Err(crate::error::PodError::NetworkError("IPFS client not connected".to_string()));
-        }
+    /// Check that the node answers `/api/v0/version`
+    pub async fn ping(&self) -> Result<()> {
+        self.get_node_version().await.map(|_| ())
+    }
 
-        let url = format!("{}/api/v0/add", self.endpoint);
-        
-        // Create multipart form with file data
-        let part = multipart::Part::bytes(content.to_vec())
-            .file_name("data")
-            .mime_str("application/octet-stream")
-            .map_err(|e| crate::error::PodError::CryptoError(format!("Failed to create multipart: {}", e)))?;
-        
-        let form = multipart::Form::new().part("file", part);
-        
+    /// Add `content` to the node with `/api/v0/add`, returning its CID
+    /// 
+    /// Like [`pin_add`](Self::pin_add) this does not require
+    /// [`connect`](Self::connect). The content is pinned as it is added.
+    pub async fn add_content(&self, content: &[u8]) -> Result<ContentHash> {
+        let part = multipart::Part::bytes(content.to_vec()).file_name("data");
         let response = self.client
-            .post(&url)
+            .post(self.api_url("add"))
             .query(&[("pin", "true"), ("quiet", "true")])
-            .multipart(form)
+            .multipart(multipart::Form::new().part("file", part))
             .send()
-            .await
-            .map_err(|e| crate::error::PodError::NetworkError(format!("IPFS add request failed: {}", e)))?;
+            .await?;
 
-        if !response.status().is_success() {
-            return Err(crate::error::PodError::NetworkError(
-                format!("IPFS add failed with status: {}", response.status())
-            ));
-        }
+        let body = api_response("add", response).await?.text().await?;
+        let added: IPFSAddResponse = serde_json::from_str(&body)?;
+        Ok(added.hash)
+    }
 
-        let response_text = response.text().await
-            .map_err(|e| crate::error::PodError::NetworkError(format!("Failed to read IPFS response: {}", e)))?;
-        
-        // Parse IPFS response (usually JSON with Hash field)
-        let add_response: IPFSAddResponse = serde_json::from_str(&response_text)
-            .map_err(|e| crate::error::PodError::CryptoError(format!("Failed to parse IPFS response: {}", e)))?;
-        
-        Ok(add_response.hash)
+    /// Read the content at `hash` with `/api/v0/cat`
+    pub async fn get_content(&self, hash: &str) -> Result<Vec<u8>> {
+        let response = self.cat_response(hash).await?;
+        Ok(response.bytes().await?.to_vec())
     }
 
-    /// Get content from IPFS
-    pub async fn get_content(&self, hash: &ContentHash) -> Result<Vec<u8>> {
-        if !self.connected {
-            return Err(crate::error::PodError::NetworkError("IPFS client not connected".to_string()));
+    /// Read the content at `hash`, failing as soon as more than `limit` bytes arrive
+    pub async fn get_content_bounded(&self, hash: &str, limit: usize) -> Result<Vec<u8>> {
+        let mut response = self.cat_response(hash).await?;
+        let mut content = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if content.len() + chunk.len() > limit {
+                return Err(IpfsError::DownloadFailed {
+                    reason: format!("content at {} exceeds {} bytes", hash, limit),
+                }.into());
+            }
+            content.extend_from_slice(&chunk);
         }
+        Ok(content)
+    }
 
-        let url = format!("{}/api/v0/cat", self.endpoint);
-        
+    /// Start reading the content at `hash` with `/api/v0/cat`
+    async fn cat_response(&self, hash: &str) -> Result<reqwest::Response> {
         let response = self.client
-            .post(&url)
+            .post(self.api_url("cat"))
             .query(&[("arg", hash)])
             .send()
-            .await
-            .map_err(|e| crate::error::PodError::NetworkError(format!("IPFS get request failed: {}", e)))?;
+            .await?;
 
-        if !response.status().is_success() {
-            return Err(crate::error::PodError::NetworkError(
-                format!("IPFS get failed with status: {}", response.status())
-            ));
-        }
-
-        let content = response.bytes().await
-            .map_err(|e| crate::error::PodError::NetworkError(format!("Failed to read IPFS content: {}", e)))?;
-        
-        Ok(content.to_vec())
+        api_response("cat", response).await
     }
 
     /// Pin content
@@ -212,6 +237,36 @@ impl IPFSClient {
         Ok(())
     }
 
+    /// Pin `cid` on the node with `/api/v0/pin/add`
+    /// 
+    /// Unlike [`pin_content`](Self::pin_content) this does not require
+    /// [`connect`](Self::connect); failures are reported as typed errors.
+    pub async fn pin_add(&self, cid: &str) -> Result<()> {
+        self.pin_command("pin/add", cid).await
+    }
+
+    /// Unpin `cid` from the node with `/api/v0/pin/rm`
+    pub async fn pin_rm(&self, cid: &str) -> Result<()> {
+        self.pin_command("pin/rm", cid).await
+    }
+
+    /// Run a pin API command on `cid`, surfacing error statuses as [`IpfsError::ApiError`]
+    async fn pin_command(&self, command: &str, cid: &str) -> Result<()> {
+        let response = self.client
+            .post(self.api_url(command))
+            .query(&[("arg", cid)])
+            .send()
+            .await?;
+
+        api_response(command, response).await?;
+        Ok(())
+    }
+
+    /// URL of an HTTP API command on the node
+    fn api_url(&self, command: &str) -> String {
+        format!("{}/api/v0/{}", self.endpoint.trim_end_matches('/'), command)
+    }
+
     /// Check pin status of content
     pub async fn check_pin_status(&self, hash: &ContentHash) -> Result<PinStatus> {
         if !self.connected {
@@ -427,6 +482,25 @@ impl IPFSClient {
     }
 }
 
+/// Pass a successful API response through, or turn its error status into [`IpfsError::ApiError`]
+async fn api_response(command: &str, response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    // The API reports failures as `{"Message": ..., "Code": ..., "Type": "error"}`
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<IPFSErrorResponse>(&body)
+        .map(|error| error.message)
+        .unwrap_or(body);
+    Err(IpfsError::ApiError {
+        command: command.to_string(),
+        status: status.as_u16(),
+        message,
+    }.into())
+}
+
 /// IPFS content statistics
 #[derive(Debug, Clone)]
 pub struct IPFSContentStats {
@@ -576,6 +650,12 @@ struct IPFSPinInfo {
     pin_type: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct IPFSErrorResponse {
+    #[serde(rename = "Message")]
+    message: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
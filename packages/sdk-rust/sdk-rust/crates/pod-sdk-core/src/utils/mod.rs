@@ -3,8 +3,25 @@
 //! Common utility functions and helpers for the PoD Protocol Rust SDK.
 
 pub mod account;
+pub mod cache;
 pub mod encryption;
 pub mod compression;
+pub mod decode;
+pub mod encoding;
+pub mod events;
 pub mod network;
+pub mod pagination;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+pub mod search;
+pub mod snapshot;
+pub mod stream;
+pub mod transport;
 pub mod ipfs;
-pub mod zk; 
\ No newline at end of file
+pub mod zk;
+
+pub use account::derive_agent_id;
+pub use cache::AccountCache;
+pub use events::{decode_events, ProtocolEvent};
+pub use pagination::{Page, PageRequest};
+pub use stream::DedupStream;
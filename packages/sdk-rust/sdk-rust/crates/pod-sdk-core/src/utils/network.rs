@@ -4,8 +4,11 @@
 
 use std::collections::{HashMap, HashSet, VecDeque, BinaryHeap};
 use std::cmp::Reverse;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use solana_sdk::pubkey::Pubkey;
-use crate::error::Result;
+use crate::config::RateLimitConfig;
+use crate::error::{PodComError, Result};
 use pod_sdk_crypto::Hash;
 
 /// Calculate distance between two agents using their public key similarity
@@ -311,6 +314,59 @@ impl NetworkTopology {
         distant_connections
     }
 
+    /// Breadth-first search for the fewest-hop path between two nodes.
+    ///
+    /// The returned path includes both endpoints; `None` means the nodes are
+    /// not connected.
+    pub fn shortest_path(&self, from: &Pubkey, to: &Pubkey) -> Option<Vec<Pubkey>> {
+        if from == to {
+            return Some(vec![*from]);
+        }
+        
+        let mut previous: HashMap<Pubkey, Pubkey> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        
+        visited.insert(*from);
+        queue.push_back(*from);
+        
+        while let Some(current) = queue.pop_front() {
+            let Some(neighbors) = self.connections.get(&current) else {
+                continue;
+            };
+            
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                previous.insert(neighbor, current);
+                
+                if neighbor == *to {
+                    // Walk back from the target to rebuild the path
+                    let mut path = vec![neighbor];
+                    let mut node = neighbor;
+                    while let Some(&prev) = previous.get(&node) {
+                        path.push(prev);
+                        node = prev;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                
+                queue.push_back(neighbor);
+            }
+        }
+        
+        None
+    }
+
+    /// Number of hops on the shortest path between two nodes
+    ///
+    /// A node is zero hops from itself; `None` means the nodes are not connected.
+    pub fn path_length(&self, from: &Pubkey, to: &Pubkey) -> Option<u32> {
+        self.shortest_path(from, to).map(|path| path.len() as u32 - 1)
+    }
+
     /// Get network statistics
     pub fn get_network_stats(&self) -> NetworkStats {
         NetworkStats {
@@ -499,6 +555,135 @@ impl NetworkStats {
     }
 }
 
+/// Token-bucket rate limiter backing [`RateLimitConfig`]
+///
+/// The bucket holds up to `burst_capacity` tokens and refills at
+/// `requests_per_second`. Every request takes one token, waiting for the
+/// bucket to refill when it is empty. On top of that, no more than
+/// `max_requests_per_window` requests start within any `window_duration`;
+/// a zero for either disables the window. Tokens are reserved before
+/// waiting, so concurrent callers are served in the order they arrive.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Mutex<TokenBucket>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// Available tokens; negative while callers are waiting on reservations
+    tokens: f64,
+    last_refill: Instant,
+    /// Start times of the latest requests, oldest first, at most one window's worth
+    window_starts: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with a full bucket
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::starting_at(config, Instant::now())
+    }
+
+    /// Create a limiter with a full bucket as of `now`
+    fn starting_at(config: RateLimitConfig, now: Instant) -> Self {
+        let bucket = TokenBucket {
+            tokens: Self::capacity(&config),
+            last_refill: now,
+            window_starts: VecDeque::new(),
+        };
+        Self { config, bucket: Mutex::new(bucket) }
+    }
+
+    /// Whether requests are limited at all
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Take a token, waiting until one is available
+    ///
+    /// Fails with [`PodComError::RateLimited`] without taking a token if the
+    /// wait would exceed `max_wait`.
+    pub async fn acquire(&self, operation: &str, max_wait: Duration) -> Result<()> {
+        let wait = self.reserve(max_wait).map_err(|retry_after| PodComError::RateLimited {
+            operation: operation.to_string(),
+            retry_after: Some(retry_after),
+        })?;
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Take a token only if one is available now
+    pub fn try_acquire(&self) -> bool {
+        self.reserve(Duration::ZERO).is_ok()
+    }
+
+    /// Reserve a token, returning how long to wait before it may be used
+    ///
+    /// On failure nothing is reserved and the required wait is returned.
+    fn reserve(&self, max_wait: Duration) -> std::result::Result<Duration, Duration> {
+        self.reserve_at(Instant::now(), max_wait)
+    }
+
+    /// [`reserve`](Self::reserve) as of `now`
+    fn reserve_at(&self, now: Instant, max_wait: Duration) -> std::result::Result<Duration, Duration> {
+        if !self.config.enabled {
+            return Ok(Duration::ZERO);
+        }
+
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        let rate = self.config.requests_per_second as f64;
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(Self::capacity(&self.config));
+        bucket.last_refill = now;
+
+        let token_wait = if bucket.tokens >= 1.0 {
+            Duration::ZERO
+        } else if rate > 0.0 {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / rate)
+        } else {
+            Duration::MAX
+        };
+        let wait = token_wait.max(self.window_wait(&bucket.window_starts, now));
+        if wait > max_wait {
+            return Err(wait);
+        }
+
+        bucket.tokens -= 1.0;
+        if let Some(limit) = self.window_limit() {
+            if bucket.window_starts.len() == limit {
+                bucket.window_starts.pop_front();
+            }
+            bucket.window_starts.push_back(now + wait);
+        }
+        Ok(wait)
+    }
+
+    /// Wait until a request started at `now` keeps within the window limit
+    fn window_wait(&self, window_starts: &VecDeque<Instant>, now: Instant) -> Duration {
+        match self.window_limit() {
+            // The oldest of the last `limit` starts must leave the window first
+            Some(limit) if window_starts.len() == limit => {
+                (window_starts[0] + self.config.window_duration).saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Requests allowed per window, if the window is enabled
+    fn window_limit(&self) -> Option<usize> {
+        let limit = self.config.max_requests_per_window as usize;
+        (limit > 0 && !self.config.window_duration.is_zero()).then_some(limit)
+    }
+
+    /// Bucket size; at least one token so requests can always get through
+    fn capacity(config: &RateLimitConfig) -> f64 {
+        config.burst_capacity.max(1) as f64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -627,6 +812,48 @@ mod tests {
         assert_eq!(network.nodes.len(), original_node_count);
     }
 
+    fn topology_from_edges(nodes: &[Pubkey], edges: &[(usize, usize)]) -> NetworkTopology {
+        let mut connections: HashMap<Pubkey, HashSet<Pubkey>> = nodes
+            .iter()
+            .map(|n| (*n, HashSet::new()))
+            .collect();
+        for &(a, b) in edges {
+            connections.get_mut(&nodes[a]).unwrap().insert(nodes[b]);
+            connections.get_mut(&nodes[b]).unwrap().insert(nodes[a]);
+        }
+        NetworkTopology::new(nodes.to_vec(), connections)
+    }
+
+    #[test]
+    fn test_shortest_path_direct_link() {
+        let nodes: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let topology = topology_from_edges(&nodes, &[(0, 1), (1, 2)]);
+        
+        assert_eq!(topology.shortest_path(&nodes[0], &nodes[1]), Some(vec![nodes[0], nodes[1]]));
+        assert_eq!(topology.path_length(&nodes[0], &nodes[1]), Some(1));
+        assert_eq!(topology.path_length(&nodes[0], &nodes[0]), Some(0));
+    }
+
+    #[test]
+    fn test_shortest_path_two_hops() {
+        let nodes: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        // 0-1-2-3 chain plus a shortcut 0-4-3
+        let topology = topology_from_edges(&nodes, &[(0, 1), (1, 2), (2, 3), (0, 4), (4, 3)]);
+        
+        assert_eq!(topology.shortest_path(&nodes[0], &nodes[3]), Some(vec![nodes[0], nodes[4], nodes[3]]));
+        assert_eq!(topology.path_length(&nodes[0], &nodes[3]), Some(2));
+        assert_eq!(topology.path_length(&nodes[3], &nodes[0]), Some(2));
+    }
+
+    #[test]
+    fn test_shortest_path_disconnected() {
+        let nodes: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let topology = topology_from_edges(&nodes, &[(0, 1), (2, 3)]);
+        
+        assert_eq!(topology.shortest_path(&nodes[0], &nodes[3]), None);
+        assert_eq!(topology.path_length(&nodes[0], &nodes[3]), None);
+    }
+
     #[test]
     fn test_health_score() {
         let network = create_test_network();
@@ -636,4 +863,86 @@ mod tests {
         assert!(health >= 0.0 && health <= 1.0);
         assert!(health > 0.5); // Should be reasonably healthy for connected network
     }
-} 
\ No newline at end of file
+
+    fn rate_limit(requests_per_second: u32, burst_capacity: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second,
+            max_requests_per_window: requests_per_second,
+            burst_capacity,
+            enabled: true,
+            window_duration: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_burst_then_waits() {
+        let start = Instant::now();
+        let limiter = RateLimiter::starting_at(rate_limit(10, 3), start);
+        let max_wait = Duration::from_secs(5);
+
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve_at(start, max_wait), Ok(Duration::ZERO));
+        }
+
+        // The bucket is empty, so the next request waits for one refill
+        assert_eq!(limiter.reserve_at(start, max_wait), Ok(Duration::from_millis(100)));
+        // ...and the one after that for the refill after it
+        assert_eq!(limiter.reserve_at(start, max_wait), Ok(Duration::from_millis(200)));
+
+        // Half a second later the reservations are paid off and three tokens are back
+        let later = start + Duration::from_millis(500);
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve_at(later, max_wait), Ok(Duration::ZERO));
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_enforces_window() {
+        let start = Instant::now();
+        let config = RateLimitConfig {
+            max_requests_per_window: 2,
+            window_duration: Duration::from_secs(60),
+            ..rate_limit(100, 100)
+        };
+        let limiter = RateLimiter::starting_at(config, start);
+        let max_wait = Duration::from_secs(120);
+
+        assert_eq!(limiter.reserve_at(start, max_wait), Ok(Duration::ZERO));
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(10), max_wait), Ok(Duration::ZERO));
+
+        // Plenty of tokens, but the window is full until the first request leaves it
+        let third = start + Duration::from_secs(20);
+        assert_eq!(limiter.reserve_at(third, Duration::from_secs(5)), Err(Duration::from_secs(40)));
+        assert_eq!(limiter.reserve_at(third, max_wait), Ok(Duration::from_secs(40)));
+
+        // The fourth waits for the second to leave, not just the first
+        assert_eq!(limiter.reserve_at(third, max_wait), Ok(Duration::from_secs(50)));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_rejects_long_waits() {
+        let limiter = RateLimiter::new(rate_limit(1, 1));
+        limiter.acquire("test", Duration::ZERO).await.unwrap();
+
+        match limiter.acquire("test", Duration::from_millis(10)).await {
+            Err(PodComError::RateLimited { operation, retry_after: Some(retry_after) }) => {
+                assert_eq!(operation, "test");
+                assert!(retry_after > Duration::from_millis(900));
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+        // A rejected request does not consume the next token
+        assert!(!limiter.try_acquire());
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let start = Instant::now();
+        let limiter = RateLimiter::starting_at(RateLimitConfig { enabled: false, ..rate_limit(0, 1) }, start);
+        assert!(!limiter.is_enabled());
+
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve_at(start, Duration::ZERO), Ok(Duration::ZERO));
+        }
+    }
+}
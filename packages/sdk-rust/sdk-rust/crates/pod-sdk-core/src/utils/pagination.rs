@@ -0,0 +1,143 @@
+//! # Pagination
+//!
+//! A uniform page type returned by the services' list methods. Cursors are
+//! opaque strings: pass a page's `next_cursor` back in the next
+//! [`PageRequest`] to continue where it left off.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ValidationError};
+
+/// Default number of items per page
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Request for a single page of results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// Cursor from a previous page, or `None` for the first page
+    pub cursor: Option<String>,
+    /// Maximum number of items to return
+    pub limit: usize,
+}
+
+impl PageRequest {
+    /// Request the first page
+    pub fn first(limit: usize) -> Self {
+        Self { cursor: None, limit }
+    }
+
+    /// Request the page following `cursor`
+    pub fn after(cursor: impl Into<String>, limit: usize) -> Self {
+        Self { cursor: Some(cursor.into()), limit }
+    }
+
+    /// Offset into the full result set encoded by the cursor
+    fn offset(&self) -> Result<usize> {
+        match &self.cursor {
+            None => Ok(0),
+            Some(cursor) => cursor.parse().map_err(|_| {
+                ValidationError::InvalidFormat {
+                    field: "cursor".to_string(),
+                    reason: format!("unrecognized cursor '{}'", cursor),
+                }
+                .into()
+            }),
+        }
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::first(DEFAULT_PAGE_SIZE)
+    }
+}
+
+/// A page of results from a list method
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// Cursor for the next page; `None` exactly when this is the last page
+    pub next_cursor: Option<String>,
+    /// Total number of items, when cheaply known
+    pub total_estimate: Option<u64>,
+}
+
+impl<T> Page<T> {
+    /// Slice a page out of an already sorted, complete result set
+    pub fn from_sorted(all: Vec<T>, request: &PageRequest) -> Result<Self> {
+        if request.limit == 0 {
+            return Err(ValidationError::OutOfRange {
+                field: "limit".to_string(),
+                value: "0".to_string(),
+                min: "1".to_string(),
+                max: "unbounded".to_string(),
+            }
+            .into());
+        }
+
+        let total = all.len();
+        let offset = request.offset()?.min(total);
+        let end = offset.saturating_add(request.limit).min(total);
+
+        Ok(Self {
+            items: all.into_iter().skip(offset).take(end - offset).collect(),
+            next_cursor: (end < total).then(|| end.to_string()),
+            total_estimate: Some(total as u64),
+        })
+    }
+
+    /// Whether this is the last page
+    pub fn is_last(&self) -> bool {
+        self.next_cursor.is_none()
+    }
+
+    /// Transform the items on this page, keeping the cursor
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total_estimate: self.total_estimate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_cursor_fetches_subsequent_page() {
+        let all: Vec<u32> = (0..7).collect();
+
+        let first = Page::from_sorted(all.clone(), &PageRequest::first(3)).unwrap();
+        assert_eq!(first.items, vec![0, 1, 2]);
+        assert_eq!(first.total_estimate, Some(7));
+
+        let cursor = first.next_cursor.clone().expect("more pages remain");
+        let second = Page::from_sorted(all.clone(), &PageRequest::after(cursor, 3)).unwrap();
+        assert_eq!(second.items, vec![3, 4, 5]);
+
+        let cursor = second.next_cursor.clone().expect("more pages remain");
+        let last = Page::from_sorted(all, &PageRequest::after(cursor, 3)).unwrap();
+        assert_eq!(last.items, vec![6]);
+        assert!(last.is_last());
+    }
+
+    #[test]
+    fn test_exact_final_page_has_no_cursor() {
+        let page = Page::from_sorted(vec![1, 2, 3, 4], &PageRequest::after("2", 2)).unwrap();
+        assert_eq!(page.items, vec![3, 4]);
+        assert_eq!(page.next_cursor, None);
+
+        let empty = Page::<u8>::from_sorted(vec![], &PageRequest::default()).unwrap();
+        assert!(empty.items.is_empty());
+        assert!(empty.is_last());
+    }
+
+    #[test]
+    fn test_invalid_page_request() {
+        assert!(Page::from_sorted(vec![1], &PageRequest::after("not-a-cursor", 1)).is_err());
+        assert!(Page::from_sorted(vec![1], &PageRequest::first(0)).is_err());
+    }
+}
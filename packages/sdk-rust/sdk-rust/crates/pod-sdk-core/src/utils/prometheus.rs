@@ -0,0 +1,107 @@
+//! # Prometheus Exporter
+//!
+//! Renders [`ServiceMetrics`] in the Prometheus text exposition format so
+//! operators can scrape them. Every sample carries a `service` label, and
+//! per-operation samples an `operation` label as well.
+
+use std::fmt::Write;
+
+use crate::services::ServiceMetrics;
+
+/// Latency quantiles reported for each operation; each is one of [`SUMMARY_PERCENTILES`](crate::services::base::SUMMARY_PERCENTILES)
+pub const LATENCY_QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Render the metrics of each named service as Prometheus text
+pub fn render_metrics<'a, I>(services: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, ServiceMetrics)>,
+{
+    let services: Vec<_> = services.into_iter().collect();
+    let mut out = String::new();
+
+    header(&mut out, "pod_service_operations_total", "counter", "Operations performed by the service.");
+    for (service, metrics) in &services {
+        sample(&mut out, "pod_service_operations_total", &[("service", service)], metrics.operations_count as f64);
+    }
+
+    header(&mut out, "pod_service_errors_total", "counter", "Operations that failed.");
+    for (service, metrics) in &services {
+        sample(&mut out, "pod_service_errors_total", &[("service", service)], metrics.failed_operations as f64);
+    }
+
+    header(&mut out, "pod_operation_latency_seconds", "summary", "Latency of each operation.");
+    for (service, metrics) in &services {
+        let mut operations: Vec<_> = metrics.latencies.operations().collect();
+        operations.sort_unstable();
+
+        for operation in operations {
+            let labels = [("service", *service), ("operation", operation)];
+            for quantile in LATENCY_QUANTILES {
+                let latency = metrics.percentile(operation, quantile * 100.0);
+                let quantile = quantile.to_string();
+                sample(
+                    &mut out,
+                    "pod_operation_latency_seconds",
+                    &[labels[0], labels[1], ("quantile", &quantile)],
+                    latency.as_secs_f64(),
+                );
+            }
+            sample(&mut out, "pod_operation_latency_seconds_sum", &labels, metrics.latencies.total(operation).as_secs_f64());
+            sample(&mut out, "pod_operation_latency_seconds_count", &labels, metrics.count(operation) as f64);
+        }
+    }
+
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+        .collect();
+    let _ = writeln!(out, "{name}{{{}}} {value}", labels.join(","));
+}
+
+/// Escape a label value as the exposition format requires
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PodComError;
+    use crate::services::base::ServiceBase;
+
+    #[tokio::test]
+    async fn test_output_parses_as_prometheus_text() {
+        let base = ServiceBase::new(crate::config::test_config()).with_service_name("agent");
+        base.execute_operation("register_agent", || async { Ok::<_, PodComError>(()) }).await.unwrap();
+
+        let text = render_metrics([("agent", base.metrics_snapshot()), ("message", ServiceMetrics::default())]);
+        let scrape = prometheus_parse::Scrape::parse(text.lines().map(|line| Ok(line.to_string()))).unwrap();
+
+        let operations = scrape.samples
+            .iter()
+            .find(|s| s.metric == "pod_service_operations_total" && s.labels.get("service") == Some("agent"))
+            .expect("operation count for agent");
+        assert!(matches!(operations.value, prometheus_parse::Value::Counter(count) if count == 1.0));
+
+        let latency = scrape.samples
+            .iter()
+            .find(|s| s.metric == "pod_operation_latency_seconds")
+            .expect("latency summary");
+        assert_eq!(latency.labels.get("operation"), Some("register_agent"));
+        assert!(matches!(latency.value, prometheus_parse::Value::Summary(_)));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
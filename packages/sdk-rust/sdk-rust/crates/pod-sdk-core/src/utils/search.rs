@@ -0,0 +1,271 @@
+//! # Local Message Search
+//!
+//! A small on-device inverted index over decrypted message content. Terms are
+//! stored as keyed hashes rather than plaintext, and the index is only ever
+//! serialized in encrypted form, so nothing readable leaves the device.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use pod_sdk_crypto::{Hash, SecureRng};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::{PodComError, Result};
+use crate::utils::encryption::{decrypt_content, encrypt_content};
+
+/// Default maximum number of (term, message) postings held by the index
+pub const DEFAULT_SEARCH_INDEX_CAPACITY: usize = 100_000;
+
+/// Keyed inverted index over decrypted message content
+pub struct MessageSearchIndex {
+    /// Key used to blind indexed terms
+    term_key: [u8; 32],
+    /// Blinded term -> messages containing it
+    postings: HashMap<[u8; 32], HashSet<Pubkey>>,
+    /// Message -> blinded terms it contributed
+    documents: HashMap<Pubkey, HashSet<[u8; 32]>>,
+    /// Messages in insertion order, oldest first
+    insertion_order: VecDeque<Pubkey>,
+    /// Current number of postings
+    entries: usize,
+    /// Maximum number of postings before old messages are evicted
+    max_entries: usize,
+}
+
+impl std::fmt::Debug for MessageSearchIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageSearchIndex")
+            .field("term_key", &"<redacted>")
+            .field("documents", &self.documents.len())
+            .field("entries", &self.entries)
+            .field("max_entries", &self.max_entries)
+            .finish()
+    }
+}
+
+/// Serialized form of the index, only ever stored encrypted
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    term_key: [u8; 32],
+    documents: Vec<(Pubkey, Vec<[u8; 32]>)>,
+    max_entries: usize,
+}
+
+impl MessageSearchIndex {
+    /// Create an empty index with a fresh random term key
+    pub fn new(max_entries: usize) -> Result<Self> {
+        let mut term_key = [0u8; 32];
+        SecureRng::new()?.fill_bytes(&mut term_key)?;
+        Ok(Self::with_key(term_key, max_entries))
+    }
+
+    /// Create an empty index using the given term key
+    pub fn with_key(term_key: [u8; 32], max_entries: usize) -> Self {
+        Self {
+            term_key,
+            postings: HashMap::new(),
+            documents: HashMap::new(),
+            insertion_order: VecDeque::new(),
+            entries: 0,
+            max_entries,
+        }
+    }
+
+    /// Index a message's decrypted content, replacing any previous entry
+    pub fn insert(&mut self, message: Pubkey, content: &[u8]) {
+        self.remove(&message);
+
+        let text = String::from_utf8_lossy(content);
+        let terms: HashSet<[u8; 32]> = tokenize(&text)
+            .map(|term| self.blind(&term))
+            .collect();
+
+        if terms.is_empty() || terms.len() > self.max_entries {
+            return;
+        }
+
+        // Evict the oldest messages until the new postings fit
+        while self.entries + terms.len() > self.max_entries {
+            match self.insertion_order.pop_front() {
+                Some(oldest) => self.remove(&oldest),
+                None => break,
+            }
+        }
+
+        for term in &terms {
+            self.postings.entry(*term).or_default().insert(message);
+        }
+        self.entries += terms.len();
+        self.documents.insert(message, terms);
+        self.insertion_order.push_back(message);
+    }
+
+    /// Remove a message from the index
+    pub fn remove(&mut self, message: &Pubkey) {
+        let Some(terms) = self.documents.remove(message) else {
+            return;
+        };
+
+        for term in &terms {
+            if let Some(messages) = self.postings.get_mut(term) {
+                messages.remove(message);
+                if messages.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.entries -= terms.len();
+        self.insertion_order.retain(|m| m != message);
+    }
+
+    /// Find messages containing every term in the query
+    pub fn search(&self, query: &str) -> Vec<Pubkey> {
+        let mut matches: Option<HashSet<Pubkey>> = None;
+
+        for term in tokenize(query) {
+            let hits = self.postings
+                .get(&self.blind(&term))
+                .cloned()
+                .unwrap_or_default();
+
+            matches = Some(match matches {
+                Some(current) => current.intersection(&hits).copied().collect(),
+                None => hits,
+            });
+        }
+
+        let mut results: Vec<Pubkey> = matches.unwrap_or_default().into_iter().collect();
+        results.sort();
+        results
+    }
+
+    /// Number of indexed messages
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether the index holds no messages
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Number of (term, message) postings currently held
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Serialize and encrypt the index for storage
+    pub fn to_encrypted_bytes(&self, storage_key: &[u8]) -> Result<Vec<u8>> {
+        let persisted = PersistedIndex {
+            term_key: self.term_key,
+            documents: self.insertion_order
+                .iter()
+                .filter_map(|m| {
+                    self.documents.get(m).map(|terms| (*m, terms.iter().copied().collect()))
+                })
+                .collect(),
+            max_entries: self.max_entries,
+        };
+
+        let plaintext = bincode::serialize(&persisted).map_err(|e| PodComError::Internal {
+            message: format!("Failed to serialize search index: {}", e),
+        })?;
+        encrypt_content(&plaintext, storage_key)
+    }
+
+    /// Decrypt and restore an index previously produced by [`Self::to_encrypted_bytes`]
+    pub fn from_encrypted_bytes(data: &[u8], storage_key: &[u8]) -> Result<Self> {
+        let plaintext = decrypt_content(data, storage_key)?;
+        let persisted: PersistedIndex = bincode::deserialize(&plaintext).map_err(|e| PodComError::Internal {
+            message: format!("Failed to deserialize search index: {}", e),
+        })?;
+
+        let mut index = Self::with_key(persisted.term_key, persisted.max_entries);
+        for (message, terms) in persisted.documents {
+            let terms: HashSet<[u8; 32]> = terms.into_iter().collect();
+            for term in &terms {
+                index.postings.entry(*term).or_default().insert(message);
+            }
+            index.entries += terms.len();
+            index.documents.insert(message, terms);
+            index.insertion_order.push_back(message);
+        }
+        Ok(index)
+    }
+
+    fn blind(&self, term: &str) -> [u8; 32] {
+        Hash::hash_keyed(&self.term_key, term.as_bytes())
+    }
+}
+
+/// Split text into lowercase alphanumeric terms
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_with_messages() -> (MessageSearchIndex, Pubkey, Pubkey, Pubkey) {
+        let mut index = MessageSearchIndex::with_key([7u8; 32], DEFAULT_SEARCH_INDEX_CAPACITY);
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        index.insert(a, b"Deploy the staging agent tonight");
+        index.insert(b, b"Staging looks healthy, deploy approved");
+        index.insert(c, b"Lunch order: noodles");
+
+        (index, a, b, c)
+    }
+
+    #[test]
+    fn test_search_contained_term() {
+        let (index, a, b, c) = index_with_messages();
+
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(index.search("deploy"), expected);
+        assert_eq!(index.search("STAGING"), expected);
+        assert_eq!(index.search("noodles"), vec![c]);
+        assert_eq!(index.search("deploy approved"), vec![b]);
+    }
+
+    #[test]
+    fn test_search_missing_term() {
+        let (index, ..) = index_with_messages();
+
+        assert!(index.search("rollback").is_empty());
+        assert!(index.search("deploy noodles").is_empty());
+        assert!(index.search("").is_empty());
+    }
+
+    #[test]
+    fn test_index_respects_capacity() {
+        let mut index = MessageSearchIndex::with_key([1u8; 32], 4);
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        index.insert(first, b"alpha beta gamma");
+        index.insert(second, b"delta epsilon");
+
+        assert!(index.entries() <= 4);
+        assert!(index.search("alpha").is_empty());
+        assert_eq!(index.search("delta"), vec![second]);
+    }
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let (index, a, b, _) = index_with_messages();
+        let storage_key = [42u8; 32];
+
+        let encrypted = index.to_encrypted_bytes(&storage_key).unwrap();
+        assert!(!encrypted.windows(6).any(|w| w == b"deploy"));
+
+        let restored = MessageSearchIndex::from_encrypted_bytes(&encrypted, &storage_key).unwrap();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(restored.search("deploy"), expected);
+        assert!(MessageSearchIndex::from_encrypted_bytes(&encrypted, &[0u8; 32]).is_err());
+    }
+}
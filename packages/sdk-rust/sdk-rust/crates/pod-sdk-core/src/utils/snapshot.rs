@@ -0,0 +1,460 @@
+//! # Protocol Snapshots
+//!
+//! A streaming archive format for backing up protocol accounts. A snapshot is
+//! an 8-byte magic, a compression flag, and then a body (optionally zstd
+//! compressed) of typed records:
+//!
+//! ```text
+//! record  := kind:u8 address:[u8; 32] len:u32le data:[u8; len]
+//! trailer := 0x00 manifest-json-len:u32le manifest-json
+//! ```
+//!
+//! Records are written and read one at a time, so neither side ever holds the
+//! whole archive in memory. The trailing manifest records per-kind counts and
+//! a Blake3 checksum over every record, which the reader verifies.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use anchor_lang::Discriminator;
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::{PodComError, Result};
+use crate::utils::decode::{decode_account, MAX_ACCOUNT_DATA_LEN};
+
+/// Leading bytes identifying a snapshot archive
+const SNAPSHOT_MAGIC: &[u8; 8] = b"PODSNAP1";
+/// Record tag marking the end of records and the start of the manifest
+const END_OF_RECORDS: u8 = 0;
+/// zstd level used for compressed snapshots
+#[cfg(feature = "zstd")]
+const SNAPSHOT_ZSTD_LEVEL: i32 = 3;
+/// Upper bound on the encoded manifest, which holds only counts and a hash
+const MAX_MANIFEST_LEN: usize = 64 * 1024;
+
+/// Kind of protocol account stored in a snapshot record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SnapshotKind {
+    /// Agent account
+    Agent,
+    /// Channel account
+    Channel,
+    /// Message account
+    Message,
+    /// Escrow account
+    Escrow,
+}
+
+impl SnapshotKind {
+    /// Every kind, in export order
+    pub const ALL: [SnapshotKind; 4] = [
+        SnapshotKind::Agent,
+        SnapshotKind::Channel,
+        SnapshotKind::Message,
+        SnapshotKind::Escrow,
+    ];
+
+    fn tag(self) -> u8 {
+        match self {
+            SnapshotKind::Agent => 1,
+            SnapshotKind::Channel => 2,
+            SnapshotKind::Message => 3,
+            SnapshotKind::Escrow => 4,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.tag() == tag)
+    }
+}
+
+/// Summary written at the end of a snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Number of agent accounts
+    pub agents: u64,
+    /// Number of channel accounts
+    pub channels: u64,
+    /// Number of message accounts
+    pub messages: u64,
+    /// Number of escrow accounts
+    pub escrows: u64,
+    /// Blake3 hash over every record, hex-encoded
+    pub checksum: String,
+    /// Whether the record body is zstd compressed
+    pub compressed: bool,
+}
+
+impl SnapshotManifest {
+    /// Number of accounts of `kind`
+    pub fn count(&self, kind: SnapshotKind) -> u64 {
+        match kind {
+            SnapshotKind::Agent => self.agents,
+            SnapshotKind::Channel => self.channels,
+            SnapshotKind::Message => self.messages,
+            SnapshotKind::Escrow => self.escrows,
+        }
+    }
+
+    fn increment(&mut self, kind: SnapshotKind) {
+        match kind {
+            SnapshotKind::Agent => self.agents += 1,
+            SnapshotKind::Channel => self.channels += 1,
+            SnapshotKind::Message => self.messages += 1,
+            SnapshotKind::Escrow => self.escrows += 1,
+        }
+    }
+
+    /// Total number of accounts
+    pub fn total(&self) -> u64 {
+        self.agents + self.channels + self.messages + self.escrows
+    }
+}
+
+/// Record body sink, compressed or not
+enum Body<W: Write> {
+    Plain(W),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> Write for Body<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Body::Plain(w) => w.write(buf),
+            #[cfg(feature = "zstd")]
+            Body::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Body::Plain(w) => w.flush(),
+            #[cfg(feature = "zstd")]
+            Body::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+/// Streaming snapshot writer
+pub struct SnapshotWriter<W: Write> {
+    body: Body<W>,
+    hasher: blake3::Hasher,
+    manifest: SnapshotManifest,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Start a snapshot on `writer`, compressing the body if requested
+    pub fn new(mut writer: W, compress: bool) -> Result<Self> {
+        writer.write_all(SNAPSHOT_MAGIC).map_err(io_error)?;
+        writer.write_all(&[compress as u8]).map_err(io_error)?;
+
+        let body = if compress {
+            zstd_body(writer)?
+        } else {
+            Body::Plain(writer)
+        };
+
+        Ok(Self {
+            body,
+            hasher: blake3::Hasher::new(),
+            manifest: SnapshotManifest {
+                compressed: compress,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Append one account record
+    pub fn write_account(&mut self, kind: SnapshotKind, address: &Pubkey, data: &[u8]) -> Result<()> {
+        let header = record_header(kind, address, data.len())?;
+        self.hasher.update(&header);
+        self.hasher.update(data);
+
+        self.body.write_all(&header).map_err(io_error)?;
+        self.body.write_all(data).map_err(io_error)?;
+        self.manifest.increment(kind);
+        Ok(())
+    }
+
+    /// Write the manifest trailer and flush, returning the manifest
+    pub fn finish(mut self) -> Result<SnapshotManifest> {
+        self.manifest.checksum = self.hasher.finalize().to_hex().to_string();
+
+        let manifest = serde_json::to_vec(&self.manifest).map_err(|e| PodComError::MalformedAccountData {
+            reason: format!("failed to encode snapshot manifest: {}", e),
+        })?;
+        self.body.write_all(&[END_OF_RECORDS]).map_err(io_error)?;
+        self.body.write_all(&(manifest.len() as u32).to_le_bytes()).map_err(io_error)?;
+        self.body.write_all(&manifest).map_err(io_error)?;
+
+        match self.body {
+            Body::Plain(mut w) => w.flush().map_err(io_error)?,
+            #[cfg(feature = "zstd")]
+            Body::Zstd(encoder) => encoder.finish().map_err(io_error)?.flush().map_err(io_error)?,
+        }
+
+        Ok(self.manifest)
+    }
+}
+
+/// Read a snapshot record by record, passing each to `visit`
+///
+/// The recomputed counts and checksum must match the trailing manifest, so a
+/// truncated or altered archive is rejected. Records already visited when the
+/// mismatch is found are not rolled back; callers loading into a cache should
+/// stage records and commit only on success.
+pub fn read_snapshot<R, F>(mut reader: R, mut visit: F) -> Result<SnapshotManifest>
+where
+    R: Read,
+    F: FnMut(SnapshotKind, Pubkey, Vec<u8>) -> Result<()>,
+{
+    let mut header = [0u8; 9];
+    reader.read_exact(&mut header).map_err(io_error)?;
+    if &header[..8] != SNAPSHOT_MAGIC {
+        return Err(malformed("not a snapshot archive"));
+    }
+    let compressed = match header[8] {
+        0 => false,
+        1 => true,
+        other => return Err(malformed(&format!("unknown compression flag {}", other))),
+    };
+
+    let mut body: Box<dyn Read> = if compressed {
+        zstd_reader(reader)?
+    } else {
+        Box::new(reader)
+    };
+
+    let mut hasher = blake3::Hasher::new();
+    let mut seen = SnapshotManifest {
+        compressed,
+        ..Default::default()
+    };
+
+    loop {
+        let mut tag = [0u8; 1];
+        body.read_exact(&mut tag).map_err(io_error)?;
+        if tag[0] == END_OF_RECORDS {
+            break;
+        }
+
+        let kind = SnapshotKind::from_tag(tag[0])
+            .ok_or_else(|| malformed(&format!("unknown record kind {}", tag[0])))?;
+        let mut rest = [0u8; 36];
+        body.read_exact(&mut rest).map_err(io_error)?;
+        let address = Pubkey::new_from_array(rest[..32].try_into().expect("slice is 32 bytes"));
+        let len = u32::from_le_bytes(rest[32..].try_into().expect("slice is 4 bytes")) as usize;
+        if len > MAX_ACCOUNT_DATA_LEN {
+            return Err(malformed(&format!("record of {} bytes exceeds the account size limit", len)));
+        }
+
+        let mut data = vec![0u8; len];
+        body.read_exact(&mut data).map_err(io_error)?;
+
+        hasher.update(&tag);
+        hasher.update(&rest);
+        hasher.update(&data);
+        seen.increment(kind);
+        visit(kind, address, data)?;
+    }
+
+    let mut len = [0u8; 4];
+    body.read_exact(&mut len).map_err(io_error)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MANIFEST_LEN {
+        return Err(malformed("snapshot manifest is too large"));
+    }
+    let mut manifest = vec![0u8; len];
+    body.read_exact(&mut manifest).map_err(io_error)?;
+    let manifest: SnapshotManifest = serde_json::from_slice(&manifest)
+        .map_err(|e| malformed(&format!("invalid manifest: {}", e)))?;
+
+    seen.checksum = hasher.finalize().to_hex().to_string();
+    if seen != manifest {
+        return Err(malformed("snapshot contents do not match its manifest"));
+    }
+
+    Ok(manifest)
+}
+
+/// Accounts loaded from a snapshot, kept as raw account data
+#[derive(Debug, Default)]
+pub struct SnapshotCache {
+    accounts: HashMap<SnapshotKind, HashMap<Pubkey, Vec<u8>>>,
+}
+
+impl SnapshotCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the cache contents with a verified snapshot
+    ///
+    /// The existing contents are kept if the snapshot fails verification.
+    pub fn load<R: Read>(&mut self, reader: R) -> Result<SnapshotManifest> {
+        let mut staged = Self::new();
+        let manifest = read_snapshot(reader, |kind, address, data| {
+            staged.insert(kind, address, data);
+            Ok(())
+        })?;
+
+        *self = staged;
+        Ok(manifest)
+    }
+
+    /// Store raw account data
+    pub fn insert(&mut self, kind: SnapshotKind, address: Pubkey, data: Vec<u8>) {
+        self.accounts.entry(kind).or_default().insert(address, data);
+    }
+
+    /// Number of cached accounts of `kind`
+    pub fn len(&self, kind: SnapshotKind) -> usize {
+        self.accounts.get(&kind).map_or(0, HashMap::len)
+    }
+
+    /// Whether the cache holds no accounts
+    pub fn is_empty(&self) -> bool {
+        self.accounts.values().all(HashMap::is_empty)
+    }
+
+    /// Raw data for a cached account
+    pub fn get_raw(&self, kind: SnapshotKind, address: &Pubkey) -> Option<&[u8]> {
+        self.accounts.get(&kind)?.get(address).map(Vec::as_slice)
+    }
+
+    /// Decode a cached account as `T`
+    pub fn get<T>(&self, kind: SnapshotKind, address: &Pubkey) -> Option<Result<T>>
+    where
+        T: Discriminator + BorshDeserialize,
+    {
+        self.get_raw(kind, address).map(decode_account)
+    }
+}
+
+fn record_header(kind: SnapshotKind, address: &Pubkey, len: usize) -> Result<[u8; 37]> {
+    let len = u32::try_from(len)
+        .ok()
+        .filter(|len| *len as usize <= MAX_ACCOUNT_DATA_LEN)
+        .ok_or_else(|| malformed(&format!("record of {} bytes exceeds the account size limit", len)))?;
+
+    let mut header = [0u8; 37];
+    header[0] = kind.tag();
+    header[1..33].copy_from_slice(address.as_ref());
+    header[33..].copy_from_slice(&len.to_le_bytes());
+    Ok(header)
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_body<W: Write>(writer: W) -> Result<Body<W>> {
+    Ok(Body::Zstd(zstd::stream::write::Encoder::new(writer, SNAPSHOT_ZSTD_LEVEL).map_err(io_error)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_body<W: Write>(_writer: W) -> Result<Body<W>> {
+    Err(malformed("compressed snapshots require the `zstd` feature"))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_reader<'a, R: Read + 'a>(reader: R) -> Result<Box<dyn Read + 'a>> {
+    Ok(Box::new(zstd::stream::read::Decoder::new(reader).map_err(io_error)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_reader<'a, R: Read + 'a>(_reader: R) -> Result<Box<dyn Read + 'a>> {
+    Err(malformed("compressed snapshots require the `zstd` feature"))
+}
+
+fn malformed(reason: &str) -> PodComError {
+    PodComError::MalformedAccountData {
+        reason: format!("snapshot: {}", reason),
+    }
+}
+
+fn io_error(e: io::Error) -> PodComError {
+    malformed(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_state() -> Vec<(SnapshotKind, Pubkey, Vec<u8>)> {
+        let mut state = Vec::new();
+        for (kind, count) in [
+            (SnapshotKind::Agent, 3),
+            (SnapshotKind::Channel, 2),
+            (SnapshotKind::Message, 5),
+            (SnapshotKind::Escrow, 1),
+        ] {
+            for i in 0..count {
+                let data: Vec<u8> = (0..(40 + i * 7)).map(|b| (b as u8).wrapping_mul(kind.tag())).collect();
+                state.push((kind, Pubkey::new_unique(), data));
+            }
+        }
+        state
+    }
+
+    fn export(state: &[(SnapshotKind, Pubkey, Vec<u8>)], compress: bool) -> (Vec<u8>, SnapshotManifest) {
+        let mut archive = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut archive, compress).unwrap();
+        for (kind, address, data) in state {
+            writer.write_account(*kind, address, data).unwrap();
+        }
+        let manifest = writer.finish().unwrap();
+        (archive, manifest)
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let state = mock_state();
+
+        let modes: &[bool] = if cfg!(feature = "zstd") { &[false, true] } else { &[false] };
+        for &compress in modes {
+            let (archive, exported) = export(&state, compress);
+            assert_eq!((exported.agents, exported.channels, exported.messages, exported.escrows), (3, 2, 5, 1));
+            assert_eq!(exported.compressed, compress);
+
+            let mut cache = SnapshotCache::new();
+            let imported = cache.load(archive.as_slice()).unwrap();
+
+            assert_eq!(imported, exported);
+            for kind in SnapshotKind::ALL {
+                assert_eq!(cache.len(kind) as u64, exported.count(kind));
+            }
+            for (kind, address, data) in &state {
+                assert_eq!(cache.get_raw(*kind, address), Some(data.as_slice()));
+            }
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_checksum_is_stable_across_compression() {
+        let state = mock_state();
+        let (_, plain) = export(&state, false);
+        let (_, compressed) = export(&state, true);
+        assert_eq!(plain.checksum, compressed.checksum);
+    }
+
+    #[test]
+    fn test_tampered_snapshot_rejected_and_cache_kept() {
+        let state = mock_state();
+        let (mut archive, _) = export(&state, false);
+        // Flip a byte inside the first record's data
+        archive[9 + 37 + 5] ^= 0xff;
+
+        let mut cache = SnapshotCache::new();
+        cache.insert(SnapshotKind::Agent, Pubkey::new_unique(), vec![1, 2, 3]);
+
+        assert!(cache.load(archive.as_slice()).is_err());
+        assert_eq!(cache.len(SnapshotKind::Agent), 1);
+
+        let (archive, _) = export(&state, false);
+        assert!(read_snapshot(&archive[..archive.len() - 3], |_, _, _| Ok(())).is_err());
+        assert!(read_snapshot(&b"NOTASNAP\0"[..], |_, _, _| Ok(())).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! # Subscription Stream Utilities
+//!
+//! Adapters for streams of message accounts. Reconnects and polling fallback
+//! can deliver the same message more than once; [`DedupStream`] drops repeats
+//! using a bounded window of recently seen message addresses.
+
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use lru::LruCache;
+use solana_sdk::pubkey::Pubkey;
+
+/// Default number of recently seen message addresses remembered per stream
+pub const DEFAULT_DEDUP_WINDOW: usize = 1024;
+
+/// Stream adapter that drops items whose message address was recently seen
+///
+/// Each adapter keeps its own window, so deduplication is per subscription.
+/// Memory is bounded by the window size; an address that has fallen out of
+/// the window will be delivered again.
+pub struct DedupStream<S> {
+    inner: Pin<Box<S>>,
+    seen: LruCache<Pubkey, ()>,
+}
+
+impl<S> DedupStream<S> {
+    /// Wrap `inner`, remembering up to `window` recent message addresses
+    pub fn new(inner: S, window: NonZeroUsize) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            seen: LruCache::new(window),
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for DedupStream<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DedupStream")
+            .field("seen", &self.seen.len())
+            .field("window", &self.seen.cap())
+            .finish()
+    }
+}
+
+impl<S, T> Stream for DedupStream<S>
+where
+    S: Stream<Item = (Pubkey, T)>,
+{
+    type Item = (Pubkey, T);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some((address, item))) => {
+                    if self.seen.put(address, ()).is_none() {
+                        return Poll::Ready(Some((address, item)));
+                    }
+                    tracing::trace!("Dropping duplicate message {}", address);
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn window(size: usize) -> NonZeroUsize {
+        NonZeroUsize::new(size).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_address_emitted_once() {
+        let message = Pubkey::new_unique();
+        let items = vec![(message, "first"), (message, "redelivered"), (message, "again")];
+
+        let out: Vec<_> = DedupStream::new(futures::stream::iter(items), window(16)).collect().await;
+
+        assert_eq!(out, vec![(message, "first")]);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_addresses_pass() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Same content re-sent as a new message gets a new address
+        let items = vec![(a, "hello"), (b, "hello"), (a, "hello")];
+
+        let out: Vec<_> = DedupStream::new(futures::stream::iter(items), window(16)).collect().await;
+
+        assert_eq!(out, vec![(a, "hello"), (b, "hello")]);
+    }
+
+    #[tokio::test]
+    async fn test_window_is_bounded() {
+        let messages: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let items = vec![
+            (messages[0], 0),
+            (messages[1], 1),
+            (messages[2], 2),
+            (messages[0], 3), // evicted from a window of 2, so delivered again
+        ];
+
+        let mut stream = DedupStream::new(futures::stream::iter(items), window(2));
+        let mut out = Vec::new();
+        while let Some(item) = stream.next().await {
+            out.push(item.1);
+        }
+
+        assert_eq!(out, vec![0, 1, 2, 3]);
+        assert_eq!(stream.seen.len(), 2);
+    }
+}
@@ -0,0 +1,217 @@
+//! # RPC Transport
+//!
+//! Every RPC request made through the client's blocking and nonblocking
+//! [`RpcClient`]s passes through [`AllowlistSender`], built by [`rpc_sender`],
+//! which enforces the optional method allowlist from
+//! [`SecurityConfig`](crate::config::SecurityConfig) before anything reaches
+//! the network. HTTP clients for RPC and IPFS are built by [`http_client`],
+//! which applies the configured proxy.
+//!
+//! [`RpcClient`]: solana_rpc_client::rpc_client::RpcClient
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_rpc_client::{
+    http_sender::HttpSender,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client_api::{
+    client_error::{ClientError, ErrorKind as ClientErrorKind, Result as ClientResult},
+    request::RpcRequest,
+};
+
+use crate::config::PodComConfig;
+use crate::error::{ConfigError, NetworkError, Result};
+
+/// Build a proxy for all outbound traffic, honouring `NO_PROXY`-style exclusions
+///
+/// Credentials embedded in `proxy_url` are used for proxy authentication.
+pub fn build_proxy(proxy_url: &str, no_proxy: Option<&str>) -> Result<reqwest::Proxy> {
+    let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| ConfigError::Invalid {
+        field: "proxy_url".to_string(),
+        value: "<redacted>".to_string(),
+        reason: e.to_string(),
+    })?;
+
+    Ok(proxy.no_proxy(no_proxy.and_then(reqwest::NoProxy::from_string)))
+}
+
+/// Build an HTTP client with the given timeout, routed through `proxy` if set
+pub fn http_client(timeout: Duration, proxy: Option<reqwest::Proxy>) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().map_err(|e| {
+        NetworkError::ConnectionFailed {
+            endpoint: "http client".to_string(),
+            reason: e.to_string(),
+        }
+        .into()
+    })
+}
+
+/// Build an RPC sender for `config`: HTTP through the configured proxy,
+/// restricted to the configured method allowlist
+/// 
+/// Each RPC client needs its own sender, so call this once per client.
+pub fn rpc_sender(config: &PodComConfig) -> Result<AllowlistSender<HttpSender>> {
+    let http_sender = match config.network.proxy_url {
+        Some(ref proxy_url) => {
+            let proxy = build_proxy(proxy_url, config.network.no_proxy.as_deref())?;
+            HttpSender::new_with_client(
+                config.rpc_url.clone(),
+                http_client(config.network.timeout, Some(proxy))?,
+            )
+        }
+        None => HttpSender::new(config.rpc_url.clone()),
+    };
+
+    Ok(AllowlistSender::new(
+        http_sender,
+        config.security_config.allowed_rpc_methods.clone(),
+    ))
+}
+
+/// Check `method` against an optional allowlist (`None` allows every method)
+pub fn check_rpc_method(allowed: Option<&HashSet<String>>, method: &str) -> Result<()> {
+    match allowed {
+        Some(methods) if !methods.contains(method) => Err(NetworkError::RpcMethodNotAllowed {
+            method: method.to_string(),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// RPC sender that rejects methods outside an allowlist before sending
+pub struct AllowlistSender<S> {
+    inner: S,
+    allowed: Option<HashSet<String>>,
+}
+
+impl<S> AllowlistSender<S> {
+    /// Wrap `inner`, restricting it to `allowed` methods when set
+    pub fn new(inner: S, allowed: Option<HashSet<String>>) -> Self {
+        Self { inner, allowed }
+    }
+}
+
+#[async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for AllowlistSender<S> {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let method = request.to_string();
+        if let Err(e) = check_rpc_method(self.allowed.as_ref(), &method) {
+            tracing::warn!("Blocked RPC call: {}", e);
+            return Err(ClientError::from(ClientErrorKind::Custom(e.to_string())));
+        }
+
+        self.inner.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockRpc;
+
+    /// Allowlist over a transport that records every request reaching it
+    fn restricted_sender() -> (AllowlistSender<MockRpc>, MockRpc) {
+        let inner = MockRpc::new(|_, _| Value::String("ok".to_string()));
+        let allowed = ["getAccountInfo", "getHealth"].iter().map(|m| m.to_string()).collect();
+        (AllowlistSender::new(inner.clone(), Some(allowed)), inner)
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_method_rejected_before_network() {
+        let (sender, inner) = restricted_sender();
+
+        let err = sender
+            .send(RpcRequest::RequestAirdrop, Value::Null)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("requestAirdrop"));
+        assert!(inner.methods().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_allowed_method_passes_through() {
+        let (sender, inner) = restricted_sender();
+
+        sender.send(RpcRequest::GetHealth, Value::Null).await.unwrap();
+        assert_eq!(inner.methods(), vec!["getHealth"]);
+    }
+
+    #[tokio::test]
+    async fn test_requests_routed_through_proxy() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v0/version"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let client = http_client(
+            Duration::from_secs(5),
+            Some(build_proxy(&proxy.uri(), None).unwrap()),
+        )
+        .unwrap();
+
+        // The target host does not resolve; only the proxy can answer
+        let response = client
+            .get("http://ipfs.pod.invalid/api/v0/version")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_no_proxy_hosts_bypass_proxy() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let proxy = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&proxy)
+            .await;
+
+        let client = http_client(
+            Duration::from_secs(5),
+            Some(build_proxy(&proxy.uri(), Some("pod.invalid")).unwrap()),
+        )
+        .unwrap();
+
+        // Sent directly, so the unresolvable host fails instead of reaching the proxy
+        assert!(client.get("http://rpc.pod.invalid/health").send().await.is_err());
+    }
+
+    #[test]
+    fn test_no_allowlist_is_unrestricted() {
+        assert!(check_rpc_method(None, "sendTransaction").is_ok());
+
+        let allowed: HashSet<String> = ["getHealth".to_string()].into_iter().collect();
+        assert!(matches!(
+            check_rpc_method(Some(&allowed), "sendTransaction"),
+            Err(crate::error::PodComError::Network(NetworkError::RpcMethodNotAllowed { .. }))
+        ));
+    }
+}
@@ -4,7 +4,10 @@
 
 use std::time::Duration;
 use crate::error::Result;
-use crate::utils::compression::{CompressionAlgorithm, CompressionLevel};
+use crate::utils::compression::{
+    available_algorithms, compress_with_algorithm, decompress_with_algorithm, CompressionAlgorithm,
+    CompressionLevel,
+};
 use pod_sdk_crypto::{Hash, SymmetricEncryption, utils::generate_salt};
 use std::collections::HashMap;
 
@@ -156,38 +159,7 @@ impl ZKCompressor {
             .map_err(|e| crate::error::PodError::CryptoError(format!("Failed to generate randomness: {}", e)))?;
 
         // Apply compression based on algorithm
-        let compressed_data = match algorithm {
-            CompressionAlgorithm::Gzip => {
-                use flate2::{write::GzEncoder, Compression};
-                use std::io::Write;
-                
-                let compression_level = match level {
-                    CompressionLevel::Fast => Compression::fast(),
-                    CompressionLevel::Balanced => Compression::default(),
-                    CompressionLevel::Best => Compression::best(),
-                };
-                
-                let mut encoder = GzEncoder::new(Vec::new(), compression_level);
-                encoder.write_all(data)
-                    .map_err(|e| crate::error::PodError::CryptoError(format!("Compression failed: {}", e)))?;
-                encoder.finish()
-                    .map_err(|e| crate::error::PodError::CryptoError(format!("Compression finalization failed: {}", e)))?
-            },
-            CompressionAlgorithm::Zstd => {
-                let compression_level = match level {
-                    CompressionLevel::Fast => 1,
-                    CompressionLevel::Balanced => 3,
-                    CompressionLevel::Best => 9,
-                };
-                
-                zstd::encode_all(data, compression_level)
-                    .map_err(|e| crate::error::PodError::CryptoError(format!("Zstd compression failed: {}", e)))?
-            },
-            _ => {
-                // Fallback to simple encoding
-                data.to_vec()
-            }
-        };
+        let compressed_data = compress_payload(data, algorithm, level)?;
 
         // Apply privacy encryption if needed
         let final_data = match privacy_level {
@@ -254,23 +226,7 @@ impl ZKCompressor {
         };
 
         // Decompress based on algorithm
-        let decompressed_data = match algorithm {
-            CompressionAlgorithm::Gzip => {
-                use flate2::read::GzDecoder;
-                use std::io::Read;
-                
-                let mut decoder = GzDecoder::new(&decrypted_data[..]);
-                let mut result = Vec::new();
-                decoder.read_to_end(&mut result)
-                    .map_err(|e| crate::error::PodError::CryptoError(format!("Gzip decompression failed: {}", e)))?;
-                result
-            },
-            CompressionAlgorithm::Zstd => {
-                zstd::decode_all(&decrypted_data[..])
-                    .map_err(|e| crate::error::PodError::CryptoError(format!("Zstd decompression failed: {}", e)))?
-            },
-            _ => decrypted_data,
-        };
+        let decompressed_data = decompress_payload(decrypted_data, algorithm)?;
 
         Ok(decompressed_data)
     }
@@ -366,11 +322,11 @@ impl ZKCompressor {
         };
 
         // Test different algorithm combinations
-        let algorithms = vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Zstd];
+        let algorithms = available_algorithms();
         let levels = vec![CompressionLevel::Fast, CompressionLevel::Balanced, CompressionLevel::Best];
         let privacy_levels = vec![PrivacyLevel::Public, PrivacyLevel::Private, PrivacyLevel::HighPrivacy];
 
-        for algorithm in algorithms {
+        for &algorithm in algorithms {
             for level in levels.iter() {
                 for privacy_level in privacy_levels.iter() {
                     // Simulate compression
@@ -441,6 +397,9 @@ impl ZKCompressor {
     }
 
     /// Estimate compressed size
+    ///
+    /// The sample is actually compressed, so algorithms are compared on the
+    /// ratio they achieve for this data rather than a fixed guess.
     fn estimate_compressed_size(
         &self,
         data: &[u8],
@@ -448,19 +407,7 @@ impl ZKCompressor {
         level: CompressionLevel,
         privacy_level: PrivacyLevel,
     ) -> Result<f64> {
-        let base_ratio = match algorithm {
-            CompressionAlgorithm::Gzip => match level {
-                CompressionLevel::Fast => 0.6,
-                CompressionLevel::Balanced => 0.5,
-                CompressionLevel::Best => 0.4,
-            },
-            CompressionAlgorithm::Zstd => match level {
-                CompressionLevel::Fast => 0.55,
-                CompressionLevel::Balanced => 0.45,
-                CompressionLevel::Best => 0.35,
-            },
-            _ => 0.8,
-        };
+        let compressed_len = compress_with_algorithm(data, algorithm, level)?.len();
 
         let privacy_overhead = match privacy_level {
             PrivacyLevel::Public => 1.0,
@@ -468,7 +415,7 @@ impl ZKCompressor {
             PrivacyLevel::HighPrivacy => 1.2,
         };
 
-        Ok(data.len() as f64 * base_ratio * privacy_overhead)
+        Ok(compressed_len as f64 * privacy_overhead)
     }
 
     /// Estimate performance metrics
@@ -491,6 +438,7 @@ impl ZKCompressor {
             CompressionLevel::Fast => 0.5,
             CompressionLevel::Balanced => 1.0,
             CompressionLevel::Best => 2.0,
+            CompressionLevel::Exact(n) => 0.5 + n as f64 / 10.0,
         };
 
         let privacy_multiplier = match privacy_level {
@@ -554,6 +502,25 @@ impl Default for ZKCompressionConfig {
     }
 }
 
+/// Compress a payload before it is encrypted and proven
+///
+/// LZ4 payloads have always been stored uncompressed, so they stay readable
+/// by earlier versions as well as this one.
+fn compress_payload(data: &[u8], algorithm: CompressionAlgorithm, level: CompressionLevel) -> Result<Vec<u8>> {
+    match algorithm {
+        CompressionAlgorithm::Lz4 => Ok(data.to_vec()),
+        _ => compress_with_algorithm(data, algorithm, level),
+    }
+}
+
+/// Reverse [`compress_payload`]
+fn decompress_payload(data: Vec<u8>, algorithm: CompressionAlgorithm) -> Result<Vec<u8>> {
+    match algorithm {
+        CompressionAlgorithm::Lz4 => Ok(data),
+        _ => decompress_with_algorithm(&data, algorithm),
+    }
+}
+
 /// Generate commitment
 pub fn generate_commitment(data: &[u8], randomness: &[u8]) -> Result<Vec<u8>> {
     if randomness.len() < 32 {
@@ -687,4 +654,34 @@ mod tests {
         assert_eq!(result.randomness.len(), 32);
         assert!(!result.proof.hash().is_empty());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_lz4_payload_stored_as_is_and_read_back() {
+        let data = b"LZ4 payloads have always been stored uncompressed";
+        
+        let stored = compress_payload(data, CompressionAlgorithm::Lz4, CompressionLevel::Balanced).unwrap();
+        assert_eq!(stored, data);
+        assert_eq!(decompress_payload(stored, CompressionAlgorithm::Lz4).unwrap(), data);
+        
+        let gzip = compress_payload(data, CompressionAlgorithm::Gzip, CompressionLevel::Balanced).unwrap();
+        assert_eq!(decompress_payload(gzip, CompressionAlgorithm::Gzip).unwrap(), data);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_optimizer_selects_zstd_when_it_compresses_better() {
+        use rand::RngCore;
+
+        // A random block repeated beyond GZIP's 32KB window only compresses under ZSTD
+        let mut block = vec![0u8; 40 * 1024];
+        rand::thread_rng().fill_bytes(&mut block);
+        let sample = [block.as_slice(), block.as_slice()].concat();
+
+        let compressor = ZKCompressor::new(ZKCompressionConfig::default());
+        let result = compressor.optimize_parameters(&sample, 1.0).await.unwrap();
+
+        assert_eq!(result.algorithm, CompressionAlgorithm::Zstd);
+        assert_eq!(result.privacy_level, PrivacyLevel::Public);
+        assert!(result.estimated_ratio < 0.6);
+    }
+}
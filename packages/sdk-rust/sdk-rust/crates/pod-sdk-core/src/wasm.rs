@@ -0,0 +1,380 @@
+//! # Browser Client
+//!
+//! [`WasmPodClient`] is the entry point for running the SDK in a browser.
+//! Browsers have no threads to block, so the client talks to the cluster only
+//! through the nonblocking [`RpcClient`] and never uses `std::thread` or
+//! `futures::executor::block_on`; every operation is a promise on the page's
+//! event loop. Inputs and outputs cross the JavaScript boundary as plain
+//! objects with camelCase keys.
+//!
+//! ```js
+//! const client = new WasmPodClient("https://api.devnet.solana.com", secretKey);
+//! const { signature, address } = await client.registerAgent({
+//!   capabilities: 1,
+//!   metadataUri: "https://example.com/agent.json",
+//! });
+//! ```
+//!
+//! Setting `client.dryRun = true` simulates transactions instead of sending
+//! them; calls then resolve to `{ dryRun: { logs, unitsConsumed,
+//! derivedAddresses, error } }`.
+
+use std::sync::Arc;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use serde::{Deserialize, Serialize};
+use solana_rpc_client::{
+    http_sender::HttpSender,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_sender::RpcSender,
+};
+use solana_rpc_client_api::config::{RpcSendTransactionConfig, UiTransactionEncoding};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    transaction::Transaction,
+};
+use wasm_bindgen::prelude::*;
+
+use pod_sdk_types::{pda, AgentAccount, MessageType};
+
+use crate::{
+    error::{NetworkError, PodComError, Result, ValidationError},
+    services::{
+        agent::to_program_message_type,
+        base::{simulation_config, DryRunResult, Outcome},
+    },
+    utils::{decode::decode_account, encoding::payload_hash},
+};
+
+/// PoD Protocol client for browser environments
+#[wasm_bindgen]
+pub struct WasmPodClient {
+    rpc: Arc<RpcClient>,
+    payer: Arc<Keypair>,
+    program_id: Pubkey,
+    dry_run: bool,
+}
+
+/// Input to [`WasmPodClient::register_agent`]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterAgentInput {
+    capabilities: u64,
+    metadata_uri: String,
+}
+
+/// Input to [`WasmPodClient::send_message`]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendMessageInput {
+    recipient: String,
+    content: String,
+    #[serde(default)]
+    message_type: Option<MessageType>,
+}
+
+/// Result of a transaction that created an account
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedAccount {
+    signature: String,
+    address: String,
+}
+
+/// What a state-changing call resolves to: the sent result or its simulation
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum JsOutcome<T> {
+    Sent(T),
+    #[serde(rename_all = "camelCase")]
+    Simulated { dry_run: SimulatedTransaction },
+}
+
+/// A transaction simulated in dry-run mode
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulatedTransaction {
+    logs: Vec<String>,
+    units_consumed: Option<u64>,
+    derived_addresses: Vec<String>,
+    error: Option<String>,
+}
+
+#[wasm_bindgen]
+impl WasmPodClient {
+    /// Connect to `rpc_url`, signing with the 64-byte keypair in `secret_key`
+    #[wasm_bindgen(constructor)]
+    pub fn new(rpc_url: String, secret_key: &[u8]) -> std::result::Result<WasmPodClient, JsError> {
+        let payer = Keypair::try_from(secret_key).map_err(|e| {
+            to_js_error(ValidationError::InvalidFormat {
+                field: "secret_key".to_string(),
+                reason: e.to_string(),
+            }.into())
+        })?;
+
+        Ok(Self::with_sender(HttpSender::new(rpc_url), payer, crate::PROGRAM_ID))
+    }
+
+    /// Public key that pays for and signs every transaction
+    #[wasm_bindgen(getter)]
+    pub fn payer(&self) -> String {
+        self.payer.pubkey().to_string()
+    }
+
+    /// Whether transactions are simulated instead of sent
+    #[wasm_bindgen(getter, js_name = dryRun)]
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Simulate transactions instead of sending them
+    #[wasm_bindgen(setter, js_name = dryRun)]
+    pub fn set_dry_run(&mut self, enabled: bool) {
+        self.dry_run = enabled;
+    }
+
+    /// Register the payer as an agent
+    ///
+    /// Takes `{ capabilities, metadataUri }` and resolves to
+    /// `{ signature, address }` with the new agent account's address, or to
+    /// `{ dryRun }` in dry-run mode.
+    #[wasm_bindgen(js_name = registerAgent)]
+    pub async fn register_agent(&self, input: JsValue) -> std::result::Result<JsValue, JsError> {
+        let input: RegisterAgentInput = from_js(input)?;
+        let created = self
+            .register_agent_with(input.capabilities, input.metadata_uri)
+            .await
+            .map_err(to_js_error)?;
+        to_js(&JsOutcome::from(created.map(CreatedAccount::from)))
+    }
+
+    /// Send a direct message from the payer's agent
+    ///
+    /// Takes `{ recipient, content, messageType? }` and resolves to
+    /// `{ signature, address }` with the new message account's address, or
+    /// to `{ dryRun }` in dry-run mode.
+    #[wasm_bindgen(js_name = sendMessage)]
+    pub async fn send_message(&self, input: JsValue) -> std::result::Result<JsValue, JsError> {
+        let input: SendMessageInput = from_js(input)?;
+        let recipient = parse_pubkey("recipient", &input.recipient).map_err(to_js_error)?;
+        let message_type = input.message_type.unwrap_or(MessageType::Text);
+
+        let created = self
+            .send_message_with(recipient, input.content.as_bytes(), message_type)
+            .await
+            .map_err(to_js_error)?;
+        to_js(&JsOutcome::from(created.map(CreatedAccount::from)))
+    }
+
+    /// Fetch an agent account by its address
+    #[wasm_bindgen(js_name = getAgent)]
+    pub async fn get_agent(&self, address: String) -> std::result::Result<JsValue, JsError> {
+        let address = parse_pubkey("address", &address).map_err(to_js_error)?;
+        let agent = self.get_agent_with(&address).await.map_err(to_js_error)?;
+        to_js(&agent)
+    }
+}
+
+impl WasmPodClient {
+    /// Build a client over a custom RPC transport
+    pub fn with_sender<S: RpcSender + Send + Sync + 'static>(
+        sender: S,
+        payer: Keypair,
+        program_id: Pubkey,
+    ) -> Self {
+        let rpc = RpcClient::new_sender(
+            sender,
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        );
+
+        Self {
+            rpc: Arc::new(rpc),
+            payer: Arc::new(payer),
+            program_id,
+            dry_run: false,
+        }
+    }
+
+    /// Register the payer as an agent, returning the agent's address
+    pub async fn register_agent_with(&self, capabilities: u64, metadata_uri: String) -> Result<Outcome<CreatedAccountInfo>> {
+        let agent_pda = self.agent_address();
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: pod_com::accounts::RegisterAgent {
+                agent_account: agent_pda,
+                signer: self.payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pod_com::instruction::RegisterAgent {
+                capabilities,
+                metadata_uri,
+            }
+            .data(),
+        };
+
+        let outcome = self.send_instruction(ix).await?;
+        Ok(outcome.map(|signature| CreatedAccountInfo { signature, address: agent_pda }))
+    }
+
+    /// Send a direct message from the payer's agent, returning the message's address
+    pub async fn send_message_with(
+        &self,
+        recipient: Pubkey,
+        content: &[u8],
+        message_type: MessageType,
+    ) -> Result<Outcome<CreatedAccountInfo>> {
+        pod_sdk_types::validate_message(content, None)?;
+
+        let sender_agent = self.agent_address();
+        let hash = payload_hash(content);
+        let program_type = to_program_message_type(message_type);
+        let (message_pda, _) = pda::message_pda(
+            &self.program_id,
+            &sender_agent,
+            &recipient,
+            &hash,
+            pda::message_type_seed(&message_type),
+        );
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: pod_com::accounts::SendMessage {
+                message_account: message_pda,
+                sender_agent,
+                signer: self.payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pod_com::instruction::SendMessage {
+                recipient,
+                payload_hash: hash,
+                message_type: program_type,
+            }
+            .data(),
+        };
+
+        let outcome = self.send_instruction(ix).await?;
+        Ok(outcome.map(|signature| CreatedAccountInfo { signature, address: message_pda }))
+    }
+
+    /// Fetch and decode an agent account
+    pub async fn get_agent_with(&self, address: &Pubkey) -> Result<AgentAccount> {
+        let data = self.rpc.get_account_data(address).await.map_err(|e| -> PodComError {
+            NetworkError::RpcFailed {
+                method: "getAccountInfo".to_string(),
+                reason: e.to_string(),
+            }.into()
+        })?;
+        decode_account(&data)
+    }
+
+    /// The payer's agent account address
+    fn agent_address(&self) -> Pubkey {
+        pda::agent_pda(&self.program_id, &self.payer.pubkey()).0
+    }
+
+    /// Sign and submit a single-instruction transaction, or simulate it in dry-run mode
+    async fn send_instruction(&self, ix: Instruction) -> Result<Outcome> {
+        if self.dry_run {
+            let tx = Transaction::new_with_payer(&[ix], Some(&self.payer.pubkey()));
+            let config = simulation_config(self.rpc.commitment());
+            let result = self.rpc.simulate_transaction_with_config(&tx, config).await.map_err(|e| -> PodComError {
+                NetworkError::RpcFailed {
+                    method: "simulateTransaction".to_string(),
+                    reason: e.to_string(),
+                }.into()
+            })?;
+            return Ok(Outcome::Simulated(DryRunResult::from_simulation(&tx, result.value)));
+        }
+
+        let blockhash = self.rpc.get_latest_blockhash().await.map_err(|e| -> PodComError {
+            NetworkError::RpcFailed {
+                method: "getLatestBlockhash".to_string(),
+                reason: e.to_string(),
+            }.into()
+        })?;
+
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.payer.pubkey()),
+            &[self.payer.as_ref()],
+            blockhash,
+        );
+
+        // An explicit encoding avoids a `getVersion` round trip per send
+        let config = RpcSendTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..Default::default()
+        };
+        let signature = self.rpc.send_transaction_with_config(&tx, config).await.map_err(|e| -> PodComError {
+            NetworkError::RpcFailed {
+                method: "sendTransaction".to_string(),
+                reason: e.to_string(),
+            }.into()
+        })?;
+        Ok(Outcome::Sent(signature))
+    }
+}
+
+/// Signature and address of an account created by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreatedAccountInfo {
+    /// Transaction signature
+    pub signature: Signature,
+    /// Address of the created account
+    pub address: Pubkey,
+}
+
+impl From<CreatedAccountInfo> for CreatedAccount {
+    fn from(info: CreatedAccountInfo) -> Self {
+        Self {
+            signature: info.signature.to_string(),
+            address: info.address.to_string(),
+        }
+    }
+}
+
+impl<T> From<Outcome<T>> for JsOutcome<T> {
+    fn from(outcome: Outcome<T>) -> Self {
+        match outcome {
+            Outcome::Sent(value) => JsOutcome::Sent(value),
+            Outcome::Simulated(dry_run) => JsOutcome::Simulated {
+                dry_run: SimulatedTransaction {
+                    logs: dry_run.logs,
+                    units_consumed: dry_run.units_consumed,
+                    derived_addresses: dry_run.derived_addresses.iter().map(Pubkey::to_string).collect(),
+                    error: dry_run.error,
+                },
+            },
+        }
+    }
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey> {
+    value.parse().map_err(|e: solana_sdk::pubkey::ParsePubkeyError| {
+        ValidationError::InvalidFormat {
+            field: field.to_string(),
+            reason: e.to_string(),
+        }
+        .into()
+    })
+}
+
+fn from_js<T: serde::de::DeserializeOwned>(value: JsValue) -> std::result::Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn to_js<T: Serialize>(value: &T) -> std::result::Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn to_js_error(error: PodComError) -> JsError {
+    JsError::new(&error.to_string())
+}
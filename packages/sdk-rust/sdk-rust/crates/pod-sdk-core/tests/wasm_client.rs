@@ -0,0 +1,128 @@
+//! Headless browser tests for the WASM client
+//!
+//! Run with `wasm-pack test --headless --chrome crates/pod-sdk-core -- --features wasm,testing`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm", feature = "testing"))]
+
+use base64::Engine;
+use serde_json::json;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signer::{keypair::Keypair, Signer},
+    transaction::Transaction,
+};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use pod_sdk_core::{
+    testing::{simulation, with_context, MockRpc, SIMULATED_UNITS},
+    wasm::WasmPodClient,
+    PROGRAM_ID,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Decode a base64 wire transaction
+fn decode(wire: &str) -> Transaction {
+    let wire = base64::engine::general_purpose::STANDARD.decode(wire).unwrap();
+    bincode::deserialize(&wire).unwrap()
+}
+
+/// Transactions sent through `rpc`, in order
+fn sent(rpc: &MockRpc) -> Vec<Transaction> {
+    rpc.params_of("sendTransaction")
+        .iter()
+        .map(|params| decode(params[0].as_str().unwrap()))
+        .collect()
+}
+
+/// Client over a transport that accepts every transaction
+fn mock_client() -> (WasmPodClient, Pubkey, MockRpc) {
+    let rpc = MockRpc::new(|request, params| match request {
+        RpcRequest::GetLatestBlockhash => with_context(json!({
+            "blockhash": Hash::new_unique().to_string(),
+            "lastValidBlockHeight": 100,
+        })),
+        RpcRequest::SendTransaction => json!(decode(params[0].as_str().unwrap()).signatures[0].to_string()),
+        RpcRequest::SimulateTransaction => with_context(simulation(
+            SIMULATED_UNITS,
+            &["Program log: Instruction: RegisterAgent"],
+        )),
+        other => panic!("unexpected RPC request {}", other),
+    });
+    let payer = Keypair::new();
+    let payer_key = payer.pubkey();
+    let client = WasmPodClient::with_sender(rpc.clone(), payer, PROGRAM_ID);
+    (client, payer_key, rpc)
+}
+
+#[wasm_bindgen_test]
+async fn register_agent_signs_and_submits() {
+    let (client, payer, rpc) = mock_client();
+
+    let created = client
+        .register_agent_with(1, "https://example.com/agent.json".to_string())
+        .await
+        .unwrap()
+        .sent()
+        .unwrap();
+
+    let (agent_pda, _) = Pubkey::find_program_address(&[b"agent", payer.as_ref()], &PROGRAM_ID);
+    assert_eq!(created.address, agent_pda);
+
+    let transactions = sent(&rpc);
+    assert_eq!(transactions.len(), 1);
+    let tx = &transactions[0];
+    assert!(tx.verify().is_ok());
+    assert_eq!(tx.signatures[0], created.signature);
+    assert_eq!(tx.message.account_keys[0], payer);
+
+    let ix = &tx.message.instructions[0];
+    assert_eq!(tx.message.account_keys[ix.program_id_index as usize], PROGRAM_ID);
+    assert!(ix.accounts.iter().any(|&i| tx.message.account_keys[i as usize] == agent_pda));
+}
+
+#[wasm_bindgen_test]
+async fn register_agent_accepts_js_objects() {
+    let (client, _, rpc) = mock_client();
+
+    let input = serde_wasm_bindgen::to_value(&json!({
+        "capabilities": 3,
+        "metadataUri": "https://example.com/agent.json",
+    }))
+    .unwrap();
+    let result = client.register_agent(input).await.map_err(JsValue::from).unwrap();
+
+    let signature = js_sys::Reflect::get(&result, &JsValue::from_str("signature")).unwrap();
+    let transactions = sent(&rpc);
+    assert_eq!(signature.as_string().unwrap(), transactions[0].signatures[0].to_string());
+}
+
+#[wasm_bindgen_test]
+async fn register_agent_rejects_malformed_input() {
+    let (client, _, rpc) = mock_client();
+
+    let input = serde_wasm_bindgen::to_value(&json!({ "capabilities": "all" })).unwrap();
+    assert!(client.register_agent(input).await.is_err());
+    assert!(sent(&rpc).is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn register_agent_dry_run_simulates_without_sending() {
+    let (mut client, payer, rpc) = mock_client();
+    client.set_dry_run(true);
+
+    let outcome = client
+        .register_agent_with(1, "https://example.com/agent.json".to_string())
+        .await
+        .unwrap();
+
+    let (agent_pda, _) = Pubkey::find_program_address(&[b"agent", payer.as_ref()], &PROGRAM_ID);
+    let dry_run = outcome.simulated().unwrap();
+    assert!(dry_run.derived_addresses.contains(&agent_pda));
+    assert_eq!(dry_run.units_consumed, Some(SIMULATED_UNITS));
+    assert_eq!(dry_run.error, None);
+    assert!(sent(&rpc).is_empty());
+}
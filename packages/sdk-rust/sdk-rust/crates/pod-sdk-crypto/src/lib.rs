@@ -8,6 +8,7 @@
 
 use thiserror::Error;
 use std::pin::Pin;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 pub use blake3;
 pub use solana_sdk::signer::keypair::Keypair;
@@ -15,6 +16,7 @@ pub use solana_sdk::signature::{Signature as SolanaSignature};
 pub use solana_sdk::pubkey::Pubkey;
 pub use rand;
 pub use sha2;
+pub use zeroize;
 
 #[cfg(feature = "aes")]
 pub use aes_gcm;
@@ -74,6 +76,12 @@ pub enum CryptoError {
 /// Maximum size for secure buffers (64KB)
 pub const MAX_SECURE_BUFFER_SIZE: usize = 64 * 1024;
 
+/// Length of a serialized Solana keypair: 32-byte secret followed by 32-byte public key
+pub const KEYPAIR_BYTES: usize = 64;
+
+/// HKDF salt for per-message keys derived from a channel master key
+pub const MESSAGE_KEY_SALT: &[u8] = b"pod-protocol:message-key:v1";
+
 /// Secure memory buffer that automatically zeros on drop
 pub struct SecureBuffer {
     data: Vec<u8>,
@@ -116,6 +124,29 @@ impl SecureBuffer {
         Ok(Box::pin(buffer))
     }
     
+    /// Store a Solana keypair's 64-byte serialized secret in a secure buffer
+    ///
+    /// The intermediate copy is zeroed before returning, so the only
+    /// remaining copies are `keypair` itself and the buffer.
+    pub fn from_keypair(keypair: &Keypair) -> Result<Pin<Box<Self>>, CryptoError> {
+        let mut bytes = keypair.to_bytes();
+        let buffer = Self::from_slice(&bytes);
+        bytes.zeroize();
+        buffer
+    }
+    
+    /// Reconstruct the Solana keypair stored by [`Self::from_keypair`]
+    pub fn to_keypair(&self) -> Result<Keypair, CryptoError> {
+        if self.data.len() != KEYPAIR_BYTES {
+            return Err(CryptoError::InvalidKeySize {
+                expected: KEYPAIR_BYTES,
+                actual: self.data.len(),
+            });
+        }
+        
+        Keypair::try_from(self.data.as_slice()).map_err(|_| CryptoError::InvalidPrivateKey)
+    }
+    
     /// Get an immutable slice of the buffer
     pub fn as_slice(&self) -> &[u8] {
         &self.data
@@ -157,16 +188,29 @@ impl SecureBuffer {
         Ok(())
     }
     
-    /// Clear the buffer (set all bytes to zero)
+    /// Clear the buffer (set all bytes to zero), keeping its length
     pub fn clear(&mut self) {
-        for byte in &mut self.data {
-            unsafe {
-                std::ptr::write_volatile(byte, 0);
-            }
-        }
+        self.zeroize();
+    }
+}
+
+impl Zeroize for SecureBuffer {
+    /// Zero every byte with volatile writes followed by a compiler fence, so
+    /// the writes cannot be elided or reordered past later code
+    fn zeroize(&mut self) {
+        // Zeroize the slice rather than the `Vec`, which would also truncate it
+        self.data.as_mut_slice().zeroize();
+    }
+}
+
+impl Drop for SecureBuffer {
+    fn drop(&mut self) {
+        self.zeroize();
     }
 }
 
+impl ZeroizeOnDrop for SecureBuffer {}
+
 impl std::fmt::Debug for SecureBuffer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SecureBuffer")
@@ -206,9 +250,11 @@ impl SecureRng {
     pub fn generate_bytes(&mut self, size: usize) -> Result<Pin<Box<SecureBuffer>>, CryptoError> {
         let mut buffer = SecureBuffer::new(size)?;
         // Safety: We just created the buffer and have exclusive access
-        unsafe {
-            let buffer_mut = Pin::get_unchecked_mut(buffer.as_mut());
-            self.fill_bytes(buffer_mut.as_mut_slice())?;
+        let buffer_mut = unsafe { Pin::get_unchecked_mut(buffer.as_mut()) };
+        if let Err(e) = self.fill_bytes(buffer_mut.as_mut_slice()) {
+            // Don't leave partially written random bytes to the allocator
+            buffer_mut.zeroize();
+            return Err(e);
         }
         Ok(buffer)
     }
@@ -267,6 +313,132 @@ impl Default for Hash {
     }
 }
 
+/// Domain prefix hashed before each leaf of a [`MerkleTree`]
+pub const MERKLE_LEAF_PREFIX: u8 = 0x00;
+
+/// Domain prefix hashed before each pair of children in a [`MerkleTree`]
+pub const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+/// Binary Merkle tree over Blake3
+///
+/// Leaves and interior nodes are hashed in separate domains: a leaf hashes
+/// as `blake3(0x00 || leaf)` and a node as `blake3(0x01 || left || right)`.
+/// Without the prefixes, an interior node could be presented as a leaf and a
+/// proof forged for data that was never in the tree.
+///
+/// A level with an odd number of nodes pairs its last node with itself.
+/// As a consequence, `[a, b, c]` and `[a, b, c, c]` have the same root, so
+/// callers that need the leaf count bound to the root must commit to it
+/// separately.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// Levels from hashed leaves up to the single root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Build a tree over `leaves`
+    ///
+    /// The root of an empty tree is all zeros.
+    pub fn new(leaves: &[[u8; 32]]) -> Self {
+        let mut levels = vec![leaves.iter().map(merkle_leaf_hash).collect::<Vec<_>>()];
+        
+        while levels[levels.len() - 1].len() > 1 {
+            let level = &levels[levels.len() - 1];
+            let next = level
+                .chunks(2)
+                .map(|pair| merkle_node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(next);
+        }
+        
+        Self { levels }
+    }
+    
+    /// Number of leaves in the tree
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+    
+    /// Whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+    
+    /// Root hash of the tree
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1]
+            .first()
+            .copied()
+            .unwrap_or([0u8; 32])
+    }
+    
+    /// Membership proof for the leaf at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`Self::len`].
+    pub fn proof(&self, index: usize) -> MerkleProof {
+        assert!(index < self.len(), "leaf index {} out of range for {} leaves", index, self.len());
+        
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+            siblings.push(*sibling);
+            position /= 2;
+        }
+        
+        MerkleProof { index, siblings }
+    }
+}
+
+/// Proof that a leaf is part of a [`MerkleTree`] with a given root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Position of the leaf in the tree
+    pub index: usize,
+    /// Sibling hashes from the leaf level up to just below the root
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    /// Check that `leaf` at this proof's index hashes up to `root`
+    pub fn verify(&self, root: &[u8; 32], leaf: &[u8; 32]) -> bool {
+        if self.siblings.len() >= usize::BITS as usize || self.index >> self.siblings.len() != 0 {
+            return false;
+        }
+        
+        let mut position = self.index;
+        let mut hash = merkle_leaf_hash(leaf);
+        for sibling in &self.siblings {
+            hash = if position.is_multiple_of(2) {
+                merkle_node_hash(&hash, sibling)
+            } else {
+                merkle_node_hash(sibling, &hash)
+            };
+            position /= 2;
+        }
+        
+        utils::constant_time_eq_fixed(&hash, root)
+    }
+}
+
+fn merkle_leaf_hash(leaf: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[MERKLE_LEAF_PREFIX]);
+    hasher.update(leaf);
+    hasher.finalize().into()
+}
+
+fn merkle_node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[MERKLE_NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
 /// Ed25519 signature utilities using Solana's implementation
 pub struct Signature;
 
@@ -302,8 +474,7 @@ impl Signature {
     /// Simple signing function for backward compatibility
     pub fn sign(private_key: &[u8; 32], message: &[u8]) -> Result<[u8; 64], CryptoError> {
         // Create a keypair from the private key bytes
-        let keypair = Keypair::from_bytes(private_key)
-            .map_err(|_| CryptoError::InvalidPrivateKey)?;
+        let keypair = Keypair::new_from_array(*private_key);
         
         Self::sign_with_keypair(&keypair, message)
     }
@@ -329,14 +500,44 @@ impl Signature {
         Self::verify_solana(&pubkey, message, &sig)
     }
     
+    /// Verify many signatures, returning one result per item in input order
+    /// 
+    /// All items are first checked together with ed25519 batch verification.
+    /// If the batch fails, each item is verified on its own so that one bad
+    /// signature only marks its own entry `false`.
+    pub fn verify_batch(items: &[(Pubkey, &[u8], [u8; 64])]) -> Vec<bool> {
+        let keys: Vec<_> = items
+            .iter()
+            .map(|(public_key, _, _)| ed25519_dalek::VerifyingKey::from_bytes(&public_key.to_bytes()).ok())
+            .collect();
+        
+        // A key that is not a curve point can never verify, so leave it out of the batch
+        if !items.is_empty() && keys.iter().all(Option::is_some) {
+            let messages: Vec<&[u8]> = items.iter().map(|(_, message, _)| *message).collect();
+            let signatures: Vec<_> = items
+                .iter()
+                .map(|(_, _, signature)| ed25519_dalek::Signature::from_bytes(signature))
+                .collect();
+            let keys: Vec<_> = keys.into_iter().flatten().collect();
+            
+            if ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok() {
+                return vec![true; items.len()];
+            }
+        }
+        
+        items
+            .iter()
+            .map(|(public_key, message, signature)| Self::verify(&public_key.to_bytes(), message, signature))
+            .collect()
+    }
+    
     /// Generate a new keypair (backward compatibility)
     pub fn generate_keypair() -> Result<([u8; 32], [u8; 32]), CryptoError> {
         use solana_sdk::signer::Signer;
         
         let keypair = Self::generate_solana_keypair()?;
         
-        let secret_bytes: [u8; 32] = keypair.secret().as_ref().try_into()
-            .map_err(|_| CryptoError::InvalidPrivateKey)?;
+        let secret_bytes: [u8; 32] = *keypair.secret_bytes();
         let public_bytes: [u8; 32] = keypair.pubkey().as_ref().try_into()
             .map_err(|_| CryptoError::InvalidPublicKey)?;
         
@@ -347,8 +548,7 @@ impl Signature {
     pub fn public_key_from_private(private_key: &[u8; 32]) -> Result<[u8; 32], CryptoError> {
         use solana_sdk::signer::Signer;
         
-        let keypair = Keypair::from_bytes(private_key)
-            .map_err(|_| CryptoError::InvalidPrivateKey)?;
+        let keypair = Keypair::new_from_array(*private_key);
         
         let public_bytes: [u8; 32] = keypair.pubkey().as_ref().try_into()
             .map_err(|_| CryptoError::InvalidPublicKey)?;
@@ -395,6 +595,21 @@ impl KeyDerivation {
         
         Ok(keys)
     }
+    
+    /// Derive the key for a single message from its channel's master key
+    /// 
+    /// The message's payload hash is the HKDF info, so every message gets its
+    /// own key and exposing one message key reveals nothing about the others.
+    pub fn derive_message_key(
+        channel_master: &[u8; 32],
+        payload_hash: &[u8; 32],
+    ) -> Result<[u8; 32], CryptoError> {
+        let okm = Self::hkdf_sha256(channel_master, Some(MESSAGE_KEY_SALT), payload_hash, 32)?;
+        
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&okm);
+        Ok(key)
+    }
 }
 
 /// Utility functions
@@ -420,13 +635,32 @@ pub mod utils {
             .fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
     }
     
+    /// Constant-time comparison of two fixed-size arrays, such as hashes
+    /// 
+    /// Lengths are equal by construction, and every byte is examined whatever
+    /// the contents, so timing reveals nothing about where the inputs differ.
+    pub fn constant_time_eq_fixed<const N: usize>(a: &[u8; N], b: &[u8; N]) -> bool {
+        constant_time_eq_fixed_clocked(a, b, || {})
+    }
+    
+    /// [`constant_time_eq_fixed`], advancing `tick` once per byte examined
+    pub(crate) fn constant_time_eq_fixed_clocked<const N: usize>(
+        a: &[u8; N],
+        b: &[u8; N],
+        mut tick: impl FnMut(),
+    ) -> bool {
+        let mut diff = 0u8;
+        for i in 0..N {
+            diff |= a[i] ^ b[i];
+            tick();
+        }
+        // Keep the optimizer from turning the loop into an early-exit memcmp
+        std::hint::black_box(diff) == 0
+    }
+    
     /// Securely overwrite memory
     pub fn secure_zero(data: &mut [u8]) {
-        for byte in data {
-            unsafe {
-                std::ptr::write_volatile(byte, 0);
-            }
-        }
+        data.zeroize();
     }
 }
 
@@ -534,6 +768,68 @@ impl SymmetricEncryption {
             .map_err(|e| CryptoError::DecryptionError(format!("ChaCha20Poly1305 decryption failed: {}", e)))
     }
     
+    /// Encrypt data using XChaCha20Poly1305
+    /// 
+    /// The 24-byte nonce is large enough to be chosen at random for every
+    /// message under a long-lived key; see [`Self::generate_xnonce`].
+    #[cfg(feature = "chacha20")]
+    pub fn encrypt_xchacha20poly1305(
+        key: &[u8; 32],
+        nonce: &[u8; 24],
+        plaintext: &[u8],
+        associated_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoError> {
+        use chacha20poly1305::{XChaCha20Poly1305, Key, XNonce};
+        use aead::{Aead, NewAead};
+        
+        let key = Key::from_slice(key);
+        let cipher = XChaCha20Poly1305::new(key);
+        
+        let nonce = XNonce::from_slice(nonce);
+        let payload = aead::Payload {
+            msg: plaintext,
+            aad: associated_data.unwrap_or(&[]),
+        };
+        
+        cipher
+            .encrypt(nonce, payload)
+            .map_err(|e| CryptoError::EncryptionError(format!("XChaCha20Poly1305 encryption failed: {}", e)))
+    }
+    
+    /// Decrypt data using XChaCha20Poly1305
+    #[cfg(feature = "chacha20")]
+    pub fn decrypt_xchacha20poly1305(
+        key: &[u8; 32],
+        nonce: &[u8; 24],
+        ciphertext: &[u8],
+        associated_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoError> {
+        use chacha20poly1305::{XChaCha20Poly1305, Key, XNonce};
+        use aead::{Aead, NewAead};
+        
+        let key = Key::from_slice(key);
+        let cipher = XChaCha20Poly1305::new(key);
+        
+        let nonce = XNonce::from_slice(nonce);
+        let payload = aead::Payload {
+            msg: ciphertext,
+            aad: associated_data.unwrap_or(&[]),
+        };
+        
+        cipher
+            .decrypt(nonce, payload)
+            .map_err(|e| CryptoError::DecryptionError(format!("XChaCha20Poly1305 decryption failed: {}", e)))
+    }
+    
+    /// Generate a random 24-byte nonce for XChaCha20Poly1305
+    pub fn generate_xnonce() -> [u8; 24] {
+        use rand::RngCore;
+        
+        let mut nonce = [0u8; 24];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        nonce
+    }
+    
     /// Generate secure nonce for encryption
     pub fn generate_nonce() -> Result<[u8; 12], CryptoError> {
         let mut rng = SecureRng::new()?;
@@ -542,6 +838,22 @@ impl SymmetricEncryption {
         Ok(nonce)
     }
     
+    /// Build a 12-byte nonce from a 4-byte prefix and a message counter
+    /// 
+    /// The nonce is `prefix` followed by `counter` in big-endian order, so
+    /// a sender that draws a random prefix once and increments the counter
+    /// for every message never repeats a nonce, however many it sends.
+    /// 
+    /// The caller must never use the same (key, prefix, counter) twice: the
+    /// counter must not be reset or rolled back while the key and prefix stay
+    /// in use, and two senders sharing a key need distinct prefixes.
+    pub fn nonce_from_counter(prefix: &[u8; 4], counter: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(prefix);
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+    
     /// Generate encryption key from password using PBKDF2
     pub fn derive_key_from_password(
         password: &[u8],
@@ -565,8 +877,6 @@ impl SymmetricEncryption {
     }
 }
 
-use hkdf;
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -599,6 +909,58 @@ mod tests {
         assert!(buffer2.secure_compare(data1));
     }
 
+    #[test]
+    fn test_secure_buffer_keypair_roundtrip() {
+        use solana_sdk::signer::Signer;
+        
+        let keypair = Keypair::new();
+        let buffer = SecureBuffer::from_keypair(&keypair).unwrap();
+        assert_eq!(buffer.len(), KEYPAIR_BYTES);
+        
+        let restored = buffer.to_keypair().unwrap();
+        assert_eq!(restored.pubkey(), keypair.pubkey());
+        
+        let message = b"at-rest key storage";
+        assert_eq!(restored.sign_message(message), keypair.sign_message(message));
+    }
+
+    #[test]
+    fn test_secure_buffer_to_keypair_rejects_wrong_length() {
+        let buffer = SecureBuffer::new(32).unwrap();
+        assert!(matches!(
+            buffer.to_keypair(),
+            Err(CryptoError::InvalidKeySize { expected: KEYPAIR_BYTES, actual: 32 })
+        ));
+        
+        let oversized = SecureBuffer::from_slice(&[7u8; KEYPAIR_BYTES + 1]).unwrap();
+        assert!(matches!(
+            oversized.to_keypair(),
+            Err(CryptoError::InvalidKeySize { expected: KEYPAIR_BYTES, actual: 65 })
+        ));
+    }
+
+    #[test]
+    fn test_secure_buffer_zeroize() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SecureBuffer>();
+        
+        let mut buffer = SecureBuffer::from_slice(&[0xA5; 48]).unwrap();
+        let ptr = buffer.as_slice().as_ptr();
+        
+        // Safety: the buffer is not moved, only zeroed in place
+        unsafe { Pin::get_unchecked_mut(buffer.as_mut()) }.zeroize();
+        
+        // Read the original allocation through the raw pointer, bypassing the buffer
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, 48) };
+        assert!(bytes.iter().all(|&b| b == 0));
+        assert_eq!(buffer.len(), 48);
+        
+        let mut rng = SecureRng::new().unwrap();
+        let mut random = rng.generate_bytes(32).unwrap();
+        unsafe { Pin::get_unchecked_mut(random.as_mut()) }.clear();
+        assert_eq!(random.as_slice(), &[0u8; 32]);
+    }
+
     #[test]
     fn test_hash_functionality() {
         let data = b"Hello, World!";
@@ -625,6 +987,28 @@ mod tests {
         assert!(!Signature::verify(&public_key, b"Wrong message", &signature));
     }
 
+    #[test]
+    fn test_verify_batch_reports_each_item() {
+        let keypairs: Vec<_> = (0..4).map(|_| Keypair::new()).collect();
+        let messages: [&[u8]; 4] = [b"first", b"second", b"third", b"fourth"];
+        let mut items: Vec<_> = keypairs
+            .iter()
+            .zip(messages)
+            .map(|(keypair, message)| {
+                use solana_sdk::signer::Signer;
+                (keypair.pubkey(), message, Signature::sign_with_keypair(keypair, message).unwrap())
+            })
+            .collect();
+        assert_eq!(Signature::verify_batch(&items), vec![true; 4]);
+        
+        // Corrupt the second signature and sign the fourth message with the wrong key
+        items[1].2[0] ^= 0xff;
+        items[3].2 = Signature::sign_with_keypair(&keypairs[0], messages[3]).unwrap();
+        assert_eq!(Signature::verify_batch(&items), vec![true, false, true, false]);
+        
+        assert!(Signature::verify_batch(&[]).is_empty());
+    }
+
     #[test]
     fn test_public_key_derivation() {
         let (private_key, expected_public_key) = Signature::generate_keypair().unwrap();
@@ -657,6 +1041,39 @@ mod tests {
         assert!(!utils::constant_time_eq(data1, b"Short"));
     }
 
+    #[test]
+    fn test_constant_time_eq_fixed_mismatch_sweep() {
+        let a = [0x5au8; 32];
+        assert!(utils::constant_time_eq_fixed(&a, &a.clone()));
+        
+        for position in 0..32 {
+            let mut b = a;
+            b[position] ^= 0x01;
+            assert!(!utils::constant_time_eq_fixed(&a, &b), "mismatch at {} not detected", position);
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq_fixed_work_independent_of_mismatch_position() {
+        // The clock advances per byte examined, so an early exit would show up as
+        // fewer ticks without depending on wall-clock noise
+        const N: usize = 4096;
+        let a = Box::new([0xa5u8; N]);
+        
+        let ticks_for = |b: &[u8; N]| {
+            let mut ticks = 0usize;
+            utils::constant_time_eq_fixed_clocked(&a, b, || ticks += 1);
+            ticks
+        };
+        
+        assert_eq!(ticks_for(&a), N);
+        for position in [0, N / 4, N / 2, 3 * N / 4, N - 1] {
+            let mut b = a.clone();
+            b[position] ^= 0xff;
+            assert_eq!(ticks_for(&b), N, "mismatch at {} ended the comparison early", position);
+        }
+    }
+
     #[test]
     #[cfg(feature = "aes")]
     fn test_aes_gcm_encryption_roundtrip() {
@@ -711,6 +1128,52 @@ mod tests {
         assert_eq!(plaintext, decrypted.as_slice());
     }
 
+    #[test]
+    #[cfg(feature = "chacha20")]
+    fn test_xchacha20poly1305_encryption_roundtrip() {
+        let key = [2u8; 32];
+        let nonce = SymmetricEncryption::generate_xnonce();
+        let plaintext = b"Hello, World! This is an XChaCha20Poly1305 test.";
+        let associated_data = b"xchacha-metadata";
+        
+        let ciphertext = SymmetricEncryption::encrypt_xchacha20poly1305(
+            &key,
+            &nonce,
+            plaintext,
+            Some(associated_data),
+        )
+        .unwrap();
+        
+        let decrypted = SymmetricEncryption::decrypt_xchacha20poly1305(
+            &key,
+            &nonce,
+            &ciphertext,
+            Some(associated_data),
+        )
+        .unwrap();
+        
+        assert_eq!(plaintext, decrypted.as_slice());
+    }
+
+    #[test]
+    #[cfg(feature = "chacha20")]
+    fn test_xchacha20poly1305_rejects_tampered_ciphertext() {
+        let key = [3u8; 32];
+        let nonce = [3u8; 24];
+        
+        let mut ciphertext = SymmetricEncryption::encrypt_xchacha20poly1305(
+            &key,
+            &nonce,
+            b"tamper with me",
+            None,
+        )
+        .unwrap();
+        ciphertext[0] ^= 0x01;
+        
+        let result = SymmetricEncryption::decrypt_xchacha20poly1305(&key, &nonce, &ciphertext, None);
+        assert!(matches!(result, Err(CryptoError::DecryptionError(_))));
+    }
+
     #[test]
     fn test_nonce_generation() {
         let nonce1 = SymmetricEncryption::generate_nonce().unwrap();
@@ -720,6 +1183,38 @@ mod tests {
         assert_ne!(nonce1, nonce2);
     }
 
+    #[test]
+    fn test_counter_nonces_are_distinct_and_ordered() {
+        let prefix = [0xde, 0xad, 0xbe, 0xef];
+        let counters = [0, 1, 2, 255, 256, u32::MAX as u64, u32::MAX as u64 + 1, u64::MAX];
+        let nonces: Vec<[u8; 12]> = counters
+            .iter()
+            .map(|&counter| SymmetricEncryption::nonce_from_counter(&prefix, counter))
+            .collect();
+        
+        // Big-endian counters keep nonces in counter order, so each is distinct
+        assert!(nonces.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            SymmetricEncryption::nonce_from_counter(&prefix, 1),
+            [0xde, 0xad, 0xbe, 0xef, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_counter_nonce_preserves_prefix() {
+        for prefix in [[0u8; 4], [1, 2, 3, 4], [0xff; 4]] {
+            let nonce = SymmetricEncryption::nonce_from_counter(&prefix, 0x0102_0304_0506_0708);
+            assert_eq!(nonce[..4], prefix);
+            assert_eq!(nonce[4..], 0x0102_0304_0506_0708u64.to_be_bytes());
+        }
+        
+        // The same counter under different prefixes gives different nonces
+        assert_ne!(
+            SymmetricEncryption::nonce_from_counter(&[1, 0, 0, 0], 7),
+            SymmetricEncryption::nonce_from_counter(&[2, 0, 0, 0], 7)
+        );
+    }
+
     #[test]
     fn test_password_key_derivation() {
         let password = b"test_password";
@@ -736,4 +1231,108 @@ mod tests {
         let key3 = SymmetricEncryption::derive_key_from_password(password, b"different_salt", iterations).unwrap();
         assert_ne!(key1, key3);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_message_key_derivation() {
+        let master = [0x42u8; 32];
+        let hash_a = Hash::hash(b"first message");
+        let hash_b = Hash::hash(b"second message");
+        
+        let key_a = KeyDerivation::derive_message_key(&master, &hash_a).unwrap();
+        let key_b = KeyDerivation::derive_message_key(&master, &hash_b).unwrap();
+        
+        // Same hash is deterministic, different hashes give different keys
+        assert_eq!(key_a, KeyDerivation::derive_message_key(&master, &hash_a).unwrap());
+        assert_ne!(key_a, key_b);
+        
+        // Different channels give different keys for the same message
+        let other_master = [0x24u8; 32];
+        assert_ne!(key_a, KeyDerivation::derive_message_key(&other_master, &hash_a).unwrap());
+    }
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| Hash::hash(&[i])).collect()
+    }
+
+    #[test]
+    fn test_merkle_single_leaf() {
+        let leaf = leaves(1)[0];
+        let tree = MerkleTree::new(&[leaf]);
+        
+        assert_eq!(tree.root(), merkle_leaf_hash(&leaf));
+        let proof = tree.proof(0);
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&tree.root(), &leaf));
+        assert!(!proof.verify(&tree.root(), &Hash::hash(b"other")));
+        
+        // A bare leaf is not its own root
+        assert_ne!(tree.root(), leaf);
+    }
+
+    #[test]
+    fn test_merkle_two_leaves() {
+        let data = leaves(2);
+        let tree = MerkleTree::new(&data);
+        
+        let expected = merkle_node_hash(&merkle_leaf_hash(&data[0]), &merkle_leaf_hash(&data[1]));
+        assert_eq!(tree.root(), expected);
+        for (i, leaf) in data.iter().enumerate() {
+            assert!(tree.proof(i).verify(&tree.root(), leaf));
+        }
+        // Leaves are bound to their position
+        assert!(!tree.proof(0).verify(&tree.root(), &data[1]));
+    }
+
+    #[test]
+    fn test_merkle_odd_leaf_counts() {
+        for count in [3, 5, 7, 9] {
+            let data = leaves(count);
+            let tree = MerkleTree::new(&data);
+            for (i, leaf) in data.iter().enumerate() {
+                assert!(tree.proof(i).verify(&tree.root(), leaf), "leaf {} of {}", i, count);
+            }
+        }
+        
+        // The last node of an odd level is paired with itself
+        let data = leaves(3);
+        let mut padded = data.clone();
+        padded.push(data[2]);
+        assert_eq!(MerkleTree::new(&data).root(), MerkleTree::new(&padded).root());
+    }
+
+    #[test]
+    fn test_merkle_tamper_detection() {
+        let data = leaves(8);
+        let tree = MerkleTree::new(&data);
+        let root = tree.root();
+        let proof = tree.proof(5);
+        assert!(proof.verify(&root, &data[5]));
+        
+        let mut tampered_leaf = data[5];
+        tampered_leaf[0] ^= 1;
+        assert!(!proof.verify(&root, &tampered_leaf));
+        
+        let mut tampered_sibling = proof.clone();
+        tampered_sibling.siblings[1][31] ^= 1;
+        assert!(!tampered_sibling.verify(&root, &data[5]));
+        
+        let mut wrong_index = proof.clone();
+        wrong_index.index = 4;
+        assert!(!wrong_index.verify(&root, &data[5]));
+        
+        let mut out_of_range = proof.clone();
+        out_of_range.index = 5 + 8;
+        assert!(!out_of_range.verify(&root, &data[5]));
+        
+        let mut truncated = proof;
+        truncated.siblings.pop();
+        assert!(!truncated.verify(&root, &data[5]));
+        
+        // An interior node cannot pass as a leaf
+        let interior = merkle_node_hash(&merkle_leaf_hash(&data[4]), &merkle_leaf_hash(&data[5]));
+        let mut node_proof = tree.proof(4);
+        node_proof.index = 2;
+        node_proof.siblings.remove(0);
+        assert!(!node_proof.verify(&root, &interior));
+    }
+}
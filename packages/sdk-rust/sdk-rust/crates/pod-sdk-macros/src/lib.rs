@@ -85,39 +85,142 @@ pub fn derive_validated_request(input: TokenStream) -> TokenStream {
 }
 
 /// Macro to generate PDA finding functions
+///
+/// The two-argument form derives from a prefix and a wallet:
+///
+/// ```ignore
+/// generate_pda_finder!("agent", "agent");
+/// // fn find_agent_pda(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8)
+/// ```
+///
+/// An optional seed list adds one parameter per seed, in order, after the
+/// prefix. Integer seeds are encoded little-endian; any other seed must
+/// implement `AsRef<[u8]>`.
+///
+/// ```ignore
+/// generate_pda_finder!("message", "message", [channel: Pubkey, message_id: &str]);
+/// // fn find_message_pda(channel: Pubkey, message_id: &str, program_id: &Pubkey) -> (Pubkey, u8)
+/// ```
 #[proc_macro]
 pub fn generate_pda_finder(input: TokenStream) -> TokenStream {
-    let input_str = input.to_string();
-    let parts: Vec<&str> = input_str.split(',').map(|s| s.trim()).collect();
-    
-    if parts.len() != 2 {
-        panic!("generate_pda_finder expects exactly 2 arguments: account_type, seed_prefix");
-    }
-    
-    let account_type = parts[0].trim_matches('"');
-    let seed_prefix = parts[1].trim_matches('"');
+    let spec = parse_macro_input!(input as PdaFinderSpec);
     
     let function_name = syn::Ident::new(
-        &format!("find_{}_pda", account_type.to_lowercase()),
+        &format!("find_{}_pda", spec.account_type.value().to_lowercase()),
         proc_macro2::Span::call_site()
     );
+    let seed_prefix = &spec.seed_prefix;
     
-    let expanded = quote! {
-        /// Find PDA for the account
-        pub fn #function_name(
-            wallet: &solana_sdk::pubkey::Pubkey,
-            program_id: &solana_sdk::pubkey::Pubkey,
-        ) -> (solana_sdk::pubkey::Pubkey, u8) {
-            solana_sdk::pubkey::Pubkey::find_program_address(
-                &[#seed_prefix.as_bytes(), wallet.as_ref()],
-                program_id,
-            )
+    let expanded = match spec.seeds {
+        None => quote! {
+            /// Find PDA for the account
+            pub fn #function_name(
+                wallet: &solana_sdk::pubkey::Pubkey,
+                program_id: &solana_sdk::pubkey::Pubkey,
+            ) -> (solana_sdk::pubkey::Pubkey, u8) {
+                solana_sdk::pubkey::Pubkey::find_program_address(
+                    &[#seed_prefix.as_bytes(), wallet.as_ref()],
+                    program_id,
+                )
+            }
+        },
+        Some(seeds) => {
+            let params = seeds.iter().map(|seed| {
+                let (name, ty) = (&seed.name, &seed.ty);
+                quote! { #name: #ty }
+            });
+            let seed_bytes = seeds.iter().map(|seed| {
+                let name = &seed.name;
+                if is_integer_type(&seed.ty) {
+                    quote! { &#name.to_le_bytes()[..] }
+                } else {
+                    quote! { ::core::convert::AsRef::<[u8]>::as_ref(&#name) }
+                }
+            });
+            
+            quote! {
+                /// Find PDA for the account
+                pub fn #function_name(
+                    #(#params,)*
+                    program_id: &solana_sdk::pubkey::Pubkey,
+                ) -> (solana_sdk::pubkey::Pubkey, u8) {
+                    solana_sdk::pubkey::Pubkey::find_program_address(
+                        &[#seed_prefix.as_bytes(), #(#seed_bytes),*],
+                        program_id,
+                    )
+                }
+            }
         }
     };
     
     TokenStream::from(expanded)
 }
 
+/// Parsed input of [`generate_pda_finder!`]
+struct PdaFinderSpec {
+    account_type: syn::LitStr,
+    seed_prefix: syn::LitStr,
+    seeds: Option<Vec<PdaSeed>>,
+}
+
+/// One `name: Type` entry of a seed list
+struct PdaSeed {
+    name: syn::Ident,
+    ty: syn::Type,
+}
+
+impl syn::parse::Parse for PdaFinderSpec {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let account_type = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let seed_prefix = input.parse()?;
+        
+        let seeds = if input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let content;
+            let brackets = syn::bracketed!(content in input);
+            let seeds = content.parse_terminated(PdaSeed::parse, syn::Token![,])?;
+            if seeds.is_empty() {
+                return Err(syn::Error::new(brackets.span.join(), "seed list cannot be empty"));
+            }
+            input.parse::<Option<syn::Token![,]>>()?;
+            Some(seeds.into_iter().collect())
+        } else {
+            None
+        };
+        
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after seed list"));
+        }
+        
+        Ok(Self { account_type, seed_prefix, seeds })
+    }
+}
+
+impl PdaSeed {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        if !input.peek(syn::Token![:]) {
+            return Err(input.error("expected `:` after seed name"));
+        }
+        input.parse::<syn::Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Self { name, ty })
+    }
+}
+
+/// Whether `ty` is a primitive integer, which is seeded by its LE bytes
+fn is_integer_type(ty: &syn::Type) -> bool {
+    const INTEGERS: [&str; 10] = ["u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128"];
+    
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .get_ident()
+            .is_some_and(|ident| INTEGERS.iter().any(|int| ident == int)),
+        _ => false,
+    }
+}
+
 /// Attribute macro for service methods that require authentication
 #[proc_macro_attribute]
 pub fn requires_auth(_args: TokenStream, input: TokenStream) -> TokenStream {
@@ -132,12 +235,12 @@ pub fn requires_auth(_args: TokenStream, input: TokenStream) -> TokenStream {
     
     // Prepend the auth check to the function body
     let original_block = &function.block;
-    function.block = Box::new(syn::parse2(quote! {
+    *function.block = syn::parse2(quote! {
         {
             #auth_check
             #original_block
         }
-    }).unwrap());
+    }).unwrap();
     
     TokenStream::from(quote! { #function })
 }
@@ -0,0 +1,6 @@
+#[test]
+fn pda_finder_expansions() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pda_multi_seed.rs");
+    t.compile_fail("tests/ui/pda_malformed_seeds.rs");
+}
@@ -0,0 +1,5 @@
+use pod_sdk_macros::generate_pda_finder;
+
+generate_pda_finder!("message", "message", [channel Pubkey, message_id: &str]);
+
+fn main() {}
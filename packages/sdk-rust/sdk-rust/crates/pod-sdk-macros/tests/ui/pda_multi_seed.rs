@@ -0,0 +1,24 @@
+use pod_sdk_macros::generate_pda_finder;
+use solana_sdk::pubkey::Pubkey;
+
+generate_pda_finder!("agent", "agent");
+generate_pda_finder!("message", "message", [channel: Pubkey, message_id: &str]);
+generate_pda_finder!("escrow", "escrow", [payer: Pubkey, escrow_id: &str, nonce: u64]);
+
+fn main() {
+    let program_id = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+
+    assert_eq!(
+        find_agent_pda(&wallet, &program_id),
+        Pubkey::find_program_address(&[b"agent", wallet.as_ref()], &program_id),
+    );
+    assert_eq!(
+        find_message_pda(wallet, "msg-1", &program_id),
+        Pubkey::find_program_address(&[b"message", wallet.as_ref(), b"msg-1"], &program_id),
+    );
+    assert_eq!(
+        find_escrow_pda(wallet, "job", 7, &program_id),
+        Pubkey::find_program_address(&[b"escrow", wallet.as_ref(), b"job", &7u64.to_le_bytes()], &program_id),
+    );
+}
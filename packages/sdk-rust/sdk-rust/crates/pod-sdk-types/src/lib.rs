@@ -29,6 +29,16 @@ pub const MAX_CHANNEL_DESCRIPTION_LENGTH: usize = 500;
 /// Maximum length for message content
 pub const MAX_MESSAGE_CONTENT_LENGTH: usize = 10000;
 
+/// Minimum message expiry, in seconds
+pub const MIN_MESSAGE_EXPIRY_SECS: u64 = 60;
+
+/// MIME types a message may be tagged with
+pub const SUPPORTED_CONTENT_TYPES: [&str; 9] = [
+    "text/plain", "text/markdown", "application/json",
+    "application/octet-stream", "image/png", "image/jpeg",
+    "audio/mpeg", "video/mp4", "application/pdf",
+];
+
 /// Agent account structure that mirrors the Solana program
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AgentAccount {
@@ -59,7 +69,7 @@ pub struct AgentAccount {
 }
 
 /// Agent account structure for Borsh serialization (without DateTime fields)
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct AgentAccountBorsh {
     /// The agent's public key (PDA)
     pub pubkey: Pubkey,
@@ -73,6 +83,10 @@ pub struct AgentAccountBorsh {
     pub reputation_score: u64,
     /// Last time the agent was updated (Unix timestamp)
     pub last_updated: i64,
+    /// Last time the agent was updated (Unix timestamp)
+    pub updated_at: i64,
+    /// Agent creation timestamp (Unix timestamp)
+    pub created_at: i64,
     /// URI pointing to agent metadata (IPFS, HTTPS, etc.)
     pub metadata_uri: String,
     /// Number of invites sent by this agent
@@ -92,6 +106,8 @@ impl From<AgentAccount> for AgentAccountBorsh {
             reputation: account.reputation,
             reputation_score: account.reputation_score,
             last_updated: account.last_updated,
+            updated_at: account.updated_at,
+            created_at: account.created_at,
             metadata_uri: account.metadata_uri,
             invites_sent: account.invites_sent,
             last_invite_at: account.last_invite_at,
@@ -109,8 +125,8 @@ impl From<AgentAccountBorsh> for AgentAccount {
             reputation: borsh.reputation,
             reputation_score: borsh.reputation_score,
             last_updated: borsh.last_updated,
-            updated_at: chrono::Utc::now().timestamp(),
-            created_at: chrono::Utc::now().timestamp(),
+            updated_at: borsh.updated_at,
+            created_at: borsh.created_at,
             metadata_uri: borsh.metadata_uri,
             invites_sent: borsh.invites_sent,
             last_invite_at: borsh.last_invite_at,
@@ -145,6 +161,24 @@ impl AgentAccount {
         }
     }
 
+    /// Whether the duplicated reputation and update-time fields agree
+    pub fn reputation_consistent(&self) -> bool {
+        self.reputation == self.reputation_score && self.last_updated == self.updated_at
+    }
+
+    /// Reconcile duplicated fields to their on-chain values
+    ///
+    /// `reputation` and `last_updated` mirror the program's account and are
+    /// canonical; `reputation_score` and `updated_at` are overwritten from
+    /// them. Returns `true` if the fields had diverged. This is a stopgap
+    /// until the duplicate fields are removed.
+    pub fn normalize(&mut self) -> bool {
+        let diverged = !self.reputation_consistent();
+        self.reputation_score = self.reputation;
+        self.updated_at = self.last_updated;
+        diverged
+    }
+
     /// Get DateTime<Utc> from timestamp
     pub fn get_updated_at(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(self.updated_at, 0).unwrap_or_default()
@@ -164,6 +198,47 @@ impl AgentAccount {
     pub fn has_all_capabilities(&self, required: u64) -> bool {
         (self.capabilities & required) == required
     }
+
+    /// Names of the capabilities set on this agent, in bit order
+    ///
+    /// Bits in the custom range are named `custom(N)`; see
+    /// [`capabilities::from_names`] for the reverse mapping.
+    pub fn capability_names(&self) -> Vec<&'static str> {
+        capabilities::names(self.capabilities)
+    }
+}
+
+/// Decay a reputation score exponentially over `elapsed` time
+///
+/// The score halves every `half_life`, rounding down. Whole half-lives are
+/// applied as exact halvings so the result does not drift for large
+/// scores. A zero `half_life` disables decay.
+///
+/// pod-com has no instruction that writes reputation, so a decayed score
+/// can be computed for ranking but not stored on the agent account.
+pub fn decay_reputation(
+    score: u64,
+    elapsed: std::time::Duration,
+    half_life: std::time::Duration,
+) -> u64 {
+    if half_life.is_zero() {
+        return score;
+    }
+
+    let half_life_nanos = half_life.as_nanos();
+    let halvings = elapsed.as_nanos() / half_life_nanos;
+    if halvings >= u64::BITS as u128 {
+        return 0;
+    }
+
+    let halved = score >> halvings;
+    let remainder = elapsed.as_nanos() % half_life_nanos;
+    if remainder == 0 {
+        return halved;
+    }
+
+    let fraction = remainder as f64 / half_life_nanos as f64;
+    (halved as f64 * 0.5f64.powf(fraction)) as u64
 }
 
 /// Message account structure
@@ -223,7 +298,7 @@ pub struct ChannelAccount {
 }
 
 /// Channel account structure for Borsh serialization (without DateTime fields)
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct ChannelAccountBorsh {
     /// Channel creator
     pub creator: Pubkey,
@@ -245,6 +320,8 @@ pub struct ChannelAccountBorsh {
     pub fee_per_message: u64,
     /// Channel creation timestamp
     pub created_at: i64,
+    /// Channel creation timestamp (Unix timestamp)
+    pub created_at_dt: i64,
     /// Last activity timestamp
     pub last_activity: i64,
     /// PDA bump seed
@@ -264,6 +341,7 @@ impl From<ChannelAccount> for ChannelAccountBorsh {
             is_active: account.is_active,
             fee_per_message: account.fee_per_message,
             created_at: account.created_at,
+            created_at_dt: account.created_at_dt,
             last_activity: account.last_activity,
             bump: account.bump,
         }
@@ -283,7 +361,7 @@ impl From<ChannelAccountBorsh> for ChannelAccount {
             is_active: borsh.is_active,
             fee_per_message: borsh.fee_per_message,
             created_at: borsh.created_at,
-            created_at_dt: chrono::Utc::now().timestamp(),
+            created_at_dt: borsh.created_at_dt,
             last_activity: borsh.last_activity,
             bump: borsh.bump,
         }
@@ -300,8 +378,65 @@ impl ChannelAccount {
     pub fn get_created_at_dt(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(self.created_at_dt, 0).unwrap_or_default()
     }
+
+    /// Check that `participant_count == participants.len() <= participant_limit`
+    pub fn validate_invariants(&self) -> Result<(), InconsistentChannelState> {
+        if self.participant_count as usize != self.participants.len() {
+            return Err(InconsistentChannelState::CountMismatch {
+                participant_count: self.participant_count,
+                listed: self.participants.len(),
+            });
+        }
+
+        if self.participants.len() > self.participant_limit as usize {
+            return Err(InconsistentChannelState::OverLimit {
+                participants: self.participants.len(),
+                participant_limit: self.participant_limit,
+            });
+        }
+
+        Ok(())
+    }
 }
 
+/// Violated relationship between a channel's participant fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InconsistentChannelState {
+    /// `participant_count` differs from the number of listed participants
+    CountMismatch {
+        /// Stored participant count
+        participant_count: u32,
+        /// Length of the participant list
+        listed: usize,
+    },
+    /// More participants than the channel's limit allows
+    OverLimit {
+        /// Length of the participant list
+        participants: usize,
+        /// Stored participant limit
+        participant_limit: u32,
+    },
+}
+
+impl fmt::Display for InconsistentChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InconsistentChannelState::CountMismatch { participant_count, listed } => write!(
+                f,
+                "participant_count is {} but {} participants are listed",
+                participant_count, listed
+            ),
+            InconsistentChannelState::OverLimit { participants, participant_limit } => write!(
+                f,
+                "{} participants exceed the limit of {}",
+                participants, participant_limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InconsistentChannelState {}
+
 /// Escrow status enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub enum EscrowStatus {
@@ -355,10 +490,13 @@ pub struct EscrowAccount {
     pub arbitrators: Option<Vec<Pubkey>>,
     /// PDA bump seed
     pub bump: u8,
+    /// Milestones funds are released against, empty for single-release escrows
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
 }
 
 /// Escrow account structure for Borsh serialization (without DateTime fields)
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct EscrowAccountBorsh {
     /// Channel this escrow belongs to
     pub channel: Pubkey,
@@ -374,12 +512,41 @@ pub struct EscrowAccountBorsh {
     pub status: EscrowStatus,
     /// Timestamp when deposit was made
     pub deposited_at: i64,
+    /// Timestamp when deposit was made (Unix timestamp)
+    pub created_at: i64,
+    /// Timeout timestamp (Unix timestamp, optional)
+    pub timeout_at: Option<i64>,
+    /// Disputed timestamp (Unix timestamp, optional)
+    pub disputed_at: Option<i64>,
     /// Release conditions
     pub conditions: Vec<EscrowCondition>,
     /// Optional arbitrators list
     pub arbitrators: Option<Vec<Pubkey>>,
     /// PDA bump seed
     pub bump: u8,
+    /// Milestones funds are released against
+    ///
+    /// Escrows written before milestones existed end at `bump`, so a missing
+    /// trailing field decodes as no milestones.
+    #[borsh(deserialize_with = "deserialize_trailing_milestones")]
+    pub milestones: Vec<Milestone>,
+}
+
+/// Decode the trailing milestone list, treating end of input as empty
+fn deserialize_trailing_milestones<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Vec<Milestone>> {
+    let mut len = [0u8; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..])? {
+            0 if filled == 0 => return Ok(Vec::new()),
+            0 => return Err(borsh::io::ErrorKind::UnexpectedEof.into()),
+            n => filled += n,
+        }
+    }
+
+    (0..u32::from_le_bytes(len))
+        .map(|_| Milestone::deserialize_reader(reader))
+        .collect()
 }
 
 impl From<EscrowAccount> for EscrowAccountBorsh {
@@ -392,9 +559,13 @@ impl From<EscrowAccount> for EscrowAccountBorsh {
             amount: account.amount,
             status: account.status,
             deposited_at: account.deposited_at,
+            created_at: account.created_at,
+            timeout_at: account.timeout_at,
+            disputed_at: account.disputed_at,
             conditions: account.conditions,
             arbitrators: account.arbitrators,
             bump: account.bump,
+            milestones: account.milestones,
         }
     }
 }
@@ -409,16 +580,105 @@ impl From<EscrowAccountBorsh> for EscrowAccount {
             amount: borsh.amount,
             status: borsh.status,
             deposited_at: borsh.deposited_at,
-            created_at: chrono::Utc::now().timestamp(),
-            timeout_at: None,
-            disputed_at: None,
+            created_at: borsh.created_at,
+            timeout_at: borsh.timeout_at,
+            disputed_at: borsh.disputed_at,
             conditions: borsh.conditions,
             arbitrators: borsh.arbitrators,
             bump: borsh.bump,
+            milestones: borsh.milestones,
         }
     }
 }
 
+/// Version byte leading every bincode encoding of an account
+///
+/// Bump this when a `*Borsh` account layout changes so older caches are
+/// rejected instead of misread.
+pub const BINCODE_FORMAT_VERSION: u8 = 1;
+
+/// Failure to encode or decode an account as versioned bincode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BincodeError {
+    /// The data has no version byte
+    Empty,
+    /// The data was written in a different format version
+    UnsupportedVersion {
+        /// Version byte found in the data
+        found: u8,
+        /// Version this build reads and writes
+        expected: u8,
+    },
+    /// bincode failed to encode or decode the account
+    Codec(String),
+}
+
+impl fmt::Display for BincodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BincodeError::Empty => write!(f, "bincode account data is empty"),
+            BincodeError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "bincode account format version {} is not supported (expected {})",
+                found, expected
+            ),
+            BincodeError::Codec(reason) => write!(f, "bincode account encoding failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BincodeError {}
+
+fn to_versioned_bincode<T: Serialize>(account: &T) -> Result<Vec<u8>, BincodeError> {
+    let mut bytes = vec![BINCODE_FORMAT_VERSION];
+    bincode::serialize_into(&mut bytes, account).map_err(|e| BincodeError::Codec(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn from_versioned_bincode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, BincodeError> {
+    let (&version, body) = bytes.split_first().ok_or(BincodeError::Empty)?;
+    if version != BINCODE_FORMAT_VERSION {
+        return Err(BincodeError::UnsupportedVersion { found: version, expected: BINCODE_FORMAT_VERSION });
+    }
+    bincode::deserialize(body).map_err(|e| BincodeError::Codec(e.to_string()))
+}
+
+impl AgentAccountBorsh {
+    /// Encode as bincode behind a [`BINCODE_FORMAT_VERSION`] byte, e.g. for a disk cache
+    pub fn to_bincode(&self) -> Result<Vec<u8>, BincodeError> {
+        to_versioned_bincode(self)
+    }
+
+    /// Decode data written by [`to_bincode`](Self::to_bincode)
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, BincodeError> {
+        from_versioned_bincode(bytes)
+    }
+}
+
+impl ChannelAccountBorsh {
+    /// Encode as bincode behind a [`BINCODE_FORMAT_VERSION`] byte, e.g. for a disk cache
+    pub fn to_bincode(&self) -> Result<Vec<u8>, BincodeError> {
+        to_versioned_bincode(self)
+    }
+
+    /// Decode data written by [`to_bincode`](Self::to_bincode)
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, BincodeError> {
+        from_versioned_bincode(bytes)
+    }
+}
+
+impl EscrowAccountBorsh {
+    /// Encode as bincode behind a [`BINCODE_FORMAT_VERSION`] byte, e.g. for a disk cache
+    pub fn to_bincode(&self) -> Result<Vec<u8>, BincodeError> {
+        to_versioned_bincode(self)
+    }
+
+    /// Decode data written by [`to_bincode`](Self::to_bincode)
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, BincodeError> {
+        from_versioned_bincode(bytes)
+    }
+}
+
 impl EscrowAccount {
     /// Get DateTime<Utc> from timestamp
     pub fn get_created_at(&self) -> DateTime<Utc> {
@@ -434,6 +694,62 @@ impl EscrowAccount {
     pub fn get_disputed_at(&self) -> Option<DateTime<Utc>> {
         self.disputed_at.map(|ts| DateTime::from_timestamp(ts, 0).unwrap_or_default())
     }
+
+    /// Total of completed milestones whose funds have not been released yet
+    pub fn releasable_amount(&self) -> u64 {
+        self.milestones
+            .iter()
+            .filter(|milestone| milestone.is_releasable())
+            .fold(0u64, |total, milestone| total.saturating_add(milestone.amount))
+    }
+}
+
+/// Portion of an escrow released once a piece of work is completed
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct Milestone {
+    /// What has to be delivered
+    pub description: String,
+    /// Amount released on completion in lamports
+    pub amount: u64,
+    /// Progress of the milestone
+    pub status: MilestoneStatus,
+    /// Completion timestamp (Unix timestamp, optional)
+    pub completed_at: Option<i64>,
+    /// Whether the milestone's amount has been paid out
+    pub released: bool,
+}
+
+impl Milestone {
+    /// Create a pending milestone worth `amount` lamports
+    pub fn new(description: impl Into<String>, amount: u64) -> Self {
+        Self {
+            description: description.into(),
+            amount,
+            status: MilestoneStatus::Pending,
+            completed_at: None,
+            released: false,
+        }
+    }
+
+    /// Whether the milestone is completed and still awaiting release
+    pub fn is_releasable(&self) -> bool {
+        self.status == MilestoneStatus::Completed && !self.released
+    }
+}
+
+/// Milestone progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub enum MilestoneStatus {
+    /// Work has not started
+    Pending,
+    /// Work is underway
+    InProgress,
+    /// Work is done and the amount may be released
+    Completed,
+    /// Completion is contested
+    Disputed,
+    /// Milestone was dropped and its amount will not be released
+    Cancelled,
 }
 
 /// Escrow condition structure
@@ -447,6 +763,109 @@ pub struct EscrowCondition {
     pub fulfilled: bool,
 }
 
+/// Condition types understood by the escrow service
+pub const ESCROW_CONDITION_TYPES: [&str; 5] = [
+    "time_elapsed",
+    "service_completion",
+    "approval_count",
+    "arbitration",
+    "external_verification",
+];
+
+impl EscrowCondition {
+    /// Create a builder for an escrow condition
+    pub fn builder() -> EscrowConditionBuilder {
+        EscrowConditionBuilder::default()
+    }
+}
+
+/// Builder for [`EscrowCondition`] with typed parameter helpers
+#[derive(Debug, Default)]
+pub struct EscrowConditionBuilder {
+    condition_type: Option<String>,
+    conflicting_type: bool,
+    parameters: std::collections::HashMap<String, String>,
+}
+
+impl EscrowConditionBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Release once `duration` has passed since the escrow was created
+    pub fn time_elapsed(self, duration: std::time::Duration) -> Self {
+        self.with_type("time_elapsed")
+            .parameter("seconds", duration.as_secs().to_string())
+    }
+
+    /// Release once the service in `channel` is marked complete
+    pub fn service_completion(self, channel: Pubkey) -> Self {
+        self.with_type("service_completion")
+            .parameter("channel", channel.to_string())
+    }
+
+    /// Release once at least `min_approvals` approvals are recorded
+    pub fn approval_count(self, min_approvals: u32) -> Self {
+        self.with_type("approval_count")
+            .parameter("min_approvals", min_approvals.to_string())
+    }
+
+    /// Set a condition type and one of its parameters directly
+    ///
+    /// May be called repeatedly to add parameters to the same condition type.
+    pub fn custom<S: Into<String>>(self, condition_type: &str, key: S, value: S) -> Self {
+        self.with_type(condition_type).parameter(key, value)
+    }
+
+    /// Build the condition
+    ///
+    /// Fails if no type was set, the type is not one the escrow service
+    /// understands, or different types were set on the same builder.
+    pub fn build(self) -> Result<EscrowCondition, &'static str> {
+        if self.conflicting_type {
+            return Err("Escrow condition has conflicting types");
+        }
+
+        let condition_type = self.condition_type.ok_or("Escrow condition type is required")?;
+        if !ESCROW_CONDITION_TYPES.contains(&condition_type.as_str()) {
+            return Err("Unknown escrow condition type");
+        }
+
+        Ok(EscrowCondition {
+            condition_type,
+            parameters: self.parameters,
+            fulfilled: false,
+        })
+    }
+
+    fn with_type(mut self, condition_type: &str) -> Self {
+        let condition_type = normalize_condition_type(condition_type);
+        match &self.condition_type {
+            Some(existing) if *existing != condition_type => self.conflicting_type = true,
+            _ => self.condition_type = Some(condition_type),
+        }
+        self
+    }
+
+    fn parameter<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.parameters.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Normalize a condition type to the lowercase snake_case used for matching
+fn normalize_condition_type(condition_type: &str) -> String {
+    condition_type
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '-' | ' ' => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
 /// Analytics account structure
 #[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct AnalyticsAccount {
@@ -553,6 +972,88 @@ pub struct RegisterAgentRequest {
     pub metadata_uri: String,
 }
 
+impl RegisterAgentRequest {
+    /// Start building a validated registration request
+    pub fn builder() -> RegisterAgentBuilder {
+        RegisterAgentBuilder::new()
+    }
+
+    /// Check the request against protocol limits before it is submitted
+    ///
+    /// The metadata URI must be non-empty and at most
+    /// [`MAX_METADATA_URI_LENGTH`] bytes.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let actual = self.metadata_uri.len();
+        if actual == 0 || actual > MAX_METADATA_URI_LENGTH {
+            return Err(ValidationError::InvalidLength {
+                field: "metadata_uri",
+                max: MAX_METADATA_URI_LENGTH,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// URI schemes accepted for agent metadata, the ones the SDK can fetch it from
+pub const METADATA_URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+
+/// Builder for agent registration requests
+#[derive(Debug, Default)]
+pub struct RegisterAgentBuilder {
+    capabilities: u64,
+    metadata_uri: Option<String>,
+}
+
+impl RegisterAgentBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a capability; repeated calls accumulate bits
+    pub fn capability(mut self, capability: u64) -> Self {
+        self.capabilities |= capability;
+        self
+    }
+
+    /// Add every capability bit in `mask`
+    pub fn capabilities(mut self, mask: u64) -> Self {
+        self.capabilities |= mask;
+        self
+    }
+
+    /// Set metadata URI
+    pub fn metadata_uri<S: Into<String>>(mut self, uri: S) -> Self {
+        self.metadata_uri = Some(uri.into());
+        self
+    }
+
+    /// Build the request
+    pub fn build(self) -> Result<RegisterAgentRequest, &'static str> {
+        let metadata_uri = self.metadata_uri.ok_or("Metadata URI is required")?;
+
+        if metadata_uri.is_empty() {
+            return Err("Metadata URI cannot be empty");
+        }
+        if metadata_uri.len() > MAX_METADATA_URI_LENGTH {
+            return Err("Metadata URI too long");
+        }
+        if !METADATA_URI_SCHEMES.iter().any(|scheme| metadata_uri.starts_with(scheme)) {
+            return Err("Metadata URI must use an https, ipfs or ar scheme");
+        }
+
+        if self.capabilities == 0 {
+            return Err("At least one capability is required");
+        }
+
+        Ok(RegisterAgentRequest {
+            capabilities: self.capabilities,
+            metadata_uri,
+        })
+    }
+}
+
 /// Request structure for sending a message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendMessageRequest {
@@ -566,8 +1067,216 @@ pub struct SendMessageRequest {
     pub expiry: Option<std::time::Duration>,
     /// Optional reply-to message
     pub reply_to: Option<Pubkey>,
+    /// Capabilities the recipient must have to execute a command message
+    #[serde(default)]
+    pub required_capabilities: Option<u64>,
+    /// Request/response correlation id; generated for commands when absent
+    #[serde(default)]
+    pub correlation_id: Option<[u8; 16]>,
+    /// MIME type of the payload, one of [`SUPPORTED_CONTENT_TYPES`]
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+impl SendMessageRequest {
+    /// Check the request against protocol limits before it is submitted
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_message(&self.payload, self.expiry)?;
+        match &self.content_type {
+            Some(content_type) => validate_content_type(content_type),
+            None => Ok(()),
+        }
+    }
+
+    /// Start building a message request
+    pub fn builder() -> SendMessageBuilder {
+        SendMessageBuilder::new()
+    }
 }
 
+/// Builder for message requests
+#[derive(Debug, Default)]
+pub struct SendMessageBuilder {
+    recipient: Option<Pubkey>,
+    payload: Option<Vec<u8>>,
+    message_type: Option<MessageType>,
+    expiry: Option<std::time::Duration>,
+    reply_to: Option<Pubkey>,
+    content_type: Option<String>,
+}
+
+impl SendMessageBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set recipient agent PDA
+    pub fn recipient(mut self, recipient: Pubkey) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    /// Set raw message payload
+    pub fn payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Set a UTF-8 text payload and mark the message as [`MessageType::Text`]
+    pub fn text(mut self, text: &str) -> Self {
+        self.payload = Some(text.as_bytes().to_vec());
+        self.message_type = Some(MessageType::Text);
+        self
+    }
+
+    /// Set message type
+    pub fn message_type(mut self, message_type: MessageType) -> Self {
+        self.message_type = Some(message_type);
+        self
+    }
+
+    /// Set how long the message stays valid
+    pub fn expiry(mut self, expiry: std::time::Duration) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Set the message this one replies to
+    pub fn reply_to(mut self, message: Pubkey) -> Self {
+        self.reply_to = Some(message);
+        self
+    }
+
+    /// Tag the payload with a MIME type from [`SUPPORTED_CONTENT_TYPES`]
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Build the request
+    pub fn build(self) -> Result<SendMessageRequest, &'static str> {
+        let recipient = self.recipient.ok_or("Recipient is required")?;
+        let payload = self.payload.ok_or("Message payload is required")?;
+
+        match validate_message(&payload, self.expiry) {
+            Ok(()) => {}
+            Err(ValidationError::EmptyPayload) => return Err("Message payload cannot be empty"),
+            Err(ValidationError::PayloadTooLarge { .. }) => return Err("Message payload too long"),
+            Err(ValidationError::ExpiryTooShort { .. }) => return Err("Message expiry too short"),
+            Err(ValidationError::InvalidLength { .. }) => return Err("Message field has an invalid length"),
+            Err(ValidationError::UnsupportedContentType) => return Err("Unsupported content type"),
+        }
+        if let Some(content_type) = &self.content_type {
+            if validate_content_type(content_type).is_err() {
+                return Err("Unsupported content type");
+            }
+        }
+
+        Ok(SendMessageRequest {
+            recipient,
+            payload,
+            message_type: self.message_type.unwrap_or(MessageType::Text),
+            expiry: self.expiry,
+            reply_to: self.reply_to,
+            required_capabilities: None,
+            correlation_id: None,
+            content_type: self.content_type,
+        })
+    }
+}
+
+/// Check a message payload and optional expiry against protocol limits
+///
+/// The payload must be non-empty and at most [`MAX_MESSAGE_CONTENT_LENGTH`]
+/// bytes; an expiry, if given, must be at least [`MIN_MESSAGE_EXPIRY_SECS`].
+pub fn validate_message(
+    payload: &[u8],
+    expiry: Option<std::time::Duration>,
+) -> Result<(), ValidationError> {
+    if payload.is_empty() {
+        return Err(ValidationError::EmptyPayload);
+    }
+    if payload.len() > MAX_MESSAGE_CONTENT_LENGTH {
+        return Err(ValidationError::PayloadTooLarge {
+            len: payload.len(),
+            max: MAX_MESSAGE_CONTENT_LENGTH,
+        });
+    }
+    if let Some(expiry) = expiry {
+        if expiry.as_secs() < MIN_MESSAGE_EXPIRY_SECS {
+            return Err(ValidationError::ExpiryTooShort {
+                expiry,
+                min_secs: MIN_MESSAGE_EXPIRY_SECS,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that `content_type` is one of [`SUPPORTED_CONTENT_TYPES`]
+pub fn validate_content_type(content_type: &str) -> Result<(), ValidationError> {
+    if SUPPORTED_CONTENT_TYPES.contains(&content_type) {
+        Ok(())
+    } else {
+        Err(ValidationError::UnsupportedContentType)
+    }
+}
+
+/// Request that violates a protocol limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Message payload is empty
+    EmptyPayload,
+    /// Message payload exceeds the maximum length
+    PayloadTooLarge {
+        /// Payload length in bytes
+        len: usize,
+        /// Maximum allowed length in bytes
+        max: usize,
+    },
+    /// Message expiry is shorter than the minimum
+    ExpiryTooShort {
+        /// Requested expiry
+        expiry: std::time::Duration,
+        /// Minimum allowed expiry in seconds
+        min_secs: u64,
+    },
+    /// A field is empty or longer than its maximum
+    InvalidLength {
+        /// Name of the offending field
+        field: &'static str,
+        /// Maximum allowed length in bytes
+        max: usize,
+        /// Actual length in bytes
+        actual: usize,
+    },
+    /// Content type is not in [`SUPPORTED_CONTENT_TYPES`]
+    UnsupportedContentType,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyPayload => write!(f, "message payload is empty"),
+            ValidationError::PayloadTooLarge { len, max } => {
+                write!(f, "message payload is {} bytes (max: {})", len, max)
+            }
+            ValidationError::ExpiryTooShort { expiry, min_secs } => {
+                write!(f, "message expiry {:?} is shorter than {}s", expiry, min_secs)
+            }
+            ValidationError::InvalidLength { field, max, actual } => {
+                write!(f, "{} is {} bytes (must be 1 to {})", field, actual, max)
+            }
+            ValidationError::UnsupportedContentType => {
+                write!(f, "content type is not one of {:?}", SUPPORTED_CONTENT_TYPES)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
 /// Request structure for creating a channel
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateChannelRequest {
@@ -715,12 +1424,274 @@ pub mod capabilities {
     
     /// Custom capability base (use with custom bit positions)
     pub const CUSTOM_BASE: u64 = 1 << 32;
+
+    /// Predefined capabilities and their names
+    pub const NAMED: [(&str, u64); 10] = [
+        ("AI_CHAT", AI_CHAT),
+        ("DATA_ANALYSIS", DATA_ANALYSIS),
+        ("TASK_AUTOMATION", TASK_AUTOMATION),
+        ("CODE_GENERATION", CODE_GENERATION),
+        ("IMAGE_PROCESSING", IMAGE_PROCESSING),
+        ("AUDIO_PROCESSING", AUDIO_PROCESSING),
+        ("VIDEO_PROCESSING", VIDEO_PROCESSING),
+        ("NLU", NLU),
+        ("ML_TRAINING", ML_TRAINING),
+        ("BLOCKCHAIN_OPS", BLOCKCHAIN_OPS),
+    ];
+
+    /// Tokens for custom capabilities; `custom(N)` is bit `CUSTOM_BASE << N`
+    const CUSTOM_NAMES: [&str; 32] = [
+        "custom(0)", "custom(1)", "custom(2)", "custom(3)",
+        "custom(4)", "custom(5)", "custom(6)", "custom(7)",
+        "custom(8)", "custom(9)", "custom(10)", "custom(11)",
+        "custom(12)", "custom(13)", "custom(14)", "custom(15)",
+        "custom(16)", "custom(17)", "custom(18)", "custom(19)",
+        "custom(20)", "custom(21)", "custom(22)", "custom(23)",
+        "custom(24)", "custom(25)", "custom(26)", "custom(27)",
+        "custom(28)", "custom(29)", "custom(30)", "custom(31)",
+    ];
+
+    /// Capability name that does not map to a known bit
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct UnknownCapability {
+        /// The unrecognized name
+        pub name: String,
+    }
+
+    impl std::fmt::Display for UnknownCapability {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "unknown capability: {:?}", self.name)
+        }
+    }
+
+    impl std::error::Error for UnknownCapability {}
+
+    /// Names of the capability bits set in `mask`, in bit order
+    ///
+    /// Reserved bits between the predefined and custom ranges have no name
+    /// and are omitted.
+    pub fn names(mask: u64) -> Vec<&'static str> {
+        let named = NAMED.iter().filter(|(_, bit)| mask & bit != 0).map(|(name, _)| *name);
+        let custom = CUSTOM_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(n, _)| mask & (CUSTOM_BASE << n) != 0)
+            .map(|(_, name)| *name);
+        named.chain(custom).collect()
+    }
+
+    /// Build a capability mask from names such as `"AI_CHAT"` or `"custom(3)"`
+    pub fn from_names(names: &[&str]) -> Result<u64, UnknownCapability> {
+        names.iter().try_fold(0, |mask, name| {
+            let bit = NAMED
+                .iter()
+                .find(|(known, _)| known == name)
+                .map(|(_, bit)| *bit)
+                .or_else(|| parse_custom(name))
+                .ok_or_else(|| UnknownCapability { name: name.to_string() })?;
+            Ok(mask | bit)
+        })
+    }
+
+    /// Jaccard similarity of two capability masks: shared bits over bits in either
+    ///
+    /// Two empty masks are identical and score 1.0.
+    pub fn similarity(a: u64, b: u64) -> f64 {
+        let union = (a | b).count_ones();
+        if union == 0 {
+            return 1.0;
+        }
+        (a & b).count_ones() as f64 / union as f64
+    }
+
+    /// Fraction of `b`'s capability bits that `a` lacks
+    ///
+    /// Measures how much `b` would add to `a`; 0.0 when `b` has no capabilities.
+    pub fn complementarity(a: u64, b: u64) -> f64 {
+        let offered = b.count_ones();
+        if offered == 0 {
+            return 0.0;
+        }
+        (b & !a).count_ones() as f64 / offered as f64
+    }
+
+    fn parse_custom(name: &str) -> Option<u64> {
+        let n: u32 = name.strip_prefix("custom(")?.strip_suffix(')')?.parse().ok()?;
+        // Reject non-canonical spellings like "custom(+1)" or "custom(01)"
+        if n >= 32 || CUSTOM_NAMES[n as usize] != name {
+            return None;
+        }
+        Some(CUSTOM_BASE << n)
+    }
+}
+
+/// Set of agent capabilities backed by a [`capabilities`] bitmask
+///
+/// ```
+/// use pod_sdk_types::{capabilities, AgentCapabilities};
+///
+/// let caps = AgentCapabilities::none().with(capabilities::AI_CHAT).with(capabilities::NLU);
+/// assert!(caps.contains(capabilities::AI_CHAT));
+/// assert_eq!(caps.iter().collect::<Vec<_>>(), vec!["AI_CHAT", "NLU"]);
+/// assert_eq!(u64::from(caps), capabilities::AI_CHAT | capabilities::NLU);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AgentCapabilities(u64);
+
+impl AgentCapabilities {
+    /// Every predefined capability; custom bits are not included
+    pub fn all() -> Self {
+        Self(capabilities::NAMED.iter().fold(0, |mask, (_, bit)| mask | bit))
+    }
+
+    /// No capabilities
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// These capabilities plus `capability`
+    pub const fn with(self, capability: u64) -> Self {
+        Self(self.0 | capability)
+    }
+
+    /// These capabilities minus `capability`
+    pub const fn without(self, capability: u64) -> Self {
+        Self(self.0 & !capability)
+    }
+
+    /// Whether every bit of `capability` is set
+    pub const fn contains(self, capability: u64) -> bool {
+        self.0 & capability == capability
+    }
+
+    /// Names of the set capabilities, in bit order
+    ///
+    /// See [`capabilities::names`] for how bits are named.
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> {
+        capabilities::names(self.0).into_iter()
+    }
+}
+
+impl From<u64> for AgentCapabilities {
+    fn from(mask: u64) -> Self {
+        Self(mask)
+    }
+}
+
+impl From<AgentCapabilities> for u64 {
+    fn from(capabilities: AgentCapabilities) -> Self {
+        capabilities.0
+    }
+}
+
+/// Program derived addresses, using the seeds the on-chain program checks
+///
+/// Every SDK derives account addresses through these functions so a change
+/// to the program's seeds only has to be mirrored in one place.
+pub mod pda {
+    use super::MessageType;
+    use solana_sdk::pubkey::Pubkey;
+
+    /// Seed prefix of agent accounts
+    pub const AGENT_SEED: &[u8] = b"agent";
+
+    /// Seed prefix of channel accounts
+    pub const CHANNEL_SEED: &[u8] = b"channel";
+
+    /// Seed prefix of direct message accounts
+    pub const MESSAGE_SEED: &[u8] = b"message";
+
+    /// Seed prefix of escrow accounts
+    pub const ESCROW_SEED: &[u8] = b"escrow";
+
+    /// Agent account registered by `wallet`
+    pub fn agent_pda(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[AGENT_SEED, wallet.as_ref()], program_id)
+    }
+
+    /// Channel account created by `creator` under `name`
+    pub fn channel_pda(program_id: &Pubkey, creator: &Pubkey, name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CHANNEL_SEED, creator.as_ref(), name.as_bytes()], program_id)
+    }
+
+    /// Direct message account sent by `sender_agent` to `recipient`
+    ///
+    /// `message_type_seed` is the byte the program derives from the message
+    /// type; see [`message_type_seed`].
+    pub fn message_pda(
+        program_id: &Pubkey,
+        sender_agent: &Pubkey,
+        recipient: &Pubkey,
+        payload_hash: &[u8; 32],
+        message_type_seed: u8,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                MESSAGE_SEED,
+                sender_agent.as_ref(),
+                recipient.as_ref(),
+                payload_hash,
+                &[message_type_seed],
+            ],
+            program_id,
+        )
+    }
+
+    /// Escrow account funded by `depositor` for `channel`
+    pub fn escrow_pda(program_id: &Pubkey, channel: &Pubkey, depositor: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_SEED, channel.as_ref(), depositor.as_ref()], program_id)
+    }
+
+    /// Seed byte the program uses for `message_type` in message addresses
+    pub fn message_type_seed(message_type: &MessageType) -> u8 {
+        match message_type {
+            MessageType::Text => 0,
+            MessageType::Data => 1,
+            MessageType::Command => 2,
+            MessageType::Response => 3,
+            MessageType::Custom(x) => 4u8.wrapping_add(*x),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pda_seeds_match_program() {
+        let program_id = Pubkey::new_unique();
+        let (wallet, channel, recipient) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let payload_hash = [7u8; 32];
+
+        let expected = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        assert_eq!(pda::agent_pda(&program_id, &wallet), expected(&[b"agent", wallet.as_ref()]));
+        assert_eq!(
+            pda::channel_pda(&program_id, &wallet, "general"),
+            expected(&[b"channel", wallet.as_ref(), b"general"])
+        );
+        assert_eq!(
+            pda::message_pda(&program_id, &wallet, &recipient, &payload_hash, 2),
+            expected(&[b"message", wallet.as_ref(), recipient.as_ref(), &payload_hash, &[2]])
+        );
+        assert_eq!(
+            pda::escrow_pda(&program_id, &channel, &wallet),
+            expected(&[b"escrow", channel.as_ref(), wallet.as_ref()])
+        );
+
+        // The program ID is part of every derivation
+        assert_ne!(pda::agent_pda(&Pubkey::new_unique(), &wallet), pda::agent_pda(&program_id, &wallet));
+    }
+
+    #[test]
+    fn test_message_type_seeds() {
+        assert_eq!(pda::message_type_seed(&MessageType::Text), 0);
+        assert_eq!(pda::message_type_seed(&MessageType::Response), 3);
+        assert_eq!(pda::message_type_seed(&MessageType::Custom(10)), 14);
+        assert_eq!(pda::message_type_seed(&MessageType::Custom(255)), 3);
+    }
+
     #[test]
     fn test_agent_capabilities() {
         let agent = AgentAccount::new(
@@ -740,6 +1711,95 @@ mod tests {
         assert!(!agent.has_all_capabilities(capabilities::AI_CHAT | capabilities::CODE_GENERATION));
     }
 
+    #[test]
+    fn test_consistent_reputation_unchanged_by_normalize() {
+        let mut agent = AgentAccount::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            capabilities::AI_CHAT,
+            "https://example.com/metadata.json".to_string(),
+            255,
+        );
+        agent.reputation = 42;
+        agent.reputation_score = 42;
+        let before = agent.clone();
+
+        assert!(agent.reputation_consistent());
+        assert!(!agent.normalize());
+        assert_eq!(agent, before);
+    }
+
+    #[test]
+    fn test_divergent_reputation_normalized_to_canonical() {
+        let borsh = AgentAccountBorsh {
+            pubkey: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            capabilities: capabilities::AI_CHAT,
+            reputation: 75,
+            reputation_score: 10,
+            last_updated: 1_700_000_000,
+            updated_at: 1_600_000_000,
+            created_at: 1_600_000_000,
+            metadata_uri: "https://example.com/metadata.json".to_string(),
+            invites_sent: 0,
+            last_invite_at: 0,
+            bump: 255,
+        };
+        let mut agent = AgentAccount::from(borsh);
+
+        assert!(!agent.reputation_consistent());
+        assert!(agent.normalize());
+        assert_eq!(agent.reputation_score, 75);
+        assert_eq!(agent.updated_at, 1_700_000_000);
+        assert!(agent.reputation_consistent());
+    }
+
+    fn channel_with(participants: usize, participant_count: u32, participant_limit: u32) -> ChannelAccount {
+        ChannelAccount::from(ChannelAccountBorsh {
+            creator: Pubkey::new_unique(),
+            name: "test".to_string(),
+            description: String::new(),
+            visibility: ChannelVisibility::Public,
+            participant_limit,
+            participant_count,
+            participants: (0..participants).map(|_| Pubkey::new_unique()).collect(),
+            is_active: true,
+            fee_per_message: 0,
+            created_at: 0,
+            created_at_dt: 0,
+            last_activity: 0,
+            bump: 255,
+        })
+    }
+
+    #[test]
+    fn test_consistent_channel_passes_invariants() {
+        assert_eq!(channel_with(3, 3, 10).validate_invariants(), Ok(()));
+        assert_eq!(channel_with(10, 10, 10).validate_invariants(), Ok(()));
+        assert_eq!(channel_with(0, 0, 0).validate_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_inconsistent_channels_rejected() {
+        assert_eq!(
+            channel_with(3, 4, 10).validate_invariants(),
+            Err(InconsistentChannelState::CountMismatch { participant_count: 4, listed: 3 })
+        );
+        assert_eq!(
+            channel_with(5, 2, 10).validate_invariants(),
+            Err(InconsistentChannelState::CountMismatch { participant_count: 2, listed: 5 })
+        );
+        assert_eq!(
+            channel_with(12, 12, 10).validate_invariants(),
+            Err(InconsistentChannelState::OverLimit { participants: 12, participant_limit: 10 })
+        );
+        // A count over the limit that also disagrees with the list is reported as a mismatch
+        assert!(matches!(
+            channel_with(1, 20, 10).validate_invariants(),
+            Err(InconsistentChannelState::CountMismatch { .. })
+        ));
+    }
+
     #[test]
     fn test_channel_builder() {
         let request = CreateChannelBuilder::new()
@@ -758,6 +1818,501 @@ mod tests {
         assert_eq!(request.fee_per_message, 1000);
     }
 
+    #[test]
+    fn test_send_message_builder_text() {
+        let recipient = Pubkey::new_unique();
+        let parent = Pubkey::new_unique();
+        let request = SendMessageRequest::builder()
+            .message_type(MessageType::Data)
+            .text("hello")
+            .recipient(recipient)
+            .expiry(std::time::Duration::from_secs(3600))
+            .reply_to(parent)
+            .build()
+            .unwrap();
+
+        assert_eq!(request.recipient, recipient);
+        assert_eq!(request.payload, b"hello");
+        assert_eq!(request.message_type, MessageType::Text);
+        assert_eq!(request.expiry, Some(std::time::Duration::from_secs(3600)));
+        assert_eq!(request.reply_to, Some(parent));
+    }
+
+    #[test]
+    fn test_send_message_builder_requires_recipient_and_payload() {
+        assert_eq!(
+            SendMessageBuilder::new().text("hello").build().unwrap_err(),
+            "Recipient is required"
+        );
+        assert_eq!(
+            SendMessageBuilder::new().recipient(Pubkey::new_unique()).build().unwrap_err(),
+            "Message payload is required"
+        );
+        assert_eq!(
+            SendMessageBuilder::new().recipient(Pubkey::new_unique()).payload(Vec::new()).build().unwrap_err(),
+            "Message payload cannot be empty"
+        );
+    }
+
+    #[test]
+    fn test_register_agent_builder_accumulates_capabilities() {
+        let request = RegisterAgentRequest::builder()
+            .capability(capabilities::AI_CHAT)
+            .capability(capabilities::DATA_ANALYSIS)
+            .capabilities(capabilities::CODE_GENERATION | capabilities::NLU)
+            .metadata_uri("https://example.com/agent.json")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.capabilities,
+            capabilities::AI_CHAT | capabilities::DATA_ANALYSIS | capabilities::CODE_GENERATION | capabilities::NLU
+        );
+        assert_eq!(request.metadata_uri, "https://example.com/agent.json");
+    }
+
+    #[test]
+    fn test_register_agent_builder_rejects_invalid_uri() {
+        let builder = || RegisterAgentRequest::builder().capability(capabilities::AI_CHAT);
+
+        assert!(builder().build().is_err());
+        assert!(builder().metadata_uri("").build().is_err());
+        assert!(builder().metadata_uri("ftp://example.com/agent.json").build().is_err());
+        assert!(builder().metadata_uri("http://example.com/agent.json").build().is_err());
+        assert!(builder()
+            .metadata_uri(format!("https://{}", "a".repeat(MAX_METADATA_URI_LENGTH)))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_register_agent_request_uri_length() {
+        let request = |len: usize| RegisterAgentRequest {
+            capabilities: capabilities::AI_CHAT,
+            metadata_uri: format!("https://{}", "a".repeat(len.saturating_sub(8))),
+        };
+        let invalid = |actual| {
+            Err(ValidationError::InvalidLength {
+                field: "metadata_uri",
+                max: MAX_METADATA_URI_LENGTH,
+                actual,
+            })
+        };
+
+        let empty = RegisterAgentRequest { metadata_uri: String::new(), ..request(0) };
+        assert_eq!(empty.validate(), invalid(0));
+        assert_eq!(request(MAX_METADATA_URI_LENGTH).validate(), Ok(()));
+        assert_eq!(request(MAX_METADATA_URI_LENGTH + 1).validate(), invalid(MAX_METADATA_URI_LENGTH + 1));
+    }
+
+    #[test]
+    fn test_register_agent_builder_rejects_zero_capabilities() {
+        let result = RegisterAgentRequest::builder()
+            .capabilities(0)
+            .metadata_uri("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi")
+            .build();
+
+        assert_eq!(result.unwrap_err(), "At least one capability is required");
+    }
+
+    mod borsh_roundtrip {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn through_borsh<A, B>(account: A) -> A
+        where
+            A: From<B>,
+            B: From<A> + BorshSerialize + BorshDeserialize,
+        {
+            let bytes = borsh::to_vec(&B::from(account)).unwrap();
+            A::from(B::try_from_slice(&bytes).unwrap())
+        }
+
+        proptest! {
+            #[test]
+            fn agent_timestamps_preserved(
+                last_updated: i64,
+                updated_at: i64,
+                created_at: i64,
+                last_invite_at: i64,
+            ) {
+                let mut agent = AgentAccount::new(
+                    Pubkey::new_unique(),
+                    Pubkey::new_unique(),
+                    capabilities::AI_CHAT,
+                    "https://example.com/agent.json".to_string(),
+                    255,
+                );
+                agent.last_updated = last_updated;
+                agent.updated_at = updated_at;
+                agent.created_at = created_at;
+                agent.last_invite_at = last_invite_at;
+
+                prop_assert_eq!(through_borsh::<_, AgentAccountBorsh>(agent.clone()), agent);
+            }
+
+            #[test]
+            fn channel_timestamps_preserved(
+                created_at: i64,
+                created_at_dt: i64,
+                last_activity: i64,
+            ) {
+                let channel = ChannelAccount {
+                    creator: Pubkey::new_unique(),
+                    name: "general".to_string(),
+                    description: String::new(),
+                    visibility: ChannelVisibility::Public,
+                    participant_limit: 10,
+                    participant_count: 0,
+                    participants: vec![],
+                    is_active: true,
+                    fee_per_message: 0,
+                    created_at,
+                    created_at_dt,
+                    last_activity,
+                    bump: 254,
+                };
+
+                prop_assert_eq!(through_borsh::<_, ChannelAccountBorsh>(channel.clone()), channel);
+            }
+
+            #[test]
+            fn escrow_timestamps_preserved(
+                deposited_at: i64,
+                created_at: i64,
+                timeout_at: Option<i64>,
+                disputed_at: Option<i64>,
+            ) {
+                let escrow = EscrowAccount {
+                    channel: Pubkey::new_unique(),
+                    depositor: Pubkey::new_unique(),
+                    payer: Pubkey::new_unique(),
+                    beneficiary: Pubkey::new_unique(),
+                    amount: 1_000,
+                    status: EscrowStatus::Active,
+                    deposited_at,
+                    created_at,
+                    timeout_at,
+                    disputed_at,
+                    conditions: vec![],
+                    arbitrators: None,
+                    bump: 253,
+                    milestones: vec![Milestone::new("design", 400)],
+                };
+
+                prop_assert_eq!(through_borsh::<_, EscrowAccountBorsh>(escrow.clone()), escrow);
+            }
+        }
+    }
+
+    #[test]
+    fn test_escrow_without_milestones_decodes_from_old_layout() {
+        let current = borsh::to_vec(&EscrowAccountBorsh::from(milestone_escrow(vec![]))).unwrap();
+
+        // The pre-milestone layout is the same bytes minus the empty list's length prefix
+        let old_layout = &current[..current.len() - 4];
+        let decoded = EscrowAccountBorsh::try_from_slice(old_layout).unwrap();
+        assert!(decoded.milestones.is_empty());
+        assert_eq!(borsh::to_vec(&decoded).unwrap(), current);
+
+        let mut truncated = current.clone();
+        truncated.truncate(current.len() - 2);
+        assert!(EscrowAccountBorsh::try_from_slice(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_bincode_roundtrip() {
+        let mut agent = AgentAccount::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            capabilities::AI_CHAT | capabilities::NLU,
+            "https://example.com/metadata.json".to_string(),
+            254,
+        );
+        agent.reputation = 77;
+        let agent = AgentAccountBorsh::from(agent);
+        let bytes = agent.to_bincode().unwrap();
+        assert_eq!(bytes[0], BINCODE_FORMAT_VERSION);
+        assert_eq!(AgentAccountBorsh::from_bincode(&bytes), Ok(agent));
+
+        let channel = ChannelAccountBorsh::from(channel_with(2, 2, 10));
+        assert_eq!(ChannelAccountBorsh::from_bincode(&channel.to_bincode().unwrap()), Ok(channel));
+
+        let mut milestone = Milestone::new("design", 400);
+        milestone.status = MilestoneStatus::Completed;
+        let escrow = EscrowAccountBorsh::from(milestone_escrow(vec![milestone]));
+        assert_eq!(EscrowAccountBorsh::from_bincode(&escrow.to_bincode().unwrap()), Ok(escrow));
+    }
+
+    #[test]
+    fn test_bincode_version_mismatch_rejected() {
+        let channel = ChannelAccountBorsh::from(channel_with(1, 1, 5));
+        let mut bytes = channel.to_bincode().unwrap();
+        bytes[0] = BINCODE_FORMAT_VERSION + 1;
+
+        let err = ChannelAccountBorsh::from_bincode(&bytes).unwrap_err();
+        assert_eq!(err, BincodeError::UnsupportedVersion { found: BINCODE_FORMAT_VERSION + 1, expected: BINCODE_FORMAT_VERSION });
+        assert!(err.to_string().contains("version 2 is not supported"));
+        assert_eq!(ChannelAccountBorsh::from_bincode(&[]), Err(BincodeError::Empty));
+    }
+
+    fn milestone_escrow(milestones: Vec<Milestone>) -> EscrowAccount {
+        EscrowAccount {
+            channel: Pubkey::new_unique(),
+            depositor: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            amount: milestones.iter().map(|m| m.amount).sum(),
+            status: EscrowStatus::Active,
+            deposited_at: 0,
+            created_at: 0,
+            timeout_at: None,
+            disputed_at: None,
+            conditions: vec![],
+            arbitrators: None,
+            bump: 255,
+            milestones,
+        }
+    }
+
+    fn completed(mut milestone: Milestone, released: bool) -> Milestone {
+        milestone.status = MilestoneStatus::Completed;
+        milestone.completed_at = Some(1_700_000_000);
+        milestone.released = released;
+        milestone
+    }
+
+    #[test]
+    fn test_releasable_amount_all_pending() {
+        let escrow = milestone_escrow(vec![Milestone::new("design", 300), Milestone::new("build", 700)]);
+        assert_eq!(escrow.releasable_amount(), 0);
+        assert_eq!(milestone_escrow(vec![]).releasable_amount(), 0);
+    }
+
+    #[test]
+    fn test_releasable_amount_partial_completion() {
+        let mut in_progress = Milestone::new("test", 200);
+        in_progress.status = MilestoneStatus::InProgress;
+        let escrow = milestone_escrow(vec![
+            completed(Milestone::new("design", 300), true),
+            completed(Milestone::new("build", 500), false),
+            in_progress,
+        ]);
+
+        // Only the completed, unreleased milestone counts
+        assert_eq!(escrow.releasable_amount(), 500);
+    }
+
+    #[test]
+    fn test_releasable_amount_full_completion() {
+        let escrow = milestone_escrow(vec![
+            completed(Milestone::new("design", 300), false),
+            completed(Milestone::new("build", 500), false),
+            completed(Milestone::new("test", 200), false),
+        ]);
+        assert_eq!(escrow.releasable_amount(), escrow.amount);
+
+        // Totals saturate rather than overflow
+        let mut overflowing = escrow.clone();
+        overflowing.milestones[0].amount = u64::MAX;
+        assert_eq!(overflowing.releasable_amount(), u64::MAX);
+    }
+
+    #[test]
+    fn test_escrow_without_milestones_deserializes() {
+        let mut json = serde_json::to_value(milestone_escrow(vec![])).unwrap();
+        json.as_object_mut().unwrap().remove("milestones");
+        let escrow: EscrowAccount = serde_json::from_value(json).unwrap();
+        assert!(escrow.milestones.is_empty());
+    }
+
+    #[test]
+    fn test_escrow_condition_builder_typed_helpers() {
+        let condition = EscrowCondition::builder()
+            .time_elapsed(std::time::Duration::from_secs(3600))
+            .build()
+            .unwrap();
+        assert_eq!(condition.condition_type, "time_elapsed");
+        assert_eq!(condition.parameters.get("seconds").map(String::as_str), Some("3600"));
+        assert!(!condition.fulfilled);
+
+        let channel = Pubkey::new_unique();
+        let condition = EscrowCondition::builder().service_completion(channel).build().unwrap();
+        assert_eq!(condition.parameters.get("channel"), Some(&channel.to_string()));
+    }
+
+    #[test]
+    fn test_escrow_condition_builder_normalizes_custom_type() {
+        let condition = EscrowCondition::builder()
+            .custom(" Approval-Count ", "min_approvals", "2")
+            .custom("approval_count", "current_approvals", "1")
+            .build()
+            .unwrap();
+
+        assert_eq!(condition.condition_type, "approval_count");
+        assert_eq!(condition.parameters.len(), 2);
+    }
+
+    #[test]
+    fn test_escrow_condition_builder_rejects_invalid_types() {
+        assert_eq!(
+            EscrowCondition::builder().custom("moon_phase", "phase", "full").build().unwrap_err(),
+            "Unknown escrow condition type"
+        );
+        assert_eq!(
+            EscrowCondition::builder().build().unwrap_err(),
+            "Escrow condition type is required"
+        );
+        assert_eq!(
+            EscrowCondition::builder()
+                .time_elapsed(std::time::Duration::from_secs(60))
+                .approval_count(1)
+                .build()
+                .unwrap_err(),
+            "Escrow condition has conflicting types"
+        );
+    }
+
+    fn send_request(payload: Vec<u8>, expiry: Option<std::time::Duration>) -> SendMessageRequest {
+        SendMessageRequest {
+            recipient: Pubkey::new_unique(),
+            payload,
+            message_type: MessageType::Text,
+            expiry,
+            reply_to: None,
+            required_capabilities: None,
+            correlation_id: None,
+            content_type: None,
+        }
+    }
+
+    #[test]
+    fn test_send_message_request_payload_limits() {
+        assert_eq!(send_request(vec![], None).validate(), Err(ValidationError::EmptyPayload));
+        assert_eq!(send_request(vec![0; MAX_MESSAGE_CONTENT_LENGTH], None).validate(), Ok(()));
+        assert_eq!(
+            send_request(vec![0; MAX_MESSAGE_CONTENT_LENGTH + 1], None).validate(),
+            Err(ValidationError::PayloadTooLarge {
+                len: MAX_MESSAGE_CONTENT_LENGTH + 1,
+                max: MAX_MESSAGE_CONTENT_LENGTH,
+            })
+        );
+    }
+
+    #[test]
+    fn test_send_message_request_expiry_minimum() {
+        use std::time::Duration;
+
+        assert!(send_request(vec![1], Some(Duration::from_secs(60))).validate().is_ok());
+        assert!(matches!(
+            send_request(vec![1], Some(Duration::from_secs(59))).validate(),
+            Err(ValidationError::ExpiryTooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_send_message_request_content_type() {
+        // Untagged payloads need no content type
+        let untagged = send_request(vec![1], None);
+        assert_eq!(untagged.content_type, None);
+        assert_eq!(untagged.validate(), Ok(()));
+
+        let json = SendMessageRequest {
+            content_type: Some("application/json".to_string()),
+            ..send_request(br#"{"ok":true}"#.to_vec(), None)
+        };
+        assert_eq!(json.validate(), Ok(()));
+
+        let unsupported = SendMessageRequest {
+            content_type: Some("application/x-msdownload".to_string()),
+            ..send_request(vec![1], None)
+        };
+        assert_eq!(
+            unsupported.validate(),
+            Err(ValidationError::UnsupportedContentType)
+        );
+
+        let built = SendMessageRequest::builder()
+            .recipient(Pubkey::new_unique())
+            .payload(vec![1])
+            .message_type(MessageType::Data)
+            .content_type("application/json")
+            .build()
+            .unwrap();
+        assert_eq!(built.content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            SendMessageRequest::builder()
+                .recipient(Pubkey::new_unique())
+                .payload(vec![1])
+                .content_type("text/x-unknown")
+                .build()
+                .unwrap_err(),
+            "Unsupported content type"
+        );
+    }
+
+    #[test]
+    fn test_capability_names_roundtrip() {
+        let mask = capabilities::AI_CHAT
+            | capabilities::BLOCKCHAIN_OPS
+            | capabilities::CUSTOM_BASE
+            | (capabilities::CUSTOM_BASE << 31);
+        let agent = AgentAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), mask, "https://example.com".to_string(), 1);
+
+        let names = agent.capability_names();
+        assert_eq!(names, vec!["AI_CHAT", "BLOCKCHAIN_OPS", "custom(0)", "custom(31)"]);
+        assert_eq!(capabilities::from_names(&names), Ok(mask));
+        assert_eq!(capabilities::from_names(&[]), Ok(0));
+    }
+
+    #[test]
+    fn test_agent_capabilities_all_and_round_trips() {
+        let all = AgentCapabilities::all();
+        for (name, bit) in capabilities::NAMED {
+            assert!(all.contains(bit), "{} missing from all()", name);
+        }
+        assert!(!all.contains(capabilities::CUSTOM_BASE));
+        assert_eq!(all.iter().count(), capabilities::NAMED.len());
+
+        let caps = AgentCapabilities::none().with(capabilities::CODE_GENERATION);
+        assert_eq!(caps.with(capabilities::ML_TRAINING).without(capabilities::ML_TRAINING), caps);
+        assert_eq!(all.without(capabilities::NLU).with(capabilities::NLU), all);
+        assert!(!AgentCapabilities::none().contains(capabilities::AI_CHAT));
+        assert_eq!(AgentCapabilities::from(u64::from(caps)), caps);
+    }
+
+    #[test]
+    fn test_capability_similarity_and_complementarity() {
+        use capabilities::{complementarity, similarity, AI_CHAT, CODE_GENERATION, DATA_ANALYSIS, NLU};
+
+        // Disjoint
+        assert_eq!(similarity(AI_CHAT, DATA_ANALYSIS), 0.0);
+        assert_eq!(complementarity(AI_CHAT, DATA_ANALYSIS | NLU), 1.0);
+
+        // Identical
+        let mask = AI_CHAT | CODE_GENERATION;
+        assert_eq!(similarity(mask, mask), 1.0);
+        assert_eq!(complementarity(mask, mask), 0.0);
+        assert_eq!(similarity(0, 0), 1.0);
+        assert_eq!(complementarity(mask, 0), 0.0);
+
+        // Overlapping: {chat, code} against {code, analysis, nlu}
+        let other = CODE_GENERATION | DATA_ANALYSIS | NLU;
+        assert_eq!(similarity(mask, other), 0.25);
+        assert_eq!(similarity(other, mask), 0.25);
+        assert_eq!(complementarity(mask, other), 2.0 / 3.0);
+        assert_eq!(complementarity(other, mask), 0.5);
+    }
+
+    #[test]
+    fn test_from_names_reports_unknown_capability() {
+        for bad in ["TELEPATHY", "ai_chat", "custom(32)", "custom(01)", "custom()"] {
+            let err = capabilities::from_names(&["AI_CHAT", bad]).unwrap_err();
+            assert_eq!(err.name, bad);
+            assert!(err.to_string().contains(bad));
+        }
+    }
+
     #[test]
     fn test_analytics_period_duration() {
         assert_eq!(
@@ -775,4 +2330,30 @@ mod tests {
         assert_eq!(MessageType::Text.to_string(), "text");
         assert_eq!(MessageType::Custom(42).to_string(), "custom(42)");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_reputation_unchanged_with_no_elapsed_time() {
+        let half_life = std::time::Duration::from_secs(86_400);
+        assert_eq!(decay_reputation(1_000, std::time::Duration::ZERO, half_life), 1_000);
+        assert_eq!(decay_reputation(1_000, half_life, std::time::Duration::ZERO), 1_000);
+    }
+
+    #[test]
+    fn test_reputation_halves_after_one_half_life() {
+        let half_life = std::time::Duration::from_secs(86_400);
+        assert_eq!(decay_reputation(1_000, half_life, half_life), 500);
+        assert_eq!(decay_reputation(u64::MAX, half_life, half_life), u64::MAX / 2);
+        // Half a half-life scales by 1/sqrt(2)
+        assert_eq!(decay_reputation(1_000, half_life / 2, half_life), 707);
+    }
+
+    #[test]
+    fn test_reputation_decays_over_many_half_lives() {
+        let half_life = std::time::Duration::from_secs(3_600);
+        assert_eq!(decay_reputation(1_024, half_life * 3, half_life), 128);
+        assert_eq!(decay_reputation(1_024, half_life * 10, half_life), 1);
+        assert_eq!(decay_reputation(1_024, half_life * 11, half_life), 0);
+        assert_eq!(decay_reputation(u64::MAX, half_life * 64, half_life), 0);
+        assert_eq!(decay_reputation(u64::MAX, std::time::Duration::MAX, half_life), 0);
+    }
+}
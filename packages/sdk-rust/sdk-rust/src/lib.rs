@@ -68,6 +68,8 @@ pub enum PodError {
     InsufficientFunds,
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
+    #[error("Status has no equivalent: {0}")]
+    UnmappedStatus(String),
 }
 
 /// Configuration for the PoD Protocol client
@@ -136,6 +138,9 @@ pub enum MessageType {
 pub mod services;
 pub use services::*;
 
+/// PDA derivation shared with the core SDK
+pub use pod_sdk_types::pda;
+
 /// Main client for interacting with the PoD Protocol
 pub struct PodClient {
     config: Config,
@@ -248,20 +253,16 @@ impl PodClient {
     }
 
     /// Get service statistics
+    /// 
+    /// Counts agents, messages, channels, and escrows concurrently. A count
+    /// that fails is reported in [`ServiceStats::warnings`] instead of failing
+    /// the whole call.
     pub async fn get_service_stats(&self) -> Result<ServiceStats> {
         if !self.has_wallet() {
             return Err(PodError::InvalidConfig("Wallet required to get service stats".to_string()).into());
         }
 
-        // This would aggregate statistics from all services
-        Ok(ServiceStats {
-            agent_count: 0,
-            message_count: 0,
-            channel_count: 0,
-            escrow_count: 0,
-            total_transactions: 0,
-            last_activity: chrono::Utc::now(),
-        })
+        Ok(ServiceStats::collect(&self.agents, &self.messages, &self.channels, &self.escrows).await)
     }
 }
 
@@ -280,7 +281,56 @@ pub struct ServiceStats {
     pub channel_count: u64,
     pub escrow_count: u64,
     pub total_transactions: u64,
+    /// Most recent transaction sent by any service, or the Unix epoch if none has been
     pub last_activity: chrono::DateTime<chrono::Utc>,
+    /// Services whose counts could not be fetched and are reported as zero
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl ServiceStats {
+    /// Aggregate account counts and transaction metrics across services
+    pub async fn collect(
+        agents: &dyn services::ServiceStatsSource,
+        messages: &dyn services::ServiceStatsSource,
+        channels: &dyn services::ServiceStatsSource,
+        escrows: &dyn services::ServiceStatsSource,
+    ) -> Self {
+        let (agent_count, message_count, channel_count, escrow_count) = tokio::join!(
+            agents.account_count(),
+            messages.account_count(),
+            channels.account_count(),
+            escrows.account_count(),
+        );
+
+        let mut warnings = Vec::new();
+        let mut count = |service: &dyn services::ServiceStatsSource, result: std::result::Result<u64, PodError>| {
+            result.unwrap_or_else(|e| {
+                tracing::warn!("Failed to count accounts for {}: {}", service.service_name(), e);
+                warnings.push(format!("{}: {}", service.service_name(), e));
+                0
+            })
+        };
+        let agent_count = count(agents, agent_count);
+        let message_count = count(messages, message_count);
+        let channel_count = count(channels, channel_count);
+        let escrow_count = count(escrows, escrow_count);
+
+        let metrics: Vec<_> = [agents, messages, channels, escrows]
+            .iter()
+            .map(|service| service.metrics())
+            .collect();
+
+        ServiceStats {
+            agent_count,
+            message_count,
+            channel_count,
+            escrow_count,
+            total_transactions: metrics.iter().map(|m| m.total_transactions).sum(),
+            last_activity: metrics.iter().filter_map(|m| m.last_activity).max().unwrap_or_default(),
+            warnings,
+        }
+    }
 }
 
 /// Utility functions for PoD Protocol operations
@@ -288,6 +338,9 @@ pub mod utils {
     use super::*;
 
     /// Generate a random agent ID
+    ///
+    /// Differs on every call, so a retried registration gets a second ID.
+    #[deprecated(since = "2.0.0", note = "not reproducible; use `pod_sdk_core::utils::derive_agent_id`")]
     pub fn generate_agent_id() -> String {
         format!("agent_{}", rand::random::<u64>())
     }
@@ -321,6 +374,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_agent_id_generation() {
         let id1 = utils::generate_agent_id();
         let id2 = utils::generate_agent_id();
@@ -334,4 +388,82 @@ mod tests {
         assert!(utils::validate_agent_name("").is_err());
         assert!(utils::validate_agent_name(&"a".repeat(101)).is_err());
     }
+
+    /// Service reporting a fixed count, or failing to count when `count` is `None`
+    struct MockStats {
+        name: &'static str,
+        count: Option<u64>,
+        metrics: ServiceMetrics,
+    }
+
+    impl MockStats {
+        fn new(name: &'static str, count: Option<u64>, total_transactions: u64, minutes_ago: i64) -> Self {
+            let metrics = ServiceMetrics {
+                total_transactions,
+                last_activity: Some(chrono::Utc::now() - chrono::Duration::minutes(minutes_ago)),
+            };
+            Self { name, count, metrics }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BaseService for MockStats {
+        async fn initialize(&mut self, _config: &Config, _rpc_client: Arc<RpcClient>) -> std::result::Result<(), PodError> {
+            Ok(())
+        }
+
+        fn service_name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn health_check(&self) -> std::result::Result<(), PodError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ServiceStatsSource for MockStats {
+        async fn account_count(&self) -> std::result::Result<u64, PodError> {
+            self.count.ok_or_else(|| PodError::Network("connection refused".to_string()))
+        }
+
+        fn metrics(&self) -> ServiceMetrics {
+            self.metrics.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_stats_aggregation() {
+        let agents = MockStats::new("agents", Some(3), 4, 30);
+        let messages = MockStats::new("messages", Some(12), 10, 1);
+        let channels = MockStats::new("channels", Some(2), 1, 60);
+        let escrows = MockStats { name: "escrows", count: Some(5), metrics: ServiceMetrics::default() };
+
+        let stats = ServiceStats::collect(&agents, &messages, &channels, &escrows).await;
+
+        assert_eq!(stats.agent_count, 3);
+        assert_eq!(stats.message_count, 12);
+        assert_eq!(stats.channel_count, 2);
+        assert_eq!(stats.escrow_count, 5);
+        assert_eq!(stats.total_transactions, 15);
+        assert_eq!(Some(stats.last_activity), messages.metrics.last_activity);
+        assert!(stats.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_service_stats_partial_on_error() {
+        let agents = MockStats::new("agents", Some(3), 4, 30);
+        let messages = MockStats::new("messages", None, 10, 1);
+        let channels = MockStats::new("channels", Some(2), 1, 60);
+        let escrows = MockStats::new("escrows", Some(5), 0, 90);
+
+        let stats = ServiceStats::collect(&agents, &messages, &channels, &escrows).await;
+
+        assert_eq!(stats.agent_count, 3);
+        assert_eq!(stats.message_count, 0);
+        assert_eq!(stats.escrow_count, 5);
+        assert_eq!(stats.total_transactions, 15);
+        assert_eq!(stats.warnings.len(), 1);
+        assert!(stats.warnings[0].starts_with("messages: "));
+    }
 }
\ No newline at end of file
@@ -14,7 +14,10 @@ use solana_client::rpc_client::RpcClient;
 use chrono::{DateTime, Utc};
 
 use crate::{Config, PodError, Agent};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{
+    BaseService, MetricsRecorder, ServiceContext, ServiceMetrics, ServiceStatsSource, TransactionResult,
+    account_utils,
+};
 
 /// Agent capabilities represented as a bitmask
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -119,11 +122,15 @@ pub struct AgentInfo {
 /// Agent service for managing AI agents on the PoD Protocol
 pub struct AgentService {
     context: Option<ServiceContext>,
+    metrics: MetricsRecorder,
 }
 
 impl AgentService {
     pub fn new() -> Self {
-        Self { context: None }
+        Self {
+            context: None,
+            metrics: MetricsRecorder::default(),
+        }
     }
 
     /// Register a new agent on the PoD Protocol
@@ -330,6 +337,7 @@ impl AgentService {
             .send_and_confirm_transaction(&transaction)
             .map_err(|e| PodError::Network(e.to_string()))?;
 
+        self.metrics.record_transaction();
         Ok(TransactionResult::new(signature))
     }
 
@@ -385,6 +393,18 @@ impl BaseService for AgentService {
     }
 }
 
+#[async_trait]
+impl ServiceStatsSource for AgentService {
+    async fn account_count(&self) -> Result<u64, PodError> {
+        let context = self.get_context()?;
+        account_utils::count_program_accounts(&context.async_rpc_client, &context.config.program_id, "AgentAccount").await
+    }
+
+    fn metrics(&self) -> ServiceMetrics {
+        self.metrics.snapshot()
+    }
+}
+
 impl Default for AgentService {
     fn default() -> Self {
         Self::new()
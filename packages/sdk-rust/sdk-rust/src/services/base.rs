@@ -1,7 +1,9 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use solana_sdk::{pubkey::Pubkey, signer::Signer, signature::Signature};
-use solana_client::rpc_client::RpcClient;
+use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use crate::{Config, PodError};
 
 /// Base trait for all PoD Protocol services
@@ -17,19 +19,64 @@ pub trait BaseService: Send + Sync {
     async fn health_check(&self) -> Result<(), PodError>;
 }
 
+/// Services that own a kind of program account and can report their usage
+#[async_trait]
+pub trait ServiceStatsSource: BaseService {
+    /// Number of program accounts of this service's kind
+    async fn account_count(&self) -> Result<u64, PodError>;
+    
+    /// Transactions this service has sent since it was created
+    fn metrics(&self) -> ServiceMetrics;
+}
+
+/// Snapshot of a service's transaction activity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceMetrics {
+    pub total_transactions: u64,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// Records a service's confirmed transactions
+#[derive(Debug, Default)]
+pub struct MetricsRecorder {
+    metrics: Mutex<ServiceMetrics>,
+}
+
+impl MetricsRecorder {
+    /// Count a confirmed transaction sent now
+    pub fn record_transaction(&self) {
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics.total_transactions += 1;
+        metrics.last_activity = Some(Utc::now());
+    }
+
+    /// Current metrics
+    pub fn snapshot(&self) -> ServiceMetrics {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
 /// Shared service context containing common resources
 #[derive(Clone)]
 pub struct ServiceContext {
     pub config: Config,
     pub rpc_client: Arc<RpcClient>,
+    /// Nonblocking client for the same endpoint, for RPC calls made inside async fns
+    pub async_rpc_client: Arc<AsyncRpcClient>,
     pub wallet: Option<Arc<dyn Signer + Send + Sync>>,
 }
 
 impl ServiceContext {
     pub fn new(config: Config, rpc_client: Arc<RpcClient>) -> Self {
+        let async_rpc_client = Arc::new(AsyncRpcClient::new_with_commitment(
+            rpc_client.url(),
+            rpc_client.commitment(),
+        ));
+        
         Self {
             config,
             rpc_client,
+            async_rpc_client,
             wallet: None,
         }
     }
@@ -117,22 +164,63 @@ pub mod instruction_utils {
 
 /// Account derivation utilities
 pub mod account_utils {
+    use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+    use solana_client::{
+        nonblocking::rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    };
     use solana_sdk::pubkey::{Pubkey, PubkeyError};
     use crate::PodError;
 
+    /// Anchor discriminator for the pod-com account type `account_name`
+    pub fn account_discriminator(account_name: &str) -> [u8; 8] {
+        let hash = solana_sdk::hash::hash(format!("account:{}", account_name).as_bytes());
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+
+    /// Count the program's accounts of type `account_name`
+    /// 
+    /// Requests an empty data slice, so only the matching addresses are
+    /// transferred.
+    pub async fn count_program_accounts(
+        rpc_client: &RpcClient,
+        program_id: &Pubkey,
+        account_name: &str,
+    ) -> Result<u64, PodError> {
+        let discriminator = account_discriminator(account_name);
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &discriminator))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        
+        rpc_client
+            .get_program_accounts_with_config(program_id, config)
+            .await
+            .map(|accounts| accounts.len() as u64)
+            .map_err(|e| PodError::Network(e.to_string()))
+    }
+
     /// Derive agent PDA (Program Derived Address)
     pub fn derive_agent_pda(
         program_id: &Pubkey,
         wallet_pubkey: &Pubkey,
     ) -> Result<(Pubkey, u8), PodError> {
-        Pubkey::find_program_address(
-            &[b"agent", wallet_pubkey.as_ref()],
-            program_id,
-        ).map_err(|e| PodError::Solana(format!("Failed to derive agent PDA: {}", e)))
-            .map(|(pubkey, bump)| (pubkey, bump))
+        Ok(crate::pda::agent_pda(program_id, wallet_pubkey))
     }
 
     /// Derive message PDA
+    /// 
+    /// Keyed by a message ID; direct messages on-chain live at
+    /// [`crate::pda::message_pda`].
+    #[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `crate::pda::message_pda`")]
     pub fn derive_message_pda(
         program_id: &Pubkey,
         from: &Pubkey,
@@ -152,6 +240,10 @@ pub mod account_utils {
     }
 
     /// Derive channel PDA
+    /// 
+    /// Omits the creator the program seeds; on-chain channels live at
+    /// [`crate::pda::channel_pda`].
+    #[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `crate::pda::channel_pda`")]
     pub fn derive_channel_pda(
         program_id: &Pubkey,
         channel_id: &str,
@@ -164,6 +256,10 @@ pub mod account_utils {
     }
 
     /// Derive escrow PDA
+    /// 
+    /// Keyed by an escrow ID; channel escrows on-chain live at
+    /// [`crate::pda::escrow_pda`].
+    #[deprecated(since = "2.0.0", note = "does not match the program's seeds; use `crate::pda::escrow_pda`")]
     pub fn derive_escrow_pda(
         program_id: &Pubkey,
         escrow_id: &str,
@@ -228,4 +324,16 @@ mod tests {
         assert_ne!(pda, Pubkey::default());
         assert!(bump <= 255);
     }
+
+    #[test]
+    fn test_agent_pda_matches_historical_derivation() {
+        use crate::services::base::account_utils::derive_agent_pda;
+        
+        let program_id = Pubkey::new_from_array([1; 32]);
+        let wallet_pubkey = Pubkey::new_from_array([2; 32]);
+        let historical = Pubkey::find_program_address(&[b"agent", wallet_pubkey.as_ref()], &program_id);
+        
+        assert_eq!(crate::pda::agent_pda(&program_id, &wallet_pubkey), historical);
+        assert_eq!(derive_agent_pda(&program_id, &wallet_pubkey).unwrap(), historical);
+    }
 }
\ No newline at end of file
@@ -14,7 +14,10 @@ use solana_client::rpc_client::RpcClient;
 use chrono::{DateTime, Utc};
 
 use crate::{Config, PodError, MessageType};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{
+    BaseService, MetricsRecorder, ServiceContext, ServiceMetrics, ServiceStatsSource, TransactionResult,
+    account_utils,
+};
 
 /// Channel types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -196,11 +199,15 @@ pub struct ChannelMessage {
 /// Channel service for group communication
 pub struct ChannelService {
     context: Option<ServiceContext>,
+    metrics: MetricsRecorder,
 }
 
 impl ChannelService {
     pub fn new() -> Self {
-        Self { context: None }
+        Self {
+            context: None,
+            metrics: MetricsRecorder::default(),
+        }
     }
 
     /// Create a new channel
@@ -220,6 +227,7 @@ impl ChannelService {
         let channel_id = self.generate_channel_id(&creation_data.name, &owner);
         
         // Derive channel PDA
+        #[allow(deprecated)]
         let (channel_pda, bump) = account_utils::derive_channel_pda(
             &context.config.program_id,
             &channel_id,
@@ -339,6 +347,7 @@ impl ChannelService {
     pub async fn get_channel(&self, channel_id: &str) -> Result<ChannelInfo, PodError> {
         let context = self.get_context()?;
         
+        #[allow(deprecated)]
         let (channel_pda, _) = account_utils::derive_channel_pda(
             &context.config.program_id,
             channel_id,
@@ -655,6 +664,7 @@ impl ChannelService {
             .send_and_confirm_transaction(&transaction)
             .map_err(|e| PodError::Network(e.to_string()))?;
 
+        self.metrics.record_transaction();
         Ok(TransactionResult::new(signature))
     }
 
@@ -707,6 +717,18 @@ impl BaseService for ChannelService {
     }
 }
 
+#[async_trait]
+impl ServiceStatsSource for ChannelService {
+    async fn account_count(&self) -> Result<u64, PodError> {
+        let context = self.get_context()?;
+        account_utils::count_program_accounts(&context.async_rpc_client, &context.config.program_id, "ChannelAccount").await
+    }
+
+    fn metrics(&self) -> ServiceMetrics {
+        self.metrics.snapshot()
+    }
+}
+
 impl Default for ChannelService {
     fn default() -> Self {
         Self::new()
@@ -15,7 +15,10 @@ use solana_client::rpc_client::RpcClient;
 use chrono::{DateTime, Utc, Duration};
 
 use crate::{Config, PodError};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{
+    BaseService, MetricsRecorder, ServiceContext, ServiceMetrics, ServiceStatsSource, TransactionResult,
+    account_utils,
+};
 
 /// Escrow status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -30,6 +33,52 @@ pub enum EscrowStatus {
     Released,
 }
 
+/// Convert to the on-chain status model in `pod-sdk-types`
+///
+/// | Here                              | `pod_sdk_types` |
+/// |-----------------------------------|-----------------|
+/// | `Created`, `Funded`, `InProgress` | `Active`        |
+/// | `Released`                        | `Released`      |
+/// | `Cancelled`                       | `Refunded`      |
+/// | `Disputed`                        | `Disputed`      |
+/// | `Completed`, `Expired`            | error           |
+///
+/// `Completed` may or may not have paid out yet and `Expired` may or may
+/// not have been refunded, so neither has a clean target.
+impl TryFrom<EscrowStatus> for pod_sdk_types::EscrowStatus {
+    type Error = PodError;
+
+    fn try_from(status: EscrowStatus) -> Result<Self, Self::Error> {
+        match status {
+            EscrowStatus::Created | EscrowStatus::Funded | EscrowStatus::InProgress => {
+                Ok(pod_sdk_types::EscrowStatus::Active)
+            }
+            EscrowStatus::Released => Ok(pod_sdk_types::EscrowStatus::Released),
+            EscrowStatus::Cancelled => Ok(pod_sdk_types::EscrowStatus::Refunded),
+            EscrowStatus::Disputed => Ok(pod_sdk_types::EscrowStatus::Disputed),
+            EscrowStatus::Completed | EscrowStatus::Expired => {
+                Err(PodError::UnmappedStatus(format!("escrow status {:?}", status)))
+            }
+        }
+    }
+}
+
+/// Convert from the on-chain status model in `pod-sdk-types`
+///
+/// `Active` means funds are locked, so it maps to `Funded`; `Refunded` maps
+/// to `Cancelled`. Every status survives a round trip back through
+/// [`TryFrom`].
+impl From<pod_sdk_types::EscrowStatus> for EscrowStatus {
+    fn from(status: pod_sdk_types::EscrowStatus) -> Self {
+        match status {
+            pod_sdk_types::EscrowStatus::Active => EscrowStatus::Funded,
+            pod_sdk_types::EscrowStatus::Released => EscrowStatus::Released,
+            pod_sdk_types::EscrowStatus::Refunded => EscrowStatus::Cancelled,
+            pod_sdk_types::EscrowStatus::Disputed => EscrowStatus::Disputed,
+        }
+    }
+}
+
 /// Escrow type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum EscrowType {
@@ -246,11 +295,15 @@ impl EscrowFilter {
 /// Escrow service for secure payments between agents
 pub struct EscrowService {
     context: Option<ServiceContext>,
+    metrics: MetricsRecorder,
 }
 
 impl EscrowService {
     pub fn new() -> Self {
-        Self { context: None }
+        Self {
+            context: None,
+            metrics: MetricsRecorder::default(),
+        }
     }
 
     /// Create a new escrow
@@ -270,6 +323,7 @@ impl EscrowService {
         let escrow_id = self.generate_escrow_id(&creation_data.title, &payer);
         
         // Derive escrow PDA
+        #[allow(deprecated)]
         let (escrow_pda, bump) = account_utils::derive_escrow_pda(
             &context.config.program_id,
             &escrow_id,
@@ -456,6 +510,7 @@ impl EscrowService {
     pub async fn get_escrow(&self, escrow_id: &str) -> Result<EscrowInfo, PodError> {
         let context = self.get_context()?;
         
+        #[allow(deprecated)]
         let (escrow_pda, _) = account_utils::derive_escrow_pda(
             &context.config.program_id,
             escrow_id,
@@ -712,6 +767,7 @@ impl EscrowService {
             .send_and_confirm_transaction(&transaction)
             .map_err(|e| PodError::Network(e.to_string()))?;
 
+        self.metrics.record_transaction();
         Ok(TransactionResult::new(signature))
     }
 
@@ -782,6 +838,18 @@ impl BaseService for EscrowService {
     }
 }
 
+#[async_trait]
+impl ServiceStatsSource for EscrowService {
+    async fn account_count(&self) -> Result<u64, PodError> {
+        let context = self.get_context()?;
+        account_utils::count_program_accounts(&context.async_rpc_client, &context.config.program_id, "EscrowAccount").await
+    }
+
+    fn metrics(&self) -> ServiceMetrics {
+        self.metrics.snapshot()
+    }
+}
+
 impl Default for EscrowService {
     fn default() -> Self {
         Self::new()
@@ -860,4 +928,35 @@ mod tests {
         assert_eq!(conditions.auto_release_after_hours, Some(72));
         assert_eq!(conditions.dispute_resolution, DisputeResolution::Automatic);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_escrow_status_round_trips_through_types() {
+        use pod_sdk_types::EscrowStatus as OnChain;
+
+        for status in [OnChain::Active, OnChain::Released, OnChain::Refunded, OnChain::Disputed] {
+            assert_eq!(OnChain::try_from(EscrowStatus::from(status)).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn test_escrow_status_to_types_mapping() {
+        use pod_sdk_types::EscrowStatus as OnChain;
+
+        let mapped = |status: EscrowStatus| OnChain::try_from(status).ok();
+        assert_eq!(mapped(EscrowStatus::Created), Some(OnChain::Active));
+        assert_eq!(mapped(EscrowStatus::Funded), Some(OnChain::Active));
+        assert_eq!(mapped(EscrowStatus::InProgress), Some(OnChain::Active));
+        assert_eq!(mapped(EscrowStatus::Released), Some(OnChain::Released));
+        assert_eq!(mapped(EscrowStatus::Cancelled), Some(OnChain::Refunded));
+        assert_eq!(mapped(EscrowStatus::Disputed), Some(OnChain::Disputed));
+
+        assert!(matches!(
+            OnChain::try_from(EscrowStatus::Completed),
+            Err(PodError::UnmappedStatus(_))
+        ));
+        assert!(matches!(
+            OnChain::try_from(EscrowStatus::Expired),
+            Err(PodError::UnmappedStatus(_))
+        ));
+    }
+}
@@ -15,7 +15,10 @@ use chrono::{DateTime, Utc};
 use blake3::Hasher;
 
 use crate::{Config, PodError, Message, MessageType};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{
+    BaseService, MetricsRecorder, ServiceContext, ServiceMetrics, ServiceStatsSource, TransactionResult,
+    account_utils,
+};
 
 /// Message encryption type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -132,11 +135,15 @@ impl MessageFilter {
 /// Message service for P2P communication between agents
 pub struct MessageService {
     context: Option<ServiceContext>,
+    metrics: MetricsRecorder,
 }
 
 impl MessageService {
     pub fn new() -> Self {
-        Self { context: None }
+        Self {
+            context: None,
+            metrics: MetricsRecorder::default(),
+        }
     }
 
     /// Send a message to another agent
@@ -160,6 +167,7 @@ impl MessageService {
         let message_id = self.generate_message_id(&from, to, &content);
         
         // Derive message PDA
+        #[allow(deprecated)]
         let (message_pda, bump) = account_utils::derive_message_pda(
             &context.config.program_id,
             &from,
@@ -455,6 +463,7 @@ impl MessageService {
             .send_and_confirm_transaction(&transaction)
             .map_err(|e| PodError::Network(e.to_string()))?;
 
+        self.metrics.record_transaction();
         Ok(TransactionResult::new(signature))
     }
 }
@@ -493,6 +502,18 @@ impl BaseService for MessageService {
     }
 }
 
+#[async_trait]
+impl ServiceStatsSource for MessageService {
+    async fn account_count(&self) -> Result<u64, PodError> {
+        let context = self.get_context()?;
+        account_utils::count_program_accounts(&context.async_rpc_client, &context.config.program_id, "MessageAccount").await
+    }
+
+    fn metrics(&self) -> ServiceMetrics {
+        self.metrics.snapshot()
+    }
+}
+
 impl Default for MessageService {
     fn default() -> Self {
         Self::new()